@@ -0,0 +1,130 @@
+// Runs a repo's `blockedFileGlobs` against every file tracked at a commit and prints the
+// `BlockedFileAudit` as JSON, exiting non-zero unless it's ok so CI can gate pattern changes on it.
+//
+// usage: audit_blocked_files [--repo <path>] [--branch <target branch>] [--rev <rev>] [--user <username>]
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+
+use ethos_core::blocked_files;
+use ethos_core::clients::git;
+use ethos_core::types::config::{AppConfig, ProjectRepoConfig};
+use ethos_core::types::repo::BlockedFileAudit;
+
+const PROJECT: &str = "audit";
+
+#[derive(Default)]
+struct Args {
+    repo: Option<String>,
+    branch: Option<String>,
+    rev: Option<String>,
+    user: Option<String>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = Args::default();
+        let mut iter = std::env::args().skip(1);
+        while let Some(flag) = iter.next() {
+            let field = match flag.as_str() {
+                "--repo" => &mut args.repo,
+                "--branch" => &mut args.branch,
+                "--rev" => &mut args.rev,
+                "--user" => &mut args.user,
+                _ => bail!("Unknown argument {}", flag),
+            };
+            *field = Some(
+                iter.next()
+                    .ok_or_else(|| anyhow!("{} needs a value", flag))?,
+            );
+        }
+
+        Ok(args)
+    }
+}
+
+async fn run(args: Args) -> Result<BlockedFileAudit> {
+    let repo_path = match args.repo {
+        Some(repo) => PathBuf::from(repo),
+        None => std::env::current_dir()?,
+    };
+
+    // without one the default config would pass with nothing blocked
+    if !repo_path.join("friendshipper.yaml").exists() {
+        bail!("No friendshipper.yaml in {}", repo_path.display());
+    }
+
+    // the repo config is only loaded through an app config pointing at the repo
+    let mut app_config = AppConfig::new(PROJECT);
+    app_config.selected_artifact_project = Some(PROJECT.to_string());
+    app_config.projects.insert(
+        PROJECT.to_string(),
+        ProjectRepoConfig {
+            repo_path: repo_path.to_string_lossy().to_string(),
+            repo_url: String::new(),
+        },
+    );
+    let repo_config = app_config.initialize_repo_config()?;
+
+    let target_branch = args
+        .branch
+        .unwrap_or_else(|| repo_config.trunk_branch.clone());
+    // the app treats an unknown branch as having nothing blocked, which would always pass here
+    if !repo_config
+        .target_branches
+        .iter()
+        .any(|branch| branch.name == target_branch)
+    {
+        bail!(
+            "{} isn't one of the target branches in friendshipper.yaml",
+            target_branch
+        );
+    }
+
+    let (tx, _rx) = std::sync::mpsc::channel();
+    let git_client = git::Git::new(repo_path, tx);
+    let rev = args.rev.unwrap_or_else(|| "HEAD".to_string());
+    let commit = git_client
+        .run_and_collect_output(&["rev-parse", &rev], git::Opts::new_without_logs())
+        .await?
+        .trim()
+        .to_string();
+    let files = git_client.tracked_files(&commit).await?;
+
+    let audit = blocked_files::audit(
+        repo_config.blocked_globs_for_branch(&target_branch),
+        &files,
+        args.user.as_deref().unwrap_or_default(),
+    );
+
+    Ok(BlockedFileAudit {
+        target_branch,
+        commit,
+        ..audit
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    let audit = match Args::parse() {
+        Ok(args) => run(args).await,
+        Err(e) => Err(e),
+    };
+
+    match audit {
+        Ok(audit) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&audit).expect("audit serializes")
+            );
+            if !audit.ok {
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to audit blocked files: {e}");
+            std::process::exit(2);
+        }
+    }
+}
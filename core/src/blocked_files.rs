@@ -1,5 +1,8 @@
 use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 
+use crate::types::config::BlockedFileGlob;
+use crate::types::repo::{BlockedFileAudit, BlockedFilePatternAudit, BlockedFilePatternDiagnostic};
+
 /// Compiles and matches `blockedFileGlobs` patterns from `friendshipper.yaml`
 /// against repo-relative, forward-slash-separated paths.
 ///
@@ -11,7 +14,37 @@ use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 /// must never be blocked — removing a wrongly-committed asset is the
 /// cleanup path this feature exists to preserve — so callers must filter out
 /// deleted files before calling `is_blocked`.
-pub struct BlockedFileMatcher(GlobSet);
+///
+/// Patterns are applied in order with gitignore semantics: the last pattern
+/// matching a path decides, and a `!`-prefixed pattern unblocks what an
+/// earlier one blocked. A pattern whose entry lists `allowedUsers` lets those
+/// users through as if it had been negated, so a later match still wins over
+/// it in either direction.
+pub struct BlockedFileMatcher {
+    set: GlobSet,
+    // One per glob in `set`, in the same order
+    patterns: Vec<BlockedFilePattern>,
+}
+
+/// A compiled `blockedFileGlobs` entry.
+#[derive(Debug, Clone)]
+pub struct BlockedFilePattern {
+    /// As written in the config, including any leading `!`.
+    pub pattern: String,
+    pub negated: bool,
+    pub allowed_users: Vec<String>,
+    pub reason: Option<String>,
+}
+
+impl BlockedFilePattern {
+    fn allows(&self, username: &str) -> bool {
+        !username.is_empty()
+            && self
+                .allowed_users
+                .iter()
+                .any(|user| user.eq_ignore_ascii_case(username))
+    }
+}
 
 /// Result of compiling a project's `blockedFileGlobs` list.
 pub struct BlockedFileMatcherResult {
@@ -29,12 +62,18 @@ impl BlockedFileMatcher {
     /// Named `compile` rather than `new` because it returns
     /// `BlockedFileMatcherResult` — bundling the matcher with per-pattern
     /// diagnostics — rather than `Self`.
-    pub fn compile(patterns: &[String]) -> BlockedFileMatcherResult {
+    pub fn compile(globs: &[BlockedFileGlob]) -> BlockedFileMatcherResult {
         let mut builder = GlobSetBuilder::new();
+        let mut compiled: Vec<BlockedFilePattern> = Vec::new();
         let mut invalid: Vec<(String, String)> = Vec::new();
         let mut warnings: Vec<(String, String)> = Vec::new();
 
-        for pattern in patterns {
+        for entry in globs {
+            let original = entry.pattern();
+            let (negated, pattern) = match original.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, original),
+            };
             let glob: Result<Glob, _> = GlobBuilder::new(pattern)
                 .case_insensitive(true)
                 .literal_separator(true)
@@ -67,7 +106,7 @@ impl BlockedFileMatcher {
                         // unconditionally on any backslash in the pattern
                         // rather than rely on that platform difference.
                         warnings.push((
-                            pattern.clone(),
+                            original.to_string(),
                             format!(
                                 "pattern `{pattern}` contains a backslash (`\\`); \
                                  blockedFileGlobs patterns must use forward slashes (`/`) as the \
@@ -83,7 +122,7 @@ impl BlockedFileMatcher {
 
                     if !pattern.contains('/') && !pattern.contains("**") {
                         warnings.push((
-                            pattern.clone(),
+                            original.to_string(),
                             format!(
                                 "pattern `{pattern}` contains neither `/` nor `**`; because \
                                  matching uses literal_separator semantics, this will only match \
@@ -93,16 +132,35 @@ impl BlockedFileMatcher {
                             ),
                         ));
                     }
+                    if negated && !entry.allowed_users().is_empty() {
+                        warnings.push((
+                            original.to_string(),
+                            format!(
+                                "pattern `{original}` is negated, so its allowedUsers list has \
+                                 no effect: a negated pattern already lets everyone through"
+                            ),
+                        ));
+                    }
+
                     builder.add(glob);
+                    compiled.push(BlockedFilePattern {
+                        pattern: original.to_string(),
+                        negated,
+                        allowed_users: entry.allowed_users().to_vec(),
+                        reason: entry.reason().map(str::to_string),
+                    });
                 }
                 Err(e) => {
-                    invalid.push((pattern.clone(), e.to_string()));
+                    invalid.push((original.to_string(), e.to_string()));
                 }
             }
         }
 
         let matcher = match builder.build() {
-            Ok(set) => BlockedFileMatcher(set),
+            Ok(set) => BlockedFileMatcher {
+                set,
+                patterns: compiled,
+            },
             Err(e) => {
                 // Building a GlobSet from already-individually-valid Globs is
                 // not expected to fail in practice, but GlobSetBuilder::build
@@ -129,16 +187,97 @@ impl BlockedFileMatcher {
 
     /// A matcher that matches nothing.
     pub fn empty() -> Self {
-        BlockedFileMatcher(GlobSet::empty())
+        BlockedFileMatcher {
+            set: GlobSet::empty(),
+            patterns: Vec::new(),
+        }
     }
 
+    /// Whether `path` is blocked for a user who isn't on any allow list.
     /// `path` must be repo-relative and forward-slash separated.
     pub fn is_blocked(&self, path: &str) -> bool {
-        self.0.is_match(path)
+        self.blocking_pattern(path, "").is_some()
+    }
+
+    /// The pattern that blocks `path` for `username`, if any. An empty
+    /// username is never on an allow list.
+    pub fn blocking_pattern(&self, path: &str, username: &str) -> Option<&BlockedFilePattern> {
+        let pattern = &self.patterns[self.deciding_pattern(path)?];
+        if pattern.negated || pattern.allows(username) {
+            return None;
+        }
+
+        Some(pattern)
+    }
+
+    // Index of the last pattern matching `path`, which has the final say on it
+    fn deciding_pattern(&self, path: &str) -> Option<usize> {
+        self.set.matches(path).into_iter().max()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.set.is_empty()
+    }
+}
+
+/// Dry-runs `globs` against `paths`, for trying out a pattern list before it's committed to
+/// `friendshipper.yaml`. The caller fills in the branch and commit the paths came from.
+pub fn audit(globs: &[BlockedFileGlob], paths: &[String], username: &str) -> BlockedFileAudit {
+    let result = BlockedFileMatcher::compile(globs);
+    let matcher = &result.matcher;
+
+    let mut files: Vec<Vec<String>> = vec![Vec::new(); matcher.patterns.len()];
+    let mut matched_any = vec![false; matcher.patterns.len()];
+    let mut blocked_files = 0;
+    for path in paths {
+        let matches = matcher.set.matches(path);
+        for &index in &matches {
+            matched_any[index] = true;
+        }
+        if let Some(&last) = matches.iter().max() {
+            files[last].push(path.clone());
+        }
+        if matcher.blocking_pattern(path, username).is_some() {
+            blocked_files += 1;
+        }
+    }
+
+    let unmatched_patterns: Vec<String> = matcher
+        .patterns
+        .iter()
+        .zip(&matched_any)
+        .filter(|(_, matched)| !**matched)
+        .map(|(pattern, _)| pattern.pattern.clone())
+        .collect();
+    let diagnostics = |list: &[(String, String)]| -> Vec<BlockedFilePatternDiagnostic> {
+        list.iter()
+            .map(|(pattern, message)| BlockedFilePatternDiagnostic {
+                pattern: pattern.clone(),
+                message: message.clone(),
+            })
+            .collect()
+    };
+
+    BlockedFileAudit {
+        total_files: paths.len(),
+        blocked_files,
+        patterns: matcher
+            .patterns
+            .iter()
+            .zip(files)
+            .map(|(pattern, files)| BlockedFilePatternAudit {
+                pattern: pattern.pattern.clone(),
+                negated: pattern.negated,
+                allowed_users: pattern.allowed_users.clone(),
+                reason: pattern.reason.clone(),
+                files,
+            })
+            .collect(),
+        ok: result.invalid.is_empty() && unmatched_patterns.is_empty(),
+        unmatched_patterns,
+        invalid: diagnostics(&result.invalid),
+        warnings: diagnostics(&result.warnings),
+        ..Default::default()
     }
 }
 
@@ -146,8 +285,8 @@ impl BlockedFileMatcher {
 mod tests {
     use super::*;
 
-    fn patterns(list: &[&str]) -> Vec<String> {
-        list.iter().map(|s| s.to_string()).collect()
+    fn patterns(list: &[&str]) -> Vec<BlockedFileGlob> {
+        list.iter().map(|s| (*s).into()).collect()
     }
 
     // -- Literal, *, **, ?, {a,b}, and [a-z] patterns -----------------------
@@ -276,6 +415,154 @@ mod tests {
         assert!(result.matcher.is_blocked("Content/Foo.uasset"));
     }
 
+    // -- Ordered negation, allow lists and reasons ---------------------------
+
+    fn rule(pattern: &str, allowed_users: &[&str], reason: Option<&str>) -> BlockedFileGlob {
+        BlockedFileGlob::Rule(crate::types::config::BlockedFileRule {
+            pattern: pattern.to_string(),
+            allowed_users: allowed_users.iter().map(|s| s.to_string()).collect(),
+            reason: reason.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn negated_pattern_unblocks_what_an_earlier_one_blocked() {
+        let result = BlockedFileMatcher::compile(&patterns(&[
+            "Content/Characters/**",
+            "!Content/Characters/Shared/**",
+        ]));
+        assert!(result.invalid.is_empty());
+        assert!(result.matcher.is_blocked("Content/Characters/Hero.uasset"));
+        assert!(!result
+            .matcher
+            .is_blocked("Content/Characters/Shared/Rig.uasset"));
+    }
+
+    #[test]
+    fn last_matching_pattern_wins() {
+        let result = BlockedFileMatcher::compile(&patterns(&[
+            "Content/**",
+            "!Content/Characters/**",
+            "Content/Characters/Hero/**",
+        ]));
+        assert!(result.matcher.is_blocked("Content/Maps/Main.umap"));
+        assert!(!result
+            .matcher
+            .is_blocked("Content/Characters/Villain.uasset"));
+        assert!(result
+            .matcher
+            .is_blocked("Content/Characters/Hero/Hero.uasset"));
+    }
+
+    #[test]
+    fn negation_alone_blocks_nothing() {
+        let result = BlockedFileMatcher::compile(&patterns(&["!Content/**"]));
+        assert!(!result.matcher.is_blocked("Content/Foo.uasset"));
+    }
+
+    #[test]
+    fn allowed_users_are_let_through_case_insensitively() {
+        let result = BlockedFileMatcher::compile(&[rule(
+            "Content/Characters/**",
+            &["TechArtist"],
+            Some("Characters are owned by tech art"),
+        )]);
+        let path = "Content/Characters/Hero.uasset";
+
+        assert!(result.matcher.is_blocked(path));
+        assert!(result
+            .matcher
+            .blocking_pattern(path, "techartist")
+            .is_none());
+        let blocking = result
+            .matcher
+            .blocking_pattern(path, "someone")
+            .expect("blocked for users not on the list");
+        assert_eq!(blocking.pattern, "Content/Characters/**");
+        assert_eq!(
+            blocking.reason.as_deref(),
+            Some("Characters are owned by tech art")
+        );
+    }
+
+    #[test]
+    fn later_pattern_without_allow_list_still_blocks_allowed_user() {
+        let result = BlockedFileMatcher::compile(&[
+            rule("Content/**", &["techartist"], None),
+            "Content/Maps/**".into(),
+        ]);
+        assert!(result
+            .matcher
+            .blocking_pattern("Content/Props/Crate.uasset", "techartist")
+            .is_none());
+        assert!(result
+            .matcher
+            .blocking_pattern("Content/Maps/Main.umap", "techartist")
+            .is_some());
+    }
+
+    #[test]
+    fn warnings_carry_over_to_negated_patterns() {
+        let result = BlockedFileMatcher::compile(&[
+            "!*.uasset".into(),
+            "!Content\\Shared\\**".into(),
+            rule("!Content/Shared/**", &["someone"], None),
+        ]);
+        assert!(result.invalid.is_empty());
+        let warned: Vec<&str> = result.warnings.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(
+            warned,
+            vec!["!*.uasset", "!Content\\Shared\\**", "!Content/Shared/**"]
+        );
+    }
+
+    // -- Dry-run audit ---------------------------------------------------------
+
+    #[test]
+    fn audit_groups_files_by_the_pattern_that_decides_them() {
+        let tree: Vec<String> = [
+            "Content/Characters/Hero.uasset",
+            "Content/Characters/Shared/Rig.uasset",
+            "Content/Maps/Main.umap",
+            "Source/Game.cpp",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let result = audit(
+            &patterns(&[
+                "Content/**",
+                "!Content/Characters/Shared/**",
+                "Config/**",
+                "Content/[broken",
+            ]),
+            &tree,
+            "",
+        );
+
+        assert_eq!(result.total_files, 4);
+        assert_eq!(result.blocked_files, 2);
+        assert_eq!(result.patterns.len(), 3);
+        assert_eq!(
+            result.patterns[0].files,
+            vec!["Content/Characters/Hero.uasset", "Content/Maps/Main.umap"]
+        );
+        assert!(result.patterns[1].negated);
+        assert_eq!(
+            result.patterns[1].files,
+            vec!["Content/Characters/Shared/Rig.uasset"]
+        );
+        assert_eq!(result.unmatched_patterns, vec!["Config/**"]);
+        assert_eq!(result.invalid.len(), 1);
+        assert_eq!(result.invalid[0].pattern, "Content/[broken");
+        assert!(!result.ok);
+
+        let clean = audit(&patterns(&["Content/**"]), &tree, "");
+        assert!(clean.ok);
+        assert!(clean.warnings.is_empty());
+    }
+
     // -- Invalid patterns are skipped, valid siblings still match -----------
 
     #[test]
@@ -7,6 +7,7 @@ use aws_credential_types::{provider::SharedCredentialsProvider, Credentials};
 use aws_sdk_ecr::{types::ImageIdentifier, Client as EcrClient};
 use aws_sdk_eks::Client as EksClient;
 use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{Delete, ObjectIdentifier};
 use aws_sdk_s3::Client as S3Client;
 use aws_sigv4::http_request::{SignableBody, SignableRequest, SignatureLocation, SigningSettings};
 use aws_smithy_runtime::client::http::hyper_014::HyperClientBuilder;
@@ -16,7 +17,6 @@ use aws_types::SdkConfig;
 use base64::prelude::{Engine as _, BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD};
 use bytes::Buf;
 use chrono::{DateTime, Utc};
-use directories_next::BaseDirs;
 use http::Request;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
@@ -25,6 +25,9 @@ use tracing::{debug, error, instrument};
 use crate::types::config::DynamicConfig;
 use crate::types::errors::CoreError;
 
+// S3 DeleteObjects accepts at most this many keys per request
+const S3_DELETE_BATCH_SIZE: usize = 1000;
+
 #[derive(Debug, Clone)]
 pub struct AWSAuthContext {
     pub credentials: Credentials,
@@ -155,11 +158,8 @@ impl AWSClient {
         let bytes = resp.body.collect().await?.into_bytes();
         let body = std::str::from_utf8(&bytes)?;
         let config: serde_json::Value = serde_json::from_str(body)?;
-        let app_name = "Friendshipper";
-
-        let config_override: Option<Result<DynamicConfig, anyhow::Error>> = BaseDirs::new()
-            .and_then(|b| {
-                let override_file = b.config_dir().join(app_name).join("dynamic-config.json");
+        let config_override: Option<Result<DynamicConfig, anyhow::Error>> =
+            DynamicConfig::override_path().and_then(|override_file| {
                 debug!(
                     "Checking if we should load dynamic config from {:?}",
                     override_file
@@ -371,6 +371,53 @@ impl AWSClient {
         Ok(object_key)
     }
 
+    // Deletes the given keys, batched to stay under the S3 limit of 1000 keys per request.
+    // Returns the number of keys deleted.
+    #[instrument(skip(self, keys), fields(count = keys.len()), err)]
+    pub async fn delete_objects(&self, bucket: &str, keys: &[String]) -> Result<usize, CoreError> {
+        let client = S3Client::new(&self.get_sdk_config().await);
+        let mut deleted = 0;
+
+        for batch in keys.chunks(S3_DELETE_BATCH_SIZE) {
+            let objects = batch
+                .iter()
+                .map(|key| ObjectIdentifier::builder().key(key).build())
+                .collect::<Result<Vec<_>, _>>()?;
+            let delete = Delete::builder()
+                .set_objects(Some(objects))
+                .quiet(true)
+                .build()?;
+
+            let res = client
+                .delete_objects()
+                .bucket(bucket)
+                .delete(delete)
+                .send()
+                .await
+                .map_err(|e| {
+                    CoreError::Internal(anyhow!(
+                        "Failed to delete objects from S3: {}",
+                        e.into_service_error()
+                    ))
+                })?;
+
+            // in quiet mode only failures are reported back
+            if let Some(err) = res.errors().first() {
+                return Err(CoreError::Internal(anyhow!(
+                    "Failed to delete {} of {} objects from S3, first error on {:?}: {:?}",
+                    res.errors().len(),
+                    batch.len(),
+                    err.key(),
+                    err.message()
+                )));
+            }
+
+            deleted += batch.len();
+        }
+
+        Ok(deleted)
+    }
+
     // Ported from: https://github.com/awslabs/aws-sdk-rust/issues/980#issuecomment-1859340980
     #[instrument(skip_all)]
     pub async fn generate_k8s_token(&self, cluster_name: &str, region: &str) -> Result<String> {
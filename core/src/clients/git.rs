@@ -1,9 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{anyhow, bail};
 use chrono::{DateTime, Utc};
@@ -17,16 +18,45 @@ use tokio::io::AsyncBufReadExt;
 use tokio::io::BufReader;
 use tokio::process::Command;
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 use tracing::warn;
 use tracing::{debug, error, info, instrument};
 
+use crate::clients::git_read::{
+    snapshot_commit_message, CliReadBackend, GitReadBackend, InProcessReadBackend, LogEntry,
+    ReadBackend,
+};
+use crate::content_profiles::ContentProfileMatcher;
+use crate::msg::GitProgress;
+use crate::types::config::ContentProfile;
 use crate::types::errors::CoreError;
 use crate::types::locks::VerifyLocksResponse;
 use crate::types::repo::File;
 use crate::types::repo::FileState;
-use crate::types::repo::Snapshot;
-
-static SNAPSHOT_PREFIX: &str = "snapshot";
+use crate::types::repo::IndexLockStatus;
+use crate::types::repo::{
+    ConflictBlob, ConflictMergePreview, ConflictSide, LfsFileInfo, RebaseConflictFile,
+    RebaseConflictsResponse,
+};
+use crate::types::repo::{
+    Snapshot, SnapshotConflictResolution, SnapshotFileRestore, SnapshotFileRestoreOutcome,
+    SnapshotFileRestoreResult, SnapshotKind, SnapshotPrunePlan, SnapshotRetention,
+};
+use crate::utils::process::kill_process_tree;
+use crate::worker::sequence_cancel_token;
+
+pub(crate) static SNAPSHOT_PREFIX: &str = "snapshot";
+pub(crate) static SNAPSHOT_REF_PREFIX: &str = "refs/friendshipper/snapshots/";
+static CONTENT_PROFILE_CONFIG_KEY: &str = "friendshipper.contentProfile";
+
+// Long enough for a slow fetch over a big history, short enough that a hung remote frees the
+// worker queue eventually
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const REFETCH_TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+const MAINTENANCE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+// A lock younger than this may belong to a git that was only just spawned
+const STALE_INDEX_LOCK_MIN_AGE: Duration = Duration::from_secs(5);
 
 lazy_static! {
     static ref WORKTREE_DIR_REGEX: Regex = Regex::new(r"^worktree (.+)").unwrap();
@@ -97,6 +127,35 @@ pub enum LfsMode {
 pub struct Git {
     pub repo_path: PathBuf,
     pub tx: std::sync::mpsc::Sender<String>,
+
+    // Typed progress parsed from git and git-lfs stderr. Without it progress lines are only logged.
+    pub progress_tx: Option<std::sync::mpsc::Sender<GitProgress>>,
+
+    // Where read-only queries like current_branch and log are answered
+    pub read_backend: ReadBackend,
+}
+
+// Runs a read-only query against the configured backend. In-process failures are retried with the
+// CLI, so callers see the same results and errors either way.
+macro_rules! read_query {
+    ($git:expr, $method:ident($($arg:expr),*)) => {{
+        let git = $git;
+        let mut result = None;
+        if git.read_backend == ReadBackend::InProcess {
+            match InProcessReadBackend::new(git.repo_path.clone()).$method($($arg),*).await {
+                Ok(value) => result = Some(value),
+                Err(e) => debug!(
+                    "In-process git {} failed, falling back to the CLI: {:?}",
+                    stringify!($method),
+                    e
+                ),
+            }
+        }
+        match result {
+            Some(value) => Ok(value),
+            None => CliReadBackend::new(git.clone()).$method($($arg),*).await,
+        }
+    }};
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -106,6 +165,9 @@ pub struct Opts<'a> {
     pub return_complete_error: bool,
     pub lfs_mode: LfsMode,
     pub skip_notify_frontend: bool,
+    // Takes precedence over the token of the repo worker sequence the command runs in
+    pub cancel: Option<&'a CancellationToken>,
+    pub timeout: Option<Duration>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -113,6 +175,7 @@ pub struct WorktreeInfo {
     pub directory: PathBuf,
     pub sha: String,
     pub branch: Option<String>, // if None, it is detached
+    pub is_main: bool,
 }
 
 impl Default for Opts<'_> {
@@ -123,6 +186,8 @@ impl Default for Opts<'_> {
             return_complete_error: false,
             lfs_mode: LfsMode::Inflated,
             skip_notify_frontend: false,
+            cancel: None,
+            timeout: None,
         }
     }
 }
@@ -135,6 +200,8 @@ impl Opts<'_> {
             return_complete_error: false,
             lfs_mode: LfsMode::Inflated,
             skip_notify_frontend: false,
+            cancel: None,
+            timeout: None,
         }
     }
 
@@ -145,6 +212,8 @@ impl Opts<'_> {
             return_complete_error: false,
             lfs_mode: LfsMode::Inflated,
             skip_notify_frontend: false,
+            cancel: None,
+            timeout: None,
         }
     }
 
@@ -155,6 +224,8 @@ impl Opts<'_> {
             return_complete_error: true,
             lfs_mode: LfsMode::Inflated,
             skip_notify_frontend: false,
+            cancel: None,
+            timeout: None,
         }
     }
 
@@ -172,6 +243,121 @@ impl Opts<'_> {
         self.skip_notify_frontend = true;
         self
     }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl<'a> Opts<'a> {
+    pub fn with_cancel(mut self, cancel: &'a CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+}
+
+// The subcommand of a git invocation, skipping global options like `-c key=value`
+fn git_subcommand<'a>(args: &[&'a str]) -> &'a str {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match *arg {
+            "-c" | "-C" => {
+                iter.next();
+            }
+            arg if arg.starts_with('-') => {}
+            arg => return arg,
+        }
+    }
+    args.first().copied().unwrap_or_default()
+}
+
+// Git processes running from inside `repo_path`, which is where git runs from whether we, an IDE
+// or a shell started it
+fn git_processes_in(repo_path: &Path) -> Vec<u32> {
+    let Ok(repo_path) = repo_path.canonicalize() else {
+        return vec![];
+    };
+
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessRefreshKind::new().with_cwd(UpdateKind::Always));
+    system
+        .processes()
+        .iter()
+        .filter(|(_, process)| {
+            process.thread_kind().is_none()
+                && process.name().to_lowercase().starts_with("git")
+                && process
+                    .cwd()
+                    .and_then(|cwd| cwd.canonicalize().ok())
+                    .is_some_and(|cwd| cwd.starts_with(&repo_path))
+        })
+        .map(|(pid, _)| pid.as_u32())
+        .collect()
+}
+
+async fn wait_for_cancel(cancel: Option<&CancellationToken>) {
+    match cancel {
+        Some(cancel) => cancel.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn wait_for_timeout(timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => std::future::pending().await,
+    }
+}
+
+// Progress updates are forwarded as they come but only the last one of each phase is kept and
+// logged, so the log and error output don't fill up with hundreds of redraws.
+fn handle_stderr_line(
+    line: &str,
+    operation: &str,
+    progress_tx: &Option<std::sync::mpsc::Sender<GitProgress>>,
+    err_lines: &RwLock<Vec<String>>,
+) {
+    let line = line.trim_end();
+    if line.is_empty() {
+        return;
+    }
+
+    if let Some(progress) = GitProgress::parse(operation, line) {
+        let finished = progress.is_complete() || line.ends_with("done.");
+        if let Some(tx) = progress_tx {
+            let _ = tx.send(progress);
+        }
+        if !finished {
+            return;
+        }
+    }
+
+    err_lines.write().push(line.to_string());
+    info!("{}", line);
+}
+
+// Trimmed, without empties or duplicates, in the order given
+fn normalize_snapshot_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = vec![];
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+// Quick submit stages its commits in a hidden sibling of the main worktree so the user's
+// worktrees are never touched
+pub fn quick_submit_worktree_path(main_worktree: &Path) -> PathBuf {
+    let folder_name = main_worktree
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    main_worktree.with_file_name(format!(".{folder_name}-wt"))
 }
 
 pub fn parse_bool_string(bool_str: &str) -> anyhow::Result<bool> {
@@ -186,7 +372,22 @@ pub fn parse_bool_string(bool_str: &str) -> anyhow::Result<bool> {
 
 impl Git {
     pub fn new(repo_path: PathBuf, tx: std::sync::mpsc::Sender<String>) -> Git {
-        Git { repo_path, tx }
+        Git {
+            repo_path,
+            tx,
+            progress_tx: None,
+            read_backend: ReadBackend::default(),
+        }
+    }
+
+    pub fn with_progress_tx(mut self, progress_tx: std::sync::mpsc::Sender<GitProgress>) -> Self {
+        self.progress_tx = Some(progress_tx);
+        self
+    }
+
+    pub fn with_read_backend(mut self, read_backend: ReadBackend) -> Self {
+        self.read_backend = read_backend;
+        self
     }
 
     pub async fn head_commit(
@@ -224,6 +425,10 @@ impl Git {
         // In the event that a fetch is already running, we will ignore the prune flag and return
         // after the current fetch completes. This should not be a problem, as the next fetch that
         // is not under contention will respect the prune flag.
+        let opts = Opts {
+            timeout: opts.timeout.or(Some(FETCH_TIMEOUT)),
+            ..opts
+        };
         if let Ok(mut running) = GIT_FETCH_LOCK.clone().try_lock_owned() {
             *running = true;
             if prune == ShouldPrune::Yes {
@@ -266,7 +471,7 @@ impl Git {
         let current_branch = self.current_branch().await?;
 
         // TODO: handle alternative remotes
-        let mut args = vec!["pull", "origin", &current_branch];
+        let mut args = vec!["pull", "--progress", "origin", &current_branch];
 
         match pull_strategy {
             PullStrategy::Rebase => args.push("--rebase"),
@@ -289,16 +494,99 @@ impl Git {
         self.run(&["push", "origin", branch], Opts::default()).await
     }
 
-    pub async fn wait_for_lock(&self) {
-        let index_lock_path = self.repo_path.join(".git/index.lock");
+    /// Waits for another git to release the index. Fails instead of waiting forever when the
+    /// lock turns out to be stale or the running sequence is cancelled.
+    pub async fn wait_for_lock(&self) -> anyhow::Result<()> {
+        let index_lock_path = self.index_lock_path()?;
+        let mut polls: u32 = 0;
         while index_lock_path.exists() {
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            if sequence_cancel_token().is_some_and(|cancel| cancel.is_cancelled()) {
+                bail!("Cancelled while waiting for {}", index_lock_path.display());
+            }
+
+            // checking for a live git walks the process list, so only do it every couple seconds
+            if polls % 20 == 19 && self.index_lock_status()?.stale {
+                bail!(
+                    "{} was left behind by a git process that is no longer running. Remove it from Diagnostics to continue.",
+                    index_lock_path.display()
+                );
+            }
+
+            polls += 1;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        Ok(())
+    }
+
+    // Resolved by hand rather than with `rev-parse --git-path` so it still works once the running
+    // sequence is cancelled and git won't start. A linked worktree's .git is a file pointing at
+    // its git dir.
+    fn index_lock_path(&self) -> anyhow::Result<PathBuf> {
+        let dot_git = self.repo_path.join(".git");
+        if dot_git.is_file() {
+            let contents = std::fs::read_to_string(&dot_git)?;
+            let Some(git_dir) = contents.trim().strip_prefix("gitdir:") else {
+                bail!("Unexpected contents in {}", dot_git.display());
+            };
+            return Ok(self.repo_path.join(git_dir.trim()).join("index.lock"));
+        }
+
+        Ok(dot_git.join("index.lock"))
+    }
+
+    pub fn index_lock_status(&self) -> anyhow::Result<IndexLockStatus> {
+        self.index_lock_status_with_min_age(STALE_INDEX_LOCK_MIN_AGE)
+    }
+
+    fn index_lock_status_with_min_age(&self, min_age: Duration) -> anyhow::Result<IndexLockStatus> {
+        let path = self.index_lock_path()?;
+        let mut status = IndexLockStatus {
+            path: path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+
+        let metadata = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(status),
+            Err(e) => return Err(e.into()),
+        };
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .unwrap_or_default();
+
+        status.exists = true;
+        status.age_seconds = age.as_secs();
+        status.git_processes = git_processes_in(&self.repo_path);
+        status.stale = status.git_processes.is_empty() && age >= min_age;
+        Ok(status)
+    }
+
+    /// Removes the index lock if it is stale. Returns whether there was one to remove.
+    pub fn remove_stale_index_lock(&self) -> anyhow::Result<bool> {
+        self.remove_index_lock_if_stale(STALE_INDEX_LOCK_MIN_AGE)
+    }
+
+    fn remove_index_lock_if_stale(&self, min_age: Duration) -> anyhow::Result<bool> {
+        let status = self.index_lock_status_with_min_age(min_age)?;
+        if !status.stale {
+            return Ok(false);
+        }
+
+        warn!("Removing stale {}", status.path);
+        match std::fs::remove_file(&status.path) {
+            Ok(_) => Ok(true),
+            // released in the meantime
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
         }
     }
 
     pub async fn hard_reset(&self, branch: &str) -> anyhow::Result<()> {
         // if .git/index.lock exists, wait for it to be gone
-        self.wait_for_lock().await;
+        self.wait_for_lock().await?;
 
         self.run(&["reset", "--hard"], Opts::default()).await?;
         self.run(&["clean", "-fd"], Opts::default()).await?;
@@ -332,54 +620,20 @@ impl Git {
         Ok(!output.contains("No local changes to save"))
     }
 
+    /// Snapshots under `refs/friendshipper/snapshots/`, newest first. Snapshots
+    /// older versions saved to the stash list only show up here once
+    /// `migrate_stash_snapshots` has moved them.
     pub async fn list_snapshots(&self) -> anyhow::Result<Vec<Snapshot>> {
-        let output = self
-            .run_and_collect_output(
-                &["stash", "list", "--pretty=format:%gd|%gs|%H|%aI"],
-                Opts::new_without_logs(),
-            )
-            .await?;
-
-        let snapshots = output
-            .lines()
-            .filter_map(|line| {
-                if !line.contains(SNAPSHOT_PREFIX) {
-                    return None;
-                }
-
-                let parts = line.split('|').collect::<Vec<_>>();
-                if parts.len() < 4 {
-                    debug!("Skipping line due to bad parse: {}", line);
-                    return None;
-                }
-
-                let stash_index = parts[0].trim();
-                // Split only at the first occurrence so messages that themselves contain
-                // the word "snapshot" (e.g. "Auto-snapshot before importing X.zip") are
-                // preserved in full instead of being truncated at the embedded match.
-                let message = match parts[1].split_once(SNAPSHOT_PREFIX) {
-                    Some((_, m)) => m.trim(),
-                    None => return None,
-                };
-                let commit = parts[2].trim();
-                let date = parts[3].trim();
-
-                match DateTime::parse_from_rfc3339(date) {
-                    Ok(date) => Some(Snapshot {
-                        commit: commit.to_string(),
-                        message: message.to_string(),
-                        timestamp: date.with_timezone(&Utc),
-                        stash_index: stash_index.to_string(),
-                    }),
-                    Err(e) => {
-                        info!("Failed to parse date: {}", e);
-                        None
-                    }
-                }
-            })
-            .collect();
+        read_query!(self, list_snapshots())
+    }
 
-        Ok(snapshots)
+    /// Look a snapshot up by id, or by commit for callers that still hold one.
+    pub async fn find_snapshot(&self, id: &str) -> anyhow::Result<Snapshot> {
+        self.list_snapshots()
+            .await?
+            .into_iter()
+            .find(|s| s.id == id || s.commit == id)
+            .ok_or_else(|| anyhow!("Snapshot {} not found", id))
     }
 
     #[instrument(name = "save_snapshot_all", skip_all, fields(message))]
@@ -387,59 +641,258 @@ impl Git {
         self.save_snapshot(message, vec![]).await
     }
 
+    /// Save an automatic snapshot, taken on the user's behalf before an
+    /// operation that may clobber their changes.
     #[instrument(name = "save_snapshot", skip_all, fields(message))]
     pub async fn save_snapshot(
         &self,
         message: &str,
         paths: Vec<String>,
     ) -> anyhow::Result<Snapshot> {
-        self.wait_for_lock().await;
+        self.save_tagged_snapshot(message, paths, SnapshotKind::Automatic, vec![])
+            .await
+    }
+
+    #[instrument(name = "save_tagged_snapshot", skip_all, fields(message))]
+    pub async fn save_tagged_snapshot(
+        &self,
+        message: &str,
+        paths: Vec<String>,
+        kind: SnapshotKind,
+        tags: Vec<String>,
+    ) -> anyhow::Result<Snapshot> {
+        self.wait_for_lock().await?;
 
         let stash_message = format!("{SNAPSHOT_PREFIX} {message}");
         let snapshot_commit = self.build_snapshot_commit(&paths, &stash_message).await?;
 
+        // Build the Snapshot from data we already have rather than searching
+        // for the just-written ref in `list_snapshots`.
+        let timestamp = Utc::now();
+        let mut snapshot = Snapshot {
+            id: Self::snapshot_id(timestamp, &snapshot_commit),
+            message: message.to_string(),
+            timestamp,
+            branch: self.current_branch().await.unwrap_or_default(),
+            files: self.get_files_in_snapshot(&snapshot_commit).await?,
+            kind,
+            tags: normalize_snapshot_tags(tags),
+            ..Default::default()
+        };
+        self.write_snapshot_ref(&mut snapshot, &snapshot_commit, None)
+            .await?;
+
+        Ok(snapshot)
+    }
+
+    /// Split the snapshots past `retention` into those whose changes are all
+    /// in HEAD already and those with files that still differ from HEAD.
+    pub async fn plan_snapshot_prune(
+        &self,
+        retention: &SnapshotRetention,
+    ) -> anyhow::Result<SnapshotPrunePlan> {
+        let snapshots = self.list_snapshots().await?;
+
+        let mut plan = SnapshotPrunePlan::default();
+        for snapshot in retention.expired(&snapshots, Utc::now()) {
+            let changed = self
+                .diff_filenames(&format!("HEAD..{}", snapshot.commit))
+                .await?;
+            if changed.iter().any(|path| snapshot.files.contains(path)) {
+                plan.differs_from_head.push(snapshot.clone());
+            } else {
+                plan.prunable.push(snapshot.clone());
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Delete the snapshots past `retention`. Ones with files that still
+    /// differ from HEAD are only deleted when their id is in `confirmed`, so
+    /// work that never made it into a commit isn't silently thrown away.
+    pub async fn prune_snapshots(
+        &self,
+        retention: &SnapshotRetention,
+        confirmed: &[String],
+    ) -> anyhow::Result<Vec<Snapshot>> {
+        let plan = self.plan_snapshot_prune(retention).await?;
+        let to_prune = plan.prunable.into_iter().chain(
+            plan.differs_from_head
+                .into_iter()
+                .filter(|s| confirmed.contains(&s.id)),
+        );
+
+        let mut pruned = vec![];
+        for snapshot in to_prune {
+            // Pinned to the commit the plan saw, so a snapshot retagged in the
+            // meantime survives.
+            self.run(
+                &[
+                    "update-ref",
+                    "-d",
+                    &Self::snapshot_ref(&snapshot.id),
+                    &snapshot.commit,
+                ],
+                Opts::default(),
+            )
+            .await?;
+            pruned.push(snapshot);
+        }
+
+        if !pruned.is_empty() {
+            info!("Pruned {} snapshots", pruned.len());
+        }
+        Ok(pruned)
+    }
+
+    /// Replace a snapshot's tags. Its id is unchanged, but it's rewritten to a
+    /// new commit since the tags live in the commit message.
+    pub async fn set_snapshot_tags(&self, id: &str, tags: Vec<String>) -> anyhow::Result<Snapshot> {
+        let mut snapshot = self.find_snapshot(id).await?;
+        let old_commit = snapshot.commit.clone();
+        snapshot.tags = normalize_snapshot_tags(tags);
+        self.write_snapshot_ref(&mut snapshot, &old_commit, Some(&old_commit))
+            .await?;
+        Ok(snapshot)
+    }
+
+    /// Push a snapshot's ref to origin, so it survives the machine or can be
+    /// handed off to a teammate by id. Forced, since editing tags rewrites
+    /// the snapshot's commit.
+    pub async fn push_snapshot(&self, id: &str) -> anyhow::Result<Snapshot> {
+        let snapshot = self.find_snapshot(id).await?;
+        let ref_name = Self::snapshot_ref(&snapshot.id);
         self.run(
-            &[
-                "stash",
-                "store",
-                "--message",
-                &stash_message,
-                &snapshot_commit,
-            ],
+            &["push", "origin", &format!("+{ref_name}:{ref_name}")],
             Opts::default(),
         )
         .await?;
+        Ok(snapshot)
+    }
 
-        // Build the Snapshot from data we already have rather than
-        // searching for our just-stored entry in `list_snapshots`.
-        // The lookup was platform-fragile — `git stash list` against the
-        // reflog-subject formatting from `git stash store --message ...`
-        // doesn't behave the same across git versions, and on some
-        // Linux builds the entry doesn't pass our SNAPSHOT_PREFIX filter
-        // even when the store itself succeeded. `git stash store` always
-        // pushes to the top of refs/stash, so the just-stored snapshot
-        // is at stash@{0} until anything else touches the stash list.
-        let new_snapshot = Snapshot {
-            commit: snapshot_commit,
-            message: message.to_string(),
-            timestamp: Utc::now(),
-            stash_index: "stash@{0}".to_string(),
-        };
+    /// Fetch a snapshot someone else pushed to origin into the local list.
+    pub async fn fetch_snapshot(&self, id: &str) -> anyhow::Result<Snapshot> {
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!("Invalid snapshot id {}", id);
+        }
 
-        // Keep at most 25 snapshots — drop the oldest if we exceeded.
-        // Best-effort: if list_snapshots hiccups for any reason we'd
-        // rather skip pruning than fail the save itself.
-        if let Ok(snapshots) = self.list_snapshots().await {
-            if snapshots.len() > 25 {
-                if let Some(snapshot) = snapshots.get(25) {
-                    let _ = self
-                        .run(&["stash", "drop", &snapshot.stash_index], Opts::default())
-                        .await;
-                }
-            }
+        let ref_name = Self::snapshot_ref(id);
+        self.run(
+            &["fetch", "origin", &format!("+{ref_name}:{ref_name}")],
+            Opts::default(),
+        )
+        .await?;
+        self.find_snapshot(id).await
+    }
+
+    fn snapshot_ref(id: &str) -> String {
+        format!("{SNAPSHOT_REF_PREFIX}{id}")
+    }
+
+    // Sortable and unique without coordination: when the snapshot was taken,
+    // then the commit it was first saved as
+    fn snapshot_id(timestamp: DateTime<Utc>, commit: &str) -> String {
+        let short_commit: String = commit.chars().take(8).collect();
+        format!("{}-{short_commit}", timestamp.format("%Y%m%d-%H%M%S"))
+    }
+
+    /// Store `snapshot` under its ref as a copy of `source_commit` (same tree
+    /// and parents, so cherry-pick restores keep working) carrying the
+    /// snapshot's metadata in its message. When `old_commit` is set the ref
+    /// is only moved if it still points there. Updates `snapshot.commit` and
+    /// `snapshot.author`.
+    async fn write_snapshot_ref(
+        &self,
+        snapshot: &mut Snapshot,
+        source_commit: &str,
+        old_commit: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let output = self
+            .run_and_collect_output(
+                &["show", "-s", "--format=%T%n%P%n%an", source_commit],
+                Opts::new_without_logs(),
+            )
+            .await?;
+        let mut lines = output.lines();
+        let tree = lines
+            .next()
+            .filter(|tree| !tree.is_empty())
+            .ok_or_else(|| anyhow!("Unable to read snapshot commit {}", source_commit))?;
+        let parents = lines.next().unwrap_or_default();
+        snapshot.author = lines.next().unwrap_or_default().to_string();
+
+        let message = snapshot_commit_message(snapshot)?;
+        let mut args = vec!["commit-tree", tree];
+        for parent in parents.split_whitespace() {
+            args.extend(["-p", parent]);
+        }
+        args.extend(["-m", message.as_str()]);
+        let commit = self
+            .run_and_collect_output(&args, Opts::new_without_logs())
+            .await?
+            .trim()
+            .to_string();
+
+        let ref_name = Self::snapshot_ref(&snapshot.id);
+        let mut args = vec!["update-ref", ref_name.as_str(), commit.as_str()];
+        args.extend(old_commit);
+        self.run(&args, Opts::default()).await?;
+
+        snapshot.commit = commit;
+        Ok(())
+    }
+
+    /// Move snapshots older versions saved to the stash list under
+    /// `refs/friendshipper/snapshots/`. Ids are derived from the stash entry,
+    /// so a migration interrupted between writing the ref and dropping the
+    /// stash picks up where it left off instead of duplicating the snapshot.
+    /// Run once at startup; anything that fails to migrate stays in the stash
+    /// list and is retried on the next launch.
+    pub async fn migrate_stash_snapshots(&self) -> anyhow::Result<()> {
+        let stashed = read_query!(self, list_stash_snapshots())?;
+        if stashed.is_empty() {
+            return Ok(());
         }
 
-        Ok(new_snapshot)
+        self.wait_for_lock().await?;
+        let migrated = read_query!(self, list_snapshots())?
+            .into_iter()
+            .map(|s| s.id)
+            .collect::<HashSet<_>>();
+
+        // Oldest first, so dropping stash@{n} never shifts the index of an
+        // entry still to go.
+        for stash in stashed.into_iter().rev() {
+            let mut snapshot = Snapshot {
+                id: Self::snapshot_id(stash.timestamp, &stash.commit),
+                kind: if stash.message.starts_with("pre-")
+                    || stash.message.starts_with("Auto-snapshot")
+                {
+                    SnapshotKind::Automatic
+                } else {
+                    SnapshotKind::Manual
+                },
+                files: self.get_files_in_snapshot(&stash.commit).await?,
+                stash_index: String::new(),
+                ..stash.clone()
+            };
+
+            if !migrated.contains(&snapshot.id) {
+                self.write_snapshot_ref(&mut snapshot, &stash.commit, None)
+                    .await?;
+            }
+            self.run(&["stash", "drop", &stash.stash_index], Opts::default())
+                .await?;
+            info!(
+                "Migrated snapshot {} from {}",
+                snapshot.id, stash.stash_index
+            );
+        }
+        Ok(())
     }
 
     /// Build a stash-shaped commit that captures exactly the requested paths
@@ -616,14 +1069,17 @@ impl Git {
         Ok(temp_file.into_temp_path())
     }
 
-    pub async fn delete_snapshot(&self, commit: &str) -> anyhow::Result<()> {
+    pub async fn delete_snapshot(&self, id: &str) -> anyhow::Result<()> {
         let snapshots = self.list_snapshots().await?;
 
-        let snapshot = snapshots.iter().find(|s| s.commit == commit);
+        let snapshot = snapshots.iter().find(|s| s.id == id || s.commit == id);
 
         if let Some(snapshot) = snapshot {
-            self.run(&["stash", "drop", &snapshot.stash_index], Opts::default())
-                .await
+            self.run(
+                &["update-ref", "-d", &Self::snapshot_ref(&snapshot.id)],
+                Opts::default(),
+            )
+            .await
         } else {
             Ok(())
         }
@@ -689,7 +1145,7 @@ impl Git {
         commit: &str,
         currently_modified_files: Vec<File>,
     ) -> anyhow::Result<()> {
-        self.wait_for_lock().await;
+        self.wait_for_lock().await?;
 
         let snapshot_files = self.get_files_in_snapshot(commit).await?;
         let untracked_files = self.get_untracked_files().await?;
@@ -884,7 +1340,7 @@ impl Git {
         currently_local_files: &[File],
         overwrite_local: bool,
     ) -> anyhow::Result<()> {
-        self.wait_for_lock().await;
+        self.wait_for_lock().await?;
 
         // Before touching disk, refuse to clobber uncommitted local changes
         // (modified OR untracked) unless the caller explicitly opted in. The
@@ -989,65 +1445,269 @@ impl Git {
         Ok(())
     }
 
-    /// Hash a file the way `git add` would — applying the same clean
-    /// filters (`autocrlf`, `text`, `eol=`, custom clean drivers, LFS)
-    /// that staging would apply. `attr_path` is the repo-relative path
-    /// used for `.gitattributes` lookup; `file` is the actual on-disk
-    /// file to hash. Used by the cherry-pick restore path so a renamed
-    /// `.localcopy` and the cherry-picked extraction compare equal when
-    /// they're semantically identical modulo normalization, instead of
-    /// raw byte-equal.
-    async fn hash_object_with_attrs(&self, attr_path: &str, file: &Path) -> anyhow::Result<String> {
-        let file_str = file
-            .to_str()
-            .ok_or_else(|| anyhow!("hash-object path is non-UTF-8"))?;
-        let path_arg = format!("--path={attr_path}");
-        let out = self
-            .run_and_collect_output(
-                &["hash-object", &path_arg, "--", file_str],
-                Opts::new_without_logs(),
-            )
-            .await?;
-        Ok(out.trim().to_string())
-    }
-
-    /// Decide whether a file restored from a snapshot matches the user's
-    /// pre-restore `.localcopy` of the same path. Byte-equal files are
-    /// identical under any clean filter, and after an uneventful restore the
-    /// two are almost always byte-equal (LFS smudge reproduces the exact
-    /// bytes the snapshot captured) — so a raw comparison short-circuits the
-    /// common case without running the LFS clean filter, a full content
-    /// hash, over each side. Only on a byte mismatch do we fall back to
-    /// filter-aware hashing via `hash_object_with_attrs`, which keeps EOL
-    /// normalization (autocrlf, eol=, custom drivers) from registering as a
-    /// conflict. Any error on either side counts as "different" so the local
-    /// copy is preserved for review rather than silently dropped.
-    async fn restored_file_matches_local(
+    /// Restore a subset of a snapshot's files, resolving each diverged file
+    /// individually. A file has diverged when it has uncommitted local
+    /// changes and its working tree copy doesn't already match the
+    /// snapshot; those need a resolution, and the whole restore is refused
+    /// before touching disk if any is missing. Files with no local changes
+    /// are simply restored. Both restored files and `.snapshotcopy` copies
+    /// go through the smudge filters, so LFS assets come out as real
+    /// content rather than pointer files.
+    pub async fn restore_snapshot_files(
         &self,
-        attr_path: &str,
-        local: &Path,
-        restored: &Path,
-    ) -> bool {
-        if let Ok(true) = Self::bytes_equal(local.to_path_buf(), restored.to_path_buf()).await {
-            return true;
-        }
+        commit: &str,
+        files: &[SnapshotFileRestore],
+        currently_local_files: &[File],
+    ) -> Result<Vec<SnapshotFileRestoreResult>, CoreError> {
+        self.wait_for_lock().await?;
 
-        let (local_hash, restored_hash) = tokio::join!(
-            self.hash_object_with_attrs(attr_path, local),
-            self.hash_object_with_attrs(attr_path, restored)
-        );
-        match (local_hash, restored_hash) {
-            (Ok(local), Ok(restored)) => local == restored,
-            _ => false,
-        }
-    }
+        let entries: HashMap<String, FileState> = self
+            .get_snapshot_entries_with_state(commit)
+            .await?
+            .into_iter()
+            .collect();
+        let local: HashSet<&str> = currently_local_files
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect();
 
-    /// Raw byte comparison of two files, run on a blocking thread since the
-    /// assets involved can be hundreds of megabytes. Bails out on the first
-    /// differing chunk (or immediately on a size mismatch).
-    async fn bytes_equal(a: PathBuf, b: PathBuf) -> anyhow::Result<bool> {
-        tokio::task::spawn_blocking(move || {
-            use std::io::Read;
+        let mut missing: Vec<&str> = Vec::new();
+        let mut unresolved: Vec<&str> = Vec::new();
+        let mut plan: Vec<(&str, &FileState, Option<SnapshotConflictResolution>)> = Vec::new();
+        for file in files {
+            let Some(state) = entries.get(&file.path) else {
+                missing.push(file.path.as_str());
+                continue;
+            };
+
+            if self
+                .working_file_matches_snapshot(commit, &file.path, state)
+                .await
+            {
+                plan.push((file.path.as_str(), state, None));
+                continue;
+            }
+
+            let resolution = if local.contains(file.path.as_str()) {
+                match file.resolution {
+                    Some(resolution) => resolution,
+                    None => {
+                        unresolved.push(file.path.as_str());
+                        continue;
+                    }
+                }
+            } else {
+                SnapshotConflictResolution::Theirs
+            };
+            plan.push((file.path.as_str(), state, Some(resolution)));
+        }
+
+        if !missing.is_empty() {
+            return Err(CoreError::Input(anyhow!(
+                "{} file(s) aren't part of snapshot {}:\n  - {}",
+                missing.len(),
+                commit,
+                missing.join("\n  - ")
+            )));
+        }
+        if !unresolved.is_empty() {
+            return Err(CoreError::Input(anyhow!(
+                "{} file(s) have local changes that differ from the snapshot:\n  - {}\n\nChoose whether to keep your version, take the snapshot's, or save the snapshot's as a copy.",
+                unresolved.len(),
+                unresolved.join("\n  - ")
+            )));
+        }
+
+        let mut results = Vec::with_capacity(plan.len());
+        let mut theirs: Vec<(String, FileState)> = Vec::new();
+        for (path, state, resolution) in plan {
+            let (outcome, copy_path) = match resolution {
+                None => (SnapshotFileRestoreOutcome::Unchanged, None),
+                Some(SnapshotConflictResolution::Theirs) => {
+                    theirs.push((path.to_string(), state.clone()));
+                    match state {
+                        FileState::Deleted => (SnapshotFileRestoreOutcome::Removed, None),
+                        _ => (SnapshotFileRestoreOutcome::Restored, None),
+                    }
+                }
+                Some(SnapshotConflictResolution::Mine) => (SnapshotFileRestoreOutcome::Kept, None),
+                // The snapshot deleted the file, so there's nothing to copy
+                Some(SnapshotConflictResolution::SaveAsCopy) if *state == FileState::Deleted => {
+                    (SnapshotFileRestoreOutcome::Kept, None)
+                }
+                Some(SnapshotConflictResolution::SaveAsCopy) => {
+                    let copy_path = self.free_snapshot_copy_path(path);
+                    self.write_snapshot_file(commit, path, &self.repo_path.join(&copy_path))
+                        .await?;
+                    (SnapshotFileRestoreOutcome::Copied, Some(copy_path))
+                }
+            };
+            results.push(SnapshotFileRestoreResult {
+                path: path.to_string(),
+                outcome,
+                copy_path,
+            });
+        }
+
+        // Every conflicting path in here was explicitly resolved as theirs
+        if !theirs.is_empty() {
+            self.restore_snapshot_selective(commit, &theirs, &[], true)
+                .await?;
+        }
+
+        Ok(results)
+    }
+
+    /// Whether the working tree already holds the snapshot's version of a
+    /// path: absent for paths the snapshot deleted, otherwise hashing to the
+    /// snapshot's blob under the path's clean filters.
+    async fn working_file_matches_snapshot(
+        &self,
+        commit: &str,
+        path: &str,
+        state: &FileState,
+    ) -> bool {
+        let abs = self.repo_path.join(path);
+        if *state == FileState::Deleted {
+            return !abs.exists();
+        }
+        if !abs.is_file() {
+            return false;
+        }
+
+        let blob = match self
+            .run_and_collect_output(
+                &["rev-parse", &format!("{commit}:{path}")],
+                Opts::new_without_logs(),
+            )
+            .await
+        {
+            Ok(blob) => blob,
+            Err(_) => return false,
+        };
+        match self.hash_object_with_attrs(path, &abs).await {
+            Ok(local) => local == blob.trim(),
+            Err(_) => false,
+        }
+    }
+
+    /// First `<path>.snapshotcopy[N]` that doesn't exist yet, so saving a
+    /// copy never clobbers one from an earlier restore.
+    fn free_snapshot_copy_path(&self, path: &str) -> String {
+        let mut copy_path = format!("{path}.snapshotcopy");
+        let mut n = 2;
+        while self.repo_path.join(&copy_path).exists() {
+            copy_path = format!("{path}.snapshotcopy{n}");
+            n += 1;
+        }
+        copy_path
+    }
+
+    /// Write a snapshot's version of `path` to `dest` via `git cat-file
+    /// --filters`, which applies the same smudge filters a checkout would.
+    /// Stdout goes straight to the destination file since LFS assets can be
+    /// large and aren't valid UTF-8.
+    async fn write_snapshot_file(
+        &self,
+        commit: &str,
+        path: &str,
+        dest: &Path,
+    ) -> anyhow::Result<()> {
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let out_file = std::fs::File::create(dest)?;
+
+        let object = format!("{commit}:{path}");
+        let mut cmd = Command::new("git");
+        cmd.args(["cat-file", "--filters", &object]);
+        cmd.env("GIT_CLONE_PROTECTION_ACTIVE", "false");
+        if !self.repo_path.as_os_str().is_empty() {
+            cmd.current_dir(&self.repo_path.canonicalize()?);
+        }
+        cmd.stdout(Stdio::from(out_file));
+        cmd.stderr(Stdio::piped());
+        #[cfg(windows)]
+        cmd.creation_flags(CREATE_NO_WINDOW);
+
+        info!(
+            "Running: git cat-file --filters {} > {}",
+            object,
+            dest.display()
+        );
+
+        // `output()` would swap stdout back to a pipe
+        let output = cmd.spawn()?.wait_with_output().await?;
+        if !output.status.success() {
+            let _ = std::fs::remove_file(dest);
+            bail!(
+                "git cat-file --filters {} failed: {}",
+                object,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    /// Hash a file the way `git add` would — applying the same clean
+    /// filters (`autocrlf`, `text`, `eol=`, custom clean drivers, LFS)
+    /// that staging would apply. `attr_path` is the repo-relative path
+    /// used for `.gitattributes` lookup; `file` is the actual on-disk
+    /// file to hash. Used by the cherry-pick restore path so a renamed
+    /// `.localcopy` and the cherry-picked extraction compare equal when
+    /// they're semantically identical modulo normalization, instead of
+    /// raw byte-equal.
+    async fn hash_object_with_attrs(&self, attr_path: &str, file: &Path) -> anyhow::Result<String> {
+        let file_str = file
+            .to_str()
+            .ok_or_else(|| anyhow!("hash-object path is non-UTF-8"))?;
+        let path_arg = format!("--path={attr_path}");
+        let out = self
+            .run_and_collect_output(
+                &["hash-object", &path_arg, "--", file_str],
+                Opts::new_without_logs(),
+            )
+            .await?;
+        Ok(out.trim().to_string())
+    }
+
+    /// Decide whether a file restored from a snapshot matches the user's
+    /// pre-restore `.localcopy` of the same path. Byte-equal files are
+    /// identical under any clean filter, and after an uneventful restore the
+    /// two are almost always byte-equal (LFS smudge reproduces the exact
+    /// bytes the snapshot captured) — so a raw comparison short-circuits the
+    /// common case without running the LFS clean filter, a full content
+    /// hash, over each side. Only on a byte mismatch do we fall back to
+    /// filter-aware hashing via `hash_object_with_attrs`, which keeps EOL
+    /// normalization (autocrlf, eol=, custom drivers) from registering as a
+    /// conflict. Any error on either side counts as "different" so the local
+    /// copy is preserved for review rather than silently dropped.
+    async fn restored_file_matches_local(
+        &self,
+        attr_path: &str,
+        local: &Path,
+        restored: &Path,
+    ) -> bool {
+        if let Ok(true) = Self::bytes_equal(local.to_path_buf(), restored.to_path_buf()).await {
+            return true;
+        }
+
+        let (local_hash, restored_hash) = tokio::join!(
+            self.hash_object_with_attrs(attr_path, local),
+            self.hash_object_with_attrs(attr_path, restored)
+        );
+        match (local_hash, restored_hash) {
+            (Ok(local), Ok(restored)) => local == restored,
+            _ => false,
+        }
+    }
+
+    /// Raw byte comparison of two files, run on a blocking thread since the
+    /// assets involved can be hundreds of megabytes. Bails out on the first
+    /// differing chunk (or immediately on a size mismatch).
+    async fn bytes_equal(a: PathBuf, b: PathBuf) -> anyhow::Result<bool> {
+        tokio::task::spawn_blocking(move || {
+            use std::io::Read;
 
             let len = std::fs::metadata(&a)?.len();
             if len != std::fs::metadata(&b)?.len() {
@@ -1260,11 +1920,7 @@ impl Git {
     }
 
     pub async fn has_local_branch(&self, branch: &str) -> anyhow::Result<bool> {
-        let output = self
-            .run_and_collect_output(&["branch", "--list", branch], Opts::default())
-            .await?;
-
-        Ok(!output.trim().is_empty())
+        read_query!(self, has_local_branch(branch))
     }
 
     pub async fn has_remote_branch(&self, branch: &str) -> anyhow::Result<bool> {
@@ -1326,18 +1982,25 @@ impl Git {
         Ok(response)
     }
 
-    pub async fn log(&self, limit: usize, git_ref: &str) -> anyhow::Result<String> {
-        self.run_and_collect_output(
-            &[
-                "--no-pager",
-                "log",
-                &format!("-{limit}"),
-                "--pretty=format:%H%x1f%B%x1f%an%x1f%aI%x1f%x1e",
-                git_ref,
-            ],
-            Opts::new_without_logs(),
-        )
-        .await
+    pub async fn log(&self, limit: usize, git_ref: &str) -> anyhow::Result<Vec<LogEntry>> {
+        read_query!(self, log(limit, git_ref))
+    }
+
+    // Full shas of every commit between from and to, inclusive of both ends. Excluding from's
+    // parents with ^<from>^@ rather than --not keeps both revisions after --end-of-options.
+    pub async fn rev_list_inclusive(&self, from: &str, to: &str) -> anyhow::Result<Vec<String>> {
+        let output = self
+            .run_and_collect_output(
+                &["rev-list", "--end-of-options", to, &format!("^{from}^@")],
+                Opts::new_without_logs(),
+            )
+            .await?;
+
+        Ok(output
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect())
     }
 
     pub async fn cherry(&self, upstream: &str, head: &str) -> anyhow::Result<String> {
@@ -1369,16 +2032,7 @@ impl Git {
     }
 
     pub async fn current_branch(&self) -> anyhow::Result<String> {
-        let output = self
-            .run_and_collect_output(&["branch", "--show-current"], Opts::default())
-            .await?;
-
-        Ok(output
-            .lines()
-            .take(1)
-            .next()
-            .unwrap_or_default()
-            .to_string())
+        read_query!(self, current_branch())
     }
 
     // this looks at two refs and identifies commits that are likely the
@@ -1452,50 +2106,329 @@ impl Git {
         from_ref: &str,
         to_ref: &str,
     ) -> anyhow::Result<(u32, u32)> {
+        read_query!(self, get_ahead_behind(from_ref, to_ref))
+    }
+
+    pub async fn diff_filenames(&self, range: &str) -> anyhow::Result<Vec<String>> {
+        read_query!(self, diff_filenames(range))
+    }
+
+    pub async fn abort_rebase(&self) -> anyhow::Result<()> {
+        self.run(&["rebase", "--abort"], Opts::default()).await
+    }
+
+    pub async fn quit_rebase(&self) -> anyhow::Result<()> {
+        self.run(&["rebase", "--quit"], Opts::default()).await
+    }
+
+    // Resolves a path inside the git dir. Linked worktrees keep their rebase state under
+    // .git/worktrees/<name>, so joining onto .git directly only works for the main worktree.
+    async fn git_path(&self, name: &str) -> anyhow::Result<PathBuf> {
         let output = self
-            .run_and_collect_output(
-                &[
-                    "rev-list",
-                    "--count",
-                    "--left-only",
-                    &format!("{from_ref}...{to_ref}"),
-                ],
-                Opts::default(),
-            )
+            .run_and_collect_output(&["rev-parse", "--git-path", name], Opts::new_without_logs())
             .await?;
-        let behind_count = output.lines().next().unwrap_or("0").parse::<u32>()?;
+        Ok(self.repo_path.join(output.trim()))
+    }
 
-        let output = self
+    pub async fn rebase_in_progress(&self) -> anyhow::Result<bool> {
+        Ok(self.git_path("rebase-merge").await?.exists()
+            || self.git_path("rebase-apply").await?.exists())
+    }
+
+    /// Lists the files the current rebase stopped on along with the blob for each stage, and
+    /// where the rebase is from and to. Lock and history info is left for the caller to fill.
+    pub async fn list_rebase_conflicts(&self) -> anyhow::Result<RebaseConflictsResponse> {
+        if !self.rebase_in_progress().await? {
+            return Ok(RebaseConflictsResponse::default());
+        }
+
+        let mut state_dir = self.git_path("rebase-merge").await?;
+        if !state_dir.exists() {
+            state_dir = self.git_path("rebase-apply").await?;
+        }
+        let read_state = |name: &str| {
+            std::fs::read_to_string(state_dir.join(name))
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+
+        let stopped_at = self
             .run_and_collect_output(
-                &[
-                    "rev-list",
-                    "--count",
-                    "--right-only",
-                    &format!("{from_ref}...{to_ref}"),
-                ],
-                Opts::default(),
+                &["rev-parse", "--verify", "--quiet", "REBASE_HEAD"],
+                Opts::new_without_logs(),
             )
+            .await
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        let mut files = self.unmerged_files(None).await?;
+        let paths = files.iter().map(|f| f.path.clone()).collect::<Vec<_>>();
+        let binary = self.binary_paths(&paths).await?;
+        for file in files.iter_mut() {
+            file.is_binary = binary.contains(&file.path);
+        }
+
+        Ok(RebaseConflictsResponse {
+            in_progress: true,
+            onto: read_state("onto"),
+            head_name: read_state("head-name"),
+            stopped_at,
+            files,
+        })
+    }
+
+    async fn unmerged_files(&self, path: Option<&str>) -> anyhow::Result<Vec<RebaseConflictFile>> {
+        let mut args = vec!["--literal-pathspecs", "ls-files", "-u", "-z"];
+        if let Some(path) = path {
+            args.extend(["--", path]);
+        }
+        let output = self
+            .run_and_collect_output(&args, Opts::new_without_logs())
             .await?;
-        let ahead_count = output.lines().next().unwrap_or("0").parse::<u32>()?;
 
-        Ok((ahead_count, behind_count))
+        // each record is "<mode> <sha> <stage>\t<path>", one per stage present
+        let mut files: Vec<RebaseConflictFile> = vec![];
+        for record in output.split('\0').filter(|r| !r.is_empty()) {
+            let Some((info, path)) = record.split_once('\t') else {
+                continue;
+            };
+            let mut parts = info.split_whitespace();
+            let (Some(mode), Some(sha), Some(stage)) = (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+
+            if !matches!(files.last(), Some(f) if f.path == path) {
+                files.push(RebaseConflictFile {
+                    path: path.to_string(),
+                    base: None,
+                    ours: None,
+                    theirs: None,
+                    is_binary: false,
+                    locked_by: None,
+                    last_change: None,
+                });
+            }
+            let file = files.last_mut().unwrap();
+            let blob = Some(ConflictBlob {
+                sha: sha.to_string(),
+                mode: mode.to_string(),
+            });
+            match stage {
+                "1" => file.base = blob,
+                "2" => file.ours = blob,
+                "3" => file.theirs = blob,
+                _ => {}
+            }
+        }
+
+        Ok(files)
     }
 
-    pub async fn diff_filenames(&self, range: &str) -> anyhow::Result<Vec<String>> {
+    // Paths that are LFS tracked or marked -text, the same split submit uses to skip binaries
+    async fn binary_paths(&self, paths: &[String]) -> anyhow::Result<HashSet<String>> {
+        let mut binary = HashSet::new();
+        if paths.is_empty() {
+            return Ok(binary);
+        }
+
+        let mut args = vec![
+            "-c",
+            "core.quotePath=false",
+            "check-attr",
+            "-z",
+            "filter",
+            "text",
+            "--",
+        ];
+        args.extend(paths.iter().map(|p| p.as_str()));
         let output = self
-            .run_and_collect_output(&["diff", "--name-only", range], Opts::new_without_logs())
+            .run_and_collect_output(&args, Opts::new_without_logs())
             .await?;
-        let mut result = output.lines().map(|s| s.to_string()).collect::<Vec<_>>();
-        result.dedup();
-        Ok(result)
+
+        let fields = output.split('\0').collect::<Vec<_>>();
+        for record in fields.chunks_exact(3) {
+            let (path, attr, value) = (record[0], record[1], record[2]);
+            if (attr == "filter" && value == "lfs") || (attr == "text" && value == "unset") {
+                binary.insert(path.to_string());
+            }
+        }
+
+        Ok(binary)
     }
 
-    pub async fn abort_rebase(&self) -> anyhow::Result<()> {
-        self.run(&["rebase", "--abort"], Opts::default()).await
+    /// Runs a 3-way merge of a conflicted text file without touching the working tree, so the
+    /// result can be previewed before picking a side.
+    pub async fn merge_conflict_preview(&self, path: &str) -> anyhow::Result<ConflictMergePreview> {
+        let Some(file) = self.unmerged_files(Some(path)).await?.into_iter().next() else {
+            bail!("{} has no unresolved conflicts", path);
+        };
+        if self
+            .binary_paths(std::slice::from_ref(&file.path))
+            .await?
+            .contains(&file.path)
+        {
+            bail!(
+                "{} is a binary file and can't be merged, pick a side instead",
+                path
+            );
+        }
+        let (Some(ours), Some(theirs)) = (&file.ours, &file.theirs) else {
+            bail!(
+                "{} was deleted on one side, keep or delete it instead of merging",
+                path
+            );
+        };
+
+        let ours_file = self.write_conflict_blob(path, Some(&ours.sha)).await?;
+        let base_file = self
+            .write_conflict_blob(path, file.base.as_ref().map(|b| b.sha.as_str()))
+            .await?;
+        let theirs_file = self.write_conflict_blob(path, Some(&theirs.sha)).await?;
+
+        let mut cmd = Command::new("git");
+        cmd.args([
+            "merge-file",
+            "-p",
+            "-L",
+            "upstream",
+            "-L",
+            "base",
+            "-L",
+            "local",
+        ]);
+        cmd.arg(ours_file.path());
+        cmd.arg(base_file.path());
+        cmd.arg(theirs_file.path());
+        cmd.env("GIT_CLONE_PROTECTION_ACTIVE", "false");
+        if !self.repo_path.as_os_str().is_empty() {
+            cmd.current_dir(&self.repo_path.canonicalize()?);
+        }
+        #[cfg(windows)]
+        cmd.creation_flags(CREATE_NO_WINDOW);
+
+        // merge-file exits with the number of conflicting hunks, and a negative code on error
+        let output = cmd.output().await?;
+        let conflict_count = match output.status.code() {
+            Some(code) if (0..=127).contains(&code) => code as u32,
+            _ => bail!(
+                "git merge-file failed for {}: {}",
+                path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        };
+
+        Ok(ConflictMergePreview {
+            path: file.path,
+            merged: String::from_utf8_lossy(&output.stdout).to_string(),
+            conflict_count,
+        })
     }
 
-    pub async fn quit_rebase(&self) -> anyhow::Result<()> {
-        self.run(&["rebase", "--quit"], Opts::default()).await
+    // Writes one stage of a conflicted file to a temp file for merge-file, or an empty file if
+    // the stage is missing. Catches binaries the attributes don't mark the same way git does.
+    async fn write_conflict_blob(
+        &self,
+        path: &str,
+        sha: Option<&str>,
+    ) -> anyhow::Result<NamedTempFile> {
+        let mut temp_file = NamedTempFile::new()?;
+        if let Some(sha) = sha {
+            let mut cmd = Command::new("git");
+            cmd.args(["cat-file", "blob", sha]);
+            if !self.repo_path.as_os_str().is_empty() {
+                cmd.current_dir(&self.repo_path.canonicalize()?);
+            }
+            #[cfg(windows)]
+            cmd.creation_flags(CREATE_NO_WINDOW);
+
+            let output = cmd.output().await?;
+            if !output.status.success() {
+                bail!(
+                    "git cat-file blob {} failed: {}",
+                    sha,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            if output.stdout.iter().take(8000).any(|b| *b == 0) {
+                bail!(
+                    "{} is a binary file and can't be merged, pick a side instead",
+                    path
+                );
+            }
+            temp_file.write_all(&output.stdout)?;
+            temp_file.flush()?;
+        }
+        Ok(temp_file)
+    }
+
+    /// Resolves a conflicted file by keeping one side whole and staging the result. If that
+    /// side deleted the file, the deletion is what gets staged.
+    pub async fn resolve_conflict(&self, path: &str, side: ConflictSide) -> anyhow::Result<()> {
+        let Some(file) = self.unmerged_files(Some(path)).await?.into_iter().next() else {
+            bail!("{} has no unresolved conflicts", path);
+        };
+
+        let (blob, flag) = match side {
+            ConflictSide::Ours => (&file.ours, "--ours"),
+            ConflictSide::Theirs => (&file.theirs, "--theirs"),
+        };
+
+        if blob.is_none() {
+            return self
+                .run(
+                    &["--literal-pathspecs", "rm", "--quiet", "--", path],
+                    Opts::default(),
+                )
+                .await;
+        }
+
+        self.run(
+            &["--literal-pathspecs", "checkout", flag, "--", path],
+            Opts::default(),
+        )
+        .await?;
+        self.run(&["--literal-pathspecs", "add", "--", path], Opts::default())
+            .await
+    }
+
+    /// Continues a rebase once every conflict is staged. Stopping again on the next commit's
+    /// conflicts is not an error, callers should list the conflicts again afterwards.
+    pub async fn continue_rebase(&self) -> anyhow::Result<()> {
+        let remaining = self.unmerged_files(None).await?;
+        if !remaining.is_empty() {
+            bail!(
+                "{} file(s) still have conflicts: {}",
+                remaining.len(),
+                remaining
+                    .iter()
+                    .map(|f| f.path.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        // no editor to confirm the commit message in, keep the original one
+        let res = self
+            .run(
+                &["-c", "core.editor=true", "rebase", "--continue"],
+                Opts::default(),
+            )
+            .await;
+
+        match res {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                if self.rebase_in_progress().await? && !self.unmerged_files(None).await?.is_empty()
+                {
+                    info!("Rebase stopped on conflicts in the next commit");
+                    return Ok(());
+                }
+                Err(e)
+            }
+        }
     }
 
     pub async fn run_maintenance(&self) -> anyhow::Result<()> {
@@ -1560,7 +2493,11 @@ impl Git {
     pub async fn refetch(&self) -> anyhow::Result<()> {
         let mut fetch_running = GIT_FETCH_LOCK.clone().lock_owned().await;
         *fetch_running = true;
-        self.run(&["fetch", "--refetch"], Opts::default()).await?;
+        self.run(
+            &["fetch", "--refetch", "--progress"],
+            Opts::default().with_timeout(REFETCH_TIMEOUT),
+        )
+        .await?;
         *fetch_running = false;
         Ok(())
     }
@@ -1569,7 +2506,9 @@ impl Git {
         let result = self
             .run(
                 &["commit-graph", "write", "--reachable"],
-                Opts::default().with_complete_error(),
+                Opts::default()
+                    .with_complete_error()
+                    .with_timeout(MAINTENANCE_TIMEOUT),
             )
             .await;
 
@@ -1583,7 +2522,10 @@ impl Git {
                 }
 
                 return self
-                    .run(&["commit-graph", "write", "--reachable"], Opts::default())
+                    .run(
+                        &["commit-graph", "write", "--reachable"],
+                        Opts::default().with_timeout(MAINTENANCE_TIMEOUT),
+                    )
                     .await;
             }
         }
@@ -1609,6 +2551,8 @@ impl Git {
                     return_complete_error: true,
                     lfs_mode: LfsMode::Stubs,
                     skip_notify_frontend: false,
+                    cancel: None,
+                    timeout: None,
                 },
             )
             .await?;
@@ -1626,6 +2570,8 @@ impl Git {
                 info.sha = caps[1].to_string();
             } else if let Some(caps) = WORKTREE_BRANCH_REGEX.captures(line) {
                 info.branch = caps.get(2).map(|m| m.as_str().to_string());
+                // git always lists the main worktree first
+                info.is_main = entries.is_empty();
                 entries.push(info.clone());
                 info = WorktreeInfo::default();
             }
@@ -1634,6 +2580,79 @@ impl Git {
         Ok(entries)
     }
 
+    // Adds a worktree at path with branch checked out. An existing local branch is checked out
+    // as is, otherwise the branch is created from start_point, or origin/<branch> if not given.
+    pub async fn add_worktree(
+        &self,
+        path: &Path,
+        branch: &str,
+        start_point: Option<&str>,
+    ) -> anyhow::Result<()> {
+        if path.exists() && path.read_dir()?.next().is_some() {
+            bail!("{} already exists and isn't empty", path.display());
+        }
+
+        let path_str = path.to_string_lossy();
+        if self.has_local_branch(branch).await? {
+            self.run(
+                &["worktree", "add", &path_str, branch],
+                Opts::new_with_complete_error(),
+            )
+            .await
+        } else {
+            let start_point = start_point
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("origin/{branch}"));
+            self.run(
+                &["worktree", "add", "-b", branch, &path_str, &start_point],
+                Opts::new_with_complete_error(),
+            )
+            .await
+        }
+    }
+
+    // Removes a linked worktree, refusing the main worktree and, unless forced, one with
+    // uncommitted changes. The worktree's branch is kept so commits on it aren't lost.
+    pub async fn remove_worktree(&self, path: &Path, force: bool) -> anyhow::Result<()> {
+        let worktrees = self.list_worktrees().await?;
+        let Some(worktree) = worktrees.iter().find(|w| w.directory == path) else {
+            bail!("{} is not a worktree of this repo", path.display());
+        };
+        if worktree.is_main {
+            bail!("The main worktree can't be removed");
+        }
+
+        // git can't remove a worktree whose directory was deleted out from under it, but
+        // pruning drops its registration
+        if !worktree.directory.exists() {
+            return self.run(&["worktree", "prune"], Opts::default()).await;
+        }
+
+        if !force {
+            let mut worktree_git = self.clone();
+            worktree_git.repo_path.clone_from(&worktree.directory);
+            let changes = worktree_git
+                .run_and_collect_output(&["status", "--porcelain"], Opts::new_without_logs())
+                .await?;
+            let count = changes.lines().filter(|l| !l.is_empty()).count();
+            if count > 0 {
+                bail!(
+                    "{} has {} uncommitted change(s). Submit, snapshot or revert them before removing it.",
+                    path.display(),
+                    count
+                );
+            }
+        }
+
+        let dir = worktree.directory.to_string_lossy();
+        let mut args = vec!["worktree", "remove"];
+        if force {
+            args.push("--force");
+        }
+        args.push(&dir);
+        self.run(&args, Opts::new_with_complete_error()).await
+    }
+
     pub async fn set_config(&self, key: &str, value: &str) -> anyhow::Result<()> {
         self.run(&["config", key, value], Opts::default()).await
     }
@@ -1716,6 +2735,113 @@ impl Git {
         Ok(())
     }
 
+    pub async fn get_config(&self, key: &str) -> anyhow::Result<Option<String>> {
+        // a missing key exits non-zero with no output
+        match self
+            .run_and_collect_output(&["config", "--get", key], Opts::new_without_logs())
+            .await
+        {
+            Ok(value) if !value.trim().is_empty() => Ok(Some(value.trim().to_string())),
+            _ => Ok(None),
+        }
+    }
+
+    async fn unset_config(&self, key: &str) -> anyhow::Result<()> {
+        if self.get_config(key).await?.is_none() {
+            return Ok(());
+        }
+        self.run(&["config", "--unset-all", key], Opts::default())
+            .await
+    }
+
+    /// Every LFS file tracked at HEAD with its size and whether it's already in the local LFS
+    /// store, including files outside the sparse checkout.
+    pub async fn lfs_files(&self) -> anyhow::Result<Vec<LfsFileInfo>> {
+        #[derive(serde::Deserialize)]
+        struct LsFilesOutput {
+            files: Option<Vec<LsFilesEntry>>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct LsFilesEntry {
+            name: String,
+            size: u64,
+            #[serde(default)]
+            downloaded: bool,
+        }
+
+        let output = self
+            .run_and_collect_output(&["lfs", "ls-files", "--json"], Opts::new_without_logs())
+            .await?;
+        let output: LsFilesOutput = serde_json::from_str(&output)?;
+
+        Ok(output
+            .files
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| LfsFileInfo {
+                path: entry.name,
+                size: entry.size,
+                downloaded: entry.downloaded,
+            })
+            .collect())
+    }
+
+    /// Every file tracked at `rev`, repo-relative and forward-slash separated, including files
+    /// outside the sparse checkout.
+    pub async fn tracked_files(&self, rev: &str) -> anyhow::Result<Vec<String>> {
+        let output = self
+            .run_and_collect_output(
+                &["ls-tree", "-r", "-z", "--name-only", "--full-tree", rev],
+                Opts::new_without_logs(),
+            )
+            .await?;
+
+        Ok(output
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(|path| path.to_string())
+            .collect())
+    }
+
+    pub async fn active_content_profile(&self) -> anyhow::Result<Option<String>> {
+        self.get_config(CONTENT_PROFILE_CONFIG_KEY).await
+    }
+
+    /// Narrows the working tree and LFS fetches to `profile`. The fetch rules are written first
+    /// and the sparse checkout is changed with LFS stubs, so the final `lfs pull` only downloads
+    /// what the profile asks for instead of smudging everything that comes into the cone.
+    pub async fn apply_content_profile(&self, profile: &ContentProfile) -> anyhow::Result<()> {
+        for (key, patterns) in [
+            ("lfs.fetchinclude", &profile.lfs_fetch_include),
+            ("lfs.fetchexclude", &profile.lfs_fetch_exclude),
+        ] {
+            if patterns.is_empty() {
+                self.unset_config(key).await?;
+            } else {
+                self.set_config(key, &patterns.join(",")).await?;
+            }
+        }
+
+        // the same directories the plan was computed from, ex. without Windows separators
+        let matcher = ContentProfileMatcher::compile(profile)?;
+        if matcher.sparse_directories().is_empty() {
+            self.run(
+                &["sparse-checkout", "disable"],
+                Opts::default().with_lfs_stubs(),
+            )
+            .await?;
+        } else {
+            let mut args = vec!["sparse-checkout", "set", "--cone", "--"];
+            args.extend(matcher.sparse_directories().iter().map(|d| d.as_str()));
+            self.run(&args, Opts::default().with_lfs_stubs()).await?;
+        }
+
+        self.run(&["lfs", "pull"], Opts::default()).await?;
+        self.set_config(CONTENT_PROFILE_CONFIG_KEY, &profile.name)
+            .await
+    }
+
     pub async fn run_and_collect_output(
         &self,
         args: &[&str],
@@ -1802,6 +2928,11 @@ impl Git {
             }
         }
 
+        let cancel = opts.cancel.cloned().or_else(sequence_cancel_token);
+        if cancel.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            bail!("Cancelled before git {} started", git_subcommand(args));
+        }
+
         let mut cmd = Command::new("git");
         for arg in args {
             cmd.arg(arg);
@@ -1814,6 +2945,11 @@ impl Git {
             cmd.env("GIT_LFS_SKIP_SMUDGE", "1");
         }
 
+        // git-lfs only draws progress on a terminal unless asked to
+        if self.progress_tx.is_some() {
+            cmd.env("GIT_LFS_FORCE_PROGRESS", "1");
+        }
+
         if !&self.repo_path.as_os_str().is_empty() {
             // if the first arg is clone, set current dir to the parent, then canonicalize
             if args[0] == "clone" {
@@ -1848,7 +2984,7 @@ impl Git {
         let stderr = git_proc.stderr.take().expect("Failed to get stderr");
 
         let mut out_reader = BufReader::new(stdout).lines();
-        let mut err_reader = BufReader::new(stderr).lines();
+        let mut err_reader = BufReader::new(stderr);
 
         let out_lines: Arc<RwLock<Vec<String>>> = Arc::new(RwLock::new(vec![]));
         let err_lines: Arc<RwLock<Vec<String>>> = Arc::new(RwLock::new(vec![]));
@@ -1876,15 +3012,79 @@ impl Git {
             }
         });
 
+        // Progress redraws itself with a carriage return, so stderr is split on those as well as
+        // newlines to see each update as it happens.
         let err_lines_thread = err_lines.clone();
+        let progress_tx = self.progress_tx.clone();
+        let operation = git_subcommand(args).to_string();
         let err_handle = tokio::spawn(async move {
-            while let Some(line) = err_reader.next_line().await.unwrap() {
-                err_lines_thread.write().push(line.clone());
-                info!("{}", line);
+            let mut segment: Vec<u8> = vec![];
+            loop {
+                let buf = match err_reader.fill_buf().await {
+                    Ok(buf) => buf,
+                    Err(e) => {
+                        warn!("Failed to read git stderr: {}", e);
+                        break;
+                    }
+                };
+                if buf.is_empty() {
+                    break;
+                }
+
+                let len = buf.len();
+                for byte in buf {
+                    if *byte == b'\r' || *byte == b'\n' {
+                        handle_stderr_line(
+                            &String::from_utf8_lossy(&segment),
+                            &operation,
+                            &progress_tx,
+                            &err_lines_thread,
+                        );
+                        segment.clear();
+                    } else {
+                        segment.push(*byte);
+                    }
+                }
+                err_reader.consume(len);
             }
+
+            handle_stderr_line(
+                &String::from_utf8_lossy(&segment),
+                &operation,
+                &progress_tx,
+                &err_lines_thread,
+            );
         });
 
-        let status = git_proc.wait().await?;
+        let interrupted = tokio::select! {
+            status = git_proc.wait() => Ok(status?),
+            _ = wait_for_cancel(cancel.as_ref()) => Err("was cancelled".to_string()),
+            _ = wait_for_timeout(opts.timeout) => Err(format!(
+                "timed out after {} seconds",
+                opts.timeout.unwrap_or_default().as_secs()
+            )),
+        };
+        let status = match interrupted {
+            Ok(status) => status,
+            Err(reason) => {
+                warn!("{} {}, killing it", git_cmd_str, reason);
+                if let Some(pid) = git_proc.id() {
+                    kill_process_tree(pid);
+                }
+                let _ = git_proc.wait().await;
+                let _ = out_handle.await;
+                let _ = err_handle.await;
+
+                // git can't release its locks when killed mid-write
+                match self.remove_index_lock_if_stale(Duration::ZERO) {
+                    Ok(true) => info!("Removed the index.lock left by the killed git process"),
+                    Ok(false) => {}
+                    Err(e) => warn!("Failed to clean up index.lock: {:?}", e),
+                }
+
+                bail!("git {} {}", git_subcommand(args), reason);
+            }
+        };
 
         // The child has exited and closed its pipes, but the spawned readers may
         // not have flushed their final lines into out_lines/err_lines yet. Join
@@ -2155,6 +3355,130 @@ mod tests {
         );
     }
 
+    // Snapshots older versions saved to the stash list move under
+    // refs/friendshipper/snapshots/ when migrated, exactly once, and editing
+    // tags rewrites the commit but keeps the id.
+    #[tokio::test]
+    async fn test_stash_snapshots_migrate_to_refs() {
+        let (git, _dir) = setup_repo();
+
+        std::fs::write(git.repo_path.join("seed.txt"), "dirty").unwrap();
+        let out = StdCommand::new("git")
+            .args(["stash", "create", "legacy"])
+            .current_dir(&git.repo_path)
+            .output()
+            .unwrap();
+        let legacy_commit = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        let out = StdCommand::new("git")
+            .args(["stash", "store", "--message", "snapshot pre-pull"])
+            .arg(&legacy_commit)
+            .current_dir(&git.repo_path)
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+
+        let manual = git
+            .save_tagged_snapshot(
+                "manual",
+                vec!["seed.txt".to_string()],
+                SnapshotKind::Manual,
+                vec![" wip ".to_string(), "wip".to_string(), String::new()],
+            )
+            .await
+            .expect("save_tagged_snapshot");
+        assert_eq!(manual.tags, vec!["wip"]);
+        assert_eq!(manual.files, vec!["seed.txt"]);
+        assert_eq!(manual.author, "test");
+
+        // listing alone leaves the stash list untouched
+        assert_eq!(git.list_snapshots().await.unwrap().len(), 1);
+
+        git.migrate_stash_snapshots()
+            .await
+            .expect("migrate_stash_snapshots");
+        let snapshots = git.list_snapshots().await.expect("list_snapshots");
+        assert_eq!(snapshots.len(), 2);
+        let migrated = snapshots.iter().find(|s| s.message == "pre-pull").unwrap();
+        assert_eq!(migrated.kind, SnapshotKind::Automatic);
+        assert_eq!(migrated.files, vec!["seed.txt"]);
+        assert!(git
+            .run_and_collect_output(&["stash", "list"], Opts::default())
+            .await
+            .unwrap()
+            .trim()
+            .is_empty());
+        git.migrate_stash_snapshots().await.unwrap();
+        assert_eq!(git.list_snapshots().await.unwrap().len(), 2);
+
+        let tagged = git
+            .set_snapshot_tags(&manual.id, vec!["handoff".to_string()])
+            .await
+            .expect("set_snapshot_tags");
+        assert_eq!(tagged.id, manual.id);
+        assert_ne!(tagged.commit, manual.commit);
+        assert_eq!(
+            git.find_snapshot(&manual.id).await.unwrap().tags,
+            vec!["handoff"]
+        );
+        assert_eq!(
+            git.get_files_in_snapshot(&tagged.commit).await.unwrap(),
+            vec!["seed.txt"]
+        );
+
+        git.delete_snapshot(&migrated.id).await.unwrap();
+        let remaining = git.list_snapshots().await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, manual.id);
+    }
+
+    // Retention only prunes snapshots whose changes already made it into HEAD
+    // on its own; the rest need the user to confirm.
+    #[tokio::test]
+    async fn test_prune_snapshots_keeps_changes_not_in_head() {
+        let (git, _dir) = setup_repo();
+
+        std::fs::write(git.repo_path.join("seed.txt"), "committed").unwrap();
+        let committed = git
+            .save_snapshot("pre-pull", vec!["seed.txt".to_string()])
+            .await
+            .expect("save_snapshot");
+        for args in [vec!["add", "seed.txt"], vec!["commit", "-m", "committed"]] {
+            let out = StdCommand::new("git")
+                .args(&args)
+                .current_dir(&git.repo_path)
+                .output()
+                .unwrap();
+            assert!(out.status.success());
+        }
+
+        std::fs::write(git.repo_path.join("seed.txt"), "uncommitted").unwrap();
+        let pending = git
+            .save_snapshot("pre-pull", vec!["seed.txt".to_string()])
+            .await
+            .expect("save_snapshot");
+
+        let retention = SnapshotRetention {
+            automatic_max_age_days: 0,
+            automatic_keep_count: 0,
+            manual_max_age_days: None,
+        };
+        let plan = git.plan_snapshot_prune(&retention).await.unwrap();
+        assert_eq!(plan.prunable.len(), 1);
+        assert_eq!(plan.prunable[0].id, committed.id);
+        assert_eq!(plan.differs_from_head.len(), 1);
+        assert_eq!(plan.differs_from_head[0].id, pending.id);
+
+        assert_eq!(git.prune_snapshots(&retention, &[]).await.unwrap().len(), 1);
+        assert_eq!(git.list_snapshots().await.unwrap().len(), 1);
+
+        let pruned = git
+            .prune_snapshots(&retention, std::slice::from_ref(&pending.id))
+            .await
+            .unwrap();
+        assert_eq!(pruned.len(), 1);
+        assert!(git.list_snapshots().await.unwrap().is_empty());
+    }
+
     // Pre-PR regression: selective snapshots could capture paths the caller
     // didn't ask for. The snapshot tree is now built against a temp index
     // seeded from HEAD with only the requested paths staged, so the diff
@@ -2366,6 +3690,331 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_add_and_remove_worktree() {
+        let (git, _dir) = setup_repo();
+        let wt_dir = tempfile::tempdir().unwrap();
+        let wt_path = wt_dir.path().join("feature");
+
+        git.add_worktree(&wt_path, "feature", Some("HEAD"))
+            .await
+            .expect("add_worktree");
+        assert!(wt_path.join("seed.txt").exists());
+        assert!(git.has_local_branch("feature").await.unwrap());
+
+        let worktrees = git.list_worktrees().await.unwrap();
+        assert_eq!(worktrees.len(), 2);
+        assert!(worktrees[0].is_main);
+        assert!(!worktrees[1].is_main);
+        assert_eq!(worktrees[1].branch.as_deref(), Some("refs/heads/feature"));
+
+        // Checking out the same branch twice is refused by git
+        assert!(git
+            .add_worktree(&wt_dir.path().join("again"), "feature", None)
+            .await
+            .is_err());
+
+        let main = worktrees[0].directory.clone();
+        let linked = worktrees[1].directory.clone();
+        assert!(git.remove_worktree(&main, true).await.is_err());
+
+        std::fs::write(linked.join("wip.txt"), "wip").unwrap();
+        let err = git
+            .remove_worktree(&linked, false)
+            .await
+            .expect_err("dirty worktree should be refused");
+        assert!(err.to_string().contains("1 uncommitted change"), "{err}");
+        assert!(linked.exists());
+
+        git.remove_worktree(&linked, true)
+            .await
+            .expect("forced remove");
+        assert!(!linked.exists());
+        assert_eq!(git.list_worktrees().await.unwrap().len(), 1);
+        assert!(git.has_local_branch("feature").await.unwrap());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_kills_git_on_timeout_and_cancel() {
+        let (git, _dir) = setup_repo();
+        let slow = ["-c", "alias.slow=!sleep 30", "slow"];
+
+        let started = std::time::Instant::now();
+        let err = git
+            .run(
+                &slow,
+                Opts::default().with_timeout(Duration::from_millis(500)),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            canceller.cancel();
+        });
+        let err = git
+            .run(&slow, Opts::default().with_cancel(&cancel))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("was cancelled"), "{err}");
+
+        // an already cancelled token doesn't start anything
+        let err = git
+            .run(&["status"], Opts::default().with_cancel(&cancel))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Cancelled before"), "{err}");
+    }
+
+    #[test]
+    fn test_stale_index_lock_is_detected_and_removed() {
+        let (git, dir) = setup_repo();
+        let lock = dir.path().join(".git/index.lock");
+
+        let status = git.index_lock_status().unwrap();
+        assert!(!status.exists && !status.stale);
+        assert!(!git.remove_stale_index_lock().unwrap());
+
+        std::fs::write(&lock, "").unwrap();
+
+        // too young to tell apart from a git that was only just spawned
+        let status = git.index_lock_status().unwrap();
+        assert!(status.exists);
+        assert!(!status.stale);
+        assert!(!git.remove_stale_index_lock().unwrap());
+        assert!(lock.exists());
+
+        let status = git.index_lock_status_with_min_age(Duration::ZERO).unwrap();
+        assert!(status.stale);
+        assert!(status.git_processes.is_empty());
+        assert!(git.remove_index_lock_if_stale(Duration::ZERO).unwrap());
+        assert!(!lock.exists());
+    }
+
+    #[tokio::test]
+    async fn test_clone_reports_typed_progress() {
+        let (_git, src) = setup_repo();
+        let dest_root = tempfile::tempdir().unwrap();
+        let dest = dest_root.path().join("clone");
+
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let (progress_tx, progress_rx) = std::sync::mpsc::channel();
+        let git = Git::new(dest.clone(), tx).with_progress_tx(progress_tx);
+        git.run(
+            &[
+                "clone",
+                "--progress",
+                "--no-local",
+                src.path().to_str().unwrap(),
+                dest.to_str().unwrap(),
+            ],
+            Opts::default(),
+        )
+        .await
+        .unwrap();
+        drop(git);
+
+        let progress = progress_rx.iter().collect::<Vec<_>>();
+        let receiving = progress
+            .iter()
+            .filter(|p| p.phase == "Receiving objects")
+            .collect::<Vec<_>>();
+        assert!(!receiving.is_empty(), "{progress:?}");
+        assert!(receiving.iter().all(|p| p.operation == "clone"));
+        assert!(receiving.last().unwrap().is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_rebase_conflicts_and_continue() {
+        let (git, dir) = setup_repo();
+        let path = dir.path();
+        let run = |args: &[&str]| {
+            StdCommand::new("git")
+                .args(args)
+                .current_dir(path)
+                .output()
+                .unwrap()
+        };
+
+        std::fs::write(path.join("notes.txt"), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(path.join("gone.txt"), "keep\n").unwrap();
+        assert!(run(&["add", "."]).status.success());
+        assert!(run(&["commit", "-m", "base"]).status.success());
+
+        // upstream edits a line and deletes a file, local edits both
+        assert!(run(&["checkout", "-b", "upstream"]).status.success());
+        std::fs::write(path.join("notes.txt"), "one\ntwo upstream\nthree\n").unwrap();
+        assert!(run(&["rm", "-q", "gone.txt"]).status.success());
+        assert!(run(&["commit", "-am", "upstream"]).status.success());
+        assert!(run(&["checkout", "-"]).status.success());
+        std::fs::write(path.join("notes.txt"), "one\ntwo local\nthree\n").unwrap();
+        std::fs::write(path.join("gone.txt"), "changed\n").unwrap();
+        assert!(run(&["commit", "-am", "local"]).status.success());
+
+        assert!(!run(&["rebase", "upstream"]).status.success());
+
+        let conflicts = git.list_rebase_conflicts().await.unwrap();
+        assert!(conflicts.in_progress);
+        assert!(conflicts.stopped_at.is_some());
+        let upstream_sha = String::from_utf8(run(&["rev-parse", "upstream"]).stdout).unwrap();
+        assert_eq!(conflicts.onto.as_deref(), Some(upstream_sha.trim()));
+
+        let paths = conflicts
+            .files
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["gone.txt", "notes.txt"]);
+        let gone = &conflicts.files[0];
+        assert!(gone.base.is_some() && gone.ours.is_none() && gone.theirs.is_some());
+        let notes = &conflicts.files[1];
+        assert!(notes.base.is_some() && notes.ours.is_some() && notes.theirs.is_some());
+        assert!(!notes.is_binary);
+
+        let preview = git.merge_conflict_preview("notes.txt").await.unwrap();
+        assert_eq!(preview.conflict_count, 1);
+        assert!(
+            preview.merged.contains("<<<<<<< upstream"),
+            "{}",
+            preview.merged
+        );
+        assert!(preview.merged.contains("two local"));
+        assert!(git.merge_conflict_preview("gone.txt").await.is_err());
+
+        let err = git.continue_rebase().await.expect_err("conflicts remain");
+        assert!(err.to_string().contains("2 file(s)"), "{err}");
+
+        git.resolve_conflict("notes.txt", ConflictSide::Theirs)
+            .await
+            .unwrap();
+        git.resolve_conflict("gone.txt", ConflictSide::Ours)
+            .await
+            .unwrap();
+        assert!(git
+            .resolve_conflict("notes.txt", ConflictSide::Ours)
+            .await
+            .is_err());
+
+        git.continue_rebase().await.expect("continue");
+        assert!(!git.rebase_in_progress().await.unwrap());
+        assert_eq!(
+            std::fs::read_to_string(path.join("notes.txt")).unwrap(),
+            "one\ntwo local\nthree\n"
+        );
+        assert!(!path.join("gone.txt").exists());
+        assert!(!git.list_rebase_conflicts().await.unwrap().in_progress);
+    }
+
+    #[tokio::test]
+    async fn test_restore_snapshot_files_resolves_each_file() {
+        let (git, _dir) = setup_repo();
+
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+            std::fs::write(git.repo_path.join(name), "base").unwrap();
+        }
+        let out = StdCommand::new("git")
+            .args(["add", "."])
+            .current_dir(&git.repo_path)
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+        let out = StdCommand::new("git")
+            .args(["commit", "-m", "add files"])
+            .current_dir(&git.repo_path)
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+            std::fs::write(git.repo_path.join(name), format!("snapshot {name}")).unwrap();
+        }
+        let snapshot = git
+            .save_snapshot_all("four files")
+            .await
+            .expect("save_snapshot_all");
+
+        // a, b and c diverge locally, d goes back to matching HEAD
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(git.repo_path.join(name), format!("mine {name}")).unwrap();
+        }
+        std::fs::write(git.repo_path.join("d.txt"), "base").unwrap();
+        let local = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|path| File {
+                path: path.to_string(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let restore = |path: &str, resolution| SnapshotFileRestore {
+            path: path.to_string(),
+            resolution,
+        };
+
+        // A diverged file with no resolution refuses the whole restore
+        let err = git
+            .restore_snapshot_files(
+                &snapshot.commit,
+                &[restore("a.txt", None), restore("d.txt", None)],
+                &local,
+            )
+            .await
+            .expect_err("unresolved divergence should be refused");
+        assert!(matches!(err, CoreError::Input(_)));
+        assert_eq!(
+            std::fs::read_to_string(git.repo_path.join("d.txt")).unwrap(),
+            "base"
+        );
+
+        let results = git
+            .restore_snapshot_files(
+                &snapshot.commit,
+                &[
+                    restore("a.txt", Some(SnapshotConflictResolution::Theirs)),
+                    restore("b.txt", Some(SnapshotConflictResolution::Mine)),
+                    restore("c.txt", Some(SnapshotConflictResolution::SaveAsCopy)),
+                    restore("d.txt", None),
+                ],
+                &local,
+            )
+            .await
+            .expect("restore_snapshot_files");
+
+        let outcomes = results
+            .iter()
+            .map(|r| (r.path.as_str(), r.outcome))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("a.txt", SnapshotFileRestoreOutcome::Restored),
+                ("b.txt", SnapshotFileRestoreOutcome::Kept),
+                ("c.txt", SnapshotFileRestoreOutcome::Copied),
+                ("d.txt", SnapshotFileRestoreOutcome::Restored),
+            ]
+        );
+        assert_eq!(results[2].copy_path.as_deref(), Some("c.txt.snapshotcopy"));
+
+        let read = |name: &str| std::fs::read_to_string(git.repo_path.join(name)).unwrap();
+        assert_eq!(read("a.txt"), "snapshot a.txt");
+        assert_eq!(read("b.txt"), "mine b.txt");
+        assert_eq!(read("c.txt"), "mine c.txt");
+        assert_eq!(read("c.txt.snapshotcopy"), "snapshot c.txt");
+        assert_eq!(read("d.txt"), "snapshot d.txt");
+
+        // Everything now matches or was deliberately kept
+        let results = git
+            .restore_snapshot_files(&snapshot.commit, &[restore("a.txt", None)], &local)
+            .await
+            .expect("already restored");
+        assert_eq!(results[0].outcome, SnapshotFileRestoreOutcome::Unchanged);
+    }
+
     // Regression: an untracked file round-tripped through the
     // cherry-pick restore could be reported as a conflict — with the
     // snapshot version dropped at `.snapshotcopy` — even though its
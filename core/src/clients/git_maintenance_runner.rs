@@ -1,4 +1,5 @@
 use crate::clients::git::{Git, ShouldPrune};
+use crate::types::config::AppConfigRef;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::mpsc::Sender as STDSender;
@@ -12,6 +13,23 @@ pub struct GitMaintenanceRunner {
     git: Git,
     config: MaintenanceConfig,
     pause: Arc<AtomicBool>,
+
+    // Read every maintenance run for the snapshot retention policy, so preference changes apply
+    // without a restart. The default policy is used without one. Its repo path is read before
+    // every fetch and maintenance run too, so switching worktrees moves the runner along.
+    app_config: Option<AppConfigRef>,
+}
+
+// The runner's git pointed at the app config's current repo path, or the one it started with
+fn current_git(git: &Git, app_config: Option<&AppConfigRef>) -> Git {
+    let mut git = git.clone();
+    if let Some(repo_path) = app_config
+        .map(|config| config.read().repo_path.clone())
+        .filter(|repo_path| !repo_path.is_empty())
+    {
+        git.repo_path = PathBuf::from(repo_path);
+    }
+    git
 }
 
 struct MaintenanceConfig {
@@ -34,7 +52,12 @@ impl GitMaintenanceRunner {
 
         let config = MaintenanceConfig::default();
 
-        GitMaintenanceRunner { git, pause, config }
+        GitMaintenanceRunner {
+            git,
+            pause,
+            config,
+            app_config: None,
+        }
     }
 
     pub fn with_fetch_interval(mut self, interval: Duration) -> Self {
@@ -47,14 +70,20 @@ impl GitMaintenanceRunner {
         self
     }
 
+    pub fn with_app_config(mut self, app_config: AppConfigRef) -> Self {
+        self.app_config = Some(app_config);
+        self
+    }
+
     pub async fn run(&self) -> anyhow::Result<()> {
         let git = self.git.clone();
         let fetch_interval = self.config.fetch_interval;
         let pause = self.pause.clone();
+        let app_config = self.app_config.clone();
         let fetch_task = tokio::task::spawn(async move {
             loop {
                 if !pause.clone().load(std::sync::atomic::Ordering::Relaxed) {
-                    match git
+                    match current_git(&git, app_config.as_ref())
                         .fetch(
                             ShouldPrune::Yes,
                             Opts::default().with_skip_notify_frontend(),
@@ -75,9 +104,11 @@ impl GitMaintenanceRunner {
         let git = self.git.clone();
         let maintenance_interval = self.config.maintenance_interval;
         let pause = self.pause.clone();
+        let app_config = self.app_config.clone();
         let maintenance_task = tokio::task::spawn(async move {
             loop {
                 if !pause.clone().load(std::sync::atomic::Ordering::Relaxed) {
+                    let git = current_git(&git, app_config.as_ref());
                     match git.run_maintenance().await {
                         Ok(_) => {
                             info!("Maintenance complete");
@@ -86,6 +117,15 @@ impl GitMaintenanceRunner {
                             error!("Error running maintenance: {:?}", e);
                         }
                     }
+
+                    // Snapshots that still differ from HEAD are left for the user to confirm
+                    let retention = app_config
+                        .as_ref()
+                        .map(|config| config.read().snapshot_retention.clone())
+                        .unwrap_or_default();
+                    if let Err(e) = git.prune_snapshots(&retention, &[]).await {
+                        error!("Error pruning snapshots: {:?}", e);
+                    }
                 }
 
                 tokio::time::sleep(maintenance_interval).await;
@@ -0,0 +1,719 @@
+use std::path::PathBuf;
+use std::sync::Once;
+
+use anyhow::{anyhow, bail};
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use git2::{DiffFindOptions, Repository, RevparseMode, Sort};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::clients::git::{Git, Opts, SNAPSHOT_PREFIX, SNAPSHOT_REF_PREFIX};
+use crate::types::repo::{Snapshot, SnapshotKind};
+
+// Repository extensions libgit2 should open repos with rather than refusing them. Partial clones
+// only need the promisor remote for objects we never read in-process.
+static GIT2_EXTENSIONS: &[&str] = &["partialclone", "worktreeconfig", "preciousobjects"];
+static GIT2_INIT: Once = Once::new();
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ReadBackend {
+    // Spawns the git CLI for every query
+    Cli,
+
+    // Answers queries in-process with libgit2, falling back to the CLI for anything it can't
+    #[default]
+    InProcess,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogEntry {
+    pub sha: String,
+    pub message: String,
+    pub author_name: String,
+    pub author_date: Option<DateTime<FixedOffset>>,
+}
+
+// The read-only queries hot enough to be worth answering without spawning git. Mutations always
+// go through the CLI.
+#[async_trait]
+pub trait GitReadBackend: Send + Sync {
+    // Empty when HEAD is detached
+    async fn current_branch(&self) -> anyhow::Result<String>;
+
+    // Commits in to_ref and not from_ref, then commits in from_ref and not to_ref
+    async fn get_ahead_behind(&self, from_ref: &str, to_ref: &str) -> anyhow::Result<(u32, u32)>;
+
+    // Newest first, git_ref may be a single ref or a from..to range
+    async fn log(&self, limit: usize, git_ref: &str) -> anyhow::Result<Vec<LogEntry>>;
+
+    async fn has_local_branch(&self, branch: &str) -> anyhow::Result<bool>;
+
+    // Paths changed in range, with the same meaning as git diff --name-only
+    async fn diff_filenames(&self, range: &str) -> anyhow::Result<Vec<String>>;
+
+    // Snapshots stored under refs/friendshipper/snapshots/, newest first
+    async fn list_snapshots(&self) -> anyhow::Result<Vec<Snapshot>>;
+
+    // Stash entries saved as snapshots by older versions, newest first
+    async fn list_stash_snapshots(&self) -> anyhow::Result<Vec<Snapshot>>;
+}
+
+// Trailer holding a snapshot's metadata in its commit message
+static SNAPSHOT_METADATA_KEY: &str = "Snapshot-Metadata:";
+
+// Everything about a snapshot but its commit. It's kept in the commit message rather than on disk
+// so it travels with the ref when the snapshot is pushed.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotMetadata {
+    id: String,
+    message: String,
+    timestamp: DateTime<Utc>,
+    author: String,
+    branch: String,
+    files: Vec<String>,
+    kind: SnapshotKind,
+    tags: Vec<String>,
+}
+
+// The commit message a snapshot is stored with: the same subject stash-based snapshots had, and
+// its metadata as a trailer
+pub(crate) fn snapshot_commit_message(snapshot: &Snapshot) -> anyhow::Result<String> {
+    let metadata = SnapshotMetadata {
+        id: snapshot.id.clone(),
+        message: snapshot.message.clone(),
+        timestamp: snapshot.timestamp,
+        author: snapshot.author.clone(),
+        branch: snapshot.branch.clone(),
+        files: snapshot.files.clone(),
+        kind: snapshot.kind,
+        tags: snapshot.tags.clone(),
+    };
+    let subject = snapshot.message.lines().next().unwrap_or_default();
+    Ok(format!(
+        "{SNAPSHOT_PREFIX} {subject}\n\n{SNAPSHOT_METADATA_KEY} {}",
+        serde_json::to_string(&metadata)?
+    ))
+}
+
+// Builds a snapshot from a commit under refs/friendshipper/snapshots/, skipping commits without
+// readable metadata
+fn parse_snapshot_commit(commit: &str, message: &str) -> Option<Snapshot> {
+    let metadata = message
+        .lines()
+        .find_map(|line| line.strip_prefix(SNAPSHOT_METADATA_KEY))?;
+    let metadata: SnapshotMetadata = match serde_json::from_str(metadata.trim()) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            debug!("Skipping snapshot {} with bad metadata: {}", commit, e);
+            return None;
+        }
+    };
+    Some(Snapshot {
+        id: metadata.id,
+        commit: commit.to_string(),
+        message: metadata.message,
+        timestamp: metadata.timestamp,
+        author: metadata.author,
+        branch: metadata.branch,
+        files: metadata.files,
+        kind: metadata.kind,
+        tags: metadata.tags,
+        stash_index: String::new(),
+    })
+}
+
+fn sort_snapshots(snapshots: &mut [Snapshot]) {
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+}
+
+// Builds a snapshot from a stash entry, skipping stashes that weren't saved as snapshots
+fn parse_snapshot(
+    stash_index: &str,
+    subject: &str,
+    commit: &str,
+    date: DateTime<Utc>,
+) -> Option<Snapshot> {
+    // Snapshots were stored with `git stash store --message "snapshot <message>"`, so only a
+    // subject in exactly that form is one. Stashes the user pushed with "snapshot" somewhere in
+    // their message ("On main: snapshot of lighting") are left alone.
+    let message = subject.strip_prefix(SNAPSHOT_PREFIX)?.strip_prefix(' ')?;
+    Some(Snapshot {
+        commit: commit.to_string(),
+        message: message.trim().to_string(),
+        timestamp: date,
+        stash_index: stash_index.to_string(),
+        ..Default::default()
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct CliReadBackend {
+    git: Git,
+}
+
+impl CliReadBackend {
+    pub fn new(git: Git) -> Self {
+        Self { git }
+    }
+}
+
+#[async_trait]
+impl GitReadBackend for CliReadBackend {
+    async fn current_branch(&self) -> anyhow::Result<String> {
+        let output = self
+            .git
+            .run_and_collect_output(&["branch", "--show-current"], Opts::default())
+            .await?;
+
+        Ok(output
+            .lines()
+            .take(1)
+            .next()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    async fn get_ahead_behind(&self, from_ref: &str, to_ref: &str) -> anyhow::Result<(u32, u32)> {
+        let output = self
+            .git
+            .run_and_collect_output(
+                &[
+                    "rev-list",
+                    "--count",
+                    "--left-only",
+                    &format!("{from_ref}...{to_ref}"),
+                ],
+                Opts::default(),
+            )
+            .await?;
+        let behind_count = output.lines().next().unwrap_or("0").parse::<u32>()?;
+
+        let output = self
+            .git
+            .run_and_collect_output(
+                &[
+                    "rev-list",
+                    "--count",
+                    "--right-only",
+                    &format!("{from_ref}...{to_ref}"),
+                ],
+                Opts::default(),
+            )
+            .await?;
+        let ahead_count = output.lines().next().unwrap_or("0").parse::<u32>()?;
+
+        Ok((ahead_count, behind_count))
+    }
+
+    async fn log(&self, limit: usize, git_ref: &str) -> anyhow::Result<Vec<LogEntry>> {
+        let output = self
+            .git
+            .run_and_collect_output(
+                &[
+                    "--no-pager",
+                    "log",
+                    &format!("-{limit}"),
+                    "--pretty=format:%H%x1f%B%x1f%an%x1f%aI%x1f%x1e",
+                    git_ref,
+                ],
+                Opts::new_without_logs(),
+            )
+            .await?;
+
+        Ok(output
+            .split('\x1e')
+            .map(|record| record.trim_start_matches('\n'))
+            .filter(|record| !record.trim().is_empty())
+            .filter_map(|record| {
+                let parts = record.split('\x1f').collect::<Vec<_>>();
+                if parts.len() < 4 {
+                    warn!(
+                        "Invalid git log line format (expected at least 4 parts): {}",
+                        record
+                    );
+                    return None;
+                }
+
+                let author_date = DateTime::parse_from_rfc3339(parts[3])
+                    .map_err(|e| {
+                        warn!(
+                            "Failed to parse timestamp '{}' from git log: {}",
+                            parts[3], e
+                        )
+                    })
+                    .ok();
+
+                Some(LogEntry {
+                    sha: parts[0].to_string(),
+                    message: parts[1].trim_end().to_string(),
+                    author_name: parts[2].to_string(),
+                    author_date,
+                })
+            })
+            .collect())
+    }
+
+    async fn has_local_branch(&self, branch: &str) -> anyhow::Result<bool> {
+        let output = self
+            .git
+            .run_and_collect_output(&["branch", "--list", branch], Opts::default())
+            .await?;
+
+        Ok(!output.trim().is_empty())
+    }
+
+    async fn diff_filenames(&self, range: &str) -> anyhow::Result<Vec<String>> {
+        let output = self
+            .git
+            .run_and_collect_output(&["diff", "--name-only", range], Opts::new_without_logs())
+            .await?;
+        let mut result = output.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+        result.dedup();
+        Ok(result)
+    }
+
+    async fn list_snapshots(&self) -> anyhow::Result<Vec<Snapshot>> {
+        // NUL-separated since commit messages span lines: sha, message, sha, message...
+        let output = self
+            .git
+            .run_and_collect_output(
+                &[
+                    "for-each-ref",
+                    "--format=%(objectname)%00%(contents)%00",
+                    SNAPSHOT_REF_PREFIX,
+                ],
+                Opts::new_without_logs(),
+            )
+            .await?;
+
+        let fields = output.split('\0').collect::<Vec<_>>();
+        let mut snapshots = fields
+            .chunks_exact(2)
+            .filter_map(|pair| parse_snapshot_commit(pair[0].trim(), pair[1]))
+            .collect::<Vec<_>>();
+        sort_snapshots(&mut snapshots);
+        Ok(snapshots)
+    }
+
+    async fn list_stash_snapshots(&self) -> anyhow::Result<Vec<Snapshot>> {
+        let output = self
+            .git
+            .run_and_collect_output(
+                &["stash", "list", "--pretty=format:%gd|%gs|%H|%aI"],
+                Opts::new_without_logs(),
+            )
+            .await?;
+
+        let snapshots = output
+            .lines()
+            .filter_map(|line| {
+                let parts = line.split('|').collect::<Vec<_>>();
+                if parts.len() < 4 {
+                    debug!("Skipping line due to bad parse: {}", line);
+                    return None;
+                }
+
+                match DateTime::parse_from_rfc3339(parts[3].trim()) {
+                    Ok(date) => parse_snapshot(
+                        parts[0].trim(),
+                        parts[1],
+                        parts[2].trim(),
+                        date.with_timezone(&Utc),
+                    ),
+                    Err(e) => {
+                        info!("Failed to parse date: {}", e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Ok(snapshots)
+    }
+}
+
+// Reads the repository in-process with libgit2. Every query opens the repository on a blocking
+// thread, which is still far cheaper than spawning git on large repos.
+#[derive(Clone, Debug)]
+pub struct InProcessReadBackend {
+    repo_path: PathBuf,
+}
+
+impl InProcessReadBackend {
+    pub fn new(repo_path: PathBuf) -> Self {
+        GIT2_INIT.call_once(|| {
+            // Safety: only called once, before any repository is opened
+            if let Err(e) = unsafe { git2::opts::set_extensions(GIT2_EXTENSIONS) } {
+                warn!("Failed to register libgit2 repository extensions: {}", e);
+            }
+        });
+        Self { repo_path }
+    }
+
+    async fn with_repo<R, F>(&self, f: F) -> anyhow::Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(Repository) -> anyhow::Result<R> + Send + 'static,
+    {
+        let repo_path = self.repo_path.clone();
+        tokio::task::spawn_blocking(move || f(Repository::open(repo_path)?)).await?
+    }
+}
+
+fn git_time(time: git2::Time) -> Option<DateTime<FixedOffset>> {
+    FixedOffset::east_opt(time.offset_minutes() * 60)?
+        .timestamp_opt(time.seconds(), 0)
+        .single()
+}
+
+#[async_trait]
+impl GitReadBackend for InProcessReadBackend {
+    async fn current_branch(&self) -> anyhow::Result<String> {
+        self.with_repo(|repo| {
+            // Read HEAD directly, since an unborn branch still has a name
+            let head = repo.find_reference("HEAD")?;
+            Ok(head
+                .symbolic_target()
+                .map(|target| target.strip_prefix("refs/heads/").unwrap_or(target))
+                .unwrap_or_default()
+                .to_string())
+        })
+        .await
+    }
+
+    async fn get_ahead_behind(&self, from_ref: &str, to_ref: &str) -> anyhow::Result<(u32, u32)> {
+        let (from_ref, to_ref) = (from_ref.to_string(), to_ref.to_string());
+        self.with_repo(move |repo| {
+            let from = repo.revparse_single(&from_ref)?.peel_to_commit()?.id();
+            let to = repo.revparse_single(&to_ref)?.peel_to_commit()?.id();
+            let (ahead, behind) = repo.graph_ahead_behind(to, from)?;
+            Ok((ahead as u32, behind as u32))
+        })
+        .await
+    }
+
+    async fn log(&self, limit: usize, git_ref: &str) -> anyhow::Result<Vec<LogEntry>> {
+        let git_ref = git_ref.to_string();
+        self.with_repo(move |repo| {
+            let spec = repo.revparse(&git_ref)?;
+            if spec.mode().contains(RevparseMode::MERGE_BASE) {
+                bail!(
+                    "symmetric difference {} isn't supported in-process",
+                    git_ref
+                );
+            }
+
+            let mut walk = repo.revwalk()?;
+            // TIME alone can put a parent before a child committed in the same second
+            walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+            match (spec.from(), spec.to()) {
+                (Some(from), Some(to)) if spec.mode().contains(RevparseMode::RANGE) => {
+                    walk.push(to.peel_to_commit()?.id())?;
+                    walk.hide(from.peel_to_commit()?.id())?;
+                }
+                (Some(single), _) => walk.push(single.peel_to_commit()?.id())?,
+                _ => bail!("unable to resolve {}", git_ref),
+            }
+
+            walk.take(limit)
+                .map(|oid| -> anyhow::Result<LogEntry> {
+                    let commit = repo.find_commit(oid?)?;
+                    let author = commit.author();
+                    Ok(LogEntry {
+                        sha: commit.id().to_string(),
+                        message: String::from_utf8_lossy(commit.message_bytes())
+                            .trim_end()
+                            .to_string(),
+                        author_name: String::from_utf8_lossy(author.name_bytes()).to_string(),
+                        author_date: git_time(author.when()),
+                    })
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn has_local_branch(&self, branch: &str) -> anyhow::Result<bool> {
+        // git branch --list takes a pattern, which we leave to the CLI
+        if branch.contains(['*', '?', '[']) {
+            bail!("branch pattern {} isn't supported in-process", branch);
+        }
+
+        let branch = branch.to_string();
+        self.with_repo(move |repo| Ok(repo.find_branch(&branch, git2::BranchType::Local).is_ok()))
+            .await
+    }
+
+    async fn diff_filenames(&self, range: &str) -> anyhow::Result<Vec<String>> {
+        let range = range.to_string();
+        self.with_repo(move |repo| {
+            let spec = repo.revparse(&range)?;
+            let (Some(from), Some(to)) = (spec.from(), spec.to()) else {
+                // A single ref diffs against the working tree, which is left to the CLI so
+                // filters and attributes are applied the same way git status does
+                bail!(
+                    "working tree diff against {} isn't supported in-process",
+                    range
+                );
+            };
+
+            let to = to.peel_to_commit()?;
+            let from = if spec.mode().contains(RevparseMode::MERGE_BASE) {
+                let base = repo.merge_base(from.peel_to_commit()?.id(), to.id())?;
+                repo.find_commit(base)?
+            } else {
+                from.peel_to_commit()?
+            };
+
+            let mut diff = repo.diff_tree_to_tree(Some(&from.tree()?), Some(&to.tree()?), None)?;
+
+            // git diff detects renames by default and only lists the new path
+            diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+            let mut result = diff
+                .deltas()
+                .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+                .map(|path| path.to_string_lossy().replace('\\', "/"))
+                .collect::<Vec<_>>();
+            result.sort();
+            result.dedup();
+            Ok(result)
+        })
+        .await
+    }
+
+    async fn list_snapshots(&self) -> anyhow::Result<Vec<Snapshot>> {
+        self.with_repo(|repo| {
+            let mut snapshots = vec![];
+            for reference in repo.references_glob(&format!("{SNAPSHOT_REF_PREFIX}*"))? {
+                let commit = reference?.peel_to_commit()?;
+                snapshots.extend(parse_snapshot_commit(
+                    &commit.id().to_string(),
+                    &String::from_utf8_lossy(commit.message_bytes()),
+                ));
+            }
+            sort_snapshots(&mut snapshots);
+            Ok(snapshots)
+        })
+        .await
+    }
+
+    async fn list_stash_snapshots(&self) -> anyhow::Result<Vec<Snapshot>> {
+        self.with_repo(|mut repo| {
+            let mut stashes = vec![];
+            repo.stash_foreach(|index, subject, oid| {
+                stashes.push((index, subject.to_string(), *oid));
+                true
+            })?;
+
+            let mut snapshots = vec![];
+            for (index, subject, oid) in stashes {
+                let commit = repo.find_commit(oid)?;
+                let date = git_time(commit.author().when())
+                    .ok_or_else(|| anyhow!("invalid author date on stash {}", oid))?;
+                snapshots.extend(parse_snapshot(
+                    &format!("stash@{{{index}}}"),
+                    &subject,
+                    &oid.to_string(),
+                    date.with_timezone(&Utc),
+                ));
+            }
+            Ok(snapshots)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command as StdCommand;
+    use std::sync::mpsc;
+
+    fn git(path: &std::path::Path, args: &[&str]) -> String {
+        let out = StdCommand::new("git")
+            .args(args)
+            .current_dir(path)
+            .output()
+            .expect("run git");
+        assert!(
+            out.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&out.stderr)
+        );
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+
+    fn commit_file(path: &std::path::Path, file: &str, content: &str, message: &str) {
+        std::fs::write(path.join(file), content).unwrap();
+        git(path, &["add", file]);
+        git(path, &["commit", "-m", message]);
+    }
+
+    fn backends(path: &std::path::Path) -> Vec<Box<dyn GitReadBackend>> {
+        let (tx, _rx) = mpsc::channel();
+        let git = Git::new(path.to_path_buf(), tx);
+        vec![
+            Box::new(CliReadBackend::new(git)),
+            Box::new(InProcessReadBackend::new(path.to_path_buf())),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_backends_agree() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        for args in [
+            &["init", "-b", "main"][..],
+            &["config", "user.email", "test@example.com"],
+            &["config", "user.name", "test"],
+            &["config", "commit.gpgsign", "false"],
+            &["config", "core.autocrlf", "false"],
+        ] {
+            git(path, args);
+        }
+
+        commit_file(path, "seed.txt", "seed", "seed");
+        git(path, &["branch", "feature"]);
+        commit_file(path, "main.txt", "main", "main change\n\nwith a body");
+        git(path, &["checkout", "feature"]);
+        commit_file(path, "feature.txt", "feature", "feature change");
+        git(path, &["mv", "seed.txt", "renamed.txt"]);
+        git(path, &["commit", "-m", "rename"]);
+
+        std::fs::write(path.join("feature.txt"), "dirty").unwrap();
+        // stored the way older versions saved snapshots to the stash list
+        let stash = git(path, &["stash", "create"]);
+        git(
+            path,
+            &[
+                "stash",
+                "store",
+                "--message",
+                "snapshot before pull",
+                &stash,
+            ],
+        );
+        std::fs::write(path.join("feature.txt"), "manual").unwrap();
+        // a user's own stash that merely mentions a snapshot isn't one
+        git(path, &["stash", "push", "-m", "snapshot of the lighting"]);
+
+        let snapshot = Snapshot {
+            id: "20240101-000000-abcdef12".to_string(),
+            message: "before submit\nsecond line".to_string(),
+            timestamp: Utc::now(),
+            author: "test".to_string(),
+            branch: "feature".to_string(),
+            files: vec!["feature.txt".to_string()],
+            kind: SnapshotKind::Automatic,
+            tags: vec!["handoff".to_string()],
+            ..Default::default()
+        };
+        let message = snapshot_commit_message(&snapshot).unwrap();
+        let out = StdCommand::new("git")
+            .args(["commit-tree", "HEAD^{tree}", "-p", "HEAD", "-m", &message])
+            .current_dir(path)
+            .output()
+            .unwrap();
+        let snapshot_commit = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        git(
+            path,
+            &[
+                "update-ref",
+                &format!("{SNAPSHOT_REF_PREFIX}{}", snapshot.id),
+                &snapshot_commit,
+            ],
+        );
+
+        let mut results = vec![];
+        for backend in backends(path) {
+            let log = backend.log(10, "feature").await.unwrap();
+            let range_log = backend.log(10, "main..feature").await.unwrap();
+            results.push((
+                backend.current_branch().await.unwrap(),
+                backend.get_ahead_behind("main", "feature").await.unwrap(),
+                log,
+                range_log.len(),
+                backend.has_local_branch("main").await.unwrap(),
+                backend.has_local_branch("missing").await.unwrap(),
+                backend.diff_filenames("main...feature").await.unwrap(),
+                backend.diff_filenames("main..feature").await.unwrap(),
+                backend
+                    .list_stash_snapshots()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|s| (s.commit, s.message, s.timestamp, s.stash_index))
+                    .collect::<Vec<_>>(),
+                backend
+                    .list_snapshots()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|s| (s.id, s.commit, s.message, s.kind, s.tags))
+                    .collect::<Vec<_>>(),
+            ));
+        }
+
+        let (
+            branch,
+            ahead_behind,
+            log,
+            range_log,
+            has_main,
+            has_missing,
+            diff,
+            two_dot,
+            stash_snapshots,
+            snapshots,
+        ) = &results[0];
+        assert_eq!(branch, "feature");
+        assert_eq!(*ahead_behind, (2, 1));
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[0].message, "rename");
+        assert_eq!(*range_log, 2);
+        assert!(*has_main && !*has_missing);
+        assert_eq!(diff, &vec!["feature.txt", "renamed.txt"]);
+        assert_eq!(two_dot, &vec!["feature.txt", "main.txt", "renamed.txt"]);
+        assert_eq!(stash_snapshots.len(), 1);
+        assert_eq!(stash_snapshots[0].1, "before pull");
+        assert_eq!(stash_snapshots[0].3, "stash@{1}");
+        assert_eq!(
+            snapshots,
+            &vec![(
+                snapshot.id.clone(),
+                snapshot_commit.clone(),
+                snapshot.message.clone(),
+                SnapshotKind::Automatic,
+                vec!["handoff".to_string()],
+            )]
+        );
+
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[tokio::test]
+    async fn test_in_process_detached_and_unsupported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        git(path, &["init", "-b", "main"]);
+        git(path, &["config", "user.email", "test@example.com"]);
+        git(path, &["config", "user.name", "test"]);
+        git(path, &["config", "commit.gpgsign", "false"]);
+
+        let backend = InProcessReadBackend::new(path.to_path_buf());
+
+        // unborn branch still reports its name
+        assert_eq!(backend.current_branch().await.unwrap(), "main");
+
+        commit_file(path, "seed.txt", "seed", "seed");
+        git(path, &["checkout", "--detach"]);
+        assert_eq!(backend.current_branch().await.unwrap(), "");
+
+        assert!(backend.diff_filenames("main").await.is_err());
+        assert!(backend.has_local_branch("ma*").await.is_err());
+    }
+}
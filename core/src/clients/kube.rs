@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
 use std::io::Write;
 use std::sync::mpsc::Sender;
@@ -37,6 +37,12 @@ use crate::{AWSClient, KUBE_SHA_LABEL_KEY};
 
 static SHA_LABEL_KEY: &str = KUBE_SHA_LABEL_KEY;
 
+// The only workflow template friendshipper is allowed to submit, and where it runs
+const PROMOTE_BUILD_WORKFLOW_TEMPLATE: &str = "promote-fellowship-build";
+const PROMOTE_BUILD_WORKFLOW_NAMESPACE: &str = "argo-unreal-ci";
+const WORKFLOW_TEMPLATE_LABEL_KEY: &str = "workflows.argoproj.io/workflow-template";
+const PROMOTE_COMMIT_LABEL_KEY: &str = "believer.dev/commit";
+
 #[derive(Clone, Debug)]
 pub struct KubeClient {
     aws_creds: AWSClient,
@@ -818,8 +824,6 @@ impl KubeClient {
         request: CreatePromoteBuildWorkflowRequest,
     ) -> Result<Workflow, CoreError> {
         // Security validation: Only allow promote build workflows
-        const ALLOWED_TEMPLATE_NAME: &str = "promote-fellowship-build";
-        const ALLOWED_NAMESPACE: &str = "argo-unreal-ci";
 
         // Only the commit field is required
 
@@ -838,16 +842,16 @@ impl KubeClient {
         }
 
         let client = Client::try_from(self.kubeconfig().await?)?;
-        let api: Api<Workflow> = Api::namespaced(client, ALLOWED_NAMESPACE);
+        let api: Api<Workflow> = Api::namespaced(client, PROMOTE_BUILD_WORKFLOW_NAMESPACE);
 
         let pp = PostParams::default();
 
         let mut labels = std::collections::BTreeMap::new();
         labels.insert(
-            "workflows.argoproj.io/workflow-template".to_string(),
-            ALLOWED_TEMPLATE_NAME.to_string(),
+            WORKFLOW_TEMPLATE_LABEL_KEY.to_string(),
+            PROMOTE_BUILD_WORKFLOW_TEMPLATE.to_string(),
         );
-        labels.insert("believer.dev/commit".to_string(), request.commit.clone());
+        labels.insert(PROMOTE_COMMIT_LABEL_KEY.to_string(), request.commit.clone());
         if let Some(pusher) = &request.pusher {
             labels.insert("believer.dev/pusher".to_string(), pusher.clone());
         }
@@ -855,7 +859,7 @@ impl KubeClient {
         let workflow = Workflow {
             metadata: kube::api::ObjectMeta {
                 generate_name: Some("promote-fellowship-build-".to_string()),
-                namespace: Some(ALLOWED_NAMESPACE.to_string()),
+                namespace: Some(PROMOTE_BUILD_WORKFLOW_NAMESPACE.to_string()),
                 labels: Some(labels),
                 ..Default::default()
             },
@@ -889,7 +893,7 @@ impl KubeClient {
                     }),
                 }),
                 workflow_template_ref: Some(WorkflowTemplateRef {
-                    name: ALLOWED_TEMPLATE_NAME.to_string(),
+                    name: PROMOTE_BUILD_WORKFLOW_TEMPLATE.to_string(),
                 }),
             },
             status: None,
@@ -901,6 +905,36 @@ impl KubeClient {
         }
     }
 
+    // Commits with a successful promote build workflow still present in the cluster
+    #[instrument(skip(self))]
+    pub async fn get_promoted_build_commits(&self) -> Result<HashSet<String>, CoreError> {
+        let client = Client::try_from(self.kubeconfig().await?)?;
+        let api: Api<Workflow> = Api::namespaced(client, PROMOTE_BUILD_WORKFLOW_NAMESPACE);
+
+        let lp = ListParams::default().labels(&format!(
+            "{}={}",
+            WORKFLOW_TEMPLATE_LABEL_KEY, PROMOTE_BUILD_WORKFLOW_TEMPLATE
+        ));
+
+        match api.list(&lp).await {
+            Ok(res) => Ok(res
+                .items
+                .into_iter()
+                .filter(|wf| {
+                    wf.status
+                        .as_ref()
+                        .is_some_and(|status| status.phase == "Succeeded")
+                })
+                .filter_map(|wf| {
+                    wf.metadata
+                        .labels
+                        .and_then(|mut labels| labels.remove(PROMOTE_COMMIT_LABEL_KEY))
+                })
+                .collect()),
+            Err(e) => Err(self.handle_kube_error(e).await),
+        }
+    }
+
     #[instrument(skip(self))]
     pub async fn get_project_configs(&self) -> Result<Vec<ProjectConfig>, CoreError> {
         // load configmap with name `projects` in `game-servers` namespace
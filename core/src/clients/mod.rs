@@ -7,5 +7,6 @@ pub mod obs;
 pub mod command;
 pub mod git;
 mod git_maintenance_runner;
+pub mod git_read;
 
 pub use git_maintenance_runner::GitMaintenanceRunner;
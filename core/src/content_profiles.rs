@@ -0,0 +1,252 @@
+use anyhow::anyhow;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::types::config::ContentProfile;
+use crate::types::repo::{ContentProfilePlan, LfsFileInfo};
+
+const LARGEST_DOWNLOADS_LIMIT: usize = 20;
+
+/// Decides which repo-relative, forward-slash-separated paths a `ContentProfile` hydrates.
+/// Mirrors what git does with the profile applied: cone-mode sparse checkout decides what is in
+/// the working tree at all, and the LFS fetch rules decide which of those LFS files are smudged.
+pub struct ContentProfileMatcher {
+    sparse_directories: Vec<String>,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl ContentProfileMatcher {
+    /// Fails on the first pattern that doesn't compile, since applying a profile with a broken
+    /// rule would hydrate something other than what the plan reported.
+    pub fn compile(profile: &ContentProfile) -> anyhow::Result<Self> {
+        let sparse_directories = profile
+            .sparse_directories
+            .iter()
+            .map(|dir| normalize(dir))
+            .filter(|dir| !dir.is_empty())
+            .collect();
+
+        let include = if profile.lfs_fetch_include.is_empty() {
+            None
+        } else {
+            Some(compile_lfs_patterns(&profile.lfs_fetch_include)?)
+        };
+        let exclude = compile_lfs_patterns(&profile.lfs_fetch_exclude)?;
+
+        Ok(ContentProfileMatcher {
+            sparse_directories,
+            include,
+            exclude,
+        })
+    }
+
+    /// The profile's directories as they're handed to `git sparse-checkout set --cone`: trimmed,
+    /// with forward slashes and no leading or trailing slash.
+    pub fn sparse_directories(&self) -> &[String] {
+        &self.sparse_directories
+    }
+
+    /// Cone mode checks out everything under each directory, plus the files directly inside the
+    /// root and inside every parent of those directories.
+    pub fn in_sparse_checkout(&self, path: &str) -> bool {
+        if self.sparse_directories.is_empty() {
+            return true;
+        }
+
+        let Some((parent, _)) = path.rsplit_once('/') else {
+            return true;
+        };
+
+        self.sparse_directories.iter().any(|dir| {
+            parent == dir
+                || parent.starts_with(&format!("{dir}/"))
+                || dir.starts_with(&format!("{parent}/"))
+        })
+    }
+
+    pub fn fetches_lfs(&self, path: &str) -> bool {
+        if !self.in_sparse_checkout(path) || self.exclude.is_match(path) {
+            return false;
+        }
+
+        match &self.include {
+            Some(include) => include.is_match(path),
+            None => true,
+        }
+    }
+}
+
+/// Works out how much LFS data applying `profile` would hydrate and download, from the LFS files
+/// tracked at HEAD.
+pub fn plan(profile: &ContentProfile, files: &[LfsFileInfo]) -> anyhow::Result<ContentProfilePlan> {
+    let matcher = ContentProfileMatcher::compile(profile)?;
+
+    let mut plan = ContentProfilePlan {
+        profile: profile.name.clone(),
+        ..Default::default()
+    };
+    let mut downloads: Vec<&LfsFileInfo> = vec![];
+
+    for file in files {
+        if matcher.fetches_lfs(&file.path) {
+            plan.included_files += 1;
+            plan.included_bytes += file.size;
+            if !file.downloaded {
+                plan.download_files += 1;
+                plan.download_bytes += file.size;
+                downloads.push(file);
+            }
+        } else {
+            plan.excluded_files += 1;
+            plan.excluded_bytes += file.size;
+        }
+    }
+
+    downloads.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    plan.largest_downloads = downloads
+        .into_iter()
+        .take(LARGEST_DOWNLOADS_LIMIT)
+        .cloned()
+        .collect();
+
+    Ok(plan)
+}
+
+fn normalize(path: &str) -> String {
+    path.trim().replace('\\', "/").trim_matches('/').to_string()
+}
+
+// git-lfs treats its fetch patterns like gitignore entries: a pattern without a slash matches a
+// file or directory name at any depth, one with a slash is anchored at the repo root, and a
+// matching directory takes everything under it.
+fn compile_lfs_patterns(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let normalized = normalize(pattern);
+        if normalized.is_empty() {
+            continue;
+        }
+
+        let anchored = if normalized.contains('/') {
+            normalized
+        } else {
+            format!("**/{normalized}")
+        };
+
+        for glob in [anchored.clone(), format!("{anchored}/**")] {
+            let glob = GlobBuilder::new(&glob)
+                .literal_separator(true)
+                .backslash_escape(false)
+                .build()
+                .map_err(|e| anyhow!("Invalid LFS fetch pattern `{}`: {}", pattern, e))?;
+            builder.add(glob);
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(sparse: &[&str], include: &[&str], exclude: &[&str]) -> ContentProfile {
+        let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect();
+        ContentProfile {
+            name: "test".to_string(),
+            description: String::new(),
+            sparse_directories: strings(sparse),
+            lfs_fetch_include: strings(include),
+            lfs_fetch_exclude: strings(exclude),
+        }
+    }
+
+    fn lfs_file(path: &str, size: u64, downloaded: bool) -> LfsFileInfo {
+        LfsFileInfo {
+            path: path.to_string(),
+            size,
+            downloaded,
+        }
+    }
+
+    #[test]
+    fn cone_includes_root_files_and_parents_of_each_directory() {
+        let matcher =
+            ContentProfileMatcher::compile(&profile(&["Content/Maps/"], &[], &[])).unwrap();
+        assert!(matcher.in_sparse_checkout("Game.uproject"));
+        assert!(matcher.in_sparse_checkout("Content/Maps/Level.umap"));
+        assert!(matcher.in_sparse_checkout("Content/Maps/Sub/Actor.uasset"));
+        assert!(matcher.in_sparse_checkout("Content/Shared.uasset"));
+        assert!(!matcher.in_sparse_checkout("Content/Audio/Music.uasset"));
+        assert!(!matcher.in_sparse_checkout("Content/MapsOld/Level.umap"));
+    }
+
+    #[test]
+    fn sparse_directories_are_normalized() {
+        let matcher = ContentProfileMatcher::compile(&profile(
+            &[" Content\\Maps\\ ", "/Content/Audio/", "  "],
+            &[],
+            &[],
+        ))
+        .unwrap();
+        assert_eq!(
+            matcher.sparse_directories(),
+            &["Content/Maps".to_string(), "Content/Audio".to_string()]
+        );
+    }
+
+    #[test]
+    fn empty_profile_fetches_everything() {
+        let matcher = ContentProfileMatcher::compile(&profile(&[], &[], &[])).unwrap();
+        assert!(matcher.fetches_lfs("Content/Audio/Music.uasset"));
+        assert!(matcher.fetches_lfs("Binaries/Game.dll"));
+    }
+
+    #[test]
+    fn lfs_patterns_follow_gitignore_rules() {
+        let matcher =
+            ContentProfileMatcher::compile(&profile(&[], &["Content/Audio", "*.wav"], &["Raw"]))
+                .unwrap();
+        assert!(matcher.fetches_lfs("Content/Audio/Music.uasset"));
+        assert!(matcher.fetches_lfs("Source/Sfx/Hit.wav"));
+        assert!(!matcher.fetches_lfs("Content/Maps/Level.umap"));
+        assert!(!matcher.fetches_lfs("Content/Audio/Raw/Music.uasset"));
+        assert!(!matcher.fetches_lfs("Raw/Take.wav"));
+    }
+
+    #[test]
+    fn invalid_pattern_fails_to_compile() {
+        let err = ContentProfileMatcher::compile(&profile(&[], &["Content/[Maps"], &[]))
+            .err()
+            .expect("unclosed character class should not compile");
+        assert!(err.to_string().contains("Content/[Maps"), "{err}");
+    }
+
+    #[test]
+    fn plan_counts_only_missing_files_as_downloads() {
+        let files = vec![
+            lfs_file("Content/Maps/Level.umap", 500, false),
+            lfs_file("Content/Maps/Cached.umap", 300, true),
+            lfs_file("Content/Maps/Big.umap", 900, false),
+            lfs_file("Content/Audio/Music.uasset", 1000, false),
+        ];
+        let plan = plan(&profile(&["Content/Maps"], &[], &[]), &files).unwrap();
+
+        assert_eq!(plan.profile, "test");
+        assert_eq!(plan.included_files, 3);
+        assert_eq!(plan.included_bytes, 1700);
+        assert_eq!(plan.download_files, 2);
+        assert_eq!(plan.download_bytes, 1400);
+        assert_eq!(plan.excluded_files, 1);
+        assert_eq!(plan.excluded_bytes, 1000);
+        let largest = plan
+            .largest_downloads
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            largest,
+            vec!["Content/Maps/Big.umap", "Content/Maps/Level.umap"]
+        );
+    }
+}
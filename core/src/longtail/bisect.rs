@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::storage::{ArtifactEntry, MethodPrefix};
+use crate::types::repo::CommitInfo;
+
+pub const BISECT_FILE: &str = "bisect.json";
+
+// Upper bound on the commits between good and bad that a session will walk
+pub const MAX_BISECT_COMMITS: usize = 5000;
+
+// Serializes read-modify-write of the session between concurrent requests
+static BISECT_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BisectVerdict {
+    Good,
+    Bad,
+
+    // The build couldn't be tested, ex. it crashes on startup for an unrelated reason
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectCommit {
+    pub sha: String,
+
+    // None when no build was published for the commit, in which case it's never tested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<ArtifactEntry>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<BisectVerdict>,
+}
+
+// The commits the regression could have landed in: everything after last_good up to and
+// including first_bad
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectRange {
+    pub last_good: String,
+    pub first_bad: String,
+    pub commits: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectSession {
+    pub project: String,
+    pub method_prefix: MethodPrefix,
+    pub good: String,
+    pub bad: String,
+
+    // Every commit after good up to and including bad, oldest first
+    pub commits: Vec<BisectCommit>,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectStatus {
+    pub session: BisectSession,
+
+    // The next build to test, None once the range can't be narrowed any further
+    pub candidate: Option<BisectCommit>,
+    pub remaining: usize,
+    pub range: BisectRange,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectExport {
+    pub project: String,
+    pub last_good: String,
+    pub first_bad: String,
+    pub finished: bool,
+    pub commits: Vec<CommitInfo>,
+}
+
+// Full shas from raw `git log` output with \x1e separated records and the sha first in each,
+// in the order git printed them
+pub fn parse_log_shas(output: &str) -> Vec<String> {
+    output
+        .split('\x1e')
+        .filter_map(|record| record.trim().split('\x1f').next())
+        .filter(|sha| !sha.is_empty())
+        .map(|sha| sha.to_lowercase())
+        .collect()
+}
+
+impl BisectSession {
+    // Starts a session over commits, given newest first as git log prints them, matching each
+    // commit to its build in artifacts
+    pub fn new(
+        project: String,
+        method_prefix: MethodPrefix,
+        good: String,
+        bad: String,
+        commits: Vec<String>,
+        artifacts: &[ArtifactEntry],
+    ) -> Result<Self> {
+        if commits.is_empty() {
+            bail!("No commits found between {} and {}", good, bad);
+        }
+        if commits.len() >= MAX_BISECT_COMMITS {
+            bail!(
+                "Too many commits between {} and {} to bisect, limit is {}",
+                good,
+                bad,
+                MAX_BISECT_COMMITS
+            );
+        }
+
+        let by_commit: HashMap<String, &ArtifactEntry> = artifacts
+            .iter()
+            .filter_map(|entry| Some((entry.commit.as_ref()?.to_lowercase(), entry)))
+            .collect();
+
+        let commits = commits
+            .into_iter()
+            .rev()
+            .map(|sha| BisectCommit {
+                artifact: by_commit.get(&sha).map(|entry| (*entry).clone()),
+                sha,
+                verdict: None,
+            })
+            .collect::<Vec<_>>();
+
+        if !commits
+            .last()
+            .is_some_and(|c| c.sha.starts_with(&bad.to_lowercase()))
+        {
+            bail!("{} is not a descendant of {}", bad, good);
+        }
+
+        Ok(Self {
+            project,
+            method_prefix,
+            good,
+            bad,
+            commits,
+            started_at: Utc::now(),
+        })
+    }
+
+    fn position(&self, commit: &str) -> Option<usize> {
+        let commit = commit.to_lowercase();
+        self.commits.iter().position(|c| c.sha.starts_with(&commit))
+    }
+
+    // Index of the newest commit known good, None for the session's good commit itself, and of
+    // the oldest commit known bad
+    fn bounds(&self) -> (Option<usize>, usize) {
+        let last_good = self
+            .commits
+            .iter()
+            .rposition(|c| c.verdict == Some(BisectVerdict::Good));
+        let first_bad = self
+            .commits
+            .iter()
+            .position(|c| c.verdict == Some(BisectVerdict::Bad))
+            .unwrap_or(self.commits.len() - 1);
+        (last_good, first_bad)
+    }
+
+    // Records a verdict for a commit in the range, rejecting ones that contradict earlier verdicts
+    pub fn mark(&mut self, commit: &str, verdict: BisectVerdict) -> Result<()> {
+        let pos = self
+            .position(commit)
+            .ok_or_else(|| anyhow!("{} is not between {} and {}", commit, self.good, self.bad))?;
+
+        let (last_good, first_bad) = self.bounds();
+        match verdict {
+            BisectVerdict::Good if pos >= first_bad => {
+                bail!("{} can't be good, it's after a bad commit", commit)
+            }
+            BisectVerdict::Bad if last_good.is_some_and(|good| pos <= good) => {
+                bail!("{} can't be bad, it's before a good commit", commit)
+            }
+            _ => {}
+        }
+
+        self.commits[pos].verdict = Some(verdict);
+        Ok(())
+    }
+
+    fn candidates(&self) -> Vec<usize> {
+        let (last_good, first_bad) = self.bounds();
+        let start = last_good.map_or(0, |good| good + 1);
+        (start..first_bad)
+            .filter(|&i| {
+                let commit = &self.commits[i];
+                commit.artifact.is_some() && commit.verdict != Some(BisectVerdict::Skip)
+            })
+            .collect()
+    }
+
+    // The testable commit closest to the middle of the remaining range
+    pub fn next_candidate(&self) -> Option<&BisectCommit> {
+        let (last_good, first_bad) = self.bounds();
+        let low = last_good.map_or(-1, |good| good as i64);
+        let mid = (low + first_bad as i64) / 2;
+        self.candidates()
+            .into_iter()
+            .min_by_key(|&i| (i as i64 - mid).abs())
+            .map(|i| &self.commits[i])
+    }
+
+    pub fn range(&self) -> BisectRange {
+        let (last_good, first_bad) = self.bounds();
+        let start = last_good.map_or(0, |good| good + 1);
+        BisectRange {
+            last_good: last_good.map_or_else(|| self.good.clone(), |i| self.commits[i].sha.clone()),
+            first_bad: self.commits[first_bad].sha.clone(),
+            commits: self.commits[start..=first_bad]
+                .iter()
+                .map(|c| c.sha.clone())
+                .collect(),
+        }
+    }
+
+    pub fn status(&self) -> BisectStatus {
+        BisectStatus {
+            session: self.clone(),
+            candidate: self.next_candidate().cloned(),
+            remaining: self.candidates().len(),
+            range: self.range(),
+        }
+    }
+}
+
+// The bisect session in progress, persisted as JSON in the download directory so it survives
+// restarts
+#[derive(Debug, Clone)]
+pub struct BisectStore {
+    path: PathBuf,
+}
+
+impl BisectStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn load(&self) -> Option<BisectSession> {
+        let _lock = BISECT_LOCK.lock();
+        Self::read(&self.path)
+    }
+
+    pub fn save(&self, session: &BisectSession) -> Result<()> {
+        let _lock = BISECT_LOCK.lock();
+        Self::write(&self.path, session)
+    }
+
+    // Applies f to the session in progress and saves the result
+    pub fn update<R>(&self, f: impl FnOnce(&mut BisectSession) -> Result<R>) -> Result<R> {
+        let _lock = BISECT_LOCK.lock();
+        let mut session =
+            Self::read(&self.path).ok_or_else(|| anyhow!("No bisect session in progress"))?;
+        let result = f(&mut session)?;
+        Self::write(&self.path, &session)?;
+        Ok(result)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        let _lock = BISECT_LOCK.lock();
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    fn write(path: &Path, session: &BisectSession) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(session)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    fn read(path: &Path) -> Option<BisectSession> {
+        let bytes = fs::read(path).ok()?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| warn!("Discarding unreadable bisect session {:?}: {}", path, e))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(published: &[usize]) -> BisectSession {
+        // git log order, newest first: c9 .. c0
+        let commits = (0..10).rev().map(|i| format!("c{i}")).collect();
+        let artifacts = published
+            .iter()
+            .map(|i| ArtifactEntry {
+                commit: Some(format!("c{i}")),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        BisectSession::new(
+            "project".to_string(),
+            MethodPrefix("s3://bucket/".to_string()),
+            "good".to_string(),
+            "c9".to_string(),
+            commits,
+            &artifacts,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_log_shas() {
+        let output = "AAAA\x1fsubject\n\nbody\x1fJane\x1f2024-03-05T10:30:00+00:00\x1f\x1e\nbbbb\x1fsubject\x1fJohn\x1f2024-03-05T10:00:00+00:00\x1f\x1e";
+        assert_eq!(parse_log_shas(output), vec!["aaaa", "bbbb"]);
+        assert!(parse_log_shas("").is_empty());
+    }
+
+    #[test]
+    fn test_bisect_narrows_to_range() {
+        let mut session = session(&[1, 2, 4, 5, 7, 8, 9]);
+        assert_eq!(session.commits[0].sha, "c0");
+
+        // midpoint of (good, c9] is c4
+        assert_eq!(session.next_candidate().unwrap().sha, "c4");
+        session.mark("c4", BisectVerdict::Good).unwrap();
+
+        // c5 and c7 are equally close to the middle, the older one wins
+        assert_eq!(session.next_candidate().unwrap().sha, "c5");
+        session.mark("c5", BisectVerdict::Skip).unwrap();
+        assert_eq!(session.next_candidate().unwrap().sha, "c7");
+        session.mark("c7", BisectVerdict::Bad).unwrap();
+
+        // c6 has no build and c5 couldn't be tested, which leaves c5..c7
+        assert!(session.next_candidate().is_none());
+        let range = session.range();
+        assert_eq!(range.last_good, "c4");
+        assert_eq!(range.first_bad, "c7");
+        assert_eq!(range.commits, vec!["c5", "c6", "c7"]);
+
+        assert!(session.mark("c8", BisectVerdict::Good).is_err());
+        assert!(session.mark("c3", BisectVerdict::Bad).is_err());
+        assert!(session.mark("zz", BisectVerdict::Bad).is_err());
+    }
+
+    #[test]
+    fn test_bisect_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BisectStore::new(dir.path().join(BISECT_FILE));
+        assert!(store.update(|_| Ok(())).is_err());
+
+        store.save(&session(&[3])).unwrap();
+        store
+            .update(|session| session.mark("c3", BisectVerdict::Bad))
+            .unwrap();
+
+        let status = store.load().unwrap().status();
+        assert!(status.candidate.is_none());
+        assert_eq!(status.range.last_good, "good");
+        assert_eq!(status.range.first_bad, "c3");
+
+        store.clear().unwrap();
+        assert!(store.load().is_none());
+    }
+}
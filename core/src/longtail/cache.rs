@@ -0,0 +1,646 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, TimestampSeconds};
+use tracing::{debug, info, warn};
+use walkdir::WalkDir;
+
+use super::index::BlockHash;
+use super::store::block_path;
+
+// Shared block cache under the local download path, laid out like a longtail store
+pub const CHUNK_CACHE_DIR: &str = "chunk_cache";
+
+pub const DEFAULT_CHUNK_CACHE_SIZE_GB: u64 = 160;
+
+const LEDGER_FILE: &str = "ledger.msgpack";
+
+// Bump when the ledger format changes, older ledgers are rebuilt from what's on disk
+const LEDGER_FORMAT_VERSION: u32 = 1;
+
+// Serializes ledger read-modify-write between concurrent syncs
+static LEDGER_LOCK: Mutex<()> = Mutex::new(());
+
+// Blocks referenced by syncs still downloading, keyed by sync along with the cache they write to.
+// They aren't claimed in the ledger until the sync finishes, so eviction treats them as pinned.
+static IN_FLIGHT: Mutex<BTreeMap<u64, (PathBuf, HashSet<BlockHash>)>> = Mutex::new(BTreeMap::new());
+static NEXT_SYNC_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CacheConsumer {
+    Client,
+    Engine,
+    Dlls,
+}
+
+impl CacheConsumer {
+    pub const ALL: [CacheConsumer; 3] = [
+        CacheConsumer::Client,
+        CacheConsumer::Engine,
+        CacheConsumer::Dlls,
+    ];
+
+    // Where each consumer kept its own cache before they were unified
+    fn legacy_dir(&self) -> &'static str {
+        match self {
+            CacheConsumer::Client => "client_cache",
+            CacheConsumer::Engine => "engine_cache",
+            CacheConsumer::Dlls => "editor_cache",
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BlockEntry {
+    size: u64,
+    #[serde_as(as = "TimestampSeconds<i64>")]
+    last_used: SystemTime,
+
+    // Consumers whose syncs have used this block. Blocks nobody claims are evicted first.
+    consumers: HashSet<CacheConsumer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Ledger {
+    version: u32,
+    blocks: HashMap<BlockHash, BlockEntry>,
+
+    // Blocks used by each consumer's most recent sync, ex. the installed engine
+    pins: HashMap<CacheConsumer, HashSet<BlockHash>>,
+
+    // Last quota each consumer synced with, for reporting
+    quotas: HashMap<CacheConsumer, u64>,
+
+    // Blocks syncs in progress reference, filled in on load
+    #[serde(skip)]
+    in_flight: HashSet<BlockHash>,
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self {
+            version: LEDGER_FORMAT_VERSION,
+            blocks: HashMap::new(),
+            pins: HashMap::new(),
+            quotas: HashMap::new(),
+            in_flight: HashSet::new(),
+        }
+    }
+}
+
+impl Ledger {
+    fn is_pinned(&self, hash: &BlockHash) -> bool {
+        self.in_flight.contains(hash) || self.pins.values().any(|pins| pins.contains(hash))
+    }
+
+    fn total_bytes(&self) -> u64 {
+        self.blocks.values().map(|b| b.size).sum()
+    }
+
+    fn consumer_bytes(&self, consumer: CacheConsumer) -> u64 {
+        self.blocks
+            .values()
+            .filter(|b| b.consumers.contains(&consumer))
+            .map(|b| b.size)
+            .sum()
+    }
+
+    // Least recently used first
+    fn lru(&self, filter: impl Fn(&BlockHash, &BlockEntry) -> bool) -> Vec<BlockHash> {
+        let mut hashes: Vec<(BlockHash, SystemTime)> = self
+            .blocks
+            .iter()
+            .filter(|&(hash, entry)| filter(hash, entry))
+            .map(|(hash, entry)| (*hash, entry.last_used))
+            .collect();
+        hashes.sort_by_key(|(_, last_used)| *last_used);
+        hashes.into_iter().map(|(hash, _)| hash).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerUsage {
+    pub consumer: Option<CacheConsumer>,
+
+    // Blocks shared between consumers count toward each of them
+    pub used_bytes: u64,
+    pub pinned_bytes: u64,
+    pub block_count: usize,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStatus {
+    pub root: PathBuf,
+    pub total_bytes: u64,
+    pub max_size_bytes: u64,
+    pub block_count: usize,
+
+    // One entry per consumer, plus one with no consumer for blocks nobody has claimed
+    pub consumers: Vec<ConsumerUsage>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EvictionReport {
+    pub deleted_blocks: usize,
+    pub deleted_bytes: u64,
+}
+
+// Keeps the blocks a sync references from being evicted while it downloads them, until it's
+// dropped. Held across record_use so there's no gap before the blocks are claimed in the ledger.
+#[derive(Debug)]
+pub struct SyncPins {
+    root: PathBuf,
+    id: u64,
+}
+
+impl SyncPins {
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn pin(&self, blocks: &HashSet<BlockHash>) {
+        IN_FLIGHT
+            .lock()
+            .entry(self.id)
+            .or_insert_with(|| (self.root.clone(), HashSet::new()))
+            .1
+            .extend(blocks.iter().copied());
+    }
+}
+
+impl Drop for SyncPins {
+    fn drop(&mut self) {
+        IN_FLIGHT.lock().remove(&self.id);
+    }
+}
+
+// Content-addressed block cache shared by client, engine and DLL syncs. A ledger next to the
+// blocks tracks which consumers use each block so eviction can respect per-consumer quotas and
+// never touch blocks pinned by a consumer's current install.
+#[derive(Debug, Clone)]
+pub struct ChunkCache {
+    root: PathBuf,
+    max_size_bytes: u64,
+}
+
+impl ChunkCache {
+    pub fn new(root: impl Into<PathBuf>, max_size_bytes: u64) -> Self {
+        Self {
+            root: root.into(),
+            max_size_bytes,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn ledger_path(&self) -> PathBuf {
+        self.root.join(LEDGER_FILE)
+    }
+
+    // Registers a sync writing into this cache, see SyncPins
+    pub fn begin_sync(&self) -> SyncPins {
+        SyncPins {
+            root: self.root.clone(),
+            id: NEXT_SYNC_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    // Loads the ledger and reconciles it with the blocks actually on disk. Blocks that showed up
+    // without going through a sync, ex. migrated from an old cache, are left unclaimed.
+    fn load_ledger(&self) -> Ledger {
+        let mut ledger = match std::fs::read(self.ledger_path()) {
+            Ok(bytes) => match rmp_serde::from_slice::<Ledger>(&bytes) {
+                Ok(ledger) if ledger.version == LEDGER_FORMAT_VERSION => ledger,
+                Ok(_) => Ledger::default(),
+                Err(e) => {
+                    warn!("Discarding unreadable chunk cache ledger: {}", e);
+                    Ledger::default()
+                }
+            },
+            Err(_) => Ledger::default(),
+        };
+
+        let on_disk = self.scan();
+        ledger.blocks.retain(|hash, _| on_disk.contains_key(hash));
+        for (hash, (size, last_used)) in on_disk {
+            ledger.blocks.entry(hash).or_insert_with(|| BlockEntry {
+                size,
+                last_used,
+                consumers: HashSet::new(),
+            });
+        }
+        ledger.in_flight = IN_FLIGHT
+            .lock()
+            .values()
+            .filter(|(root, _)| *root == self.root)
+            .flat_map(|(_, blocks)| blocks.iter().copied())
+            .collect();
+        ledger
+    }
+
+    fn save_ledger(&self, ledger: &Ledger) -> Result<()> {
+        std::fs::create_dir_all(&self.root)?;
+        let bytes = rmp_serde::to_vec_named(ledger)
+            .map_err(|e| anyhow!("Failed to serialize chunk cache ledger: {}", e))?;
+        let path = self.ledger_path();
+        let tmp_path = path.with_extension("msgpack.tmp");
+        std::fs::write(&tmp_path, bytes)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    fn scan(&self) -> HashMap<BlockHash, (u64, SystemTime)> {
+        WalkDir::new(self.root.join("chunks"))
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| {
+                let hash = parse_block_file_name(entry.file_name().to_str()?)?;
+                let metadata = entry.metadata().ok()?;
+                let last_used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                Some((hash, (metadata.len(), last_used)))
+            })
+            .collect()
+    }
+
+    fn delete_block(&self, hash: BlockHash) {
+        let path = self.root.join(block_path(hash));
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Unable to delete cached block {:?}: {:?}", path, e);
+            }
+        }
+    }
+
+    // Records the blocks a successful sync used and pins them as the consumer's current install
+    pub fn record_use(&self, consumer: CacheConsumer, blocks: &HashSet<BlockHash>) -> Result<()> {
+        let _lock = LEDGER_LOCK.lock();
+        let mut ledger = self.load_ledger();
+
+        let now = SystemTime::now();
+        let mut pinned = HashSet::new();
+        for hash in blocks {
+            if let Some(entry) = ledger.blocks.get_mut(hash) {
+                entry.last_used = now;
+                entry.consumers.insert(consumer);
+                pinned.insert(*hash);
+            }
+        }
+        debug!("Pinned {} cached blocks for {:?}", pinned.len(), consumer);
+        ledger.pins.insert(consumer, pinned);
+
+        self.save_ledger(&ledger)
+    }
+
+    // Brings the consumer under its quota, then the whole cache under max_size_bytes, evicting
+    // least recently used blocks first. Pinned blocks, and ones a sync in progress references,
+    // are never evicted.
+    pub fn evict(&self, consumer: CacheConsumer, quota_bytes: u64) -> Result<EvictionReport> {
+        let _lock = LEDGER_LOCK.lock();
+        let mut ledger = self.load_ledger();
+        let mut report = EvictionReport::default();
+        ledger.quotas.insert(consumer, quota_bytes);
+
+        // Over quota, the consumer gives up its claim on its oldest blocks. They're only deleted
+        // once no other consumer claims them.
+        let mut consumer_bytes = ledger.consumer_bytes(consumer);
+        if consumer_bytes > quota_bytes {
+            let own_pins = ledger.pins.get(&consumer).cloned().unwrap_or_default();
+            for hash in ledger
+                .lru(|hash, entry| entry.consumers.contains(&consumer) && !own_pins.contains(hash))
+            {
+                if consumer_bytes <= quota_bytes {
+                    break;
+                }
+                let entry = ledger.blocks.get_mut(&hash).unwrap();
+                entry.consumers.remove(&consumer);
+                consumer_bytes -= entry.size;
+                let (size, unclaimed) = (entry.size, entry.consumers.is_empty());
+
+                if unclaimed && !ledger.is_pinned(&hash) {
+                    ledger.blocks.remove(&hash);
+                    self.delete_block(hash);
+                    report.deleted_blocks += 1;
+                    report.deleted_bytes += size;
+                }
+            }
+        }
+
+        // Unclaimed blocks go before anything a consumer still uses
+        let mut total_bytes = ledger.total_bytes();
+        if total_bytes > self.max_size_bytes {
+            let unclaimed =
+                ledger.lru(|hash, entry| entry.consumers.is_empty() && !ledger.is_pinned(hash));
+            let claimed =
+                ledger.lru(|hash, entry| !entry.consumers.is_empty() && !ledger.is_pinned(hash));
+            for hash in unclaimed.into_iter().chain(claimed) {
+                if total_bytes <= self.max_size_bytes {
+                    break;
+                }
+                let entry = ledger.blocks.remove(&hash).unwrap();
+                self.delete_block(hash);
+                total_bytes -= entry.size;
+                report.deleted_blocks += 1;
+                report.deleted_bytes += entry.size;
+            }
+        }
+
+        if report.deleted_blocks > 0 {
+            info!(
+                "Evicted {} blocks ({} bytes) from the chunk cache, {} bytes remain",
+                report.deleted_blocks, report.deleted_bytes, total_bytes
+            );
+        }
+
+        self.save_ledger(&ledger)?;
+        Ok(report)
+    }
+
+    // Removes specific blocks regardless of pins, ex. when they may be corrupt
+    pub fn remove_blocks(&self, blocks: &HashSet<BlockHash>) -> Result<()> {
+        let _lock = LEDGER_LOCK.lock();
+        let mut ledger = self.load_ledger();
+
+        for hash in blocks {
+            if ledger.blocks.remove(hash).is_some() {
+                self.delete_block(*hash);
+            }
+        }
+        for pins in ledger.pins.values_mut() {
+            pins.retain(|hash| !blocks.contains(hash));
+        }
+
+        self.save_ledger(&ledger)
+    }
+
+    // Drops everything a consumer claims or pins, deleting blocks no other consumer uses
+    pub fn clear(&self, consumer: CacheConsumer) -> Result<()> {
+        let _lock = LEDGER_LOCK.lock();
+        let mut ledger = self.load_ledger();
+        ledger.pins.remove(&consumer);
+
+        let mut deleted = vec![];
+        for (hash, entry) in ledger.blocks.iter_mut() {
+            if entry.consumers.remove(&consumer) && entry.consumers.is_empty() {
+                deleted.push(*hash);
+            }
+        }
+        for hash in &deleted {
+            if !ledger.is_pinned(hash) {
+                ledger.blocks.remove(hash);
+                self.delete_block(*hash);
+            }
+        }
+        info!("Cleared {} cached blocks for {:?}", deleted.len(), consumer);
+
+        self.save_ledger(&ledger)
+    }
+
+    pub fn status(&self) -> CacheStatus {
+        let _lock = LEDGER_LOCK.lock();
+        let ledger = self.load_ledger();
+
+        let mut consumers: Vec<ConsumerUsage> = CacheConsumer::ALL
+            .iter()
+            .map(|consumer| {
+                let pins = ledger.pins.get(consumer);
+                let blocks: Vec<(&BlockHash, &BlockEntry)> = ledger
+                    .blocks
+                    .iter()
+                    .filter(|(_, entry)| entry.consumers.contains(consumer))
+                    .collect();
+                ConsumerUsage {
+                    consumer: Some(*consumer),
+                    used_bytes: blocks.iter().map(|(_, entry)| entry.size).sum(),
+                    pinned_bytes: blocks
+                        .iter()
+                        .filter(|(hash, _)| pins.is_some_and(|pins| pins.contains(*hash)))
+                        .map(|(_, entry)| entry.size)
+                        .sum(),
+                    block_count: blocks.len(),
+                    quota_bytes: ledger.quotas.get(consumer).copied(),
+                }
+            })
+            .collect();
+
+        let unclaimed: Vec<&BlockEntry> = ledger
+            .blocks
+            .values()
+            .filter(|entry| entry.consumers.is_empty())
+            .collect();
+        consumers.push(ConsumerUsage {
+            consumer: None,
+            used_bytes: unclaimed.iter().map(|entry| entry.size).sum(),
+            block_count: unclaimed.len(),
+            ..Default::default()
+        });
+
+        CacheStatus {
+            root: self.root.clone(),
+            total_bytes: ledger.total_bytes(),
+            max_size_bytes: self.max_size_bytes,
+            block_count: ledger.blocks.len(),
+            consumers,
+        }
+    }
+
+    // Moves blocks out of the per-consumer caches that predate this one. Everything in them uses
+    // the same block layout, so they're picked up as unclaimed blocks rather than re-downloaded.
+    pub fn migrate_legacy_caches(&self, download_path: &Path) -> Result<()> {
+        for consumer in CacheConsumer::ALL {
+            let legacy = download_path.join(consumer.legacy_dir());
+            if !legacy.exists() {
+                continue;
+            }
+
+            let mut moved = 0;
+            let mut failed = 0;
+            for entry in WalkDir::new(&legacy).into_iter().filter_map(|e| e.ok()) {
+                let Some(hash) = entry.file_name().to_str().and_then(parse_block_file_name) else {
+                    continue;
+                };
+                let dest = self.root.join(block_path(hash));
+                if dest.exists() {
+                    continue;
+                }
+                if let Some(parent) = dest.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                match move_block(entry.path(), &dest) {
+                    Ok(()) => moved += 1,
+                    Err(e) => {
+                        warn!("Unable to migrate block {:?}: {:?}", entry.path(), e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            info!(
+                "Migrated {} blocks from legacy {:?} cache at {:?}",
+                moved, consumer, legacy
+            );
+
+            // Anything left behind is retried on the next launch rather than thrown away
+            if failed > 0 {
+                warn!(
+                    "Keeping legacy {:?} cache at {:?}: {} blocks could not be migrated",
+                    consumer, legacy, failed
+                );
+                continue;
+            }
+            std::fs::remove_dir_all(&legacy)?;
+        }
+        Ok(())
+    }
+}
+
+// A rename fails across devices, so fall back to copying the block and removing the original
+fn move_block(src: &Path, dest: &Path) -> std::io::Result<()> {
+    if std::fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+
+    if let Err(e) = std::fs::copy(src, dest) {
+        let _ = std::fs::remove_file(dest);
+        return Err(e);
+    }
+    std::fs::remove_file(src)
+}
+
+// Block files are named 0x<16 hex digits>.lsb
+fn parse_block_file_name(name: &str) -> Option<BlockHash> {
+    let hex = name.strip_prefix("0x")?.strip_suffix(".lsb")?;
+    u64::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_block(cache: &ChunkCache, hash: BlockHash, size: usize) {
+        let path = cache.root().join(block_path(hash));
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, vec![0u8; size]).unwrap();
+    }
+
+    fn exists(cache: &ChunkCache, hash: BlockHash) -> bool {
+        cache.root().join(block_path(hash)).exists()
+    }
+
+    #[test]
+    fn test_parse_block_file_name() {
+        assert_eq!(
+            parse_block_file_name("0x123456789abcdef0.lsb"),
+            Some(0x1234_5678_9abc_def0)
+        );
+        assert_eq!(parse_block_file_name("ledger.msgpack"), None);
+        assert_eq!(parse_block_file_name("0xnothex.lsb"), None);
+    }
+
+    #[test]
+    fn test_quota_respects_pins_and_sharing() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ChunkCache::new(dir.path(), 1000);
+        for hash in 1..=4 {
+            write_block(&cache, hash, 100);
+        }
+
+        // an older client build used 1 and 2, the current one uses 2 and 3, and the engine 3 and 4
+        cache
+            .record_use(CacheConsumer::Client, &HashSet::from([1, 2]))
+            .unwrap();
+        cache
+            .record_use(CacheConsumer::Client, &HashSet::from([2, 3]))
+            .unwrap();
+        cache
+            .record_use(CacheConsumer::Engine, &HashSet::from([3, 4]))
+            .unwrap();
+
+        let report = cache.evict(CacheConsumer::Client, 100).unwrap();
+        assert_eq!(report.deleted_blocks, 1);
+        assert!(!exists(&cache, 1));
+        assert!(exists(&cache, 2));
+        assert!(exists(&cache, 3));
+
+        let status = cache.status();
+        assert_eq!(status.total_bytes, 300);
+        let client = &status.consumers[0];
+        assert_eq!(client.consumer, Some(CacheConsumer::Client));
+        assert_eq!(client.used_bytes, 200);
+        assert_eq!(client.pinned_bytes, 200);
+        assert_eq!(client.quota_bytes, Some(100));
+    }
+
+    #[test]
+    fn test_global_budget_evicts_unclaimed_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ChunkCache::new(dir.path(), 250);
+        for hash in 1..=4 {
+            write_block(&cache, hash, 100);
+        }
+        cache
+            .record_use(CacheConsumer::Dlls, &HashSet::from([1, 2]))
+            .unwrap();
+        // the dlls moved on to block 3, so 1 and 2 are claimed but no longer pinned
+        cache
+            .record_use(CacheConsumer::Dlls, &HashSet::from([3]))
+            .unwrap();
+
+        cache.evict(CacheConsumer::Dlls, u64::MAX).unwrap();
+        assert!(exists(&cache, 3));
+        assert!(!exists(&cache, 4));
+        assert_eq!(cache.status().total_bytes, 200);
+
+        cache.clear(CacheConsumer::Dlls).unwrap();
+        assert_eq!(cache.status().block_count, 0);
+    }
+
+    #[test]
+    fn test_in_flight_blocks_survive_eviction() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ChunkCache::new(dir.path(), 0);
+
+        // another sync is still downloading block 1, so it isn't in the ledger's pins yet
+        let sync = cache.begin_sync();
+        sync.pin(&HashSet::from([1]));
+        write_block(&cache, 1, 100);
+        write_block(&cache, 2, 100);
+
+        cache.evict(CacheConsumer::Client, 0).unwrap();
+        assert!(exists(&cache, 1));
+        assert!(!exists(&cache, 2));
+
+        drop(sync);
+        cache.evict(CacheConsumer::Client, 0).unwrap();
+        assert!(!exists(&cache, 1));
+    }
+
+    #[test]
+    fn test_migrate_legacy_caches() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = ChunkCache::new(dir.path().join("engine_cache"), 0);
+        write_block(&legacy, 7, 10);
+
+        let cache = ChunkCache::new(dir.path().join(CHUNK_CACHE_DIR), 1000);
+        cache.migrate_legacy_caches(dir.path()).unwrap();
+        assert!(exists(&cache, 7));
+        assert!(!dir.path().join("engine_cache").exists());
+
+        let status = cache.status();
+        assert_eq!(status.consumers.last().unwrap().used_bytes, 10);
+    }
+}
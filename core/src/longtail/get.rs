@@ -0,0 +1,1048 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures::{StreamExt, TryStreamExt};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use super::cache::SyncPins;
+use super::index::{Asset, BlockHash, ChunkHash, StoreIndex, StoredBlock, VersionIndex};
+use super::journal::SyncJournal;
+use super::send_msg;
+use super::store::{block_path, store_index_location, BlobLocation, BlobReader};
+use super::verify::{verify_asset, ChunkHasher};
+use crate::msg::{LongtailMsg, LongtailPhase};
+
+// Written to the target after a successful get so the next one only touches what changed
+pub const TARGET_INDEX_NAME: &str = ".longtail.index.cache.lvi";
+
+const MAX_CONCURRENT_BLOCK_DOWNLOADS: usize = 8;
+
+// Decompressed blocks kept around while writing, assets tend to reuse the last few
+const BLOCK_LRU_SIZE: usize = 16;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+// Running totals for a get, summed across retries by the caller
+#[derive(Debug, Clone, Default)]
+pub struct GetStats {
+    // Block bytes fetched from the remote store
+    pub downloaded_bytes: u64,
+    pub downloaded_blocks: u64,
+
+    // Block bytes that were already in the local cache
+    pub cached_bytes: u64,
+    pub cached_blocks: u64,
+
+    pub written_bytes: u64,
+
+    // Assets an interrupted get had already written, and ones rewritten after failing verification
+    pub resumed_assets: u64,
+    pub repaired_assets: u64,
+
+    // Every block the synced version is built from, whether or not this get needed it
+    pub referenced_blocks: HashSet<BlockHash>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GetProgress {
+    pub phase: LongtailPhase,
+    pub done_bytes: u64,
+    pub total_bytes: u64,
+    pub chunks_done: u64,
+    pub chunks_total: u64,
+    pub elapsed: Duration,
+}
+
+impl GetProgress {
+    fn fraction(&self) -> Option<f64> {
+        match (self.total_bytes, self.chunks_total) {
+            (0, 0) => None,
+            (0, chunks_total) => Some(self.chunks_done as f64 / chunks_total as f64),
+            (total_bytes, _) => Some(self.done_bytes as f64 / total_bytes as f64),
+        }
+    }
+
+    pub fn percent(&self) -> u64 {
+        self.fraction()
+            .map_or(0, |fraction| ((fraction * 100.0) as u64).min(100))
+    }
+
+    // Straight-line estimate from the rate so far in this phase
+    pub fn remaining(&self) -> Option<Duration> {
+        let fraction = self.fraction()?;
+        if fraction <= 0.0 || fraction >= 1.0 {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((1.0 - fraction) / fraction))
+    }
+}
+
+impl From<GetProgress> for LongtailMsg {
+    fn from(progress: GetProgress) -> Self {
+        LongtailMsg::Progress {
+            phase: progress.phase,
+            done_bytes: progress.done_bytes,
+            total_bytes: progress.total_bytes,
+            chunks_done: progress.chunks_done,
+            chunks_total: progress.chunks_total,
+            elapsed: progress.elapsed,
+            eta: progress.remaining(),
+        }
+    }
+}
+
+// Same shape as the progress lines golongtail used to print, ex. "Downloading 42% 1m2s:3m4s"
+impl std::fmt::Display for GetProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}% {}",
+            self.phase,
+            self.percent(),
+            format_duration(self.elapsed)
+        )?;
+        if let Some(remaining) = self.remaining() {
+            write!(f, ":{}", format_duration(remaining))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{seconds}s"),
+        (0, _) => format!("{minutes}m{seconds}s"),
+        _ => format!("{hours}h{minutes}m{seconds}s"),
+    }
+}
+
+// Throttled progress reporting for one phase of a get
+#[derive(Clone)]
+struct ProgressReporter {
+    tx: Sender<LongtailMsg>,
+    phase: LongtailPhase,
+    started: Instant,
+    last_report: Arc<parking_lot::Mutex<Option<Instant>>>,
+}
+
+impl ProgressReporter {
+    fn new(tx: &Sender<LongtailMsg>, phase: LongtailPhase) -> Self {
+        Self {
+            tx: tx.clone(),
+            phase,
+            started: Instant::now(),
+            last_report: Arc::new(parking_lot::Mutex::new(None)),
+        }
+    }
+
+    fn report(
+        &self,
+        (done_bytes, total_bytes): (u64, u64),
+        (chunks_done, chunks_total): (u64, u64),
+    ) {
+        {
+            let finished = done_bytes >= total_bytes && chunks_done >= chunks_total;
+            let mut last_report = self.last_report.lock();
+            let due = last_report.is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL);
+            if !due && !finished {
+                return;
+            }
+            *last_report = Some(Instant::now());
+        }
+
+        let progress = GetProgress {
+            phase: self.phase,
+            done_bytes,
+            total_bytes,
+            chunks_done,
+            chunks_total,
+            elapsed: self.started.elapsed(),
+        };
+        send_msg(&self.tx, progress.into());
+    }
+}
+
+// Downloads the merged contents of one or more archives into target. Later sources win when
+// they share a path. Blocks land in the cache when given, or a temp dir that's removed after.
+pub async fn get(
+    reader: &BlobReader,
+    target: &Path,
+    cache: Option<&SyncPins>,
+    sources: &[String],
+    tx: &Sender<LongtailMsg>,
+    cancel: &CancellationToken,
+    stats: &mut GetStats,
+) -> Result<()> {
+    ProgressReporter::new(tx, LongtailPhase::Indexing).report((0, 0), (0, 0));
+    let mut versions = Vec::with_capacity(sources.len());
+    let mut chunk_blocks: HashMap<ChunkHash, (BlobLocation, BlockHash)> = HashMap::new();
+    for source in sources {
+        check_cancelled(cancel)?;
+
+        let config = reader.read_get_config(source).await?;
+        let storage = BlobLocation::parse(&config.storage_uri)?;
+        let version = VersionIndex::from_bytes(
+            &reader
+                .read(&BlobLocation::parse(&config.source_path)?)
+                .await?,
+        )
+        .with_context(|| format!("Invalid version index {}", config.source_path))?;
+
+        let store_index_location = match &config.version_local_store_index_path {
+            Some(path) => BlobLocation::parse(path)?,
+            None => store_index_location(&storage),
+        };
+        let store = StoreIndex::from_bytes(&reader.read(&store_index_location).await?)
+            .with_context(|| format!("Invalid store index {store_index_location}"))?;
+
+        for chunk in version.chunks.keys() {
+            let block = store.chunk_blocks.get(chunk).ok_or_else(|| {
+                anyhow!(
+                    "Chunk {:#018x} of {} is missing from {}",
+                    chunk,
+                    source,
+                    store_index_location
+                )
+            })?;
+            chunk_blocks.insert(*chunk, (storage.clone(), block.block_hash));
+        }
+        versions.push(version);
+    }
+
+    let merged = VersionIndex::merge(versions).ok_or_else(|| anyhow!("No archives to get"))?;
+    stats.referenced_blocks.extend(
+        merged
+            .chunks
+            .keys()
+            .filter_map(|chunk| chunk_blocks.get(chunk).map(|(_, block)| *block)),
+    );
+    // Before anything is downloaded, so a concurrent sync's eviction can't take them
+    if let Some(cache) = cache {
+        cache.pin(&stats.referenced_blocks);
+    }
+    let cache_dir = cache.map(SyncPins::root);
+    let previous = read_target_index(target);
+    let (journal, completed) = SyncJournal::open(target, sources)?;
+    let changed = changed_assets(target, &merged, previous.as_ref(), &completed);
+
+    // Everything written since the last completed get gets verified, resumed or not
+    let to_verify: Vec<Asset> = {
+        let changed_paths: HashSet<&str> = changed.iter().map(|a| a.path.as_str()).collect();
+        let resumed: Vec<Asset> =
+            changed_assets(target, &merged, previous.as_ref(), &HashMap::new())
+                .into_iter()
+                .filter(|a| !changed_paths.contains(a.path.as_str()))
+                .collect();
+        stats.resumed_assets += resumed.len() as u64;
+        changed.iter().cloned().chain(resumed).collect()
+    };
+    info!(
+        "{} of {} assets in {} need updating",
+        changed.len(),
+        merged.assets.len(),
+        target.display()
+    );
+
+    let temp_dir = match cache_dir {
+        Some(_) => None,
+        None => Some(tempfile::tempdir()?),
+    };
+    let block_dir: PathBuf = cache_dir
+        .map(Path::to_path_buf)
+        .or_else(|| temp_dir.as_ref().map(|d| d.path().to_path_buf()))
+        .unwrap();
+
+    let needed = needed_blocks(&changed, &merged, &chunk_blocks)?;
+    download_blocks(reader, &block_dir, needed.clone(), tx, cancel, stats).await?;
+
+    let writer = AssetWriter {
+        target: target.to_path_buf(),
+        block_dir: block_dir.clone(),
+        chunk_blocks: Arc::new(
+            chunk_blocks
+                .iter()
+                .map(|(chunk, (_, block))| (*chunk, *block))
+                .collect(),
+        ),
+        reporter: ProgressReporter::new(tx, LongtailPhase::Writing),
+        journal: Arc::new(parking_lot::Mutex::new(journal)),
+        cancel: cancel.clone(),
+    };
+    stats.written_bytes += {
+        let writer = writer.clone();
+        tokio::task::spawn_blocking(move || writer.write(&changed)).await??
+    };
+
+    // Corrupt files are rewritten, after dropping any cached blocks that don't hash correctly
+    let merged = Arc::new(merged);
+    let hasher = ChunkHasher::from_identifier(merged.hash_identifier);
+    if hasher.is_none() {
+        warn!(
+            "Unsupported hash algorithm {:#x}, only verifying file sizes",
+            merged.hash_identifier
+        );
+    }
+    let corrupt = verify_assets(target, &merged, to_verify, hasher, tx, cancel).await?;
+    if !corrupt.is_empty() {
+        warn!("{} assets failed verification, repairing", corrupt.len());
+        stats.repaired_assets += corrupt.len() as u64;
+
+        let mut block_chunks: HashMap<BlockHash, Vec<ChunkHash>> = HashMap::new();
+        for chunk in corrupt.iter().flat_map(|a| a.chunks.iter()) {
+            if let Some((_, block)) = chunk_blocks.get(chunk) {
+                block_chunks.entry(*block).or_default().push(*chunk);
+            }
+        }
+        let bad_blocks = {
+            let writer = writer.clone();
+            tokio::task::spawn_blocking(move || writer.corrupt_blocks(block_chunks, hasher))
+                .await??
+        };
+        let needed = needed_blocks(&corrupt, &merged, &chunk_blocks)?;
+        for block in &bad_blocks {
+            warn!("Removing corrupt block {:#018x}", block);
+            fs::remove_file(block_dir.join(block_path(*block)))?;
+        }
+        download_blocks(reader, &block_dir, needed, tx, cancel, stats).await?;
+
+        stats.written_bytes += {
+            let (writer, corrupt) = (writer.clone(), corrupt.clone());
+            tokio::task::spawn_blocking(move || writer.write(&corrupt)).await??
+        };
+        let still_corrupt = verify_assets(target, &merged, corrupt, hasher, tx, cancel).await?;
+        if let Some(asset) = still_corrupt.first() {
+            bail!(
+                "{} assets failed verification after repair, including {}",
+                still_corrupt.len(),
+                asset.path
+            );
+        }
+    }
+
+    remove_deleted_assets(target, &merged, previous.as_ref());
+    fs::write(target.join(TARGET_INDEX_NAME), merged.to_bytes())?;
+    let journal = Arc::into_inner(writer.journal)
+        .ok_or_else(|| anyhow!("Sync journal still in use"))?
+        .into_inner();
+    journal.finish()?;
+
+    // Keep the temp dir alive until every block has been written out
+    drop(temp_dir);
+    Ok(())
+}
+
+// Blocks holding the chunks of assets, with the chunk bytes and counts used for progress
+fn needed_blocks(
+    assets: &[Asset],
+    merged: &VersionIndex,
+    chunk_blocks: &HashMap<ChunkHash, (BlobLocation, BlockHash)>,
+) -> Result<HashMap<BlockHash, NeededBlock>> {
+    let needed_chunks: HashSet<ChunkHash> = assets
+        .iter()
+        .flat_map(|a| a.chunks.iter().copied())
+        .collect();
+    let mut needed_blocks: HashMap<BlockHash, NeededBlock> = HashMap::new();
+    for chunk in needed_chunks {
+        let (storage, block) = chunk_blocks
+            .get(&chunk)
+            .ok_or_else(|| anyhow!("No block found for chunk {:#018x}", chunk))?;
+        let needed = needed_blocks.entry(*block).or_insert_with(|| NeededBlock {
+            location: storage.join(&block_path(*block)),
+            chunk_bytes: 0,
+            chunks: 0,
+        });
+        needed.chunk_bytes += merged.chunks.get(&chunk).map_or(0, |c| c.size as u64);
+        needed.chunks += 1;
+    }
+    Ok(needed_blocks)
+}
+
+// Returns the assets that don't match the version index on disk
+async fn verify_assets(
+    target: &Path,
+    merged: &Arc<VersionIndex>,
+    assets: Vec<Asset>,
+    hasher: Option<ChunkHasher>,
+    tx: &Sender<LongtailMsg>,
+    cancel: &CancellationToken,
+) -> Result<Vec<Asset>> {
+    let (target, merged, cancel) = (target.to_path_buf(), merged.clone(), cancel.clone());
+    let reporter = ProgressReporter::new(tx, LongtailPhase::Verifying);
+    tokio::task::spawn_blocking(move || {
+        let total_bytes: u64 = assets.iter().map(|a| a.size).sum();
+        let chunks_total: u64 = assets.iter().map(|a| a.chunks.len() as u64).sum();
+        let (mut done_bytes, mut chunks_done) = (0, 0);
+        let mut corrupt = vec![];
+        for asset in assets {
+            check_cancelled(&cancel)?;
+            let valid = verify_asset(&target, &asset, &merged.chunks, hasher)?;
+            done_bytes += asset.size;
+            chunks_done += asset.chunks.len() as u64;
+            reporter.report((done_bytes, total_bytes), (chunks_done, chunks_total));
+            if !valid {
+                debug!("{} failed verification", asset.path);
+                corrupt.push(asset);
+            }
+        }
+        reporter.report((total_bytes, total_bytes), (chunks_total, chunks_total));
+        Ok(corrupt)
+    })
+    .await?
+}
+
+fn check_cancelled(cancel: &CancellationToken) -> Result<()> {
+    if cancel.is_cancelled() {
+        bail!("Longtail get was cancelled");
+    }
+    Ok(())
+}
+
+fn read_target_index(target: &Path) -> Option<VersionIndex> {
+    let bytes = fs::read(target.join(TARGET_INDEX_NAME)).ok()?;
+    match VersionIndex::from_bytes(&bytes) {
+        Ok(index) => Some(index),
+        Err(e) => {
+            warn!("Ignoring unreadable target index in {:?}: {}", target, e);
+            None
+        }
+    }
+}
+
+// Assets that aren't already on disk with the same content and size as the last get left them,
+// or as an interrupted get of the same version finished writing them
+fn changed_assets(
+    target: &Path,
+    merged: &VersionIndex,
+    previous: Option<&VersionIndex>,
+    completed: &HashMap<String, u64>,
+) -> Vec<Asset> {
+    let mut previous_hashes: HashMap<&str, u64> = previous
+        .map(|p| {
+            p.assets
+                .iter()
+                .map(|a| (a.path.as_str(), a.content_hash))
+                .collect()
+        })
+        .unwrap_or_default();
+    previous_hashes.extend(completed.iter().map(|(path, hash)| (path.as_str(), *hash)));
+
+    merged
+        .assets
+        .iter()
+        .filter(|asset| {
+            if asset.is_dir() {
+                return !target.join(&asset.path).is_dir();
+            }
+            let unchanged = previous_hashes.get(asset.path.as_str()) == Some(&asset.content_hash)
+                && fs::metadata(target.join(&asset.path)).is_ok_and(|m| m.len() == asset.size);
+            !unchanged
+        })
+        .cloned()
+        .collect()
+}
+
+// A block holding chunks of changed assets. Progress is measured in those chunks rather than
+// the whole block, since that's what ends up on disk.
+#[derive(Clone)]
+struct NeededBlock {
+    location: BlobLocation,
+    chunk_bytes: u64,
+    chunks: u64,
+}
+
+async fn download_blocks(
+    reader: &BlobReader,
+    block_dir: &Path,
+    blocks: HashMap<BlockHash, NeededBlock>,
+    tx: &Sender<LongtailMsg>,
+    cancel: &CancellationToken,
+    stats: &mut GetStats,
+) -> Result<()> {
+    let mut missing: Vec<(BlockHash, NeededBlock)> = Vec::new();
+    for (hash, block) in blocks {
+        match fs::metadata(block_dir.join(block_path(hash))) {
+            Ok(metadata) => {
+                stats.cached_bytes += metadata.len();
+                stats.cached_blocks += 1;
+            }
+            Err(_) => missing.push((hash, block)),
+        }
+    }
+    debug!("Downloading {} blocks into {:?}", missing.len(), block_dir);
+
+    let reporter = ProgressReporter::new(tx, LongtailPhase::Downloading);
+    let total_bytes: u64 = missing.iter().map(|(_, b)| b.chunk_bytes).sum();
+    let chunks_total: u64 = missing.iter().map(|(_, b)| b.chunks).sum();
+    let (mut done_bytes, mut chunks_done) = (0, 0);
+    reporter.report((done_bytes, total_bytes), (chunks_done, chunks_total));
+
+    let mut downloads = futures::stream::iter(missing)
+        .map(|(hash, needed)| async move {
+            check_cancelled(cancel)?;
+            let location = needed.location;
+            let bytes = reader.read(&location).await?;
+
+            // Parsing up front means a truncated or corrupt block never makes it into the cache
+            let block = StoredBlock::from_bytes(&bytes)
+                .with_context(|| format!("Invalid block {location}"))?;
+            if block.block_hash != hash {
+                bail!(
+                    "Block {} has hash {:#018x}, expected {:#018x}",
+                    location,
+                    block.block_hash,
+                    hash
+                );
+            }
+
+            let path = block_dir.join(block_path(hash));
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let tmp_path = path.with_extension("lsb.tmp");
+            tokio::fs::write(&tmp_path, &bytes).await?;
+            tokio::fs::rename(&tmp_path, &path).await?;
+            Ok::<_, anyhow::Error>((bytes.len() as u64, needed.chunk_bytes, needed.chunks))
+        })
+        .buffer_unordered(MAX_CONCURRENT_BLOCK_DOWNLOADS);
+
+    while let Some((downloaded, chunk_bytes, chunks)) = downloads.try_next().await? {
+        stats.downloaded_bytes += downloaded;
+        stats.downloaded_blocks += 1;
+        done_bytes += chunk_bytes;
+        chunks_done += chunks;
+        reporter.report((done_bytes, total_bytes), (chunks_done, chunks_total));
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+struct AssetWriter {
+    target: PathBuf,
+    block_dir: PathBuf,
+    chunk_blocks: Arc<HashMap<ChunkHash, BlockHash>>,
+    reporter: ProgressReporter,
+    journal: Arc<parking_lot::Mutex<SyncJournal>>,
+    cancel: CancellationToken,
+}
+
+impl AssetWriter {
+    // Returns the number of bytes written
+    fn write(&self, assets: &[Asset]) -> Result<u64> {
+        let total_bytes: u64 = assets.iter().map(|a| a.size).sum();
+        let chunks_total: u64 = assets.iter().map(|a| a.chunks.len() as u64).sum();
+        let (mut done_bytes, mut chunks_done) = (0, 0);
+        let mut blocks: VecDeque<Arc<StoredBlock>> = VecDeque::with_capacity(BLOCK_LRU_SIZE);
+
+        for asset in assets {
+            check_cancelled(&self.cancel)?;
+
+            let path = self.target.join(&asset.path);
+            if asset.is_dir() {
+                fs::create_dir_all(&path)?;
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            // Files synced by a previous get may have been left read-only
+            if let Ok(metadata) = fs::metadata(&path) {
+                let mut permissions = metadata.permissions();
+                if permissions.readonly() {
+                    #[allow(clippy::permissions_set_readonly_false)]
+                    permissions.set_readonly(false);
+                    fs::set_permissions(&path, permissions)?;
+                }
+
+                // Unlinked rather than truncated, the file may be hardlinked into another install
+                fs::remove_file(&path)?;
+            }
+
+            let mut file = BufWriter::new(
+                File::create(&path).with_context(|| format!("Failed to create {path:?}"))?,
+            );
+            for chunk in &asset.chunks {
+                let block = self.load_block(&mut blocks, *chunk)?;
+                let data = block.chunk(*chunk).ok_or_else(|| {
+                    anyhow!(
+                        "Chunk {:#018x} missing from block {:#018x}",
+                        chunk,
+                        block.block_hash
+                    )
+                })?;
+                file.write_all(data)?;
+                done_bytes += data.len() as u64;
+                chunks_done += 1;
+                self.reporter
+                    .report((done_bytes, total_bytes), (chunks_done, chunks_total));
+            }
+            file.flush()?;
+            drop(file);
+
+            set_permissions(&path, asset.permissions)?;
+            self.journal.lock().record(asset)?;
+        }
+
+        self.reporter
+            .report((done_bytes, total_bytes), (chunks_total, chunks_total));
+        Ok(done_bytes)
+    }
+
+    // Cached blocks that are unreadable or hold chunks that don't match their hash
+    fn corrupt_blocks(
+        &self,
+        blocks: HashMap<BlockHash, Vec<ChunkHash>>,
+        hasher: Option<ChunkHasher>,
+    ) -> Result<Vec<BlockHash>> {
+        let mut corrupt = vec![];
+        for (hash, chunks) in blocks {
+            check_cancelled(&self.cancel)?;
+            let path = self.block_dir.join(block_path(hash));
+            let Ok(bytes) = fs::read(&path) else {
+                continue;
+            };
+            let valid = StoredBlock::from_bytes(&bytes).is_ok_and(|block| {
+                chunks.iter().all(|chunk| {
+                    block
+                        .chunk(*chunk)
+                        .is_some_and(|data| hasher.is_none_or(|hasher| hasher.hash(data) == *chunk))
+                })
+            });
+            if !valid {
+                corrupt.push(hash);
+            }
+        }
+        Ok(corrupt)
+    }
+
+    fn load_block(
+        &self,
+        blocks: &mut VecDeque<Arc<StoredBlock>>,
+        chunk: ChunkHash,
+    ) -> Result<Arc<StoredBlock>> {
+        let hash = *self
+            .chunk_blocks
+            .get(&chunk)
+            .ok_or_else(|| anyhow!("No block found for chunk {:#018x}", chunk))?;
+
+        if let Some(pos) = blocks.iter().position(|b| b.block_hash == hash) {
+            let block = blocks.remove(pos).unwrap();
+            blocks.push_front(block.clone());
+            return Ok(block);
+        }
+
+        let path = self.block_dir.join(block_path(hash));
+        let block = Arc::new(
+            StoredBlock::from_bytes(
+                &fs::read(&path).with_context(|| format!("Failed to read {path:?}"))?,
+            )
+            .with_context(|| format!("Invalid cached block {path:?}"))?,
+        );
+        if blocks.len() == BLOCK_LRU_SIZE {
+            blocks.pop_back();
+        }
+        blocks.push_front(block.clone());
+        Ok(block)
+    }
+}
+
+#[cfg(unix)]
+fn set_permissions(path: &Path, mode: u16) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if mode != 0 {
+        fs::set_permissions(path, fs::Permissions::from_mode(mode as u32))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_permissions(_path: &Path, _mode: u16) -> Result<()> {
+    Ok(())
+}
+
+// Removes anything the previous get wrote that's no longer part of the version
+fn remove_deleted_assets(target: &Path, merged: &VersionIndex, previous: Option<&VersionIndex>) {
+    let Some(previous) = previous else {
+        return;
+    };
+
+    let current: HashSet<&str> = merged.assets.iter().map(|a| a.path.as_str()).collect();
+    let mut removed: Vec<&Asset> = previous
+        .assets
+        .iter()
+        .filter(|a| !current.contains(a.path.as_str()))
+        .collect();
+
+    // Deepest paths first so directories are empty by the time we reach them
+    removed.sort_by_key(|a| std::cmp::Reverse(a.path.len()));
+    for asset in removed {
+        let path = target.join(&asset.path);
+        let result = match asset.is_dir() {
+            true => fs::remove_dir(&path),
+            false => fs::remove_file(&path),
+        };
+        if let Err(e) = result {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                debug!("Unable to remove deleted asset {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::index::test_util::{block_bytes, store_index_bytes};
+    use super::super::index::ChunkInfo;
+    use super::super::journal::SYNC_JOURNAL_NAME;
+    use super::super::verify::BLAKE3_HASH_IDENTIFIER;
+    use super::*;
+
+    struct TestStore {
+        dir: tempfile::TempDir,
+    }
+
+    // An archive name and its assets, each a name and the chunks it's made of
+    type TestVersion<'a> = (&'a str, Vec<(&'a str, Vec<(ChunkHash, &'static [u8])>)>);
+
+    fn pins(cache_dir: &Path) -> SyncPins {
+        super::super::cache::ChunkCache::new(cache_dir, u64::MAX).begin_sync()
+    }
+
+    fn content_hash(chunks: &[(ChunkHash, &[u8])]) -> u64 {
+        chunks.iter().fold(0, |acc, c| acc.wrapping_add(c.0))
+    }
+
+    impl TestStore {
+        // A file:// store holding one archive per entry of versions
+        fn new(versions: &[TestVersion]) -> Self {
+            Self::with_hash_identifier(0, versions)
+        }
+
+        fn with_hash_identifier(hash_identifier: u32, versions: &[TestVersion]) -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let store = dir.path().join("store");
+            let mut blocks = vec![];
+
+            for (name, assets) in versions {
+                let mut index = VersionIndex {
+                    hash_identifier,
+                    target_chunk_size: 32768,
+                    assets: vec![],
+                    chunks: HashMap::new(),
+                };
+                for (path, chunks) in assets {
+                    for (hash, data) in chunks {
+                        index.chunks.insert(
+                            *hash,
+                            ChunkInfo {
+                                size: data.len() as u32,
+                                tag: 0,
+                            },
+                        );
+                        // one block per chunk keeps the fixture simple
+                        let block_hash = hash.wrapping_add(0x1000);
+                        if !blocks.iter().any(|(b, _, _)| *b == block_hash) {
+                            let bytes = block_bytes(block_hash, &[(*hash, data)]);
+                            let path = store.join(block_path(block_hash));
+                            fs::create_dir_all(path.parent().unwrap()).unwrap();
+                            fs::write(path, bytes).unwrap();
+                            blocks.push((block_hash, 0, vec![(*hash, data.len() as u32)]));
+                        }
+                    }
+                    index.assets.push(Asset {
+                        path: path.to_string(),
+                        path_hash: 0,
+                        content_hash: content_hash(chunks),
+                        size: chunks.iter().map(|c| c.1.len() as u64).sum(),
+                        permissions: 0o644,
+                        chunks: chunks.iter().map(|c| c.0).collect(),
+                    });
+                }
+
+                let lvi = dir.path().join(format!("{name}.lvi"));
+                fs::write(&lvi, index.to_bytes()).unwrap();
+                fs::write(
+                    dir.path().join(format!("{name}.json")),
+                    serde_json::json!({
+                        "storage-uri": store.to_string_lossy(),
+                        "source-path": lvi.to_string_lossy(),
+                    })
+                    .to_string(),
+                )
+                .unwrap();
+            }
+
+            fs::write(store.join("store.lsi"), store_index_bytes(&blocks)).unwrap();
+            Self { dir }
+        }
+
+        fn source(&self, name: &str) -> String {
+            self.dir
+                .path()
+                .join(format!("{name}.json"))
+                .to_string_lossy()
+                .to_string()
+        }
+    }
+
+    #[test]
+    fn test_progress_format() {
+        let progress = GetProgress {
+            phase: LongtailPhase::Downloading,
+            done_bytes: 42,
+            total_bytes: 100,
+            chunks_done: 1,
+            chunks_total: 10,
+            elapsed: Duration::from_secs(62),
+        };
+        assert!(progress.to_string().starts_with("Downloading 42% 1m2s:1m"));
+
+        let progress = GetProgress {
+            done_bytes: 100,
+            ..progress
+        };
+        assert_eq!(progress.to_string(), "Downloading 100% 1m2s");
+        assert!(progress.remaining().is_none());
+
+        // without byte counts progress falls back to chunks
+        let progress = GetProgress {
+            done_bytes: 0,
+            total_bytes: 0,
+            ..progress
+        };
+        assert_eq!(progress.percent(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_get_merges_and_updates_incrementally() {
+        let store = TestStore::new(&[
+            (
+                "game",
+                vec![
+                    ("Binaries/", vec![]),
+                    ("Binaries/game.exe", vec![(1, b"game"), (2, b"exe")]),
+                    ("readme.txt", vec![(3, b"old")]),
+                ],
+            ),
+            ("symbols", vec![("Binaries/game.pdb", vec![(4, b"pdb")])]),
+            ("patch", vec![("Binaries/game.exe", vec![(5, b"patched")])]),
+        ]);
+        let target = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let reader = BlobReader::new(&aws_credential_types::Credentials::from_keys("", "", None));
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let cancel = CancellationToken::new();
+        let mut stats = GetStats::default();
+
+        get(
+            &reader,
+            target.path(),
+            Some(&pins(cache.path())),
+            &[store.source("game"), store.source("symbols")],
+            &tx,
+            &cancel,
+            &mut stats,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.downloaded_blocks, 4);
+        assert_eq!(stats.referenced_blocks.len(), 4);
+        assert_eq!(stats.written_bytes, 13);
+        assert_eq!(
+            fs::read(target.path().join("Binaries/game.exe")).unwrap(),
+            b"gameexe"
+        );
+        assert_eq!(
+            fs::read(target.path().join("Binaries/game.pdb")).unwrap(),
+            b"pdb"
+        );
+        assert!(cache.path().join(block_path(0x1001)).exists());
+
+        // a second get of the same archives is served entirely from disk
+        let mut stats = GetStats::default();
+        get(
+            &reader,
+            target.path(),
+            Some(&pins(cache.path())),
+            &[store.source("game"), store.source("symbols")],
+            &tx,
+            &cancel,
+            &mut stats,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.downloaded_blocks, 0);
+        assert_eq!(stats.written_bytes, 0);
+
+        // the patch replaces game.exe, and readme.txt and the symbols are no longer part of it
+        let mut stats = GetStats::default();
+        get(
+            &reader,
+            target.path(),
+            Some(&pins(cache.path())),
+            &[store.source("patch")],
+            &tx,
+            &cancel,
+            &mut stats,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.downloaded_blocks, 1);
+        assert_eq!(
+            fs::read(target.path().join("Binaries/game.exe")).unwrap(),
+            b"patched"
+        );
+        assert!(!target.path().join("readme.txt").exists());
+        assert!(!target.path().join("Binaries/game.pdb").exists());
+    }
+
+    // Reconstructs the fixture archive, see testdata/README.md
+    #[tokio::test]
+    async fn test_get_fixture_store() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/longtail/testdata");
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("fixture.json");
+        fs::write(
+            &config,
+            serde_json::json!({
+                "storage-uri": fixtures.to_string_lossy(),
+                "source-path": fixtures.join("version.lvi").to_string_lossy(),
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let reader = BlobReader::new(&aws_credential_types::Credentials::from_keys("", "", None));
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let cancel = CancellationToken::new();
+        let mut stats = GetStats::default();
+
+        get(
+            &reader,
+            target.path(),
+            Some(&pins(cache.path())),
+            &[config.to_string_lossy().to_string()],
+            &tx,
+            &cancel,
+            &mut stats,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.downloaded_blocks, 4);
+        assert_eq!(stats.repaired_assets, 0);
+
+        let game = fs::read(target.path().join("Binaries/game.exe")).unwrap();
+        assert!(game.starts_with(b"MZ"));
+        assert!(game.ends_with(b"zstd compressed executable body. "));
+        let readme = fs::read_to_string(target.path().join("Content/readme.txt")).unwrap();
+        assert_eq!(readme.lines().count(), 8);
+        assert!(readme.ends_with("second lz4 chunk in the same block\n"));
+        let pak = fs::read(target.path().join("Content/data.pak")).unwrap();
+        assert!(pak.starts_with(b"brotli compressed pak data "));
+        // the uncompressed header chunk is shared with game.exe
+        assert_eq!(&pak[216..], &game[..49]);
+    }
+
+    #[tokio::test]
+    async fn test_get_cancelled() {
+        let store = TestStore::new(&[("game", vec![("game.exe", vec![(1, b"game")])])]);
+        let target = tempfile::tempdir().unwrap();
+        let reader = BlobReader::new(&aws_credential_types::Credentials::from_keys("", "", None));
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let result = get(
+            &reader,
+            target.path(),
+            None,
+            &[store.source("game")],
+            &tx,
+            &cancel,
+            &mut GetStats::default(),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(!target.path().join("game.exe").exists());
+    }
+
+    #[tokio::test]
+    async fn test_get_resumes_and_repairs() {
+        fn hash(data: &[u8]) -> ChunkHash {
+            ChunkHasher::Blake3.hash(data)
+        }
+        let exe_chunks: Vec<(ChunkHash, &'static [u8])> =
+            vec![(hash(b"game"), b"game"), (hash(b"exe"), b"exe")];
+        let pdb_chunks: Vec<(ChunkHash, &'static [u8])> = vec![(hash(b"pdb"), b"pdb")];
+        let store = TestStore::with_hash_identifier(
+            BLAKE3_HASH_IDENTIFIER,
+            &[(
+                "game",
+                vec![
+                    ("game.exe", exe_chunks.clone()),
+                    ("game.pdb", pdb_chunks.clone()),
+                ],
+            )],
+        );
+        let sources = [store.source("game")];
+        let target = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let reader = BlobReader::new(&aws_credential_types::Credentials::from_keys("", "", None));
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let cancel = CancellationToken::new();
+
+        // an interrupted get already wrote game.pdb
+        let (mut journal, _) = SyncJournal::open(target.path(), &sources).unwrap();
+        fs::write(target.path().join("game.pdb"), b"pdb").unwrap();
+        journal
+            .record(&Asset {
+                path: "game.pdb".to_string(),
+                path_hash: 0,
+                content_hash: content_hash(&pdb_chunks),
+                size: 3,
+                permissions: 0o644,
+                chunks: vec![hash(b"pdb")],
+            })
+            .unwrap();
+        drop(journal);
+
+        // and the cache holds a block whose header is fine but whose data isn't
+        let exe_block = hash(b"exe").wrapping_add(0x1000);
+        let path = cache.path().join(block_path(exe_block));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, block_bytes(exe_block, &[(hash(b"exe"), b"EXE")])).unwrap();
+
+        let mut stats = GetStats::default();
+        get(
+            &reader,
+            target.path(),
+            Some(&pins(cache.path())),
+            &sources,
+            &tx,
+            &cancel,
+            &mut stats,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.resumed_assets, 1);
+        assert_eq!(stats.repaired_assets, 1);
+        assert_eq!(
+            fs::read(target.path().join("game.exe")).unwrap(),
+            b"gameexe"
+        );
+        assert!(!target.path().join(SYNC_JOURNAL_NAME).exists());
+    }
+}
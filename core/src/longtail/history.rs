@@ -0,0 +1,132 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+pub const SYNC_HISTORY_FILE: &str = "sync-history.json";
+
+// Oldest syncs are dropped past this many
+const MAX_SYNC_HISTORY: usize = 200;
+
+// Serializes read-modify-write of the history file between concurrent syncs
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSummary {
+    pub started_at: DateTime<Utc>,
+    pub target: PathBuf,
+    pub archives: Vec<String>,
+
+    // Block bytes fetched from remote storage vs. served from the local chunk cache
+    pub downloaded_bytes: u64,
+    pub cached_bytes: u64,
+    pub written_bytes: u64,
+
+    // Assets left by an interrupted sync, and ones rewritten after failing verification
+    #[serde(default)]
+    pub resumed_assets: u64,
+    #[serde(default)]
+    pub repaired_assets: u64,
+
+    pub wall_time_ms: u64,
+    pub retries: u32,
+    pub succeeded: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SyncSummary {
+    // Fraction of the blocks this sync needed that were already cached
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        match self.downloaded_bytes + self.cached_bytes {
+            0 => None,
+            total => Some(self.cached_bytes as f64 / total as f64),
+        }
+    }
+}
+
+// Recent sync summaries, newest first, persisted as JSON in the download directory
+#[derive(Debug, Clone)]
+pub struct SyncHistory {
+    path: PathBuf,
+}
+
+impl SyncHistory {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn load(&self) -> Vec<SyncSummary> {
+        let _lock = HISTORY_LOCK.lock();
+        Self::read(&self.path)
+    }
+
+    pub fn record(&self, summary: SyncSummary) -> Result<()> {
+        let _lock = HISTORY_LOCK.lock();
+
+        let mut history = Self::read(&self.path);
+        history.insert(0, summary);
+        history.truncate(MAX_SYNC_HISTORY);
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(&history)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    fn read(path: &Path) -> Vec<SyncSummary> {
+        let Ok(bytes) = std::fs::read(path) else {
+            return vec![];
+        };
+        serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            warn!("Discarding unreadable sync history {:?}: {}", path, e);
+            vec![]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(downloaded_bytes: u64, cached_bytes: u64) -> SyncSummary {
+        SyncSummary {
+            started_at: Utc::now(),
+            target: PathBuf::from("/tmp/client"),
+            archives: vec!["s3://bucket/v1/proj/client/win64/development/abc.json".to_string()],
+            downloaded_bytes,
+            cached_bytes,
+            written_bytes: downloaded_bytes + cached_bytes,
+            resumed_assets: 0,
+            repaired_assets: 0,
+            wall_time_ms: 1000,
+            retries: 0,
+            succeeded: true,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_record_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = SyncHistory::new(dir.path().join(SYNC_HISTORY_FILE));
+        assert!(history.load().is_empty());
+
+        history.record(summary(100, 0)).unwrap();
+        history.record(summary(25, 75)).unwrap();
+
+        let loaded = history.load();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].cache_hit_rate(), Some(0.75));
+        assert_eq!(loaded[1].cache_hit_rate(), Some(0.0));
+        assert_eq!(summary(0, 0).cache_hit_rate(), None);
+    }
+}
@@ -0,0 +1,743 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+
+// Readers and writers for the binary index formats produced by longtail. All values are little
+// endian and laid out back to back, in the order they're read below.
+//   https://github.com/DanEngelbrecht/longtail/blob/main/src/longtail.c
+
+pub type ChunkHash = u64;
+pub type BlockHash = u64;
+
+pub const VERSION_INDEX_VERSION: u32 = 2 << 8;
+pub const STORE_INDEX_VERSION: u32 = 1 << 24;
+
+// Compression is identified by the top three bytes of a block tag, the last byte is the level
+const COMPRESSION_FAMILY_MASK: u32 = 0xffff_ff00;
+const BROTLI_FAMILY: u32 = u32::from_be_bytes([b'b', b't', b'l', 0]);
+const LZ4_FAMILY: u32 = u32::from_be_bytes([b'l', b'z', b'4', 0]);
+const ZSTD_FAMILY: u32 = u32::from_be_bytes([b'z', b't', b'd', 0]);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Brotli,
+    Lz4,
+    Zstd,
+}
+
+impl Compression {
+    pub fn from_tag(tag: u32) -> Result<Self> {
+        if tag == 0 {
+            return Ok(Compression::None);
+        }
+
+        match tag & COMPRESSION_FAMILY_MASK {
+            BROTLI_FAMILY => Ok(Compression::Brotli),
+            LZ4_FAMILY => Ok(Compression::Lz4),
+            ZSTD_FAMILY => Ok(Compression::Zstd),
+            _ => Err(anyhow!("Unsupported block compression tag {:#010x}", tag)),
+        }
+    }
+
+    pub fn decompress(&self, data: &[u8], uncompressed_size: usize) -> Result<Vec<u8>> {
+        let out = match self {
+            Compression::None => data.to_vec(),
+            Compression::Brotli => {
+                let mut out = Vec::with_capacity(uncompressed_size);
+                brotli_decompressor::BrotliDecompress(&mut &data[..], &mut out)?;
+                out
+            }
+            Compression::Lz4 => lz4_flex::block::decompress(data, uncompressed_size)?,
+            Compression::Zstd => zstd::bulk::decompress(data, uncompressed_size)?,
+        };
+
+        if out.len() != uncompressed_size {
+            bail!(
+                "Decompressed block is {} bytes, expected {}",
+                out.len(),
+                uncompressed_size
+            );
+        }
+        Ok(out)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unexpected end of data reading {} bytes at offset {} of {}",
+                    len,
+                    self.offset,
+                    self.data.len()
+                )
+            })?;
+        let slice = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let slice = &self.data[self.offset..];
+        self.offset = self.data.len();
+        slice
+    }
+
+    fn u16s(&mut self, count: usize) -> Result<Vec<u16>> {
+        Ok(self
+            .take(count * 2)?
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect())
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(self.u32s(1)?[0])
+    }
+
+    fn u32s(&mut self, count: usize) -> Result<Vec<u32>> {
+        Ok(self
+            .take(count * 4)?
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .collect())
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(self.u64s(1)?[0])
+    }
+
+    fn u64s(&mut self, count: usize) -> Result<Vec<u64>> {
+        Ok(self
+            .take(count * 8)?
+            .chunks_exact(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    data: Vec<u8>,
+}
+
+impl Writer {
+    fn u16s(&mut self, values: &[u16]) {
+        values
+            .iter()
+            .for_each(|v| self.data.extend_from_slice(&v.to_le_bytes()));
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32s(&mut self, values: &[u32]) {
+        values.iter().for_each(|v| self.u32(*v));
+    }
+
+    fn u64s(&mut self, values: &[u64]) {
+        values
+            .iter()
+            .for_each(|v| self.data.extend_from_slice(&v.to_le_bytes()));
+    }
+}
+
+// A single file or directory in a version. Directories have a path ending in '/' and no chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    pub path: String,
+    pub path_hash: u64,
+    pub content_hash: u64,
+    pub size: u64,
+    pub permissions: u16,
+    pub chunks: Vec<ChunkHash>,
+}
+
+impl Asset {
+    pub fn is_dir(&self) -> bool {
+        self.path.ends_with('/')
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkInfo {
+    pub size: u32,
+    pub tag: u32,
+}
+
+// The .lvi file describing every asset in a version and the chunks it's built from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionIndex {
+    pub hash_identifier: u32,
+    pub target_chunk_size: u32,
+    pub assets: Vec<Asset>,
+    pub chunks: HashMap<ChunkHash, ChunkInfo>,
+}
+
+impl VersionIndex {
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(data);
+
+        let version = r.u32()?;
+        if version != VERSION_INDEX_VERSION {
+            bail!("Unsupported version index version {:#x}", version);
+        }
+        let hash_identifier = r.u32()?;
+        let target_chunk_size = r.u32()?;
+        let asset_count = r.u32()? as usize;
+        let chunk_count = r.u32()? as usize;
+        let asset_chunk_index_count = r.u32()? as usize;
+
+        let path_hashes = r.u64s(asset_count)?;
+        let content_hashes = r.u64s(asset_count)?;
+        let asset_sizes = r.u64s(asset_count)?;
+        let asset_chunk_counts = r.u32s(asset_count)?;
+        let asset_chunk_index_starts = r.u32s(asset_count)?;
+        let asset_chunk_indexes = r.u32s(asset_chunk_index_count)?;
+        let chunk_hashes = r.u64s(chunk_count)?;
+        let chunk_sizes = r.u32s(chunk_count)?;
+        let chunk_tags = r.u32s(chunk_count)?;
+        let name_offsets = r.u32s(asset_count)?;
+        let permissions = r.u16s(asset_count)?;
+        let name_data = r.rest();
+
+        let chunks = chunk_hashes
+            .iter()
+            .zip(chunk_sizes.iter().zip(chunk_tags.iter()))
+            .map(|(hash, (size, tag))| {
+                (
+                    *hash,
+                    ChunkInfo {
+                        size: *size,
+                        tag: *tag,
+                    },
+                )
+            })
+            .collect();
+
+        let mut assets = Vec::with_capacity(asset_count);
+        for i in 0..asset_count {
+            let start = asset_chunk_index_starts[i] as usize;
+            let end = start + asset_chunk_counts[i] as usize;
+            let chunk_indexes = asset_chunk_indexes.get(start..end).ok_or_else(|| {
+                anyhow!("Asset {} chunk range {}..{} out of bounds", i, start, end)
+            })?;
+            let chunks = chunk_indexes
+                .iter()
+                .map(|index| {
+                    chunk_hashes
+                        .get(*index as usize)
+                        .copied()
+                        .ok_or_else(|| anyhow!("Chunk index {} out of bounds", index))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            assets.push(Asset {
+                path: read_name(name_data, name_offsets[i] as usize)?,
+                path_hash: path_hashes[i],
+                content_hash: content_hashes[i],
+                size: asset_sizes[i],
+                permissions: permissions[i],
+                chunks,
+            });
+        }
+
+        Ok(Self {
+            hash_identifier,
+            target_chunk_size,
+            assets,
+            chunks,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Chunks are written in first-use order so the output is stable for a given asset list
+        let mut chunk_order: Vec<ChunkHash> = Vec::new();
+        let mut chunk_indexes: HashMap<ChunkHash, u32> = HashMap::new();
+        let mut asset_chunk_indexes: Vec<u32> = Vec::new();
+        let mut asset_chunk_index_starts: Vec<u32> = Vec::new();
+        for asset in &self.assets {
+            asset_chunk_index_starts.push(asset_chunk_indexes.len() as u32);
+            for hash in &asset.chunks {
+                let index = *chunk_indexes.entry(*hash).or_insert_with(|| {
+                    chunk_order.push(*hash);
+                    (chunk_order.len() - 1) as u32
+                });
+                asset_chunk_indexes.push(index);
+            }
+        }
+
+        let mut name_data: Vec<u8> = Vec::new();
+        let mut name_offsets: Vec<u32> = Vec::new();
+        for asset in &self.assets {
+            name_offsets.push(name_data.len() as u32);
+            name_data.extend_from_slice(asset.path.as_bytes());
+            name_data.push(0);
+        }
+
+        let mut w = Writer::default();
+        w.u32(VERSION_INDEX_VERSION);
+        w.u32(self.hash_identifier);
+        w.u32(self.target_chunk_size);
+        w.u32(self.assets.len() as u32);
+        w.u32(chunk_order.len() as u32);
+        w.u32(asset_chunk_indexes.len() as u32);
+        w.u64s(&self.assets.iter().map(|a| a.path_hash).collect::<Vec<_>>());
+        w.u64s(
+            &self
+                .assets
+                .iter()
+                .map(|a| a.content_hash)
+                .collect::<Vec<_>>(),
+        );
+        w.u64s(&self.assets.iter().map(|a| a.size).collect::<Vec<_>>());
+        w.u32s(
+            &self
+                .assets
+                .iter()
+                .map(|a| a.chunks.len() as u32)
+                .collect::<Vec<_>>(),
+        );
+        w.u32s(&asset_chunk_index_starts);
+        w.u32s(&asset_chunk_indexes);
+        w.u64s(&chunk_order);
+        let infos: Vec<ChunkInfo> = chunk_order
+            .iter()
+            .map(|hash| {
+                self.chunks
+                    .get(hash)
+                    .copied()
+                    .unwrap_or(ChunkInfo { size: 0, tag: 0 })
+            })
+            .collect();
+        w.u32s(&infos.iter().map(|c| c.size).collect::<Vec<_>>());
+        w.u32s(&infos.iter().map(|c| c.tag).collect::<Vec<_>>());
+        w.u32s(&name_offsets);
+        w.u16s(
+            &self
+                .assets
+                .iter()
+                .map(|a| a.permissions)
+                .collect::<Vec<_>>(),
+        );
+        w.data.extend_from_slice(&name_data);
+        w.data
+    }
+
+    // Combines several versions into one, later versions winning when they share a path
+    pub fn merge(indexes: Vec<VersionIndex>) -> Option<VersionIndex> {
+        let mut iter = indexes.into_iter();
+        let mut merged = iter.next()?;
+        for index in iter {
+            let mut positions: HashMap<String, usize> = merged
+                .assets
+                .iter()
+                .enumerate()
+                .map(|(i, a)| (a.path.clone(), i))
+                .collect();
+            for asset in index.assets {
+                match positions.get(&asset.path) {
+                    Some(i) => merged.assets[*i] = asset,
+                    None => {
+                        positions.insert(asset.path.clone(), merged.assets.len());
+                        merged.assets.push(asset);
+                    }
+                }
+            }
+            merged.chunks.extend(index.chunks);
+        }
+        Some(merged)
+    }
+}
+
+fn read_name(name_data: &[u8], offset: usize) -> Result<String> {
+    let bytes = name_data
+        .get(offset..)
+        .ok_or_else(|| anyhow!("Asset name offset {} out of bounds", offset))?;
+    let end = bytes
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or_else(|| anyhow!("Unterminated asset name at offset {}", offset))?;
+    Ok(String::from_utf8_lossy(&bytes[..end]).to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockLocation {
+    pub block_hash: BlockHash,
+    pub tag: u32,
+}
+
+// The .lsi file listing which block in a store holds each chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreIndex {
+    pub hash_identifier: u32,
+    pub chunk_blocks: HashMap<ChunkHash, BlockLocation>,
+}
+
+impl StoreIndex {
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(data);
+
+        let version = r.u32()?;
+        if version != STORE_INDEX_VERSION {
+            bail!("Unsupported store index version {:#x}", version);
+        }
+        let hash_identifier = r.u32()?;
+        let block_count = r.u32()? as usize;
+        let chunk_count = r.u32()? as usize;
+
+        let block_hashes = r.u64s(block_count)?;
+        let chunk_hashes = r.u64s(chunk_count)?;
+        let block_chunk_offsets = r.u32s(block_count)?;
+        let block_chunk_counts = r.u32s(block_count)?;
+        let block_tags = r.u32s(block_count)?;
+        // Chunk sizes follow, but they're repeated in each block's own index
+
+        let mut chunk_blocks = HashMap::with_capacity(chunk_count);
+        for i in 0..block_count {
+            let start = block_chunk_offsets[i] as usize;
+            let end = start + block_chunk_counts[i] as usize;
+            let chunks = chunk_hashes.get(start..end).ok_or_else(|| {
+                anyhow!("Block {} chunk range {}..{} out of bounds", i, start, end)
+            })?;
+            for chunk in chunks {
+                chunk_blocks.insert(
+                    *chunk,
+                    BlockLocation {
+                        block_hash: block_hashes[i],
+                        tag: block_tags[i],
+                    },
+                );
+            }
+        }
+
+        Ok(Self {
+            hash_identifier,
+            chunk_blocks,
+        })
+    }
+}
+
+// A .lsb file: a small index of the chunks it holds, followed by the chunk data. Compressed
+// blocks prefix the data with the uncompressed and compressed sizes.
+#[derive(Debug, Clone)]
+pub struct StoredBlock {
+    pub block_hash: BlockHash,
+    chunks: HashMap<ChunkHash, (usize, usize)>,
+    data: Vec<u8>,
+}
+
+impl StoredBlock {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(bytes);
+
+        let block_hash = r.u64()?;
+        let _hash_identifier = r.u32()?;
+        let chunk_count = r.u32()? as usize;
+        let tag = r.u32()?;
+        let chunk_hashes = r.u64s(chunk_count)?;
+        let chunk_sizes = r.u32s(chunk_count)?;
+
+        let data = match Compression::from_tag(tag)? {
+            Compression::None => r.rest().to_vec(),
+            compression => {
+                let uncompressed_size = r.u32()? as usize;
+                let compressed_size = r.u32()? as usize;
+                compression.decompress(r.take(compressed_size)?, uncompressed_size)?
+            }
+        };
+
+        let mut chunks = HashMap::with_capacity(chunk_count);
+        let mut offset = 0;
+        for (hash, size) in chunk_hashes.iter().zip(chunk_sizes.iter()) {
+            chunks.insert(*hash, (offset, *size as usize));
+            offset += *size as usize;
+        }
+        if offset > data.len() {
+            bail!(
+                "Block {:#018x} holds {} bytes of chunks but only {} bytes of data",
+                block_hash,
+                offset,
+                data.len()
+            );
+        }
+
+        Ok(Self {
+            block_hash,
+            chunks,
+            data,
+        })
+    }
+
+    pub fn chunk(&self, hash: ChunkHash) -> Option<&[u8]> {
+        self.chunks
+            .get(&hash)
+            .map(|(offset, size)| &self.data[*offset..*offset + *size])
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use super::*;
+
+    // A block's hash and tag, and the hash and size of each chunk in it
+    pub type TestBlock = (BlockHash, u32, Vec<(ChunkHash, u32)>);
+
+    pub fn store_index_bytes(blocks: &[TestBlock]) -> Vec<u8> {
+        let mut w = Writer::default();
+        let chunk_count: usize = blocks.iter().map(|b| b.2.len()).sum();
+        w.u32(STORE_INDEX_VERSION);
+        w.u32(0);
+        w.u32(blocks.len() as u32);
+        w.u32(chunk_count as u32);
+        w.u64s(&blocks.iter().map(|b| b.0).collect::<Vec<_>>());
+        w.u64s(
+            &blocks
+                .iter()
+                .flat_map(|b| b.2.iter().map(|c| c.0))
+                .collect::<Vec<_>>(),
+        );
+        let mut offset = 0;
+        for block in blocks {
+            w.u32(offset);
+            offset += block.2.len() as u32;
+        }
+        w.u32s(&blocks.iter().map(|b| b.2.len() as u32).collect::<Vec<_>>());
+        w.u32s(&blocks.iter().map(|b| b.1).collect::<Vec<_>>());
+        w.u32s(
+            &blocks
+                .iter()
+                .flat_map(|b| b.2.iter().map(|c| c.1))
+                .collect::<Vec<_>>(),
+        );
+        w.data
+    }
+
+    // Writes an uncompressed block holding the given chunks
+    pub fn block_bytes(block_hash: BlockHash, chunks: &[(ChunkHash, &[u8])]) -> Vec<u8> {
+        let mut w = Writer::default();
+        w.u64s(&[block_hash]);
+        w.u32(0);
+        w.u32(chunks.len() as u32);
+        w.u32(0);
+        w.u64s(&chunks.iter().map(|c| c.0).collect::<Vec<_>>());
+        w.u32s(&chunks.iter().map(|c| c.1.len() as u32).collect::<Vec<_>>());
+        for chunk in chunks {
+            w.data.extend_from_slice(chunk.1);
+        }
+        w.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::*;
+    use super::*;
+
+    fn asset(path: &str, content_hash: u64, chunks: &[(ChunkHash, u32)]) -> Asset {
+        Asset {
+            path: path.to_string(),
+            path_hash: content_hash ^ 0xff,
+            content_hash,
+            size: chunks.iter().map(|c| c.1 as u64).sum(),
+            permissions: 0o644,
+            chunks: chunks.iter().map(|c| c.0).collect(),
+        }
+    }
+
+    fn version(assets: Vec<Asset>, chunk_sizes: &[(ChunkHash, u32)]) -> VersionIndex {
+        VersionIndex {
+            hash_identifier: 0,
+            target_chunk_size: 32768,
+            assets,
+            chunks: chunk_sizes
+                .iter()
+                .map(|(hash, size)| {
+                    (
+                        *hash,
+                        ChunkInfo {
+                            size: *size,
+                            tag: 0,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_version_index_round_trip() {
+        let chunks = [(1, 10), (2, 20), (3, 30)];
+        let index = version(
+            vec![
+                asset("Binaries/", 0, &[]),
+                asset("Binaries/game.exe", 100, &[(1, 10), (2, 20)]),
+                asset("Content/pak.pak", 200, &[(2, 20), (3, 30)]),
+            ],
+            &chunks,
+        );
+
+        let parsed = VersionIndex::from_bytes(&index.to_bytes()).unwrap();
+        assert_eq!(parsed, index);
+        assert!(parsed.assets[0].is_dir());
+        assert_eq!(parsed.assets[2].chunks, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_version_index_rejects_truncated_data() {
+        let index = version(vec![asset("a.txt", 1, &[(1, 10)])], &[(1, 10)]);
+        let bytes = index.to_bytes();
+        assert!(VersionIndex::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+        assert!(VersionIndex::from_bytes(&[0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_merge_prefers_later_versions() {
+        let game = version(
+            vec![
+                asset("game.exe", 1, &[(1, 10)]),
+                asset("shared.dll", 2, &[(2, 10)]),
+            ],
+            &[(1, 10), (2, 10)],
+        );
+        let symbols = version(
+            vec![
+                asset("game.pdb", 3, &[(3, 10)]),
+                asset("shared.dll", 4, &[(4, 10)]),
+            ],
+            &[(3, 10), (4, 10)],
+        );
+
+        let merged = VersionIndex::merge(vec![game, symbols]).unwrap();
+        let paths: Vec<_> = merged.assets.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(paths, vec!["game.exe", "shared.dll", "game.pdb"]);
+        assert_eq!(merged.assets[1].content_hash, 4);
+        assert_eq!(merged.chunks.len(), 4);
+    }
+
+    #[test]
+    fn test_store_index_and_block() {
+        let store = StoreIndex::from_bytes(&store_index_bytes(&[
+            (0xaa, 0, vec![(1, 3), (2, 2)]),
+            (0xbb, 0, vec![(3, 1)]),
+        ]))
+        .unwrap();
+        assert_eq!(store.chunk_blocks[&2].block_hash, 0xaa);
+        assert_eq!(store.chunk_blocks[&3].block_hash, 0xbb);
+
+        let block =
+            StoredBlock::from_bytes(&block_bytes(0xaa, &[(1, b"abc"), (2, b"de")])).unwrap();
+        assert_eq!(block.chunk(1), Some(&b"abc"[..]));
+        assert_eq!(block.chunk(2), Some(&b"de"[..]));
+        assert_eq!(block.chunk(3), None);
+    }
+
+    // See testdata/README.md for how these were made
+    const FIXTURE_VERSION: &[u8] = include_bytes!("testdata/version.lvi");
+    const FIXTURE_STORE: &[u8] = include_bytes!("testdata/store.lsi");
+    const FIXTURE_BLOCKS: [(&[u8], u32); 4] = [
+        (
+            include_bytes!("testdata/chunks/5931/0x593116f45bd2f09b.lsb"),
+            0,
+        ),
+        (
+            include_bytes!("testdata/chunks/9250/0x92507ed3779d19ef.lsb"),
+            u32::from_be_bytes(*b"ztd2"),
+        ),
+        (
+            include_bytes!("testdata/chunks/b81d/0xb81d83c9d888185c.lsb"),
+            u32::from_be_bytes(*b"lz42"),
+        ),
+        (
+            include_bytes!("testdata/chunks/d114/0xd114b015616d5c08.lsb"),
+            u32::from_be_bytes(*b"btl1"),
+        ),
+    ];
+
+    #[test]
+    fn test_fixture_indexes() {
+        let version = VersionIndex::from_bytes(FIXTURE_VERSION).unwrap();
+        assert_eq!(version.hash_identifier, u32::from_be_bytes(*b"blk3"));
+        assert_eq!(version.target_chunk_size, 32768);
+        let assets: Vec<_> = version
+            .assets
+            .iter()
+            .map(|a| (a.path.as_str(), a.size, a.chunks.len()))
+            .collect();
+        assert_eq!(
+            assets,
+            vec![
+                ("Binaries/", 0, 0),
+                ("Binaries/game.exe", 247, 2),
+                ("Content/", 0, 0),
+                ("Content/readme.txt", 240, 2),
+                ("Content/data.pak", 265, 2),
+            ]
+        );
+        assert_eq!(version.chunks.len(), 5);
+        assert_eq!(version.to_bytes(), FIXTURE_VERSION);
+
+        let store = StoreIndex::from_bytes(FIXTURE_STORE).unwrap();
+        assert_eq!(store.chunk_blocks.len(), 5);
+        for asset in &version.assets {
+            for chunk in &asset.chunks {
+                let location = store.chunk_blocks[chunk];
+                assert_eq!(location.tag, version.chunks[chunk].tag);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixture_blocks() {
+        let version = VersionIndex::from_bytes(FIXTURE_VERSION).unwrap();
+        let store = StoreIndex::from_bytes(FIXTURE_STORE).unwrap();
+
+        for (bytes, tag) in FIXTURE_BLOCKS {
+            let block = StoredBlock::from_bytes(bytes).unwrap();
+            let chunks: Vec<_> = store
+                .chunk_blocks
+                .iter()
+                .filter(|(_, location)| location.block_hash == block.block_hash)
+                .collect();
+            assert!(!chunks.is_empty());
+            for (chunk, location) in chunks {
+                assert_eq!(location.tag, tag);
+                let data = block.chunk(*chunk).unwrap();
+                assert_eq!(data.len(), version.chunks[chunk].size as usize);
+                assert_eq!(
+                    u64::from_le_bytes(blake3::hash(data).as_bytes()[..8].try_into().unwrap()),
+                    *chunk
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_compression_tags() {
+        assert_eq!(Compression::from_tag(0).unwrap(), Compression::None);
+        assert_eq!(
+            Compression::from_tag(u32::from_be_bytes(*b"ztd2")).unwrap(),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::from_tag(u32::from_be_bytes(*b"lz42")).unwrap(),
+            Compression::Lz4
+        );
+        assert!(Compression::from_tag(u32::from_be_bytes(*b"nope")).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use super::get::TARGET_INDEX_NAME;
+use super::index::VersionIndex;
+
+pub const INSTALLS_FILE: &str = "installs.json";
+
+pub const DEFAULT_MAX_INSTALLED_BUILDS: usize = 1;
+
+// Serializes read-modify-write of the registry between concurrent syncs
+static INSTALLS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledBuild {
+    pub commit: String,
+
+    // Builds of different kinds or platforms at the same commit install side by side
+    pub base_name: String,
+    pub path: PathBuf,
+    pub archives: Vec<String>,
+    pub installed_at: DateTime<Utc>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_launched_at: Option<DateTime<Utc>>,
+}
+
+impl InstalledBuild {
+    // Directory name for an install, ex. client-win64-1a2b3c4d
+    pub fn dir_name(base_name: &str, commit: &str) -> String {
+        let short_commit: String = commit.chars().take(8).collect();
+        format!("{base_name}-{short_commit}")
+    }
+
+    fn last_used_at(&self) -> DateTime<Utc> {
+        self.last_launched_at.map_or(self.installed_at, |launched| {
+            launched.max(self.installed_at)
+        })
+    }
+}
+
+// Client builds installed side by side, most recently installed first, persisted as JSON in the
+// download directory
+#[derive(Debug, Clone)]
+pub struct InstallRegistry {
+    path: PathBuf,
+}
+
+impl InstallRegistry {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    // Installs whose directory has since been removed by hand are left out
+    pub fn load(&self) -> Vec<InstalledBuild> {
+        let _lock = INSTALLS_LOCK.lock();
+        Self::read(&self.path)
+            .into_iter()
+            .filter(|install| install.path.is_dir())
+            .collect()
+    }
+
+    pub fn find(&self, commit: &str, base_name: Option<&str>) -> Option<InstalledBuild> {
+        self.load().into_iter().find(|install| {
+            install.commit.starts_with(commit) && base_name.is_none_or(|b| install.base_name == b)
+        })
+    }
+
+    // The most recently used install of the same kind of build, to seed a new one from
+    pub fn closest(&self, base_name: &str, exclude: &Path) -> Option<InstalledBuild> {
+        self.load()
+            .into_iter()
+            .filter(|install| install.base_name == base_name && install.path != exclude)
+            .max_by_key(InstalledBuild::last_used_at)
+    }
+
+    pub fn record(&self, install: InstalledBuild) -> Result<()> {
+        self.update(|installs| {
+            installs.retain(|existing| existing.path != install.path);
+            installs.insert(0, install);
+        })
+    }
+
+    pub fn mark_launched(&self, path: &Path) -> Result<()> {
+        self.update(|installs| {
+            if let Some(install) = installs.iter_mut().find(|i| i.path == path) {
+                install.last_launched_at = Some(Utc::now());
+            }
+        })
+    }
+
+    // Deletes an install from disk and the registry
+    pub fn remove(&self, path: &Path) -> Result<InstalledBuild> {
+        let mut removed = None;
+        self.update(|installs| {
+            if let Some(pos) = installs.iter().position(|i| i.path == path) {
+                removed = Some(installs.remove(pos));
+            }
+        })?;
+        let removed = removed.ok_or_else(|| anyhow!("No install found at {:?}", path))?;
+
+        info!("Removing installed build {:?}", removed.path);
+        if removed.path.exists() {
+            fs::remove_dir_all(&removed.path)?;
+        }
+        Ok(removed)
+    }
+
+    // Removes the least recently used installs past max_installs, never touching keep
+    pub fn enforce_limit(&self, max_installs: usize, keep: &Path) -> Result<Vec<InstalledBuild>> {
+        let mut installs = self.load();
+        installs.sort_by_key(|install| std::cmp::Reverse(install.last_used_at()));
+
+        let mut removed = vec![];
+        let mut kept = 0;
+        for install in installs {
+            if install.path == keep || kept < max_installs.saturating_sub(1) {
+                if install.path != keep {
+                    kept += 1;
+                }
+                continue;
+            }
+            match self.remove(&install.path) {
+                Ok(install) => removed.push(install),
+                Err(e) => warn!("Failed to remove install {:?}: {:?}", install.path, e),
+            }
+        }
+        Ok(removed)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Vec<InstalledBuild>)) -> Result<()> {
+        let _lock = INSTALLS_LOCK.lock();
+
+        let mut installs = Self::read(&self.path);
+        f(&mut installs);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&installs)?)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    fn read(path: &Path) -> Vec<InstalledBuild> {
+        let Ok(bytes) = fs::read(path) else {
+            return vec![];
+        };
+        serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            warn!("Discarding unreadable install registry {:?}: {}", path, e);
+            vec![]
+        })
+    }
+}
+
+// Hardlinks every file of an existing install into an empty target, along with a copy of its
+// target index, so the next get into target only writes what changed between the two builds.
+// Files that can't be linked, ex. across volumes, are left for the get to download.
+pub fn seed_install(from: &Path, to: &Path) -> Result<u64> {
+    let index_path = from.join(TARGET_INDEX_NAME);
+    let Ok(bytes) = fs::read(&index_path) else {
+        return Ok(0);
+    };
+    let index = VersionIndex::from_bytes(&bytes)?;
+
+    let mut linked = 0;
+    for asset in index.assets.iter().filter(|a| !a.is_dir()) {
+        let dest = to.join(&asset.path);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match fs::hard_link(from.join(&asset.path), &dest) {
+            Ok(()) => linked += 1,
+            Err(e) => {
+                debug!("Unable to link {:?} into {:?}: {}", asset.path, to, e);
+            }
+        }
+    }
+
+    // Copied rather than linked, since it's rewritten in place at the end of every get
+    fs::copy(&index_path, to.join(TARGET_INDEX_NAME))?;
+    info!("Seeded {:?} with {} files from {:?}", to, linked, from);
+    Ok(linked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::index::Asset;
+    use super::*;
+    use std::collections::HashMap;
+
+    fn install(dir: &Path, commit: &str, minutes_ago: i64) -> InstalledBuild {
+        let path = dir.join(InstalledBuild::dir_name("client-win64", commit));
+        fs::create_dir_all(&path).unwrap();
+        InstalledBuild {
+            commit: commit.to_string(),
+            base_name: "client-win64".to_string(),
+            path,
+            archives: vec![],
+            installed_at: Utc::now() - chrono::Duration::minutes(minutes_ago),
+            last_launched_at: None,
+        }
+    }
+
+    #[test]
+    fn test_enforce_limit_keeps_recent() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = InstallRegistry::new(dir.path().join(INSTALLS_FILE));
+        let (old, older, new) = (
+            install(dir.path(), "aaaaaaaaaa", 10),
+            install(dir.path(), "bbbbbbbbbb", 20),
+            install(dir.path(), "cccccccccc", 0),
+        );
+        registry.record(older.clone()).unwrap();
+        registry.record(old.clone()).unwrap();
+        registry.record(new.clone()).unwrap();
+        registry.mark_launched(&older.path).unwrap();
+
+        // older was launched most recently, so old is the one to go
+        let removed = registry.enforce_limit(2, &new.path).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].commit, old.commit);
+        assert!(!old.path.exists());
+
+        assert!(registry.find("bbbbbbbb", None).is_some());
+        assert_eq!(
+            registry.closest("client-win64", &new.path).unwrap().commit,
+            older.commit
+        );
+    }
+
+    #[test]
+    fn test_seed_install_links_indexed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("from"), dir.path().join("to"));
+        fs::create_dir_all(from.join("Binaries")).unwrap();
+        fs::write(from.join("Binaries/game.exe"), b"game").unwrap();
+        fs::write(from.join("crash.log"), b"not part of the build").unwrap();
+
+        let index = VersionIndex {
+            hash_identifier: 0,
+            target_chunk_size: 32768,
+            assets: vec![Asset {
+                path: "Binaries/game.exe".to_string(),
+                path_hash: 0,
+                content_hash: 1,
+                size: 4,
+                permissions: 0o644,
+                chunks: vec![],
+            }],
+            chunks: HashMap::new(),
+        };
+        fs::write(from.join(TARGET_INDEX_NAME), index.to_bytes()).unwrap();
+
+        assert_eq!(seed_install(&from, &to).unwrap(), 1);
+        assert_eq!(fs::read(to.join("Binaries/game.exe")).unwrap(), b"game");
+        assert!(to.join(TARGET_INDEX_NAME).exists());
+        assert!(!to.join("crash.log").exists());
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use super::index::Asset;
+
+// Written to the target while a get is in progress and removed once it completes
+pub const SYNC_JOURNAL_NAME: &str = ".longtail.sync.journal";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct JournalHeader {
+    sources: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalEntry {
+    path: String,
+    content_hash: u64,
+}
+
+// Newline-delimited JSON: a header naming the sources being synced, then one line per asset as
+// it finishes writing. Every line is flushed, so an interrupted get loses at most the asset it
+// was in the middle of.
+pub struct SyncJournal {
+    path: PathBuf,
+    file: File,
+}
+
+impl SyncJournal {
+    // Opens the journal for a get of sources into target, returning the assets an interrupted get
+    // of the same sources already wrote, by path and content hash. A journal left by a get of
+    // different sources is discarded.
+    pub fn open(target: &Path, sources: &[String]) -> Result<(Self, HashMap<String, u64>)> {
+        let path = target.join(SYNC_JOURNAL_NAME);
+        let header = JournalHeader {
+            sources: sources.to_vec(),
+        };
+
+        let completed = Self::read(&path, &header);
+        if !completed.is_empty() {
+            info!(
+                "Resuming interrupted sync into {:?}, {} assets already written",
+                target,
+                completed.len()
+            );
+            let mut file = OpenOptions::new().read(true).append(true).open(&path)?;
+
+            // Start on a fresh line if we stopped partway through one
+            file.seek(SeekFrom::End(-1))?;
+            let mut last = [0u8];
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                writeln!(file)?;
+            }
+            return Ok((Self { path, file }, completed));
+        }
+
+        let mut file = File::create(&path)?;
+        writeln!(file, "{}", serde_json::to_string(&header)?)?;
+        file.flush()?;
+        Ok((Self { path, file }, HashMap::new()))
+    }
+
+    fn read(path: &Path, header: &JournalHeader) -> HashMap<String, u64> {
+        let Ok(file) = File::open(path) else {
+            return HashMap::new();
+        };
+        let mut lines = BufReader::new(file).lines();
+
+        let previous = lines
+            .next()
+            .and_then(|line| line.ok())
+            .and_then(|line| serde_json::from_str::<JournalHeader>(&line).ok());
+        if previous.as_ref() != Some(header) {
+            debug!("Discarding sync journal {:?} for different sources", path);
+            return HashMap::new();
+        }
+
+        // A torn line is just the asset that was being written when we stopped
+        lines
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_str::<JournalEntry>(&line).ok())
+            .map(|entry| (entry.path, entry.content_hash))
+            .collect()
+    }
+
+    pub fn record(&mut self, asset: &Asset) -> Result<()> {
+        let entry = JournalEntry {
+            path: asset.path.clone(),
+            content_hash: asset.content_hash,
+        };
+        writeln!(self.file, "{}", serde_json::to_string(&entry)?)?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<()> {
+        drop(self.file);
+        std::fs::remove_file(&self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(path: &str, content_hash: u64) -> Asset {
+        Asset {
+            path: path.to_string(),
+            path_hash: 0,
+            content_hash,
+            size: 0,
+            permissions: 0o644,
+            chunks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_resume_same_sources_only() {
+        let target = tempfile::tempdir().unwrap();
+        let sources = vec!["s3://bucket/client/abc.json".to_string()];
+
+        let (mut journal, completed) = SyncJournal::open(target.path(), &sources).unwrap();
+        assert!(completed.is_empty());
+        journal.record(&asset("game.exe", 1)).unwrap();
+        journal.record(&asset("game.pak", 2)).unwrap();
+        drop(journal);
+
+        // an interrupted write leaves a partial line behind
+        let path = target.path().join(SYNC_JOURNAL_NAME);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"path\":\"game.p").unwrap();
+        drop(file);
+
+        let (mut journal, completed) = SyncJournal::open(target.path(), &sources).unwrap();
+        assert_eq!(completed.len(), 2);
+        assert_eq!(completed["game.pak"], 2);
+        journal.record(&asset("game.pdb", 3)).unwrap();
+        drop(journal);
+
+        let (journal, completed) = SyncJournal::open(target.path(), &sources).unwrap();
+        assert_eq!(completed.len(), 3);
+        journal.finish().unwrap();
+        assert!(!path.exists());
+
+        let (_, completed) = SyncJournal::open(target.path(), &sources).unwrap();
+        assert!(completed.is_empty());
+        let other = vec!["s3://bucket/client/def.json".to_string()];
+        let (_, completed) = SyncJournal::open(target.path(), &other).unwrap();
+        assert!(completed.is_empty());
+    }
+}
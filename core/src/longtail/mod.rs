@@ -0,0 +1,299 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::Result;
+use aws_credential_types::Credentials;
+use chrono::Utc;
+use parking_lot::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, instrument, warn};
+
+use super::fs::LocalDownloadPath;
+use super::msg::LongtailMsg;
+use bisect::{BisectStore, BISECT_FILE};
+use cache::{CacheConsumer, ChunkCache, CHUNK_CACHE_DIR, DEFAULT_CHUNK_CACHE_SIZE_GB};
+use get::GetStats;
+use history::{SyncHistory, SyncSummary, SYNC_HISTORY_FILE};
+use installs::{InstallRegistry, INSTALLS_FILE};
+
+pub mod bisect;
+pub mod cache;
+pub mod get;
+pub mod history;
+pub mod index;
+pub mod installs;
+pub mod journal;
+pub mod store;
+pub mod verify;
+
+// Send a Msg down the transmit channel
+pub fn send_msg(tx: &Sender<LongtailMsg>, msg: LongtailMsg) {
+    if tx.send(msg.clone()).is_err() {
+        // We probably can't and don't want to do much in the way of error handling,
+        // since we're likely on a thread. At worst the UI gets a little wonky, because
+        // log messages and the busy spinner stops working...
+        info!("Failed to send message! {:?} {:?}", tx, msg)
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Longtail {
+    pub app_name: String,
+    pub download_path: LocalDownloadPath,
+
+    // Every get runs under its own child of this token, cancel_all() swaps in a fresh one
+    #[serde(skip)]
+    cancel: Arc<Mutex<CancellationToken>>,
+
+    // Global size budget of the chunk cache, shared by every clone so config changes apply to
+    // syncs already queued
+    #[serde(skip, default = "default_chunk_cache_budget")]
+    chunk_cache_budget: Arc<AtomicU64>,
+}
+
+fn default_chunk_cache_budget() -> Arc<AtomicU64> {
+    Arc::new(AtomicU64::new(
+        DEFAULT_CHUNK_CACHE_SIZE_GB * 1024 * 1024 * 1024,
+    ))
+}
+
+// Which consumer of the shared chunk cache a get is for, and how much of the cache it may hold on
+// to once its current blocks are no longer pinned
+pub struct CacheControl {
+    pub consumer: CacheConsumer,
+    pub quota_bytes: u64,
+}
+
+impl Longtail {
+    pub fn new(app_name: &str) -> Self {
+        Longtail {
+            app_name: app_name.to_string(),
+            download_path: LocalDownloadPath::new(app_name),
+            cancel: Arc::new(Mutex::new(CancellationToken::new())),
+            chunk_cache_budget: default_chunk_cache_budget(),
+        }
+    }
+
+    pub fn set_chunk_cache_budget(&self, max_size_bytes: u64) {
+        self.chunk_cache_budget
+            .store(max_size_bytes, Ordering::Relaxed);
+    }
+
+    pub fn chunk_cache(&self) -> ChunkCache {
+        ChunkCache::new(
+            self.download_path.0.join(CHUNK_CACHE_DIR),
+            self.chunk_cache_budget.load(Ordering::Relaxed),
+        )
+    }
+
+    // Token for a single get, cancelling it stops only that get
+    pub fn cancel_token(&self) -> CancellationToken {
+        self.cancel.lock().child_token()
+    }
+
+    // Cancels every get in flight, ex. on shutdown. Gets started afterwards are unaffected.
+    pub fn cancel_all(&self) {
+        let token = std::mem::take(&mut *self.cancel.lock());
+        token.cancel();
+    }
+
+    pub fn sync_history(&self) -> SyncHistory {
+        SyncHistory::new(self.download_path.0.join(SYNC_HISTORY_FILE))
+    }
+
+    pub fn installs(&self) -> InstallRegistry {
+        InstallRegistry::new(self.download_path.0.join(INSTALLS_FILE))
+    }
+
+    pub fn bisect(&self) -> BisectStore {
+        BisectStore::new(self.download_path.0.join(BISECT_FILE))
+    }
+
+    // Download and unpack the given archives into path. A failed get is retried in place, picking
+    // up where it stopped, then from a clean target without the cached blocks it used. Every call
+    // is recorded in the sync history.
+    #[instrument(skip(self, cache, tx, credentials, cancel), err)]
+    pub async fn get_archive(
+        &self,
+        path: &Path,
+        cache: Option<CacheControl>,
+        archives: &[String],
+        tx: Sender<LongtailMsg>,
+        credentials: Credentials,
+        cancel: CancellationToken,
+    ) -> Result<()> {
+        info!(
+            "Attempting to download longtail archives {:?} to path {:?}",
+            &archives, path
+        );
+
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let mut stats = GetStats::default();
+        let mut retries = 0;
+        let result = self
+            .get_archive_with_retries(
+                path,
+                cache.as_ref(),
+                archives,
+                &tx,
+                &credentials,
+                &cancel,
+                &mut stats,
+                &mut retries,
+            )
+            .await;
+        info!("Longtail get result: {:?}", result);
+
+        let summary = SyncSummary {
+            started_at,
+            target: path.to_path_buf(),
+            archives: archives.to_vec(),
+            downloaded_bytes: stats.downloaded_bytes,
+            cached_bytes: stats.cached_bytes,
+            written_bytes: stats.written_bytes,
+            resumed_assets: stats.resumed_assets,
+            repaired_assets: stats.repaired_assets,
+            wall_time_ms: started.elapsed().as_millis() as u64,
+            retries,
+            succeeded: result.is_ok(),
+            error: result.as_ref().err().map(|e| format!("{e:#}")),
+        };
+        if let Err(e) = self.sync_history().record(summary) {
+            warn!("Failed to record sync summary: {:?}", e);
+        }
+
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn get_archive_with_retries(
+        &self,
+        path: &Path,
+        cache: Option<&CacheControl>,
+        archives: &[String],
+        tx: &Sender<LongtailMsg>,
+        credentials: &Credentials,
+        cancel: &CancellationToken,
+        stats: &mut GetStats,
+        retries: &mut u32,
+    ) -> Result<()> {
+        let reader = store::BlobReader::new(credentials);
+
+        let Some(cache) = cache else {
+            return self
+                .get_archive_internal(&reader, path, None, archives, tx, cancel, stats)
+                .await;
+        };
+
+        let mut result: Result<()> = self
+            .get_archive_internal(&reader, path, Some(cache), archives, tx, cancel, stats)
+            .await;
+        if result.is_err() && !cancel.is_cancelled() {
+            warn!("Longtail get failed. Retrying, resuming from what was already written. Original error was: {:?}", result);
+            *retries += 1;
+            result = self
+                .get_archive_internal(&reader, path, Some(cache), archives, tx, cancel, stats)
+                .await;
+
+            if result.is_err() && !cancel.is_cancelled() {
+                warn!("Longtail get failed AGAIN. Attempting to clear target path and this sync's cached blocks and retry download + unpack. Original error was: {:?}", result);
+                *retries += 1;
+                if path.exists() {
+                    std::fs::remove_dir_all(path)?;
+                }
+                self.chunk_cache().remove_blocks(&stats.referenced_blocks)?;
+                result = self
+                    .get_archive_internal(&reader, path, Some(cache), archives, tx, cancel, stats)
+                    .await;
+            }
+        }
+
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn get_archive_internal(
+        &self,
+        reader: &store::BlobReader,
+        path: &Path,
+        cache: Option<&CacheControl>,
+        archives: &[String],
+        tx: &Sender<LongtailMsg>,
+        cancel: &CancellationToken,
+        stats: &mut GetStats,
+    ) -> Result<()> {
+        send_msg(
+            tx,
+            LongtailMsg::ExecEvt(format!("get {:?} -> {:?}", archives, path)),
+        );
+
+        fs::create_dir_all(path)?;
+        let chunk_cache = self.chunk_cache();
+        // Held until the ledger claims the blocks below
+        let sync = cache.map(|_| chunk_cache.begin_sync());
+        let result = get::get(reader, path, sync.as_ref(), archives, tx, cancel, stats).await;
+        if let Err(e) = &result {
+            send_msg(tx, LongtailMsg::ErrEvt(format!("{e:#}")));
+        }
+        result?;
+
+        send_msg(tx, LongtailMsg::DoneArcSyncEvt);
+
+        // The sync itself succeeded, so cache bookkeeping problems are only logged. Both walk the
+        // whole cache, so they run off the async runtime.
+        if let Some(cache) = cache {
+            let (consumer, quota_bytes) = (cache.consumer, cache.quota_bytes);
+            let blocks = stats.referenced_blocks.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                chunk_cache
+                    .record_use(consumer, &blocks)
+                    .and_then(|_| chunk_cache.evict(consumer, quota_bytes))
+            })
+            .await;
+            match result {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => warn!("Failed to update chunk cache ledger: {:?}", e),
+                Err(e) => warn!("Failed to update chunk cache ledger: {:?}", e),
+            }
+        }
+        drop(sync);
+
+        Ok(())
+    }
+
+    #[instrument]
+    pub fn log_message(msg: LongtailMsg) {
+        match msg {
+            LongtailMsg::Log(s) => {
+                info!("Log: {}", s);
+            }
+            LongtailMsg::ExecEvt(s) => {
+                info!("Executing: {}", s)
+            }
+            LongtailMsg::ErrEvt(s) => {
+                error!("ERROR: {}", s)
+            }
+            LongtailMsg::DoneArcSyncEvt => {
+                info!("Done syncing");
+            }
+            LongtailMsg::Progress {
+                phase,
+                done_bytes,
+                total_bytes,
+                chunks_done,
+                chunks_total,
+                ..
+            } => {
+                debug!(
+                    "{}: {}/{} bytes, {}/{} chunks",
+                    phase, done_bytes, total_bytes, chunks_done, chunks_total
+                );
+            }
+        };
+    }
+}
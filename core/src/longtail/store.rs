@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use aws_credential_types::Credentials;
+use aws_sdk_s3::config::BehaviorVersion;
+use aws_sdk_s3::Client as S3Client;
+use aws_types::region::Region;
+use serde::Deserialize;
+
+use super::index::BlockHash;
+
+const STORE_INDEX_NAME: &str = "store.lsi";
+
+// The small JSON document uploaded alongside each archive, pointing at its version index and the
+// block store holding its content. This is what an artifact key resolves to.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GetConfig {
+    pub storage_uri: String,
+    pub source_path: String,
+
+    // A store index trimmed down to the blocks this version uses, much cheaper than store.lsi
+    #[serde(default)]
+    pub version_local_store_index_path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlobLocation {
+    S3 { bucket: String, key: String },
+    File(PathBuf),
+}
+
+impl BlobLocation {
+    // Accepts s3://bucket/key, file:// URLs (including file:///C:/... on Windows) and bare paths
+    pub fn parse(uri: &str) -> Result<Self> {
+        if let Some(rest) = uri.strip_prefix("s3://") {
+            let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+            if bucket.is_empty() {
+                return Err(anyhow!("Missing bucket in storage URI {}", uri));
+            }
+            return Ok(BlobLocation::S3 {
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+            });
+        }
+
+        if let Some(rest) = uri.strip_prefix("file://") {
+            let bytes = rest.as_bytes();
+            let is_drive_path = bytes.len() >= 3
+                && bytes[0] == b'/'
+                && bytes[1].is_ascii_alphabetic()
+                && bytes[2] == b':';
+            let path = if is_drive_path { &rest[1..] } else { rest };
+            return Ok(BlobLocation::File(PathBuf::from(path)));
+        }
+
+        if uri.contains("://") {
+            return Err(anyhow!("Unsupported storage URI {}", uri));
+        }
+        Ok(BlobLocation::File(PathBuf::from(uri)))
+    }
+
+    pub fn join(&self, relative: &str) -> Self {
+        match self {
+            BlobLocation::S3 { bucket, key } => BlobLocation::S3 {
+                bucket: bucket.clone(),
+                key: match key.trim_end_matches('/') {
+                    "" => relative.to_string(),
+                    key => format!("{key}/{relative}"),
+                },
+            },
+            BlobLocation::File(path) => BlobLocation::File(path.join(relative)),
+        }
+    }
+}
+
+impl std::fmt::Display for BlobLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlobLocation::S3 { bucket, key } => write!(f, "s3://{bucket}/{key}"),
+            BlobLocation::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+// Relative path of a block in a store, shared by remote stores and the local chunk cache
+pub fn block_path(hash: BlockHash) -> String {
+    format!("chunks/{:04x}/0x{:016x}.lsb", hash >> 48, hash)
+}
+
+pub fn store_index_location(storage_uri: &BlobLocation) -> BlobLocation {
+    storage_uri.join(STORE_INDEX_NAME)
+}
+
+// Reads blobs from S3 or the local filesystem
+#[derive(Debug, Clone)]
+pub struct BlobReader {
+    s3: S3Client,
+}
+
+impl BlobReader {
+    pub fn new(credentials: &Credentials) -> Self {
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(crate::AWS_REGION))
+            .credentials_provider(credentials.clone())
+            .build();
+
+        Self {
+            s3: S3Client::from_conf(config),
+        }
+    }
+
+    pub async fn read(&self, location: &BlobLocation) -> Result<Vec<u8>> {
+        match location {
+            BlobLocation::S3 { bucket, key } => {
+                let output = self
+                    .s3
+                    .get_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        anyhow!("Failed to get {}: {}", location, e.into_service_error())
+                    })?;
+                let body = output
+                    .body
+                    .collect()
+                    .await
+                    .with_context(|| format!("Failed to read body of {location}"))?;
+                Ok(body.into_bytes().to_vec())
+            }
+            BlobLocation::File(path) => tokio::fs::read(path)
+                .await
+                .with_context(|| format!("Failed to read {location}")),
+        }
+    }
+
+    pub async fn read_get_config(&self, uri: &str) -> Result<GetConfig> {
+        let bytes = self.read(&BlobLocation::parse(uri)?).await?;
+        serde_json::from_slice(&bytes).with_context(|| format!("Invalid get config at {uri}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locations() {
+        assert_eq!(
+            BlobLocation::parse("s3://bucket/store/v1").unwrap(),
+            BlobLocation::S3 {
+                bucket: "bucket".to_string(),
+                key: "store/v1".to_string()
+            }
+        );
+        assert_eq!(
+            BlobLocation::parse("file:///mnt/builds/store").unwrap(),
+            BlobLocation::File(PathBuf::from("/mnt/builds/store"))
+        );
+        assert_eq!(
+            BlobLocation::parse("file:///C:/builds/store").unwrap(),
+            BlobLocation::File(PathBuf::from("C:/builds/store"))
+        );
+        assert!(BlobLocation::parse("gs://bucket/store").is_err());
+        assert!(BlobLocation::parse("s3:///store").is_err());
+    }
+
+    #[test]
+    fn test_block_path() {
+        let store = BlobLocation::parse("s3://bucket/store/").unwrap();
+        assert_eq!(
+            store.join(&block_path(0x1234_5678_9abc_def0)),
+            BlobLocation::S3 {
+                bucket: "bucket".to_string(),
+                key: "store/chunks/1234/0x123456789abcdef0.lsb".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::index::{Asset, ChunkHash, ChunkInfo};
+
+// Longtail packs the hash algorithm name into the identifier, ex. 'blk3' for blake3
+pub const BLAKE3_HASH_IDENTIFIER: u32 = u32::from_be_bytes(*b"blk3");
+
+// The algorithm a version's chunk hashes were computed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkHasher {
+    Blake3,
+}
+
+impl ChunkHasher {
+    // None for algorithms we can't reproduce, in which case only sizes can be checked
+    pub fn from_identifier(hash_identifier: u32) -> Option<Self> {
+        match hash_identifier {
+            BLAKE3_HASH_IDENTIFIER => Some(ChunkHasher::Blake3),
+            _ => None,
+        }
+    }
+
+    // Longtail keeps the first 8 bytes of the digest
+    pub fn hash(&self, data: &[u8]) -> ChunkHash {
+        match self {
+            ChunkHasher::Blake3 => {
+                let digest = blake3::hash(data);
+                u64::from_le_bytes(digest.as_bytes()[..8].try_into().unwrap())
+            }
+        }
+    }
+}
+
+// Checks a file on disk against its asset, chunk by chunk when the hash algorithm is known
+pub fn verify_asset(
+    target: &Path,
+    asset: &Asset,
+    chunks: &HashMap<ChunkHash, ChunkInfo>,
+    hasher: Option<ChunkHasher>,
+) -> Result<bool> {
+    let path = target.join(&asset.path);
+    if asset.is_dir() {
+        return Ok(path.is_dir());
+    }
+
+    let Ok(metadata) = std::fs::metadata(&path) else {
+        return Ok(false);
+    };
+    if metadata.len() != asset.size {
+        return Ok(false);
+    }
+    let Some(hasher) = hasher else {
+        return Ok(true);
+    };
+
+    let mut file = BufReader::new(File::open(&path)?);
+    let mut buf = Vec::new();
+    for chunk in &asset.chunks {
+        let Some(info) = chunks.get(chunk) else {
+            return Ok(false);
+        };
+        buf.resize(info.size as usize, 0);
+        file.read_exact(&mut buf)?;
+        if hasher.hash(&buf) != *chunk {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_asset() {
+        let hasher = ChunkHasher::from_identifier(BLAKE3_HASH_IDENTIFIER);
+        assert_eq!(hasher, Some(ChunkHasher::Blake3));
+        assert_eq!(ChunkHasher::from_identifier(0), None);
+
+        let (first, second) = (
+            ChunkHasher::Blake3.hash(b"game"),
+            ChunkHasher::Blake3.hash(b"exe"),
+        );
+        let chunks = HashMap::from([
+            (first, ChunkInfo { size: 4, tag: 0 }),
+            (second, ChunkInfo { size: 3, tag: 0 }),
+        ]);
+        let asset = Asset {
+            path: "game.exe".to_string(),
+            path_hash: 0,
+            content_hash: 0,
+            size: 7,
+            permissions: 0o644,
+            chunks: vec![first, second],
+        };
+
+        let target = tempfile::tempdir().unwrap();
+        let path = target.path().join("game.exe");
+        assert!(!verify_asset(target.path(), &asset, &chunks, hasher).unwrap());
+
+        std::fs::write(&path, b"gameexe").unwrap();
+        assert!(verify_asset(target.path(), &asset, &chunks, hasher).unwrap());
+
+        // same size, different content, only caught when hashing
+        std::fs::write(&path, b"gameEXE").unwrap();
+        assert!(!verify_asset(target.path(), &asset, &chunks, hasher).unwrap());
+        assert!(verify_asset(target.path(), &asset, &chunks, None).unwrap());
+    }
+}
@@ -1,8 +1,168 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum LongtailPhase {
+    Indexing,
+    Downloading,
+    Writing,
+    Verifying,
+}
+
+impl std::fmt::Display for LongtailPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LongtailPhase::Indexing => write!(f, "Indexing"),
+            LongtailPhase::Downloading => write!(f, "Downloading"),
+            LongtailPhase::Writing => write!(f, "Writing"),
+            LongtailPhase::Verifying => write!(f, "Verifying"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum LongtailMsg {
     Log(String),
     ExecEvt(String),
     ErrEvt(String),
-    DoneLtDlEvt,
     DoneArcSyncEvt,
+    Progress {
+        phase: LongtailPhase,
+        done_bytes: u64,
+        total_bytes: u64,
+        chunks_done: u64,
+        chunks_total: u64,
+        elapsed: Duration,
+        eta: Option<Duration>,
+    },
+}
+
+lazy_static! {
+    // "Receiving objects:  43% (430/1000), 1.20 MiB | 3.40 MiB/s" from git, and
+    // "Downloading LFS objects:  50% (5/10), 1.2 GB | 20 MB/s" from git-lfs
+    static ref DETERMINATE_PROGRESS_REGEX: Regex = Regex::new(
+        r"^(?:remote:\s*)?([A-Za-z][A-Za-z ]*?):\s+\d+%\s+\((\d+)/(\d+)\)(?:,\s*[^|,]+?\s*\|\s*([^,]+?))?(?:,\s*done\.?)?\s*$"
+    )
+    .unwrap();
+
+    // "Counting objects: 1234" or "Enumerating objects: 5, done."
+    static ref COUNT_PROGRESS_REGEX: Regex =
+        Regex::new(r"^(?:remote:\s*)?([A-Za-z][A-Za-z ]*?):\s+(\d+)(?:,\s*done\.?)?\s*$").unwrap();
+}
+
+/// A progress update parsed from a line git or git-lfs wrote to stderr. `total` is None for
+/// phases that only count up, like "Counting objects".
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitProgress {
+    pub operation: String,
+    pub phase: String,
+    pub current: u64,
+    pub total: Option<u64>,
+    pub throughput: Option<String>,
+}
+
+impl GitProgress {
+    pub fn parse(operation: &str, line: &str) -> Option<GitProgress> {
+        let line = line.trim();
+
+        if let Some(captures) = DETERMINATE_PROGRESS_REGEX.captures(line) {
+            return Some(GitProgress {
+                operation: operation.to_string(),
+                phase: captures[1].to_string(),
+                current: captures[2].parse().ok()?,
+                total: Some(captures[3].parse().ok()?),
+                throughput: captures.get(4).map(|m| m.as_str().to_string()),
+            });
+        }
+
+        let captures = COUNT_PROGRESS_REGEX.captures(line)?;
+        Some(GitProgress {
+            operation: operation.to_string(),
+            phase: captures[1].to_string(),
+            current: captures[2].parse().ok()?,
+            total: None,
+            throughput: None,
+        })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.total.is_some_and(|total| self.current >= total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_git_progress_with_throughput() {
+        let progress = GitProgress::parse(
+            "fetch",
+            "Receiving objects:  43% (430/1000), 1.20 MiB | 3.40 MiB/s",
+        )
+        .unwrap();
+        assert_eq!(progress.operation, "fetch");
+        assert_eq!(progress.phase, "Receiving objects");
+        assert_eq!(progress.current, 430);
+        assert_eq!(progress.total, Some(1000));
+        assert_eq!(progress.throughput.as_deref(), Some("3.40 MiB/s"));
+        assert!(!progress.is_complete());
+    }
+
+    #[test]
+    fn parses_finished_and_remote_progress() {
+        let progress = GitProgress::parse(
+            "pull",
+            "Receiving objects: 100% (1000/1000), 2.50 MiB | 3.40 MiB/s, done.",
+        )
+        .unwrap();
+        assert_eq!(progress.throughput.as_deref(), Some("3.40 MiB/s"));
+        assert!(progress.is_complete());
+
+        let progress =
+            GitProgress::parse("pull", "remote: Compressing objects:  12% (3/25)").unwrap();
+        assert_eq!(progress.phase, "Compressing objects");
+        assert_eq!(progress.total, Some(25));
+        assert_eq!(progress.throughput, None);
+
+        let progress = GitProgress::parse("pull", "Resolving deltas: 100% (5/5), done.").unwrap();
+        assert!(progress.is_complete());
+    }
+
+    #[test]
+    fn parses_lfs_progress() {
+        let progress = GitProgress::parse(
+            "lfs",
+            "Downloading LFS objects:  50% (5/10), 1.2 GB | 20 MB/s",
+        )
+        .unwrap();
+        assert_eq!(progress.phase, "Downloading LFS objects");
+        assert_eq!(progress.current, 5);
+        assert_eq!(progress.total, Some(10));
+        assert_eq!(progress.throughput.as_deref(), Some("20 MB/s"));
+    }
+
+    #[test]
+    fn parses_counting_progress_without_total() {
+        let progress =
+            GitProgress::parse("clone", "remote: Enumerating objects: 1234, done.").unwrap();
+        assert_eq!(progress.phase, "Enumerating objects");
+        assert_eq!(progress.current, 1234);
+        assert_eq!(progress.total, None);
+    }
+
+    #[test]
+    fn ignores_other_output() {
+        assert_eq!(GitProgress::parse("pull", "From github.com:org/repo"), None);
+        assert_eq!(
+            GitProgress::parse("pull", "error: cannot lock ref: 3 refs"),
+            None
+        );
+        assert_eq!(
+            GitProgress::parse("pull", "Successfully rebased and updated refs/heads/main."),
+            None
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use crate::clients::git;
 use crate::clients::git::Opts;
+use crate::clients::git_read::LogEntry;
 use crate::types::commits::Commit;
 use crate::types::config::RepoConfig;
 use crate::types::errors::CoreError;
@@ -10,7 +11,6 @@ use crate::types::repo::{LockRequest, RepoStatusRef};
 use crate::worker::Task;
 use anyhow::bail;
 use async_trait::async_trait;
-use chrono::DateTime;
 use reqwest::StatusCode;
 use std::env;
 use std::io::Write;
@@ -49,6 +49,19 @@ impl Task for CommitOp {
 
 pub type LogResponse = Vec<Commit>;
 
+fn commit_from_log_entry(entry: LogEntry) -> Commit {
+    Commit {
+        sha: entry.sha.chars().take(8).collect(),
+        message: Some(entry.message),
+        author: Some(entry.author_name),
+        timestamp: entry
+            .author_date
+            .map(|ts| ts.with_timezone(&chrono::Local).to_string()),
+        status: None,
+        merge_timestamp: None,
+    }
+}
+
 #[derive(Clone)]
 pub struct LogOp {
     pub limit: usize,
@@ -87,49 +100,12 @@ impl LogOp {
             return Ok(vec![]);
         }
 
-        let output = self.git_client.log(self.limit, &git_ref).await?;
-        let result = output
-            .split("\x1e\n")
-            .filter_map(|line| {
-                let parts = line.split('\x1f').collect::<Vec<_>>();
-
-                // Ensure we have at least 4 parts
-                if parts.len() < 4 {
-                    warn!(
-                        "Invalid git log line format (expected at least 4 parts): {}",
-                        line
-                    );
-                    return None;
-                }
-
-                // Safely parse timestamp
-                let timestamp = match DateTime::parse_from_rfc3339(parts[3]) {
-                    Ok(ts) => Some(ts.with_timezone(&chrono::Local).to_string()),
-                    Err(e) => {
-                        warn!(
-                            "Failed to parse timestamp '{}' from git log: {}",
-                            parts[3], e
-                        );
-                        None
-                    }
-                };
-
-                // Safely extract SHA (ensure it has enough characters)
-                let sha = if parts[0].len() >= 8 {
-                    parts[0][..8].to_string()
-                } else {
-                    parts[0].to_string()
-                };
-
-                Some(Commit {
-                    sha,
-                    message: Some(parts[1].to_string()),
-                    author: Some(parts[2].to_string()),
-                    timestamp,
-                    status: None,
-                    merge_timestamp: None,
-                })
-            })
+        let result = self
+            .git_client
+            .log(self.limit, &git_ref)
+            .await?
+            .into_iter()
+            .map(commit_from_log_entry)
             .collect::<Vec<_>>();
 
         Ok(result)
@@ -333,38 +309,18 @@ impl BranchCompareOp {
             new_commit_shas.len(),
             commit_range
         );
-        let output = self.git_client.log(self.limit, &commit_range).await?;
-        let result = output
-            .split("\x1e\n")
-            .filter(|line| !line.trim().is_empty())
-            .filter_map(|line| {
-                let parts = line.split('\x1f').collect::<Vec<_>>();
-
-                if parts.len() >= 4 {
-                    let full_sha = parts[0];
-                    // Only include commits that are not cherrypicked
-                    if new_commit_shas
-                        .iter()
-                        .any(|&cherry_sha| full_sha.starts_with(cherry_sha))
-                    {
-                        let timestamp = DateTime::parse_from_rfc3339(parts[3])
-                            .unwrap_or_else(|_| chrono::Utc::now().into());
-
-                        Some(Commit {
-                            sha: parts[0][..8.min(parts[0].len())].to_string(),
-                            message: Some(parts[1].to_string()),
-                            author: Some(parts[2].to_string()),
-                            timestamp: Some(timestamp.with_timezone(&chrono::Local).to_string()),
-                            status: None,
-                            merge_timestamp: None,
-                        })
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
+        let result = self
+            .git_client
+            .log(self.limit, &commit_range)
+            .await?
+            .into_iter()
+            // Only include commits that are not cherrypicked
+            .filter(|entry| {
+                new_commit_shas
+                    .iter()
+                    .any(|&cherry_sha| entry.sha.starts_with(cherry_sha))
             })
+            .map(commit_from_log_entry)
             .collect::<Vec<_>>();
 
         Ok(result)
@@ -90,6 +90,16 @@ impl Display for ArtifactBuildConfig {
 }
 
 impl ArtifactBuildConfig {
+    pub fn all() -> Vec<Self> {
+        vec![
+            ArtifactBuildConfig::Debug,
+            ArtifactBuildConfig::DebugGame,
+            ArtifactBuildConfig::Development,
+            ArtifactBuildConfig::Shipping,
+            ArtifactBuildConfig::Test,
+        ]
+    }
+
     pub fn new(config: &str) -> Self {
         match config {
             "debug" => ArtifactBuildConfig::Debug,
@@ -130,6 +140,17 @@ impl Display for Platform {
 }
 
 impl Platform {
+    pub fn all() -> Vec<Self> {
+        vec![
+            Platform::Win64,
+            Platform::Mac,
+            Platform::Ios,
+            Platform::Android,
+            Platform::Linux,
+            Platform::LinuxArm64,
+        ]
+    }
+
     pub fn new(platform: &str) -> Self {
         match platform {
             "win64" => Platform::Win64,
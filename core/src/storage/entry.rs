@@ -7,7 +7,7 @@ use std::time::SystemTime;
 
 use crate::{storage::ArtifactStorage, types::errors::CoreError};
 
-use super::ArtifactConfig;
+use super::{ArtifactConfig, ArtifactManifest, StorageSchemaVersion};
 
 // This is the path inside the underlying storage, without the prefix or bucket
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +97,16 @@ impl ObjectLocation {
         )
     }
 
+    // The schema version is the first element of the key, examples:
+    //   v1: v1/testproj/client/win64/shipping/abcdef0123abcdef0123abcdef0123abcdef0123.json -> V1
+    //   v2: v2/testproj/client/win64/shipping/abcdef0123abcdef0123abcdef0123abcdef0123.json -> V2
+    pub fn schema_version(&self) -> Option<StorageSchemaVersion> {
+        self.0
+            .split('/')
+            .next()
+            .and_then(|version| version.parse().ok())
+    }
+
     // This assumes that the key contains a version string as the last element before the basename, examples:
     //   friendshipper/v1.2.3/friendshipper.exe
     //
@@ -138,6 +148,9 @@ pub struct ArtifactEntry {
     pub last_modified: SystemTime,
     // If possible, derived commit sha
     pub commit: Option<String>,
+    // Build metadata from the artifact's manifest, only available in the v2 schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ArtifactManifest>,
 }
 
 impl Default for ArtifactEntry {
@@ -147,6 +160,7 @@ impl Default for ArtifactEntry {
             display_name: String::new(),
             last_modified: SystemTime::now(),
             commit: None,
+            metadata: None,
         }
     }
 }
@@ -169,6 +183,7 @@ impl From<aws_sdk_s3::types::Object> for ArtifactEntry {
             display_name,
             last_modified,
             commit,
+            metadata: None,
         }
     }
 }
@@ -184,6 +199,7 @@ impl ArtifactEntry {
             display_name,
             last_modified: SystemTime::now(),
             commit,
+            metadata: None,
         }
     }
 
@@ -196,15 +212,28 @@ impl ArtifactEntry {
         self.key.base_name()
     }
 
-    // Convert this entry to a path from another config
+    // Attach a parsed manifest, preferring its commit over the one derived from the key
+    pub fn with_metadata(mut self, metadata: ArtifactManifest) -> Self {
+        if !metadata.commit.is_empty() {
+            self.commit = Some(metadata.commit.clone());
+        }
+        self.metadata = Some(metadata);
+        self
+    }
+
+    // Convert this entry to a path from another config. The entry's own schema is used
+    // when it can be derived from the key, since v1 and v2 entries are listed side by side
+    // while a project migrates.
     pub fn convert_to_config(
         &self,
         config: &ArtifactConfig,
         storage: &ArtifactStorage,
     ) -> Result<Self, CoreError> {
         let commit = self.commit.clone();
-        let path = storage.resolve_path(config);
-        // This only works for V1 schema
+        let path = match self.key.schema_version() {
+            Some(schema_version) => ArtifactStorage::resolve_path_for(&schema_version, config),
+            None => storage.resolve_path(config),
+        };
         Ok(Self::new(format!(
             "{}{}.json",
             path,
@@ -217,7 +246,7 @@ mod tests {
     #[allow(unused_imports)]
     use crate::storage::{
         mock::MockArtifactProvider, ArtifactBuildConfig, ArtifactConfig, ArtifactKind,
-        ArtifactStorage, Platform,
+        ArtifactManifest, ArtifactStorage, Platform, StorageSchemaVersion,
     };
     #[allow(unused_imports)]
     use std::{collections::HashMap, sync::Arc};
@@ -234,6 +263,7 @@ mod tests {
             ("game_full_commit".into(), "testproj/game/win64/shipping/game-win64-abcdef01abcdef01abcdef01abcdef01abcdef01.json".into()),
             ("game_path".into(), "testproj/client/win64/shipping/".into()),
             ("game_v1".into(), "v1/believerco-testproj/client/win64/shipping/abcdef01abcdef01abcdef01abcdef01abcdef01.json".into()),
+            ("game_v2".into(), "v2/believerco-testproj/client/win64/shipping/abcdef01abcdef01abcdef01abcdef01abcdef01.json".into()),
             ("invalid_short_path".into(), "path/without-enough-elements".into()),
         ]);
     }
@@ -380,5 +410,46 @@ mod tests {
             converted.key.0,
             "v1/believerco-testproj/editor-symbols/win64/development/abcdef01abcdef01abcdef01abcdef01abcdef01.json"
         );
+
+        // v1 entries keep their v1 paths even when the storage is on v2
+        let storage = ArtifactStorage::new(Arc::new(mp), "v2".parse().unwrap());
+        let converted = entry.convert_to_config(&config, &storage).unwrap();
+        assert_eq!(
+            converted.key.0,
+            "v1/believerco-testproj/editor-symbols/win64/development/abcdef01abcdef01abcdef01abcdef01abcdef01.json"
+        );
+        let entry = get_entry("game_v2");
+        let converted = entry.convert_to_config(&config, &storage).unwrap();
+        assert_eq!(
+            converted.key.0,
+            "v2/believerco-testproj/editor-symbols/win64/development/abcdef01abcdef01abcdef01abcdef01abcdef01.json"
+        );
+    }
+
+    #[test]
+    fn schema_version() {
+        assert_eq!(
+            get_objectlocation("game_v1").schema_version(),
+            Some(StorageSchemaVersion::V1)
+        );
+        assert_eq!(
+            get_objectlocation("game_v2").schema_version(),
+            Some(StorageSchemaVersion::V2)
+        );
+        assert_eq!(get_objectlocation("game_path").schema_version(), None);
+    }
+
+    #[test]
+    fn with_metadata() {
+        let entry = get_entry("game_v2").with_metadata(ArtifactManifest {
+            commit: "1234567812345678123456781234567812345678".to_string(),
+            branch: Some("main".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            entry.commit,
+            Some("1234567812345678123456781234567812345678".to_string())
+        );
+        assert_eq!(entry.metadata.unwrap().branch, Some("main".to_string()));
     }
 }
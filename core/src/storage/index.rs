@@ -0,0 +1,530 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::anyhow;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, TimestampSeconds};
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+use crate::storage::config::{ArtifactConfig, Project};
+use crate::storage::entry::ArtifactEntry;
+use crate::storage::list::{ArtifactList, MethodPrefix};
+use crate::storage::{ArtifactStorage, StorageSchemaVersion};
+use crate::types::errors::CoreError;
+
+// Bump when the on-disk format changes, older indexes are discarded and rebuilt
+const INDEX_FORMAT_VERSION: u32 = 2;
+
+const INDEX_EXTENSION: &str = "msgpack";
+
+// How long an index is served without kicking off a background refresh
+pub const DEFAULT_INDEX_MAX_AGE: Duration = Duration::from_secs(60);
+
+// Refreshes in between only pick up artifacts newer than the index. A full listing every so
+// often is what drops artifacts deleted upstream.
+const FULL_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+// Persisted listing for a single ArtifactConfig
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactIndex {
+    pub version: u32,
+    pub method_prefix: MethodPrefix,
+    pub entries: Vec<ArtifactEntry>,
+    #[serde_as(as = "TimestampSeconds<f64>")]
+    pub refreshed_at: SystemTime,
+    #[serde_as(as = "TimestampSeconds<f64>")]
+    pub full_refreshed_at: SystemTime,
+}
+
+impl ArtifactIndex {
+    fn new(method_prefix: MethodPrefix) -> Self {
+        Self {
+            version: INDEX_FORMAT_VERSION,
+            method_prefix,
+            entries: vec![],
+            refreshed_at: SystemTime::UNIX_EPOCH,
+            full_refreshed_at: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    pub fn newest_last_modified(&self) -> Option<SystemTime> {
+        self.entries.iter().map(|e| e.last_modified).max()
+    }
+
+    pub fn is_fresh(&self, max_age: Duration, now: SystemTime) -> bool {
+        now.duration_since(self.refreshed_at)
+            .is_ok_and(|age| age < max_age)
+    }
+
+    fn needs_full_refresh(&self, now: SystemTime) -> bool {
+        !now.duration_since(self.full_refreshed_at)
+            .is_ok_and(|age| age < FULL_REFRESH_INTERVAL)
+    }
+
+    // Merges a listing into the index. Entries we already have that haven't been re-uploaded
+    // keep their cached metadata, anything newer is taken from the listing. A complete listing
+    // also drops keys missing from it, since they were deleted upstream.
+    fn merge(
+        &mut self,
+        method_prefix: MethodPrefix,
+        listed: Vec<ArtifactEntry>,
+        complete: bool,
+        now: SystemTime,
+    ) {
+        let newest = self.newest_last_modified();
+        let mut cached: HashMap<String, ArtifactEntry> = self
+            .entries
+            .drain(..)
+            .map(|e| (e.key.0.clone(), e))
+            .collect();
+
+        let mut added = 0;
+        self.entries = listed
+            .into_iter()
+            .map(|entry| match cached.remove(&entry.key.0) {
+                Some(cached_entry) if entry.last_modified <= cached_entry.last_modified => {
+                    cached_entry
+                }
+                _ => {
+                    added += 1;
+                    entry
+                }
+            })
+            .collect();
+
+        let removed = match complete {
+            true => cached.len(),
+            false => {
+                self.entries.extend(cached.into_values());
+                0
+            }
+        };
+        self.entries
+            .sort_by_key(|e| std::cmp::Reverse(e.last_modified));
+
+        debug!(
+            "Merged artifact index: {} new or updated since {:?}, {} removed",
+            added, newest, removed
+        );
+        self.method_prefix = method_prefix;
+        self.refreshed_at = now;
+        if complete {
+            self.full_refreshed_at = now;
+        }
+    }
+
+    // During a v2 migration a commit can land on v2 after its v1 artifact was indexed. The v2
+    // artifact replaces it, same as in a full listing.
+    fn drop_superseded_v1(&mut self) {
+        let v2_commits: HashSet<String> = self
+            .entries
+            .iter()
+            .filter(|e| e.key.schema_version() == Some(StorageSchemaVersion::V2))
+            .filter_map(|e| e.commit.clone())
+            .collect();
+        self.entries.retain(|e| {
+            e.key.schema_version() != Some(StorageSchemaVersion::V1)
+                || e.commit.as_ref().is_none_or(|c| !v2_commits.contains(c))
+        });
+    }
+}
+
+// On-disk cache of artifact listings, one msgpack file per schema, project, provider and artifact
+// config, where the provider is a short hash of its method prefix, ex. s3://bucket/:
+//   <root>/v1/<project>/<provider>/<kind>-<platform>-<config>.msgpack
+#[derive(Debug, Clone)]
+pub struct ArtifactIndexCache {
+    root: PathBuf,
+    max_age: Duration,
+
+    // Indexes with a background refresh in flight, so a busy builds page doesn't stack them up
+    refreshing: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+// Clears an index's refreshing flag however the refresh ends, including by panicking
+struct RefreshGuard {
+    refreshing: Arc<Mutex<HashSet<PathBuf>>>,
+    path: PathBuf,
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        self.refreshing.lock().remove(&self.path);
+    }
+}
+
+impl ArtifactIndexCache {
+    pub fn new(root: impl Into<PathBuf>, max_age: Duration) -> Self {
+        Self {
+            root: root.into(),
+            max_age,
+            refreshing: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    fn project_dir(&self, schema_version: &StorageSchemaVersion, project: &Project) -> PathBuf {
+        let schema = match schema_version {
+            StorageSchemaVersion::V1 => "v1",
+            StorageSchemaVersion::V2 => "v2",
+        };
+        self.root.join(schema).join(&project.0)
+    }
+
+    fn index_path(
+        &self,
+        schema_version: &StorageSchemaVersion,
+        method_prefix: &MethodPrefix,
+        artifact_config: &ArtifactConfig,
+    ) -> PathBuf {
+        let provider = hex::encode(&Sha256::digest(method_prefix.0.as_bytes())[..8]);
+        self.project_dir(schema_version, &artifact_config.project)
+            .join(provider)
+            .join(format!(
+                "{}-{}-{}.{INDEX_EXTENSION}",
+                artifact_config.artifact_kind.to_path_string(),
+                artifact_config.platform.to_path_string(),
+                artifact_config.artifact_build_config.to_path_string(),
+            ))
+    }
+
+    // Marks the index as refreshing, or returns None if a refresh is already in flight
+    fn start_refresh(&self, path: &Path) -> Option<RefreshGuard> {
+        self.refreshing
+            .lock()
+            .insert(path.to_path_buf())
+            .then(|| RefreshGuard {
+                refreshing: self.refreshing.clone(),
+                path: path.to_path_buf(),
+            })
+    }
+
+    // A missing, unreadable or outdated index is treated as a cache miss
+    async fn load(path: &Path) -> Option<ArtifactIndex> {
+        let bytes = tokio::fs::read(path).await.ok()?;
+        match rmp_serde::from_slice::<ArtifactIndex>(&bytes) {
+            Ok(index) if index.version == INDEX_FORMAT_VERSION => Some(index),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("Discarding unreadable artifact index {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    // Written to a temp file and renamed into place so readers never see a partial index
+    async fn save(path: &Path, index: &ArtifactIndex) -> Result<(), CoreError> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let bytes = rmp_serde::to_vec_named(index).map_err(|e| {
+            CoreError::Internal(anyhow!("Failed to serialize artifact index: {}", e))
+        })?;
+        let tmp_path = path.with_extension(format!("{INDEX_EXTENSION}.tmp"));
+        tokio::fs::write(&tmp_path, bytes).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+
+    // Removes the cached indexes for a project, or every index when no project is given
+    pub async fn invalidate(&self, project: Option<&Project>) -> Result<(), CoreError> {
+        let dirs = match project {
+            Some(project) => vec![
+                self.project_dir(&StorageSchemaVersion::V1, project),
+                self.project_dir(&StorageSchemaVersion::V2, project),
+            ],
+            None => vec![self.root.clone()],
+        };
+
+        for dir in dirs {
+            match tokio::fs::remove_dir_all(&dir).await {
+                Ok(_) => info!("Invalidated artifact index at {:?}", dir),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ArtifactStorage {
+    // Serves the listing from the on-disk index when there is one, with stale-while-revalidate
+    // semantics: a fresh index is returned as-is, a stale one is returned immediately while a
+    // background refresh updates it. Without an index cache configured this is artifact_list.
+    pub async fn artifact_list_cached(&self, artifact_config: ArtifactConfig) -> ArtifactList {
+        let Some(cache) = self.index.clone() else {
+            return self.artifact_list(artifact_config).await;
+        };
+
+        let path = self.index_path(&cache, &artifact_config);
+        let index = match ArtifactIndexCache::load(&path).await {
+            Some(index) => {
+                if !index.is_fresh(cache.max_age, SystemTime::now()) {
+                    self.spawn_index_refresh(&cache, artifact_config.clone(), path);
+                }
+                index
+            }
+            None => match self.refresh_index(&cache, &artifact_config, None).await {
+                Ok(index) => index,
+                Err(e) => {
+                    warn!(
+                        "Failed to build artifact index for {:?}, listing directly. {}",
+                        artifact_config, e
+                    );
+                    return self.artifact_list(artifact_config).await;
+                }
+            },
+        };
+
+        let mut list = ArtifactList::new(artifact_config, index.method_prefix);
+        list.entries = index.entries;
+        list
+    }
+
+    fn index_path(&self, cache: &ArtifactIndexCache, artifact_config: &ArtifactConfig) -> PathBuf {
+        cache.index_path(
+            &self.schema_version,
+            &self.provider.get_method_prefix(),
+            artifact_config,
+        )
+    }
+
+    fn spawn_index_refresh(
+        &self,
+        cache: &ArtifactIndexCache,
+        config: ArtifactConfig,
+        path: PathBuf,
+    ) {
+        let Some(guard) = cache.start_refresh(&path) else {
+            return;
+        };
+
+        let storage = self.clone();
+        let cache = cache.clone();
+        tokio::spawn(async move {
+            let _guard = guard;
+            let existing = ArtifactIndexCache::load(&path).await;
+            // Offline or mid credential refresh, the stale index keeps being served
+            if let Err(e) = storage.refresh_index(&cache, &config, existing).await {
+                debug!("Background artifact index refresh failed: {}", e);
+            }
+        });
+    }
+
+    // Only artifacts at or after the newest one already indexed are listed, with a full listing
+    // every FULL_REFRESH_INTERVAL to catch deletions
+    async fn refresh_index(
+        &self,
+        cache: &ArtifactIndexCache,
+        artifact_config: &ArtifactConfig,
+        existing: Option<ArtifactIndex>,
+    ) -> Result<ArtifactIndex, CoreError> {
+        let now = SystemTime::now();
+        let mut index =
+            existing.unwrap_or_else(|| ArtifactIndex::new(self.provider.get_method_prefix()));
+
+        let since = match index.needs_full_refresh(now) {
+            true => None,
+            false => index.newest_last_modified(),
+        };
+        let known: HashMap<String, ArtifactEntry> = index
+            .entries
+            .iter()
+            .map(|e| (e.key.0.clone(), e.clone()))
+            .collect();
+        let (method_prefix, entries) = self.list_entries(artifact_config, &known, since).await?;
+        index.merge(method_prefix, entries, since.is_none(), now);
+        if self.schema_version == StorageSchemaVersion::V2 {
+            index.drop_superseded_v1();
+        }
+
+        ArtifactIndexCache::save(&self.index_path(cache, artifact_config), &index).await?;
+        Ok(index)
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use std::sync::Arc;
+
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::storage::mock::MockArtifactProvider;
+    #[allow(unused_imports)]
+    use crate::storage::{
+        ArtifactBuildConfig, ArtifactKind, ArtifactManifest, ArtifactProvider, Platform,
+    };
+
+    #[allow(dead_code)]
+    fn client_config() -> ArtifactConfig {
+        ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        )
+    }
+
+    #[allow(dead_code)]
+    fn entry(key: &str, last_modified: SystemTime) -> ArtifactEntry {
+        let mut entry = ArtifactEntry::new(key.to_string());
+        entry.last_modified = last_modified;
+        entry
+    }
+
+    #[test]
+    fn test_merge_keeps_known_entries() {
+        let now = SystemTime::now();
+        let old = now - Duration::from_secs(60 * 60);
+
+        let mut cached = entry("v2/proj/client/win64/development/a.json", old);
+        cached.metadata = Some(ArtifactManifest {
+            pusher: Some("cached".to_string()),
+            ..Default::default()
+        });
+        let mut index = ArtifactIndex::new("test:///".into());
+        index.entries = vec![
+            cached,
+            entry("v2/proj/client/win64/development/b.json", old),
+        ];
+        index.refreshed_at = old;
+
+        // b was deleted upstream and c is new
+        index.merge(
+            "test:///".into(),
+            vec![
+                entry("v2/proj/client/win64/development/a.json", old),
+                entry("v2/proj/client/win64/development/c.json", now),
+            ],
+            true,
+            now,
+        );
+
+        assert_eq!(index.entries.len(), 2);
+        assert!(index.entries[0].key.0.ends_with("c.json"));
+        assert_eq!(
+            index.entries[1].metadata.as_ref().unwrap().pusher,
+            Some("cached".to_string())
+        );
+        assert!(index.is_fresh(Duration::from_secs(60), now));
+        assert!(!index.needs_full_refresh(now));
+    }
+
+    #[test]
+    fn test_incremental_merge_keeps_unlisted_entries() {
+        let now = SystemTime::now();
+        let old = now - Duration::from_secs(60 * 60);
+
+        let mut index = ArtifactIndex::new("test:///".into());
+        index.entries = vec![
+            entry("v1/proj/client/win64/development/a.json", old),
+            entry("v1/proj/client/win64/development/b.json", old),
+        ];
+        assert!(index.needs_full_refresh(now));
+
+        // only entries newer than the index were listed, so a and b must survive
+        index.merge(
+            "test:///".into(),
+            vec![entry("v1/proj/client/win64/development/c.json", now)],
+            false,
+            now,
+        );
+
+        assert_eq!(index.entries.len(), 3);
+        assert!(index.entries[0].key.0.ends_with("c.json"));
+        assert!(index.needs_full_refresh(now));
+    }
+
+    #[test]
+    fn test_drop_superseded_v1() {
+        let now = SystemTime::now();
+        let commit = "3deadbeef90deadbeef90deadbeef90deadbeef9";
+
+        let mut index = ArtifactIndex::new("test:///".into());
+        index.entries =
+            vec![
+            entry(&format!("v2/proj/client/win64/development/{commit}.json"), now),
+            entry(&format!("v1/proj/client/win64/development/{commit}.json"), now),
+            entry(
+                "v1/proj/client/win64/development/0deadbeef90deadbeef90deadbeef90deadbeef9.json",
+                now,
+            ),
+        ];
+        index.drop_superseded_v1();
+
+        assert_eq!(index.entries.len(), 2);
+        assert!(!index
+            .entries
+            .iter()
+            .any(|e| e.key.0 == format!("v1/proj/client/win64/development/{commit}.json")));
+    }
+
+    #[test]
+    fn test_index_path_is_keyed_by_provider() {
+        let cache = ArtifactIndexCache::new("/tmp/index", DEFAULT_INDEX_MAX_AGE);
+        let s3 = cache.index_path(
+            &StorageSchemaVersion::V2,
+            &"s3://bucket/".into(),
+            &client_config(),
+        );
+        let local = cache.index_path(
+            &StorageSchemaVersion::V2,
+            &"file:///mnt/artifacts/".into(),
+            &client_config(),
+        );
+        assert_ne!(s3, local);
+        assert_eq!(s3.file_name(), local.file_name());
+    }
+
+    #[test]
+    fn test_refresh_guard_clears_on_drop() {
+        let cache = ArtifactIndexCache::new("/tmp/index", DEFAULT_INDEX_MAX_AGE);
+        let path = PathBuf::from("/tmp/index/v1/proj/index.msgpack");
+
+        let guard = cache.start_refresh(&path).unwrap();
+        assert!(cache.start_refresh(&path).is_none());
+        drop(guard);
+        assert!(cache.start_refresh(&path).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cached_listing_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArtifactIndexCache::new(dir.path(), DEFAULT_INDEX_MAX_AGE);
+        let storage = ArtifactStorage::new(
+            Arc::new(MockArtifactProvider::new()),
+            StorageSchemaVersion::V2,
+        )
+        .with_index_cache(cache.clone());
+
+        let al = storage.artifact_list_cached(client_config()).await;
+        assert_eq!(al.entries.len(), 5);
+
+        let path = cache.index_path(
+            &StorageSchemaVersion::V2,
+            &MockArtifactProvider::new().get_method_prefix(),
+            &client_config(),
+        );
+        let index = ArtifactIndexCache::load(&path).await.unwrap();
+        assert_eq!(index.entries.len(), 5);
+        // manifests survive the msgpack round trip
+        assert!(index.entries.iter().any(|e| e
+            .metadata
+            .as_ref()
+            .is_some_and(|m| m.pusher == Some("test_user".to_string()))));
+
+        cache
+            .invalidate(Some(&"believerco-gameprototypemp".into()))
+            .await
+            .unwrap();
+        assert!(ArtifactIndexCache::load(&path).await.is_none());
+    }
+}
@@ -14,6 +14,11 @@ impl MethodPrefix {
     pub fn get_storage_url(&self, artifact_entry: &ArtifactEntry) -> String {
         format!("{}{}", self.0, artifact_entry.key)
     }
+
+    // True when the artifacts live on a local disk or file share rather than in a bucket
+    pub fn is_local(&self) -> bool {
+        self.0.starts_with("file://")
+    }
 }
 
 impl Display for MethodPrefix {
@@ -46,6 +51,10 @@ pub struct ArtifactList {
 
     // URL method and prefix, ex. s3://$bucketname/
     pub method_prefix: MethodPrefix,
+
+    // Set on paginated listings when there are more entries to fetch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl ArtifactList {
@@ -54,6 +63,7 @@ impl ArtifactList {
             entries: Vec::new(),
             artifact_config,
             method_prefix,
+            next_cursor: None,
         }
     }
 
@@ -0,0 +1,396 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use crate::storage::{entry::ArtifactEntry, list::MethodPrefix, ArtifactProvider};
+use crate::types::errors::CoreError;
+
+// Local filesystem storage provider, for file shares or NAS mirrors of the artifact bucket.
+// The directory tree under `root` is expected to follow the same layout as the bucket, ex:
+//   <root>/v1/<project>/<kind>/<platform>/<config>/<sha>.json
+#[derive(Debug, Clone)]
+pub struct LocalFsArtifactProvider {
+    root: PathBuf,
+}
+
+impl LocalFsArtifactProvider {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    // Lists the files (not directories) directly under the given key prefix, mirroring an S3
+    // listing with a "/" delimiter. A missing directory is an empty listing, not an error.
+    async fn list_files(&self, dir_key: &str) -> Result<Vec<(String, SystemTime)>, CoreError> {
+        let dir = self.root.join(dir_key);
+        let mut files = Vec::new();
+
+        let mut read_dir = match tokio::fs::read_dir(&dir).await {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+            Err(e) => {
+                return Err(CoreError::Internal(anyhow!(
+                    "Error reading artifact directory {:?}: {}",
+                    dir,
+                    e
+                )))
+            }
+        };
+
+        while let Some(entry) = read_dir.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if !metadata.is_file() {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().to_string();
+            let last_modified = metadata.modified().unwrap_or(SystemTime::now());
+            files.push((name, last_modified));
+        }
+
+        Ok(files)
+    }
+}
+
+// Splits a key prefix into the directory part (including the trailing slash) and the
+// partial filename, ex: v1/proj/client/win64/development/abc -> (v1/proj/client/win64/development/, abc)
+fn split_prefix(prefix: &str) -> (&str, &str) {
+    match prefix.rfind('/') {
+        Some(idx) => prefix.split_at(idx + 1),
+        None => ("", prefix),
+    }
+}
+
+#[async_trait]
+impl ArtifactProvider for LocalFsArtifactProvider {
+    // Longtail takes file:// URLs, so we normalize Windows paths to forward slashes and make
+    // sure drive letters end up as file:///C:/...
+    fn get_method_prefix(&self) -> MethodPrefix {
+        let mut root = self.root.to_string_lossy().replace('\\', "/");
+        if !root.starts_with('/') {
+            root.insert(0, '/');
+        }
+        if !root.ends_with('/') {
+            root.push('/');
+        }
+        format!("file://{root}").into()
+    }
+
+    async fn get_artifact_by_prefix(&self, prefix: &str) -> Result<String, CoreError> {
+        let (dir_key, name_prefix) = split_prefix(prefix);
+
+        let matches: Vec<String> = self
+            .list_files(dir_key)
+            .await?
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(name_prefix))
+            .map(|(name, _)| name)
+            .collect();
+
+        match matches.len() {
+            0 => Err(CoreError::Internal(anyhow!(
+                "No object found in {:?} with prefix {}",
+                self.root,
+                prefix
+            ))),
+            1 => Ok(format!(
+                "{}{}{}",
+                self.get_method_prefix(),
+                dir_key,
+                matches[0]
+            )),
+            c => Err(CoreError::Internal(anyhow!(
+                "Multiple object found! Prefix {prefix} returned {c} objects, expected 1",
+            ))),
+        }
+    }
+
+    async fn get_artifact_list(
+        &self,
+        path: &str,
+    ) -> Result<(MethodPrefix, Vec<ArtifactEntry>), CoreError> {
+        let mut entry_list: Vec<ArtifactEntry> = self
+            .list_files(path)
+            .await?
+            .into_iter()
+            .map(|(name, last_modified)| {
+                let mut entry = ArtifactEntry::new(format!("{path}{name}"));
+                entry.last_modified = last_modified;
+                entry
+            })
+            .collect();
+
+        entry_list.sort_by_key(|e| std::cmp::Reverse(e.last_modified));
+        Ok((self.get_method_prefix(), entry_list))
+    }
+
+    async fn read_object(&self, key: &str) -> Result<Vec<u8>, CoreError> {
+        let path = self.root.join(key);
+        tokio::fs::read(&path)
+            .await
+            .map_err(|e| CoreError::Internal(anyhow!("Failed to read object {:?}: {}", path, e)))
+    }
+
+    async fn delete_objects(&self, keys: &[String]) -> Result<usize, CoreError> {
+        let mut deleted = 0;
+        for key in keys {
+            let path = self.root.join(key);
+            match tokio::fs::remove_file(&path).await {
+                Ok(_) => deleted += 1,
+                // already gone, nothing to do
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(CoreError::Internal(anyhow!(
+                        "Failed to delete object {:?}: {}",
+                        path,
+                        e
+                    )))
+                }
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use std::time::{Duration, SystemTime};
+
+    #[allow(unused_imports)]
+    use crate::storage::local::LocalFsArtifactProvider;
+    #[allow(unused_imports)]
+    use crate::storage::*;
+
+    #[allow(dead_code)]
+    const CLIENT_DIR: &str = "v1/believerco-gameprototypemp/client/win64/development";
+
+    #[allow(dead_code)]
+    fn write_artifact(root: &std::path::Path, name: &str, age_secs: u64) {
+        let dir = root.join(CLIENT_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, "{}").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_artifact_list() {
+        let dir = tempfile::tempdir().unwrap();
+        write_artifact(
+            dir.path(),
+            "0deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            30,
+        );
+        write_artifact(
+            dir.path(),
+            "1deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            20,
+        );
+        write_artifact(
+            dir.path(),
+            "2deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            10,
+        );
+        // nested directories are not part of the listing
+        std::fs::create_dir_all(dir.path().join(CLIENT_DIR).join("nested")).unwrap();
+
+        let provider = LocalFsArtifactProvider::new(dir.path());
+        let storage = ArtifactStorage::new(Arc::new(provider), StorageSchemaVersion::V1);
+        let ac = ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+
+        let al = storage.artifact_list(ac.clone()).await;
+        assert_eq!(al.entries.len(), 3);
+        assert_eq!(
+            al.entries[0].key.0,
+            "v1/believerco-gameprototypemp/client/win64/development/2deadbeef90deadbeef90deadbeef90deadbeef9.json"
+        );
+        assert_eq!(
+            al.entries[0].commit,
+            Some("2deadbeef90deadbeef90deadbeef90deadbeef9".to_string())
+        );
+        assert!(al.method_prefix.0.starts_with("file:///"));
+        assert!(al.method_prefix.0.ends_with('/'));
+
+        // a config with no directory on disk is just empty
+        let engine = ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Engine,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+        assert!(storage.artifact_list(engine).await.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_artifact_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        write_artifact(
+            dir.path(),
+            "0deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            20,
+        );
+        write_artifact(
+            dir.path(),
+            "0deadbeef91deadbeef90deadbeef90deadbeef9.json",
+            10,
+        );
+        write_artifact(
+            dir.path(),
+            "1deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            10,
+        );
+
+        let provider = LocalFsArtifactProvider::new(dir.path());
+
+        let url = provider
+            .get_artifact_by_prefix(&format!("{CLIENT_DIR}/1dead"))
+            .await
+            .unwrap();
+        assert_eq!(
+            url,
+            format!(
+                "{}{CLIENT_DIR}/1deadbeef90deadbeef90deadbeef90deadbeef9.json",
+                provider.get_method_prefix()
+            )
+        );
+
+        // ambiguous prefix
+        assert!(provider
+            .get_artifact_by_prefix(&format!("{CLIENT_DIR}/0deadbeef9"))
+            .await
+            .is_err());
+
+        // no match
+        assert!(provider
+            .get_artifact_by_prefix(&format!("{CLIENT_DIR}/ffff"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_artifact_list_v2() {
+        let dir = tempfile::tempdir().unwrap();
+        write_artifact(
+            dir.path(),
+            "0deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            30,
+        );
+
+        let v2_dir = dir
+            .path()
+            .join("v2/believerco-gameprototypemp/client/win64/development");
+        std::fs::create_dir_all(v2_dir.join("manifests")).unwrap();
+        std::fs::write(
+            v2_dir.join("1deadbeef90deadbeef90deadbeef90deadbeef9.json"),
+            "{}",
+        )
+        .unwrap();
+        std::fs::write(
+            v2_dir.join("manifests/1deadbeef90deadbeef90deadbeef90deadbeef9.json"),
+            r#"{"commit": "1deadbeef90deadbeef90deadbeef90deadbeef9", "branch": "main", "pusher": "artist"}"#,
+        )
+        .unwrap();
+
+        let provider = LocalFsArtifactProvider::new(dir.path());
+        let storage = ArtifactStorage::new(Arc::new(provider), StorageSchemaVersion::V2);
+        let ac = ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+
+        let al = storage.artifact_list(ac).await;
+        assert_eq!(al.entries.len(), 2);
+        assert_eq!(
+            al.entries[0].key.0,
+            "v2/believerco-gameprototypemp/client/win64/development/1deadbeef90deadbeef90deadbeef90deadbeef9.json"
+        );
+        let metadata = al.entries[0].metadata.clone().unwrap();
+        assert_eq!(metadata.pusher, Some("artist".to_string()));
+        assert!(al.entries[1].key.0.starts_with("v1/"));
+        assert!(al.entries[1].metadata.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_artifact_list_without_aws() {
+        let dir = tempfile::tempdir().unwrap();
+        write_artifact(
+            dir.path(),
+            "0deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            30,
+        );
+
+        let config: crate::types::config::DynamicConfig = serde_json::from_value(
+            serde_json::json!({"artifactProvider": {"type": "localFs", "rootPath": dir.path()}}),
+        )
+        .unwrap();
+        assert!(ArtifactProviderConfig::S3.create_provider(None).is_none());
+
+        let provider = config.artifact_provider.create_provider(None).unwrap();
+        let storage = ArtifactStorage::new(provider, config.storage_schema);
+        let ac = ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+
+        let al = storage.artifact_list(ac).await;
+        assert!(al.method_prefix.is_local());
+        assert_eq!(al.entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_objects() {
+        let dir = tempfile::tempdir().unwrap();
+        write_artifact(
+            dir.path(),
+            "0deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            30,
+        );
+        write_artifact(
+            dir.path(),
+            "1deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            20,
+        );
+
+        let provider = LocalFsArtifactProvider::new(dir.path());
+        let deleted = provider
+            .delete_objects(&[
+                format!("{CLIENT_DIR}/0deadbeef90deadbeef90deadbeef90deadbeef9.json"),
+                format!("{CLIENT_DIR}/manifests/0deadbeef90deadbeef90deadbeef90deadbeef9.json"),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+
+        let remaining = provider
+            .get_artifact_list(&format!("{CLIENT_DIR}/"))
+            .await
+            .unwrap()
+            .1;
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0]
+            .key
+            .0
+            .ends_with("1deadbeef90deadbeef90deadbeef90deadbeef9.json"));
+    }
+
+    #[test]
+    fn test_method_prefix() {
+        let provider = LocalFsArtifactProvider::new("/mnt/builds");
+        assert_eq!(provider.get_method_prefix().0, "file:///mnt/builds/");
+
+        let provider = LocalFsArtifactProvider::new("C:\\builds\\mirror\\");
+        assert_eq!(provider.get_method_prefix().0, "file:///C:/builds/mirror/");
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Directory, relative to an artifact listing path, holding the per-artifact manifests in the v2
+// schema. Keeping them in a sub-directory means a delimited listing of the artifact path only
+// returns the artifacts themselves, and short sha prefix lookups stay unambiguous.
+//   v2: v2/testproj/client/win64/shipping/abcdef0123abcdef0123abcdef0123abcdef0123.json
+//    -> v2/testproj/client/win64/shipping/manifests/abcdef0123abcdef0123abcdef0123abcdef0123.json
+pub const MANIFEST_DIR: &str = "manifests/";
+
+// Build metadata published alongside every artifact in the v2 schema
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactManifest {
+    // Full commit sha the artifact was built from
+    pub commit: String,
+
+    // Branch the commit was built from, ex. main or a feature branch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+
+    // User who pushed the commit that triggered the build
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pusher: Option<String>,
+
+    // When the build finished
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_timestamp: Option<DateTime<Utc>>,
+
+    // Total unpacked size of the artifact in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+
+    // Engine version the artifact was built against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine_version: Option<String>,
+
+    // Key of the symbols archive matching this artifact, if one was published
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbols: Option<String>,
+
+    // Free-form labels, ex. nightly, promoted, playtest
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl ArtifactManifest {
+    // Builds the manifest key for an artifact key
+    //   v2/testproj/client/win64/shipping/abc.json -> v2/testproj/client/win64/shipping/manifests/abc.json
+    pub fn key_for_artifact(artifact_key: &str) -> String {
+        match artifact_key.rfind('/') {
+            Some(idx) => {
+                let (dir, filename) = artifact_key.split_at(idx + 1);
+                format!("{dir}{MANIFEST_DIR}{filename}")
+            }
+            None => format!("{MANIFEST_DIR}{artifact_key}"),
+        }
+    }
+
+    pub fn is_trunk(&self, trunk_branch: &str) -> bool {
+        self.branch.as_deref() == Some(trunk_branch)
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_key_for_artifact() {
+        assert_eq!(
+            ArtifactManifest::key_for_artifact(
+                "v2/believerco-testproj/client/win64/development/abcdef01abcdef01abcdef01abcdef01abcdef01.json"
+            ),
+            "v2/believerco-testproj/client/win64/development/manifests/abcdef01abcdef01abcdef01abcdef01abcdef01.json"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_partial_manifest() {
+        let manifest: ArtifactManifest = serde_json::from_str(
+            r#"{
+                "commit": "abcdef01abcdef01abcdef01abcdef01abcdef01",
+                "branch": "main",
+                "buildTimestamp": "2024-05-01T12:00:00Z",
+                "tags": ["nightly"]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.commit, "abcdef01abcdef01abcdef01abcdef01abcdef01");
+        assert!(manifest.is_trunk("main"));
+        assert!(!manifest.is_trunk("content-main"));
+        assert_eq!(manifest.pusher, None);
+        assert_eq!(manifest.tags, vec!["nightly".to_string()]);
+    }
+}
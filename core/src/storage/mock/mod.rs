@@ -2,8 +2,9 @@ use anyhow::Result;
 use async_trait::async_trait;
 
 use crate::storage::anyhow;
-use crate::storage::entry::ArtifactEntry;
+use crate::storage::entry::{ArtifactEntry, ObjectLocation};
 use crate::storage::list::MethodPrefix;
+use crate::storage::manifest::{ArtifactManifest, MANIFEST_DIR};
 use crate::storage::ArtifactProvider;
 use crate::types::errors::CoreError;
 
@@ -109,6 +110,16 @@ impl ArtifactProvider for MockArtifactProvider {
                     path, "3deadbeef90deadbeef90deadbeef90deadbeef9.json"
                 )));
             }
+            "v2/believerco-gameprototypemp/client/win64/development/" => {
+                fake_entries.push(ArtifactEntry::new(format!(
+                    "{}{}",
+                    path, "3deadbeef90deadbeef90deadbeef90deadbeef9.json"
+                )));
+                fake_entries.push(ArtifactEntry::new(format!(
+                    "{}{}",
+                    path, "4deadbeef90deadbeef90deadbeef90deadbeef9.json"
+                )));
+            }
             _ => {
                 fake_entries.push(ArtifactEntry::new(format!("{}{}", path, "test/file/1")));
                 fake_entries.push(ArtifactEntry::new(format!("{}{}", path, "test/file/2")));
@@ -118,6 +129,29 @@ impl ArtifactProvider for MockArtifactProvider {
         }
         Ok((self.get_method_prefix(), fake_entries))
     }
+
+    // Serves a manifest for any key under a manifests/ directory, using the filename as the commit
+    async fn read_object(&self, key: &str) -> Result<Vec<u8>, CoreError> {
+        if !key.contains(MANIFEST_DIR) {
+            return Err(CoreError::Internal(anyhow!("No fake object for {}", key)));
+        }
+
+        let commit = ObjectLocation::from(key).commit(false).unwrap_or_default();
+        let manifest = ArtifactManifest {
+            commit,
+            branch: Some("main".to_string()),
+            pusher: Some("test_user".to_string()),
+            ..Default::default()
+        };
+        Ok(serde_json::to_vec(&manifest)?)
+    }
+
+    async fn delete_objects(&self, keys: &[String]) -> Result<usize, CoreError> {
+        match keys.iter().any(|key| key.contains("fail")) {
+            true => Err(CoreError::Internal(anyhow!("fail".to_string()))),
+            false => Ok(keys.len()),
+        }
+    }
 }
 
 mod tests {
@@ -147,4 +181,186 @@ mod tests {
             "v1/believerco-gameprototypemp/client/win64/development/0deadbeef90deadbeef90deadbeef90deadbeef9.json"
         );
     }
+
+    #[tokio::test]
+    async fn test_artifact_list_v2() {
+        let mp = MockArtifactProvider::new();
+
+        let ac = ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+
+        // v2 lists its own artifacts plus any v1 artifacts for commits not yet on v2
+        let schema = "v2".parse().unwrap();
+        let storage = ArtifactStorage::new(Arc::new(mp), schema);
+        let al = storage.artifact_list(ac.clone()).await;
+        assert_eq!(al.entries.len(), 5);
+
+        let v2_entries: Vec<_> = al
+            .entries
+            .iter()
+            .filter(|e| e.key.0.starts_with("v2/"))
+            .collect();
+        assert_eq!(v2_entries.len(), 2);
+        assert!(v2_entries
+            .iter()
+            .all(|e| e.metadata.as_ref().unwrap().branch == Some("main".to_string())));
+
+        // commit 3 is published under both schemas and should only be listed once, from v2
+        let commit_3: Vec<_> = al
+            .entries
+            .iter()
+            .filter(|e| e.commit == Some("3deadbeef90deadbeef90deadbeef90deadbeef9".to_string()))
+            .collect();
+        assert_eq!(commit_3.len(), 1);
+        assert!(commit_3[0].key.0.starts_with("v2/"));
+    }
+
+    #[tokio::test]
+    async fn test_artifact_page() {
+        let mp = MockArtifactProvider::new();
+        let storage = ArtifactStorage::new(Arc::new(mp), StorageSchemaVersion::V1);
+        let ac = ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+
+        let mut query = ArtifactListQuery {
+            limit: 3,
+            ..Default::default()
+        };
+        let page = storage.artifact_page(ac.clone(), &query).await.unwrap();
+        assert_eq!(page.entries.len(), 3);
+        assert!(page.next_cursor.is_some());
+
+        query.cursor = page.next_cursor;
+        let page = storage.artifact_page(ac.clone(), &query).await.unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.next_cursor, None);
+
+        // filtering keeps pulling pages until the listing runs out
+        let query = ArtifactListQuery {
+            limit: 3,
+            cursor: None,
+            filter: ArtifactFilter {
+                commits: Some(std::collections::HashSet::from([
+                    "2deadbeef90deadbeef90deadbeef90deadbeef9".to_string(),
+                ])),
+                ..Default::default()
+            },
+        };
+        let page = storage.artifact_page(ac, &query).await.unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn test_artifact_page_v2() {
+        let mp = MockArtifactProvider::new();
+        let storage = ArtifactStorage::new(Arc::new(mp), StorageSchemaVersion::V2);
+        let ac = ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+
+        // v1 artifacts for commits already on v2 stay hidden across page boundaries
+        let mut query = ArtifactListQuery {
+            limit: 2,
+            ..Default::default()
+        };
+        let page = storage.artifact_page(ac.clone(), &query).await.unwrap();
+        assert_eq!(page.entries.len(), 2);
+        assert!(page.next_cursor.is_some());
+        let mut keys: Vec<String> = page.entries.iter().map(|e| e.key.0.clone()).collect();
+
+        query.cursor = page.next_cursor;
+        query.limit = 10;
+        let page = storage.artifact_page(ac, &query).await.unwrap();
+        assert_eq!(page.entries.len(), 3);
+        assert_eq!(page.next_cursor, None);
+        keys.extend(page.entries.iter().map(|e| e.key.0.clone()));
+
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), 5);
+        assert!(keys.contains(
+            &"v2/believerco-gameprototypemp/client/win64/development/3deadbeef90deadbeef90deadbeef90deadbeef9.json".to_string()
+        ));
+        assert!(!keys.contains(
+            &"v1/believerco-gameprototypemp/client/win64/development/3deadbeef90deadbeef90deadbeef90deadbeef9.json".to_string()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_artifact_page_one_at_a_time() {
+        let mp = MockArtifactProvider::new();
+        let storage = ArtifactStorage::new(Arc::new(mp), StorageSchemaVersion::V2);
+        let ac = ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+
+        // the cursor walks v2 in key order, then moves on to v1
+        let mut query = ArtifactListQuery {
+            limit: 1,
+            ..Default::default()
+        };
+        let mut keys = vec![];
+        loop {
+            let page = storage.artifact_page(ac.clone(), &query).await.unwrap();
+            keys.extend(page.entries.into_iter().map(|e| e.key.0));
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            keys,
+            vec![
+                "v2/believerco-gameprototypemp/client/win64/development/3deadbeef90deadbeef90deadbeef90deadbeef9.json",
+                "v2/believerco-gameprototypemp/client/win64/development/4deadbeef90deadbeef90deadbeef90deadbeef9.json",
+                "v1/believerco-gameprototypemp/client/win64/development/0deadbeef90deadbeef90deadbeef90deadbeef9.json",
+                "v1/believerco-gameprototypemp/client/win64/development/1deadbeef90deadbeef90deadbeef90deadbeef9.json",
+                "v1/believerco-gameprototypemp/client/win64/development/2deadbeef90deadbeef90deadbeef90deadbeef9.json",
+            ]
+        );
+
+        query.cursor = Some("v3/somewhere/else".to_string());
+        assert!(storage.artifact_page(ac, &query).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_artifact_page_since() {
+        let mp = MockArtifactProvider::new();
+        let storage = ArtifactStorage::new(Arc::new(mp), StorageSchemaVersion::V2);
+        let ac = ArtifactConfig::new(
+            "believerco-gameprototypemp".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+
+        // mock entries are all created "now"
+        let query = ArtifactListQuery {
+            limit: 10,
+            cursor: None,
+            filter: ArtifactFilter {
+                since: Some(chrono::Utc::now() + chrono::Duration::days(1)),
+                ..Default::default()
+            },
+        };
+        let page = storage.artifact_page(ac, &query).await.unwrap();
+        assert!(page.entries.is_empty());
+        assert_eq!(page.next_cursor, None);
+    }
 }
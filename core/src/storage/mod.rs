@@ -1,25 +1,40 @@
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use tracing::error;
+use tracing::{error, warn};
 
 pub use crate::storage::config::ArtifactBuildConfig;
 pub use crate::storage::config::ArtifactConfig;
 pub use crate::storage::config::ArtifactKind;
 pub use crate::storage::config::Platform;
 pub use crate::storage::entry::ArtifactEntry;
+pub use crate::storage::index::ArtifactIndexCache;
 pub use crate::storage::list::ArtifactList;
 pub use crate::storage::list::MethodPrefix;
+pub use crate::storage::local::LocalFsArtifactProvider;
+pub use crate::storage::manifest::ArtifactManifest;
+pub use crate::storage::query::{ArtifactFilter, ArtifactListQuery};
+use crate::storage::query::{PageCursor, ProviderPage, MAX_PAGE_SIZE};
+pub use crate::storage::retention::{RetentionPlan, RetentionPolicy, RetentionProtections};
 pub use crate::storage::s3::S3ArtifactProvider;
 use crate::types::errors::CoreError;
+use crate::AWSClient;
 
 pub mod config;
 pub mod entry;
+pub mod index;
 pub mod list;
+pub mod local;
+pub mod manifest;
 pub mod mock;
+pub mod query;
+pub mod retention;
 pub mod s3;
 
 // Trait implementing artifact lookup different storage providers
@@ -33,8 +48,52 @@ pub trait ArtifactProvider: std::fmt::Debug + Send + Sync {
         path: &str,
     ) -> Result<(MethodPrefix, Vec<ArtifactEntry>), CoreError>;
 
+    // Pulls the listing for the given path, keeping only entries modified at or after `since`.
+    // Neither S3 nor a file share can filter on modification time while listing, so by default
+    // this lists the path and drops older entries before anything else, ex. manifests, is
+    // fetched for them.
+    async fn get_artifact_list_since(
+        &self,
+        path: &str,
+        since: Option<SystemTime>,
+    ) -> Result<(MethodPrefix, Vec<ArtifactEntry>), CoreError> {
+        let (method_prefix, mut entries) = self.get_artifact_list(path).await?;
+        if let Some(since) = since {
+            entries.retain(|entry| entry.last_modified >= since);
+        }
+        Ok((method_prefix, entries))
+    }
+
+    // Pulls up to `page_size` entries of the listing for the given path in key order, starting
+    // after the key `start_after`. By default this lists the whole path and slices it, providers
+    // that can page natively should override it.
+    async fn get_artifact_page(
+        &self,
+        path: &str,
+        start_after: Option<&str>,
+        page_size: usize,
+    ) -> Result<ProviderPage, CoreError> {
+        let (method_prefix, mut entries) = self.get_artifact_list(path).await?;
+        entries.retain(|entry| start_after.is_none_or(|after| entry.key.0.as_str() > after));
+        entries.sort_by(|a, b| a.key.0.cmp(&b.key.0));
+
+        let truncated = entries.len() > page_size;
+        entries.truncate(page_size);
+        Ok(ProviderPage {
+            method_prefix,
+            entries,
+            truncated,
+        })
+    }
+
     // Get a single artifact matching the given prefix, erroring if there is more than one match
     async fn get_artifact_by_prefix(&self, prefix: &str) -> Result<String, CoreError>;
+
+    // Read the full contents of a single object, used for small metadata files like manifests
+    async fn read_object(&self, key: &str) -> Result<Vec<u8>, CoreError>;
+
+    // Delete the given objects, returning how many were removed
+    async fn delete_objects(&self, keys: &[String]) -> Result<usize, CoreError>;
 }
 
 // Storage version implementations
@@ -42,6 +101,8 @@ pub trait ArtifactProvider: std::fmt::Debug + Send + Sync {
 pub enum StorageSchemaVersion {
     #[default]
     V1,
+    // Same layout as v1, with a manifest for every artifact under manifests/
+    V2,
 }
 
 impl FromStr for StorageSchemaVersion {
@@ -50,16 +111,55 @@ impl FromStr for StorageSchemaVersion {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "v1" => Ok(StorageSchemaVersion::V1),
+            "v2" => Ok(StorageSchemaVersion::V2),
             _ => Err(anyhow!("Unknown storage schema version: {}", s)),
         }
     }
 }
 
+// Storage provider selection, configured via the dynamic config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ArtifactProviderConfig {
+    // The artifact bucket configured on the AWS client
+    #[default]
+    S3,
+    // A local directory or file share mirroring the bucket layout
+    #[serde(rename_all = "camelCase")]
+    LocalFs { root_path: String },
+}
+
+impl ArtifactProviderConfig {
+    // None for S3 until there's an AWS client, a local provider doesn't need one
+    pub fn create_provider(
+        &self,
+        aws_client: Option<&AWSClient>,
+    ) -> Option<Arc<dyn ArtifactProvider>> {
+        match self {
+            ArtifactProviderConfig::S3 => aws_client.map(|aws_client| {
+                Arc::new(S3ArtifactProvider::new(
+                    aws_client,
+                    &aws_client.get_artifact_bucket(),
+                )) as Arc<dyn ArtifactProvider>
+            }),
+            ArtifactProviderConfig::LocalFs { root_path } => {
+                Some(Arc::new(LocalFsArtifactProvider::new(root_path)))
+            }
+        }
+    }
+}
+
+// Number of manifests fetched concurrently when listing v2 artifacts
+const MANIFEST_FETCH_CONCURRENCY: usize = 16;
+
 // Wrapper pairing an artifact provider with the schema to use
 #[derive(Clone, Debug)]
 pub struct ArtifactStorage {
     provider: Arc<dyn ArtifactProvider>,
     schema_version: StorageSchemaVersion,
+
+    // Optional on-disk listing cache, see artifact_list_cached
+    index: Option<ArtifactIndexCache>,
 }
 
 impl ArtifactStorage {
@@ -67,13 +167,24 @@ impl ArtifactStorage {
         Self {
             provider,
             schema_version,
+            index: None,
         }
     }
 
+    pub fn with_index_cache(mut self, index: ArtifactIndexCache) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    pub fn index_cache(&self) -> Option<&ArtifactIndexCache> {
+        self.index.as_ref()
+    }
+
     // Use the provider to get the artifact with the given short sha prefix. This allows
     // for the v0 schema, where it'll match the filename without the .json extension,
     // and the v1 schema where any number of characters in the prefix of the 40-character sha
-    // can be used for the engine lookup.
+    // can be used for the engine lookup. In the v2 schema, we fall back to the v1 path for
+    // artifacts that were published before the project migrated.
     pub async fn get_from_short_sha(
         &self,
         artifact_config: ArtifactConfig,
@@ -81,16 +192,30 @@ impl ArtifactStorage {
     ) -> Result<String, CoreError> {
         let path = self.resolve_path(&artifact_config);
 
-        self.provider
+        let result = self
+            .provider
             .get_artifact_by_prefix(&format!("{path}{short_sha}"))
-            .await
+            .await;
+
+        match (&self.schema_version, result) {
+            (StorageSchemaVersion::V2, Err(e)) => {
+                warn!(
+                    "No v2 artifact found for {}, falling back to v1. Error: {}",
+                    short_sha, e
+                );
+                let path = Self::resolve_path_v1(&artifact_config);
+                self.provider
+                    .get_artifact_by_prefix(&format!("{path}{short_sha}"))
+                    .await
+            }
+            (_, result) => result,
+        }
     }
 
     // Use the provider to get an artifact listing by constructing the lookup path.
     pub async fn artifact_list(&self, artifact_config: ArtifactConfig) -> ArtifactList {
         let artifact_list_result = self
-            .provider
-            .get_artifact_list(&self.resolve_path(&artifact_config))
+            .list_entries(&artifact_config, &HashMap::new(), None)
             .await;
 
         match artifact_list_result {
@@ -108,6 +233,169 @@ impl ArtifactStorage {
         }
     }
 
+    // Paginated and filtered listing. With an index cache, pages are cut from the whole cached
+    // listing sorted newest first, so ordering and the v1/v2 dedup hold across pages. Without
+    // one, pages are pulled from the provider in key order, v2 before v1, so a request only lists
+    // about a page of keys. Either way, the cursor doesn't move when artifacts are uploaded
+    // between requests.
+    pub async fn artifact_page(
+        &self,
+        artifact_config: ArtifactConfig,
+        query: &ArtifactListQuery,
+    ) -> Result<ArtifactList, CoreError> {
+        match self.index {
+            Some(_) => self.artifact_page_cached(artifact_config, query).await,
+            None => self.artifact_page_listed(artifact_config, query).await,
+        }
+    }
+
+    // Pages through the cached listing newest first. The cursor is the last entry returned.
+    async fn artifact_page_cached(
+        &self,
+        artifact_config: ArtifactConfig,
+        query: &ArtifactListQuery,
+    ) -> Result<ArtifactList, CoreError> {
+        let limit = query.limit.max(1);
+        let cursor = query.cursor.as_deref().map(PageCursor::parse).transpose()?;
+
+        let mut list = self.artifact_list_cached(artifact_config).await;
+        list.entries.retain(|entry| {
+            query.filter.matches(entry)
+                && cursor.as_ref().is_none_or(|cursor| cursor.precedes(entry))
+        });
+        list.entries.sort_by(PageCursor::cmp_entries);
+
+        if list.entries.len() > limit {
+            list.entries.truncate(limit);
+            list.next_cursor = list
+                .entries
+                .last()
+                .map(|entry| PageCursor::after(entry).to_string());
+        }
+        Ok(list)
+    }
+
+    // Pages through the provider listing, pulling provider pages until `query.limit` entries
+    // match or the listing runs out. The cursor is the last key looked at, or the next path to
+    // list once one runs out. Each page is sorted newest first, but pages follow key order.
+    async fn artifact_page_listed(
+        &self,
+        artifact_config: ArtifactConfig,
+        query: &ArtifactListQuery,
+    ) -> Result<ArtifactList, CoreError> {
+        let limit = query.limit.max(1);
+        let paths = self.listing_paths(&artifact_config);
+        let mut start_after = query.cursor.clone();
+        let mut segment = match &start_after {
+            Some(cursor) => paths
+                .iter()
+                .position(|path| cursor.starts_with(path.as_str()))
+                .ok_or_else(|| CoreError::Input(anyhow!("Invalid listing cursor {}", cursor)))?,
+            None => 0,
+        };
+
+        let mut list =
+            ArtifactList::new(artifact_config.clone(), self.provider.get_method_prefix());
+        while segment < paths.len() && list.entries.len() < limit {
+            let page = self
+                .provider
+                .get_artifact_page(
+                    &paths[segment],
+                    start_after.as_deref(),
+                    limit.min(MAX_PAGE_SIZE),
+                )
+                .await?;
+            list.method_prefix = page.method_prefix;
+
+            // During a v2 migration, v1 artifacts for commits already on v2 are hidden
+            let hidden = match (&self.schema_version, segment) {
+                (StorageSchemaVersion::V2, 1) => {
+                    self.v2_commits_among(&artifact_config, &page.entries)
+                        .await?
+                }
+                _ => HashSet::new(),
+            };
+
+            let mut remaining = page.entries.into_iter();
+            let mut examined = 0;
+            let mut entries = Vec::new();
+            while list.entries.len() + entries.len() < limit {
+                let Some(entry) = remaining.next() else {
+                    break;
+                };
+                examined += 1;
+                start_after = Some(entry.key.0.clone());
+                if query.filter.matches(&entry)
+                    && entry
+                        .commit
+                        .as_ref()
+                        .is_none_or(|commit| !hidden.contains(commit))
+                {
+                    entries.push(entry);
+                }
+            }
+            let page_done = remaining.next().is_none();
+
+            list.entries.extend(match (&self.schema_version, segment) {
+                (StorageSchemaVersion::V2, 0) => {
+                    self.attach_manifests(entries, &HashMap::new()).await
+                }
+                _ => entries,
+            });
+
+            if page_done && (!page.truncated || examined == 0) {
+                segment += 1;
+                start_after = paths.get(segment).cloned();
+            }
+        }
+
+        if segment < paths.len() {
+            list.next_cursor = start_after;
+        }
+        list.sort_by_last_modified();
+        Ok(list)
+    }
+
+    // Commits among a page of v1 entries that are also published under v2. Both paths are keyed
+    // by commit, so only the range of the v2 listing the page covers is pulled.
+    async fn v2_commits_among(
+        &self,
+        artifact_config: &ArtifactConfig,
+        entries: &[ArtifactEntry],
+    ) -> Result<HashSet<String>, CoreError> {
+        let commits = entries.iter().filter_map(|entry| entry.commit.as_ref());
+        let (Some(first), Some(last)) = (commits.clone().min(), commits.max()) else {
+            return Ok(HashSet::new());
+        };
+
+        let path = Self::resolve_path_v2(artifact_config);
+        let mut start_after = format!("{path}{first}");
+        let mut v2_commits = HashSet::new();
+        loop {
+            let page = self
+                .provider
+                .get_artifact_page(&path, Some(&start_after), MAX_PAGE_SIZE)
+                .await?;
+            let Some(last_key) = page.entries.last().map(|entry| entry.key.0.clone()) else {
+                break;
+            };
+
+            for commit in page.entries.into_iter().filter_map(|entry| entry.commit) {
+                if commit > *last {
+                    return Ok(v2_commits);
+                }
+                v2_commits.insert(commit);
+            }
+
+            if !page.truncated {
+                break;
+            }
+            start_after = last_key;
+        }
+
+        Ok(v2_commits)
+    }
+
     pub async fn get_artifact_for_commit(
         &self,
         artifact_config: ArtifactConfig,
@@ -122,21 +410,150 @@ impl ArtifactStorage {
         Ok(artifact_entry.clone())
     }
 
-    fn resolve_path(&self, artifact_config: &ArtifactConfig) -> String {
+    // Fetch and parse the manifest published alongside the given artifact key
+    pub async fn get_manifest(&self, artifact_key: &str) -> Result<ArtifactManifest, CoreError> {
+        let key = ArtifactManifest::key_for_artifact(artifact_key);
+        let bytes = self.provider.read_object(&key).await?;
+        serde_json::from_slice(&bytes).map_err(|e| {
+            CoreError::Internal(anyhow!("Failed to parse artifact manifest {}: {}", key, e))
+        })
+    }
+
+    // Lists the entries for the config under the storage schema, optionally only those modified
+    // at or after `since`. Entries in `known` whose object hasn't changed since are reused as-is,
+    // rather than fetching their manifests again.
+    pub(crate) async fn list_entries(
+        &self,
+        artifact_config: &ArtifactConfig,
+        known: &HashMap<String, ArtifactEntry>,
+        since: Option<SystemTime>,
+    ) -> Result<(MethodPrefix, Vec<ArtifactEntry>), CoreError> {
         match &self.schema_version {
+            StorageSchemaVersion::V1 => {
+                self.provider
+                    .get_artifact_list_since(&self.resolve_path(artifact_config), since)
+                    .await
+            }
+            StorageSchemaVersion::V2 => {
+                self.get_artifact_list_v2(artifact_config, known, since)
+                    .await
+            }
+        }
+    }
+
+    // Lists v2 artifacts along with their manifests, followed by any v1 artifacts for commits
+    // that haven't been published under v2, so both show up side by side during a migration.
+    // A v1 artifact is hidden by its v2 counterpart even when only the v1 one is after `since`.
+    async fn get_artifact_list_v2(
+        &self,
+        artifact_config: &ArtifactConfig,
+        known: &HashMap<String, ArtifactEntry>,
+        since: Option<SystemTime>,
+    ) -> Result<(MethodPrefix, Vec<ArtifactEntry>), CoreError> {
+        let (method_prefix, mut entries) = self
+            .provider
+            .get_artifact_list(&Self::resolve_path_v2(artifact_config))
+            .await?;
+        let v2_commits: HashSet<String> = entries.iter().filter_map(|e| e.commit.clone()).collect();
+        if let Some(since) = since {
+            entries.retain(|entry| entry.last_modified >= since);
+        }
+        let mut entries = self.attach_manifests(entries, known).await;
+
+        match self
+            .provider
+            .get_artifact_list_since(&Self::resolve_path_v1(artifact_config), since)
+            .await
+        {
+            Ok((_, v1_entries)) => {
+                entries.extend(v1_entries.into_iter().filter(|e| match &e.commit {
+                    Some(commit) => !v2_commits.contains(commit),
+                    None => true,
+                }));
+            }
+            Err(e) => warn!(
+                "Failed to list v1 artifacts alongside v2 for config {:?}. {}",
+                artifact_config, e
+            ),
+        }
+
+        Ok((method_prefix, entries))
+    }
+
+    // A missing or malformed manifest isn't fatal. The entry is kept without metadata and
+    // falls back to the commit derived from its key.
+    async fn attach_manifests(
+        &self,
+        entries: Vec<ArtifactEntry>,
+        known: &HashMap<String, ArtifactEntry>,
+    ) -> Vec<ArtifactEntry> {
+        futures::stream::iter(entries)
+            .map(|entry| async move {
+                if let Some(known_entry) = known.get(&entry.key.0) {
+                    if known_entry.metadata.is_some()
+                        && entry.last_modified <= known_entry.last_modified
+                    {
+                        return known_entry.clone();
+                    }
+                }
+
+                match self.get_manifest(&entry.key.0).await {
+                    Ok(manifest) => entry.with_metadata(manifest),
+                    Err(e) => {
+                        warn!("No usable manifest for artifact {}: {}", entry.key, e);
+                        entry
+                    }
+                }
+            })
+            .buffered(MANIFEST_FETCH_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    // Every path a listing covers, in order. v2 projects still list v1 artifacts after their own.
+    fn listing_paths(&self, artifact_config: &ArtifactConfig) -> Vec<String> {
+        match self.schema_version {
+            StorageSchemaVersion::V1 => vec![Self::resolve_path_v1(artifact_config)],
+            StorageSchemaVersion::V2 => vec![
+                Self::resolve_path_v2(artifact_config),
+                Self::resolve_path_v1(artifact_config),
+            ],
+        }
+    }
+
+    fn resolve_path(&self, artifact_config: &ArtifactConfig) -> String {
+        Self::resolve_path_for(&self.schema_version, artifact_config)
+    }
+
+    pub(crate) fn resolve_path_for(
+        schema_version: &StorageSchemaVersion,
+        artifact_config: &ArtifactConfig,
+    ) -> String {
+        match schema_version {
             StorageSchemaVersion::V1 => Self::resolve_path_v1(artifact_config),
+            StorageSchemaVersion::V2 => Self::resolve_path_v2(artifact_config),
         }
     }
+
     // This is the v1 path resolver with full support for the new enums
     fn resolve_path_v1(artifact_config: &ArtifactConfig) -> String {
-        let path = format!(
-            "v1/{}/{}/{}/{}/",
+        Self::resolve_versioned_path("v1", artifact_config)
+    }
+
+    // The v2 layout matches v1, manifests live in a sub-directory of the same path
+    fn resolve_path_v2(artifact_config: &ArtifactConfig) -> String {
+        Self::resolve_versioned_path("v2", artifact_config)
+    }
+
+    fn resolve_versioned_path(version: &str, artifact_config: &ArtifactConfig) -> String {
+        format!(
+            "{}/{}/{}/{}/{}/",
+            version,
             artifact_config.project,
             artifact_config.artifact_kind.to_path_string(),
             artifact_config.platform.to_path_string(),
             artifact_config.artifact_build_config.to_path_string(),
-        );
-        path
+        )
     }
 }
 
@@ -168,5 +585,24 @@ mod test {
             ArtifactStorage::resolve_path_v1(&ac),
             "v1/believerco-gameprototypemp/engine/win64/development/"
         );
+        assert_eq!(
+            ArtifactStorage::resolve_path_v2(&ac),
+            "v2/believerco-gameprototypemp/engine/win64/development/"
+        );
+    }
+
+    #[test]
+    fn test_artifact_provider_config() {
+        let config: ArtifactProviderConfig =
+            serde_json::from_str(r#"{"type": "localFs", "rootPath": "/mnt/builds"}"#).unwrap();
+        assert_eq!(
+            config,
+            ArtifactProviderConfig::LocalFs {
+                root_path: "/mnt/builds".to_string()
+            }
+        );
+
+        let config: ArtifactProviderConfig = serde_json::from_str(r#"{"type": "s3"}"#).unwrap();
+        assert_eq!(config, ArtifactProviderConfig::S3);
     }
 }
@@ -0,0 +1,211 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::entry::ArtifactEntry;
+use crate::storage::list::MethodPrefix;
+use crate::types::errors::CoreError;
+
+// S3 caps a single listing request at 1000 keys
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+// A single page of a provider listing, in key order
+#[derive(Debug)]
+pub struct ProviderPage {
+    pub method_prefix: MethodPrefix,
+    pub entries: Vec<ArtifactEntry>,
+
+    // Set when the listing goes on past the last entry of this page
+    pub truncated: bool,
+}
+
+// Filters applied to a paginated artifact listing. Unset fields match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactFilter {
+    // Only artifacts uploaded at or after this time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+
+    // Only artifacts uploaded at or before this time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+
+    // Only artifacts built from one of these full commit shas, ex. a resolved commit range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commits: Option<HashSet<String>>,
+}
+
+impl ArtifactFilter {
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none() && self.until.is_none() && self.commits.is_none()
+    }
+
+    pub fn matches(&self, entry: &ArtifactEntry) -> bool {
+        let last_modified = DateTime::<Utc>::from(entry.last_modified);
+        if self.since.is_some_and(|since| last_modified < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| last_modified > until) {
+            return false;
+        }
+
+        match (&self.commits, &entry.commit) {
+            (None, _) => true,
+            (Some(commits), Some(commit)) => commits.contains(&commit.to_lowercase()),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactListQuery {
+    // Maximum number of matching entries to return
+    pub limit: usize,
+
+    // The next_cursor of a previous page
+    pub cursor: Option<String>,
+
+    pub filter: ArtifactFilter,
+}
+
+// Position in a paginated listing, the last entry of the previous page. Entries are ordered
+// newest first, ties broken by key, and serialized as <last modified millis>:<key>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PageCursor {
+    pub last_modified_millis: u128,
+    pub key: String,
+}
+
+impl PageCursor {
+    pub fn after(entry: &ArtifactEntry) -> Self {
+        Self {
+            last_modified_millis: millis(entry.last_modified),
+            key: entry.key.0.clone(),
+        }
+    }
+
+    pub fn parse(cursor: &str) -> Result<Self, CoreError> {
+        let invalid = || CoreError::Input(anyhow!("Invalid listing cursor {}", cursor));
+
+        let (millis, key) = cursor.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            last_modified_millis: millis.parse::<u128>().map_err(|_| invalid())?,
+            key: key.to_string(),
+        })
+    }
+
+    // Whether the entry belongs on a page after this cursor
+    pub fn precedes(&self, entry: &ArtifactEntry) -> bool {
+        (
+            std::cmp::Reverse(self.last_modified_millis),
+            self.key.as_str(),
+        ) < (
+            std::cmp::Reverse(millis(entry.last_modified)),
+            entry.key.0.as_str(),
+        )
+    }
+
+    // Page order: newest first, then by key so entries modified together keep a stable order
+    pub fn cmp_entries(a: &ArtifactEntry, b: &ArtifactEntry) -> Ordering {
+        millis(b.last_modified)
+            .cmp(&millis(a.last_modified))
+            .then_with(|| a.key.0.cmp(&b.key.0))
+    }
+}
+
+impl std::fmt::Display for PageCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.last_modified_millis, self.key)
+    }
+}
+
+// Cursors only keep millisecond precision, so entries are compared at the same precision
+fn millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use std::time::{Duration, SystemTime};
+
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let mut entry = ArtifactEntry::new(
+            "v1/believerco-testproj/client/win64/development/abcdef01abcdef01abcdef01abcdef01abcdef01.json"
+                .to_string(),
+        );
+        entry.last_modified = SystemTime::now() - Duration::from_secs(60 * 60);
+
+        assert!(ArtifactFilter::default().matches(&entry));
+
+        let filter = ArtifactFilter {
+            since: Some(Utc::now() - chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        assert!(filter.matches(&entry));
+
+        let filter = ArtifactFilter {
+            until: Some(Utc::now() - chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&entry));
+
+        let filter = ArtifactFilter {
+            commits: Some(HashSet::from([
+                "abcdef01abcdef01abcdef01abcdef01abcdef01".to_string()
+            ])),
+            ..Default::default()
+        };
+        assert!(filter.matches(&entry));
+
+        let filter = ArtifactFilter {
+            commits: Some(HashSet::new()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&entry));
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = PageCursor {
+            last_modified_millis: 1_700_000_000_123,
+            key: "v1/believerco-testproj/client/win64/development/abcdef01.json".to_string(),
+        };
+        assert_eq!(
+            cursor.to_string(),
+            "1700000000123:v1/believerco-testproj/client/win64/development/abcdef01.json"
+        );
+        assert_eq!(PageCursor::parse(&cursor.to_string()).unwrap(), cursor);
+
+        assert!(PageCursor::parse("garbage").is_err());
+        assert!(PageCursor::parse("x:abc").is_err());
+    }
+
+    #[test]
+    fn test_cursor_order() {
+        let now = SystemTime::now();
+        let mut newer = ArtifactEntry::new("v1/proj/client/win64/development/b.json".to_string());
+        newer.last_modified = now;
+        let mut tied = ArtifactEntry::new("v1/proj/client/win64/development/c.json".to_string());
+        tied.last_modified = now;
+        let mut older = ArtifactEntry::new("v1/proj/client/win64/development/a.json".to_string());
+        older.last_modified = now - Duration::from_secs(60);
+
+        let cursor = PageCursor::after(&newer);
+        assert!(!cursor.precedes(&newer));
+        assert!(cursor.precedes(&tied));
+        assert!(cursor.precedes(&older));
+        assert_eq!(PageCursor::cmp_entries(&newer, &older), Ordering::Less);
+        assert_eq!(PageCursor::cmp_entries(&newer, &tied), Ordering::Less);
+    }
+}
@@ -0,0 +1,424 @@
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, TimestampSeconds};
+use tracing::info;
+
+use crate::storage::config::Project;
+use crate::storage::entry::{ArtifactEntry, ObjectLocation};
+use crate::storage::{
+    ArtifactBuildConfig, ArtifactConfig, ArtifactKind, ArtifactList, ArtifactManifest,
+    ArtifactStorage, Platform, StorageSchemaVersion,
+};
+use crate::types::errors::CoreError;
+
+// Manifest tag marking an artifact as promoted, for builds promoted outside of a workflow
+pub const PROMOTED_TAG: &str = "promoted";
+
+// Shortest commit prefix we'll treat as identifying a build, matching the short sha in display names
+const MIN_COMMIT_PREFIX_LEN: usize = 8;
+
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+// Retention rules for a single kind of artifact
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    pub artifact_kind: ArtifactKind,
+
+    // Number of most recent artifacts always kept for each platform and build config
+    pub keep_last: usize,
+
+    // Artifacts older than this that aren't otherwise protected are deleted
+    pub max_age_days: u64,
+}
+
+// Commits that must be kept regardless of age
+#[derive(Debug, Clone, Default)]
+pub struct RetentionProtections {
+    // Versions referenced by playtests that still exist in the cluster
+    pub playtest_commits: HashSet<String>,
+
+    // Commits promoted via a PromoteBuildShard workflow
+    pub promoted_commits: HashSet<String>,
+}
+
+impl RetentionProtections {
+    fn contains(commits: &HashSet<String>, commit: &str) -> bool {
+        commits
+            .iter()
+            .any(|protected| commits_match(protected, commit))
+    }
+}
+
+// Either side may be a short sha, so compare on the common prefix
+fn commits_match(a: &str, b: &str) -> bool {
+    let len = a.len().min(b.len());
+    len >= MIN_COMMIT_PREFIX_LEN && a[..len].eq_ignore_ascii_case(&b[..len])
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RetentionReason {
+    KeepLast,
+    ActivePlaytest,
+    Promoted,
+    WithinMaxAge,
+    Expired,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionDecision {
+    pub key: ObjectLocation,
+    pub commit: Option<String>,
+    #[serde_as(as = "TimestampSeconds<f64>")]
+    pub last_modified: SystemTime,
+    pub reason: RetentionReason,
+}
+
+impl RetentionDecision {
+    fn new(entry: &ArtifactEntry, reason: RetentionReason) -> Self {
+        Self {
+            key: entry.key.clone(),
+            commit: entry.commit.clone(),
+            last_modified: entry.last_modified,
+            reason,
+        }
+    }
+}
+
+// The dry-run output of the retention planner. Nothing is deleted until the plan is executed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPlan {
+    pub keep: Vec<RetentionDecision>,
+    pub delete: Vec<RetentionDecision>,
+}
+
+impl RetentionPlan {
+    // Evaluate a single listing, which is already scoped to one kind, platform and build config.
+    // Expects the list sorted newest first, as returned by ArtifactStorage::artifact_list.
+    pub fn evaluate(
+        list: &ArtifactList,
+        policy: &RetentionPolicy,
+        protections: &RetentionProtections,
+        now: SystemTime,
+    ) -> Self {
+        let max_age = Duration::from_secs(policy.max_age_days * SECONDS_PER_DAY);
+        let mut plan = RetentionPlan::default();
+
+        for (i, entry) in list.entries.iter().enumerate() {
+            let commit = entry.commit.as_deref().unwrap_or_default();
+            let age = now
+                .duration_since(entry.last_modified)
+                .unwrap_or(Duration::ZERO);
+
+            let reason = if i < policy.keep_last {
+                RetentionReason::KeepLast
+            } else if RetentionProtections::contains(&protections.playtest_commits, commit) {
+                RetentionReason::ActivePlaytest
+            } else if RetentionProtections::contains(&protections.promoted_commits, commit)
+                || Self::is_tagged_promoted(entry)
+            {
+                RetentionReason::Promoted
+            } else if age <= max_age {
+                RetentionReason::WithinMaxAge
+            } else {
+                RetentionReason::Expired
+            };
+
+            let decision = RetentionDecision::new(entry, reason);
+            match reason {
+                RetentionReason::Expired => plan.delete.push(decision),
+                _ => plan.keep.push(decision),
+            }
+        }
+
+        plan
+    }
+
+    fn is_tagged_promoted(entry: &ArtifactEntry) -> bool {
+        entry
+            .metadata
+            .as_ref()
+            .is_some_and(|m| m.tags.iter().any(|t| t == PROMOTED_TAG))
+    }
+
+    pub fn extend(&mut self, other: RetentionPlan) {
+        self.keep.extend(other.keep);
+        self.delete.extend(other.delete);
+    }
+
+    // Whether this plan deletes exactly the artifacts a caller reviewed in an earlier dry run.
+    // Anything uploaded or changed in between shows up as a difference.
+    pub fn deletes_exactly(&self, reviewed: &[String]) -> bool {
+        let mut planned: Vec<&str> = self.delete.iter().map(|d| d.key.0.as_str()).collect();
+        let mut reviewed: Vec<&str> = reviewed.iter().map(String::as_str).collect();
+        planned.sort_unstable();
+        reviewed.sort_unstable();
+        planned == reviewed
+    }
+
+    // Every object key the plan would remove, including the manifests of v2 artifacts
+    pub fn delete_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        for decision in &self.delete {
+            keys.push(decision.key.0.clone());
+            if decision.key.schema_version() == Some(StorageSchemaVersion::V2) {
+                keys.push(ArtifactManifest::key_for_artifact(&decision.key.0));
+            }
+        }
+        keys
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReport {
+    pub deleted_artifacts: usize,
+    pub deleted_objects: usize,
+}
+
+impl ArtifactStorage {
+    // Build a retention plan covering every platform and build config for each policy's kind
+    pub async fn plan_retention(
+        &self,
+        project: &Project,
+        policies: &[RetentionPolicy],
+        protections: &RetentionProtections,
+        now: SystemTime,
+    ) -> RetentionPlan {
+        let mut plan = RetentionPlan::default();
+
+        for policy in policies {
+            for platform in Platform::all() {
+                for build_config in ArtifactBuildConfig::all() {
+                    let artifact_config = ArtifactConfig::new(
+                        project.clone(),
+                        policy.artifact_kind.clone(),
+                        build_config,
+                        platform.clone(),
+                    );
+                    let list = self.artifact_list(artifact_config).await;
+                    plan.extend(RetentionPlan::evaluate(&list, policy, protections, now));
+                }
+            }
+        }
+
+        plan
+    }
+
+    pub async fn execute_retention_plan(
+        &self,
+        plan: &RetentionPlan,
+    ) -> Result<RetentionReport, CoreError> {
+        let keys = plan.delete_keys();
+        if keys.is_empty() {
+            return Ok(RetentionReport::default());
+        }
+
+        info!(
+            "Deleting {} expired artifacts ({} objects)",
+            plan.delete.len(),
+            keys.len()
+        );
+        let deleted_objects = self.provider.delete_objects(&keys).await?;
+
+        Ok(RetentionReport {
+            deleted_artifacts: plan.delete.len(),
+            deleted_objects,
+        })
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use std::sync::Arc;
+
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::storage::mock::MockArtifactProvider;
+
+    #[allow(dead_code)]
+    const DAY: Duration = Duration::from_secs(SECONDS_PER_DAY);
+
+    #[allow(dead_code)]
+    fn make_list(now: SystemTime, ages_in_days: &[u64]) -> ArtifactList {
+        let ac = ArtifactConfig::new(
+            "believerco-testproj".into(),
+            ArtifactKind::Client,
+            ArtifactBuildConfig::Development,
+            Platform::Win64,
+        );
+        let mut list = ArtifactList::new(ac, "test:///".into());
+        for (i, age) in ages_in_days.iter().enumerate() {
+            let mut entry = ArtifactEntry::new(format!(
+                "v1/believerco-testproj/client/win64/development/{i}deadbeef9{i}deadbeef9{i}deadbeef9{i}deadbeef9.json"
+            ));
+            entry.last_modified = now - DAY * (*age as u32);
+            list.entries.push(entry);
+        }
+        list.sort_by_last_modified();
+        list
+    }
+
+    #[allow(dead_code)]
+    fn policy() -> RetentionPolicy {
+        RetentionPolicy {
+            artifact_kind: ArtifactKind::Client,
+            keep_last: 2,
+            max_age_days: 14,
+        }
+    }
+
+    #[test]
+    fn test_keep_last_and_max_age() {
+        let now = SystemTime::now();
+        let list = make_list(now, &[1, 2, 10, 20, 30]);
+
+        let plan = RetentionPlan::evaluate(&list, &policy(), &Default::default(), now);
+        let reasons: Vec<_> = plan.keep.iter().map(|d| d.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                RetentionReason::KeepLast,
+                RetentionReason::KeepLast,
+                RetentionReason::WithinMaxAge
+            ]
+        );
+        assert_eq!(plan.delete.len(), 2);
+        assert!(plan
+            .delete
+            .iter()
+            .all(|d| d.reason == RetentionReason::Expired));
+    }
+
+    #[test]
+    fn test_keep_last_wins_over_age() {
+        let now = SystemTime::now();
+        let list = make_list(now, &[100, 200]);
+
+        let plan = RetentionPlan::evaluate(&list, &policy(), &Default::default(), now);
+        assert_eq!(plan.keep.len(), 2);
+        assert!(plan.delete.is_empty());
+    }
+
+    #[test]
+    fn test_protections() {
+        let now = SystemTime::now();
+        let list = make_list(now, &[1, 2, 20, 30, 40]);
+
+        let protections = RetentionProtections {
+            // short shas from playtests still protect the full commit
+            playtest_commits: HashSet::from(["3deadbee".to_string()]),
+            promoted_commits: HashSet::from([
+                "4deadbeef94deadbeef94deadbeef94deadbeef9".to_string()
+            ]),
+        };
+
+        let plan = RetentionPlan::evaluate(&list, &policy(), &protections, now);
+        assert_eq!(plan.delete.len(), 1);
+        assert_eq!(
+            plan.delete[0].commit,
+            Some("2deadbeef92deadbeef92deadbeef92deadbeef9".to_string())
+        );
+        assert!(plan
+            .keep
+            .iter()
+            .any(|d| d.reason == RetentionReason::ActivePlaytest));
+        assert!(plan
+            .keep
+            .iter()
+            .any(|d| d.reason == RetentionReason::Promoted));
+    }
+
+    #[test]
+    fn test_promoted_tag() {
+        let now = SystemTime::now();
+        let mut list = make_list(now, &[1, 2, 30]);
+        list.entries[2].metadata = Some(ArtifactManifest {
+            tags: vec![PROMOTED_TAG.to_string()],
+            ..Default::default()
+        });
+
+        let plan = RetentionPlan::evaluate(&list, &policy(), &Default::default(), now);
+        assert!(plan.delete.is_empty());
+        assert_eq!(plan.keep[2].reason, RetentionReason::Promoted);
+    }
+
+    #[test]
+    fn test_delete_keys_include_manifests() {
+        let now = SystemTime::now();
+        let mut entry = ArtifactEntry::new(
+            "v2/believerco-testproj/client/win64/development/abcdef01abcdef01abcdef01abcdef01abcdef01.json"
+                .to_string(),
+        );
+        entry.last_modified = now - DAY * 30;
+        let plan = RetentionPlan {
+            keep: vec![],
+            delete: vec![RetentionDecision::new(&entry, RetentionReason::Expired)],
+        };
+
+        assert_eq!(
+            plan.delete_keys(),
+            vec![
+                "v2/believerco-testproj/client/win64/development/abcdef01abcdef01abcdef01abcdef01abcdef01.json",
+                "v2/believerco-testproj/client/win64/development/manifests/abcdef01abcdef01abcdef01abcdef01abcdef01.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deletes_exactly() {
+        let now = SystemTime::now();
+        let list = make_list(now, &[1, 2, 20, 30]);
+        let plan = RetentionPlan::evaluate(&list, &policy(), &Default::default(), now);
+
+        let mut reviewed: Vec<String> = plan.delete.iter().map(|d| d.key.0.clone()).collect();
+        reviewed.reverse();
+        assert!(plan.deletes_exactly(&reviewed));
+
+        // an artifact that expired after the review isn't deleted without another look
+        assert!(!plan.deletes_exactly(&reviewed[..1]));
+        reviewed.push("v1/believerco-testproj/client/win64/development/other.json".to_string());
+        assert!(!plan.deletes_exactly(&reviewed));
+    }
+
+    #[tokio::test]
+    async fn test_plan_and_execute_with_mock() {
+        let storage = ArtifactStorage::new(
+            Arc::new(MockArtifactProvider::new()),
+            StorageSchemaVersion::V1,
+        );
+        let policies = vec![RetentionPolicy {
+            artifact_kind: ArtifactKind::Client,
+            keep_last: 1,
+            max_age_days: 0,
+        }];
+
+        // mock entries are all created "now", so age them by planning from the future
+        let now = SystemTime::now() + DAY;
+        let plan = storage
+            .plan_retention(
+                &"believerco-gameprototypemp".into(),
+                &policies,
+                &Default::default(),
+                now,
+            )
+            .await;
+
+        // The mock serves 4 entries for win64 development and a fallback listing for every other
+        // combination, so each listing keeps 1 and deletes the rest.
+        let listings = Platform::all().len() * ArtifactBuildConfig::all().len();
+        assert_eq!(plan.keep.len(), listings);
+        assert_eq!(plan.delete.len(), listings * 3);
+
+        let report = storage.execute_retention_plan(&plan).await.unwrap();
+        assert_eq!(report.deleted_artifacts, plan.delete.len());
+        assert_eq!(report.deleted_objects, plan.delete.len());
+    }
+}
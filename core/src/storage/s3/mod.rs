@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use aws_sdk_s3::Client;
 use tracing::warn;
 
+use crate::storage::query::{ProviderPage, MAX_PAGE_SIZE};
 use crate::storage::{entry::ArtifactEntry, list::MethodPrefix, ArtifactProvider};
 use crate::types::errors::CoreError;
 use crate::AWSClient;
@@ -103,6 +104,84 @@ impl ArtifactProvider for S3ArtifactProvider {
         entry_list.sort_by(|a, b| b.last_modified.partial_cmp(&a.last_modified).unwrap());
         Ok((self.get_method_prefix(), entry_list))
     }
+
+    // Starts the listing after the cursor key, so only page_size keys are fetched per request
+    async fn get_artifact_page(
+        &self,
+        path: &str,
+        start_after: Option<&str>,
+        page_size: usize,
+    ) -> Result<ProviderPage, CoreError> {
+        self.aws_client.check_expiration().await?;
+        let client = Client::new(&self.aws_client.get_sdk_config().await);
+
+        let res = client
+            .list_objects_v2()
+            .bucket(self.s3_bucket.clone())
+            .prefix(path)
+            .delimiter("/")
+            .max_keys(page_size.min(MAX_PAGE_SIZE) as i32)
+            .set_start_after(start_after.map(|key| key.to_string()))
+            .send()
+            .await;
+
+        let res = match res {
+            Ok(res) => res,
+            Err(err) => {
+                warn!("Error getting page of objects from S3: [{:?}", err);
+                if let Some(sdk_error) = err.as_service_error() {
+                    if sdk_error.meta().code() == Some("ExpiredToken") {
+                        return Err(CoreError::Unauthorized);
+                    }
+                }
+
+                return Err(CoreError::Internal(anyhow!(
+                    "Error getting page of objects from S3: {:?}",
+                    err
+                )));
+            }
+        };
+
+        Ok(ProviderPage {
+            method_prefix: self.get_method_prefix(),
+            entries: res
+                .contents()
+                .iter()
+                .map(|object| ArtifactEntry::from(object.clone()))
+                .collect(),
+            truncated: res.is_truncated().unwrap_or_default(),
+        })
+    }
+
+    async fn read_object(&self, key: &str) -> Result<Vec<u8>, CoreError> {
+        self.aws_client.check_expiration().await?;
+        let client = Client::new(&self.aws_client.get_sdk_config().await);
+
+        let res = client
+            .get_object()
+            .bucket(self.s3_bucket.clone())
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| {
+                CoreError::Internal(anyhow!(
+                    "Failed to get object {} from S3: {}",
+                    key,
+                    e.into_service_error()
+                ))
+            })?;
+
+        let body = res.body.collect().await.map_err(|e| {
+            CoreError::Internal(anyhow!("Failed to collect object body for {}: {}", key, e))
+        })?;
+
+        Ok(body.into_bytes().to_vec())
+    }
+
+    async fn delete_objects(&self, keys: &[String]) -> Result<usize, CoreError> {
+        self.aws_client.check_expiration().await?;
+        self.aws_client.delete_objects(&self.s3_bucket, keys).await
+    }
 }
 
 mod tests {
@@ -0,0 +1,475 @@
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::types::config::{SubmitValidator, SubmitValidatorRule};
+use crate::utils::process::run_with_stdin;
+
+#[cfg(windows)]
+use crate::CREATE_NO_WINDOW;
+
+/// The submit being validated.
+pub struct SubmitValidation<'a> {
+    pub repo_path: &'a Path,
+    pub target_branch: &'a str,
+    pub commit_message: &'a str,
+
+    /// Repo-relative, forward-slash-separated paths. Deleted files should be left out: removing a
+    /// file is always allowed, whatever it was named or however big it was.
+    pub files: &'a [String],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationFailure {
+    pub validator: String,
+
+    /// None when the failure is about the submit as a whole, like its commit message.
+    pub path: Option<String>,
+    pub reason: String,
+}
+
+/// What a `command` validator is given on stdin.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandValidatorInput<'a> {
+    pub repo_path: &'a Path,
+    pub target_branch: &'a str,
+    pub commit_message: &'a str,
+
+    // Only the files the validator applies to
+    pub files: &'a [String],
+}
+
+/// What a `command` validator answers with on stdout. An empty list passes the submit.
+#[derive(Debug, Default, Deserialize)]
+pub struct CommandValidatorOutput {
+    #[serde(default)]
+    pub failures: Vec<CommandValidatorFailure>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommandValidatorFailure {
+    #[serde(default)]
+    pub path: Option<String>,
+    pub message: String,
+}
+
+/// (validator name, error) for every validator whose globs or regex don't compile. Those are
+/// skipped when validating a submit.
+pub fn validator_errors(validators: &[SubmitValidator]) -> Vec<(String, String)> {
+    validators
+        .iter()
+        .filter_map(|validator| {
+            CompiledValidator::compile(validator)
+                .err()
+                .map(|e| (validator.name.clone(), e.to_string()))
+        })
+        .collect()
+}
+
+/// Runs every validator that applies to the submit's target branch, one after the other, and
+/// collects what failed. An empty result means the submit can go ahead.
+pub async fn run_submit_validators(
+    validators: &[SubmitValidator],
+    submit: &SubmitValidation<'_>,
+) -> Vec<ValidationFailure> {
+    let mut failures = vec![];
+
+    for validator in validators {
+        if !validator.branches.is_empty()
+            && !validator
+                .branches
+                .iter()
+                .any(|branch| branch == submit.target_branch)
+        {
+            continue;
+        }
+
+        let compiled = match CompiledValidator::compile(validator) {
+            Ok(compiled) => compiled,
+            Err(e) => {
+                // already reported when the config was loaded
+                warn!(
+                    "Skipping invalid submit validator '{}': {}",
+                    validator.name, e
+                );
+                continue;
+            }
+        };
+
+        let files: Vec<String> = submit
+            .files
+            .iter()
+            .filter(|path| compiled.applies_to(path))
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+
+        failures.extend(
+            compiled
+                .run(&files, submit)
+                .await
+                .into_iter()
+                .map(|(path, reason)| ValidationFailure {
+                    validator: validator.name.clone(),
+                    path,
+                    reason,
+                }),
+        );
+    }
+
+    failures
+}
+
+struct CompiledValidator<'a> {
+    validator: &'a SubmitValidator,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    regex: Option<Regex>,
+}
+
+impl<'a> CompiledValidator<'a> {
+    fn compile(validator: &'a SubmitValidator) -> anyhow::Result<Self> {
+        let include = if validator.files.is_empty() {
+            None
+        } else {
+            Some(compile_globs(&validator.files)?)
+        };
+        let exclude = compile_globs(&validator.exclude_files)?;
+
+        let regex = match &validator.rule {
+            SubmitValidatorRule::PathRegex { pattern }
+            | SubmitValidatorRule::CommitMessageRegex { pattern } => Some(Regex::new(pattern)?),
+            SubmitValidatorRule::Command { command, .. } if command.is_empty() => {
+                return Err(anyhow!("command is empty"));
+            }
+            _ => None,
+        };
+
+        Ok(CompiledValidator {
+            validator,
+            include,
+            exclude,
+            regex,
+        })
+    }
+
+    fn applies_to(&self, path: &str) -> bool {
+        self.include.as_ref().is_none_or(|set| set.is_match(path)) && !self.exclude.is_match(path)
+    }
+
+    // (path, reason) for each failure
+    async fn run(
+        &self,
+        files: &[String],
+        submit: &SubmitValidation<'_>,
+    ) -> Vec<(Option<String>, String)> {
+        let reason = |default: String| {
+            if self.validator.message.is_empty() {
+                default
+            } else {
+                self.validator.message.clone()
+            }
+        };
+
+        match &self.validator.rule {
+            SubmitValidatorRule::Forbidden => files
+                .iter()
+                .map(|path| {
+                    (
+                        Some(path.clone()),
+                        reason("These files are not allowed to be submitted".to_string()),
+                    )
+                })
+                .collect(),
+            SubmitValidatorRule::MaxFileSize { max_bytes } => files
+                .iter()
+                .filter(|path| {
+                    std::fs::metadata(submit.repo_path.join(path))
+                        .is_ok_and(|metadata| metadata.len() > *max_bytes)
+                })
+                .map(|path| {
+                    (
+                        Some(path.clone()),
+                        reason(format!(
+                            "These files are larger than the {} limit",
+                            format_size(*max_bytes)
+                        )),
+                    )
+                })
+                .collect(),
+            SubmitValidatorRule::PathRegex { pattern } => {
+                let regex = self.regex.as_ref().expect("compiled with the validator");
+                files
+                    .iter()
+                    .filter(|path| !regex.is_match(path))
+                    .map(|path| {
+                        (
+                            Some(path.clone()),
+                            reason(format!("These paths don't match `{pattern}`")),
+                        )
+                    })
+                    .collect()
+            }
+            SubmitValidatorRule::CommitMessageRegex { pattern } => {
+                let regex = self.regex.as_ref().expect("compiled with the validator");
+                if regex.is_match(submit.commit_message) {
+                    vec![]
+                } else {
+                    vec![(
+                        None,
+                        reason(format!("The commit message doesn't match `{pattern}`")),
+                    )]
+                }
+            }
+            SubmitValidatorRule::Command {
+                command,
+                args,
+                timeout_seconds,
+            } => {
+                let input = CommandValidatorInput {
+                    repo_path: submit.repo_path,
+                    target_branch: submit.target_branch,
+                    commit_message: submit.commit_message,
+                    files,
+                };
+                let result = run_command(
+                    submit.repo_path,
+                    command,
+                    args,
+                    &input,
+                    Duration::from_secs(*timeout_seconds),
+                )
+                .await
+                .and_then(parse_command_output);
+
+                match result {
+                    Ok(output) => output
+                        .failures
+                        .into_iter()
+                        .map(|failure| (failure.path, failure.message))
+                        .collect(),
+                    // fail closed: a validator that can't run hasn't passed anything
+                    Err(e) => vec![(None, format!("The validator couldn't be run: {e}"))],
+                }
+            }
+        }
+    }
+}
+
+async fn run_command(
+    repo_path: &Path,
+    command: &str,
+    args: &[String],
+    input: &CommandValidatorInput<'_>,
+    timeout: Duration,
+) -> anyhow::Result<Output> {
+    let program = if command.contains('/') || command.contains('\\') {
+        repo_path.join(command)
+    } else {
+        PathBuf::from(command)
+    };
+
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    cmd.current_dir(repo_path);
+    cmd.kill_on_drop(true);
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    let stdin = serde_json::to_vec(input)?;
+    tokio::time::timeout(timeout, run_with_stdin(cmd, stdin))
+        .await
+        .map_err(|_| anyhow!("timed out after {}s", timeout.as_secs()))?
+}
+
+/// A validator that exits non-zero has failed the submit even if it reports no failures, so a
+/// crashing script can't let everything through.
+pub fn parse_command_output(output: Output) -> anyhow::Result<CommandValidatorOutput> {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let parsed = if stdout.trim().is_empty() {
+        Ok(CommandValidatorOutput::default())
+    } else {
+        serde_json::from_str::<CommandValidatorOutput>(&stdout)
+    };
+
+    match parsed {
+        Ok(parsed) if output.status.success() || !parsed.failures.is_empty() => Ok(parsed),
+        Ok(_) => Err(anyhow!(
+            "exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(e) => Err(anyhow!("its output isn't valid JSON: {e}")),
+    }
+}
+
+fn compile_globs(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .literal_separator(true)
+                .backslash_escape(false)
+                .build()?,
+        );
+    }
+
+    Ok(builder.build()?)
+}
+
+fn format_size(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    if bytes >= MB && bytes.is_multiple_of(MB) {
+        format!("{} MB", bytes / MB)
+    } else {
+        format!("{bytes} byte")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator(rule: SubmitValidatorRule) -> SubmitValidator {
+        SubmitValidator {
+            name: "test".to_string(),
+            message: String::new(),
+            files: vec![],
+            exclude_files: vec![],
+            branches: vec![],
+            rule,
+        }
+    }
+
+    fn paths(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    async fn validate(
+        validators: &[SubmitValidator],
+        repo_path: &Path,
+        files: &[String],
+        commit_message: &str,
+    ) -> Vec<ValidationFailure> {
+        run_submit_validators(
+            validators,
+            &SubmitValidation {
+                repo_path,
+                target_branch: "main",
+                commit_message,
+                files,
+            },
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn forbidden_files_are_scoped_by_globs_and_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut forbidden = validator(SubmitValidatorRule::Forbidden);
+        forbidden.files = paths(&["Content/Developers/**"]);
+        forbidden.exclude_files = paths(&["Content/Developers/Shared/**"]);
+        forbidden.message = "Developer folders stay local".to_string();
+
+        let files = paths(&[
+            "Content/Developers/alice/Test.uasset",
+            "Content/Developers/Shared/Kit.uasset",
+            "Content/Maps/Main.umap",
+        ]);
+        let failures = validate(&[forbidden.clone()], dir.path(), &files, "fix").await;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].path.as_deref(),
+            Some("Content/Developers/alice/Test.uasset")
+        );
+        assert_eq!(failures[0].reason, "Developer folders stay local");
+
+        forbidden.branches = paths(&["release"]);
+        assert!(validate(&[forbidden], dir.path(), &files, "fix")
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn size_and_naming_rules_check_each_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("Content")).unwrap();
+        std::fs::write(dir.path().join("Content/T_Big.uasset"), vec![0u8; 64]).unwrap();
+        std::fs::write(dir.path().join("Content/Small.uasset"), vec![0u8; 8]).unwrap();
+
+        let mut naming = validator(SubmitValidatorRule::PathRegex {
+            pattern: r"/(T|SM|BP)_[^/]+$".to_string(),
+        });
+        naming.files = paths(&["Content/**/*.uasset"]);
+        let size = validator(SubmitValidatorRule::MaxFileSize { max_bytes: 32 });
+
+        let files = paths(&["Content/T_Big.uasset", "Content/Small.uasset"]);
+        let failures = validate(&[naming, size], dir.path(), &files, "fix").await;
+        let failed: Vec<_> = failures.iter().filter_map(|f| f.path.as_deref()).collect();
+        assert_eq!(failed, vec!["Content/Small.uasset", "Content/T_Big.uasset"]);
+    }
+
+    #[tokio::test]
+    async fn commit_message_rule_reports_the_submit_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let rule = validator(SubmitValidatorRule::CommitMessageRegex {
+            pattern: r"^[A-Z]+-\d+ ".to_string(),
+        });
+        let files = paths(&["a.txt", "b.txt"]);
+
+        let failures = validate(std::slice::from_ref(&rule), dir.path(), &files, "fix").await;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].path, None);
+        assert!(validate(&[rule], dir.path(), &files, "GAME-12 fix")
+            .await
+            .is_empty());
+    }
+
+    #[test]
+    fn invalid_validators_are_reported() {
+        let broken = validator(SubmitValidatorRule::PathRegex {
+            pattern: "(".to_string(),
+        });
+        let errors = validator_errors(&[broken, validator(SubmitValidatorRule::Forbidden)]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "test");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_output_must_be_json_and_a_failing_exit_fails() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let output = |code: i32, stdout: &str| Output {
+            status: std::process::ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: b"boom".to_vec(),
+        };
+
+        let parsed = parse_command_output(output(
+            1,
+            r#"{"failures": [{"path": "a.uasset", "message": "texture is 8192px"}]}"#,
+        ))
+        .unwrap();
+        assert_eq!(parsed.failures.len(), 1);
+        assert_eq!(parsed.failures[0].path.as_deref(), Some("a.uasset"));
+
+        assert!(parse_command_output(output(0, ""))
+            .unwrap()
+            .failures
+            .is_empty());
+        assert!(parse_command_output(output(1, "")).is_err());
+        assert!(parse_command_output(output(0, "not json")).is_err());
+    }
+}
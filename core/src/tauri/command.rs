@@ -4,8 +4,8 @@ use crate::types::commits::Commit;
 use crate::types::config::{AppConfig, RepoConfig};
 use crate::types::logs::LogEntry;
 use crate::types::repo::{
-    CloneRequest, ConfigureUserRequest, LockRequest, PullResponse, RebaseStatusResponse,
-    RevertFilesRequest,
+    CloneRequest, ConfigureUserRequest, ConflictMergePreview, LockRequest, PullResponse,
+    RebaseConflictsResponse, RebaseStatusResponse, ResolveConflictsRequest, RevertFilesRequest,
 };
 use futures::TryFutureExt;
 
@@ -472,6 +472,85 @@ pub async fn rebase(state: tauri::State<'_, State>) -> Result<(), TauriError> {
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_rebase_conflicts(
+    state: tauri::State<'_, State>,
+) -> Result<RebaseConflictsResponse, TauriError> {
+    let res = state
+        .client
+        .get(format!(
+            "{}/repo/diagnostics/rebase/conflicts",
+            state.server_url
+        ))
+        .send()
+        .await?;
+
+    if res.status().is_client_error() || res.status().is_server_error() {
+        let status_code = res.status().as_u16();
+        let body = res.text().await?;
+        return Err(TauriError {
+            message: body,
+            status_code,
+        });
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn get_conflict_merge_preview(
+    state: tauri::State<'_, State>,
+    path: String,
+) -> Result<ConflictMergePreview, TauriError> {
+    let res = state
+        .client
+        .get(format!(
+            "{}/repo/diagnostics/rebase/conflicts/preview",
+            state.server_url
+        ))
+        .query(&[("path", path)])
+        .send()
+        .await?;
+
+    if res.status().is_client_error() || res.status().is_server_error() {
+        let status_code = res.status().as_u16();
+        let body = res.text().await?;
+        return Err(TauriError {
+            message: body,
+            status_code,
+        });
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn resolve_rebase_conflicts(
+    state: tauri::State<'_, State>,
+    req: ResolveConflictsRequest,
+) -> Result<RebaseConflictsResponse, TauriError> {
+    let res = state
+        .client
+        .post(format!(
+            "{}/repo/diagnostics/rebase/conflicts/resolve",
+            state.server_url
+        ))
+        .json(&req)
+        .send()
+        .await?;
+
+    if res.status().is_client_error() || res.status().is_server_error() {
+        let status_code = res.status().as_u16();
+        let body = res.text().await?;
+        return Err(TauriError {
+            message: body,
+            status_code,
+        });
+    }
+
+    Ok(res.json().await?)
+}
+
 #[tauri::command]
 pub async fn acquire_locks(
     state: tauri::State<'_, State>,
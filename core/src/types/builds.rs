@@ -1,3 +1,4 @@
+use crate::longtail::bisect::BisectVerdict;
 use crate::storage::{ArtifactEntry, MethodPrefix};
 use serde::{Deserialize, Serialize};
 
@@ -25,3 +26,42 @@ pub struct LaunchOptions {
     pub name: String,
     pub launch_mode: LaunchMode,
 }
+
+// Picks an installed build by commit, and by base name when several kinds of build are installed
+// for that commit
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallRequest {
+    pub commit: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_name: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_options: Option<LaunchOptions>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartBisectRequest {
+    pub good: String,
+    pub bad: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkBisectRequest {
+    pub commit: String,
+    pub verdict: BisectVerdict,
+}
+
+// Syncs the session's next candidate, launching it when launch options are given
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncBisectRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_options: Option<LaunchOptions>,
+}
@@ -7,7 +7,10 @@ use std::sync::Arc;
 
 #[cfg(not(target_os = "windows"))]
 use crate::fs::LocalDownloadPath;
-use crate::storage::StorageSchemaVersion;
+use crate::longtail::cache::DEFAULT_CHUNK_CACHE_SIZE_GB;
+use crate::longtail::installs::DEFAULT_MAX_INSTALLED_BUILDS;
+use crate::storage::{ArtifactProviderConfig, RetentionPolicy, StorageSchemaVersion};
+use crate::types::repo::SnapshotRetention;
 use crate::AWS_REGION;
 use anyhow::{anyhow, bail, Result};
 use axum::http::StatusCode;
@@ -137,6 +140,24 @@ pub struct AppConfig {
     #[serde(default, rename = "maxClientCacheSizeGb")]
     pub max_client_cache_size_gb: u64,
 
+    // Budget for the chunk cache shared by client, engine and DLL downloads
+    #[serde(
+        default = "default_max_chunk_cache_size_gb",
+        rename = "maxChunkCacheSizeGb"
+    )]
+    pub max_chunk_cache_size_gb: u64,
+
+    // Client builds kept installed side by side, including the one most recently synced
+    #[serde(
+        default = "default_max_installed_builds",
+        rename = "maxInstalledBuilds"
+    )]
+    pub max_installed_builds: usize,
+
+    // How long snapshots are kept before background maintenance prunes them
+    #[serde(default, rename = "snapshotRetention")]
+    pub snapshot_retention: SnapshotRetention,
+
     #[serde(default, rename = "recordPlay")]
     pub record_play: bool,
 
@@ -184,6 +205,14 @@ fn default_playtest_region() -> String {
     AWS_REGION.to_string()
 }
 
+fn default_max_chunk_cache_size_gb() -> u64 {
+    DEFAULT_CHUNK_CACHE_SIZE_GB
+}
+
+fn default_max_installed_builds() -> usize {
+    DEFAULT_MAX_INSTALLED_BUILDS
+}
+
 impl AppConfig {
     pub fn new(app_name: &str) -> Self {
         #[cfg(target_os = "windows")]
@@ -227,6 +256,9 @@ impl AppConfig {
             otlp_endpoint: None,
             otlp_headers: None,
             max_client_cache_size_gb: 32,
+            max_chunk_cache_size_gb: default_max_chunk_cache_size_gb(),
+            max_installed_builds: default_max_installed_builds(),
+            snapshot_retention: Default::default(),
             initialized: false,
             last_quick_submit_type: None,
             last_quick_submit_scope: None,
@@ -329,6 +361,19 @@ impl AppConfig {
             }
         }
 
+        // Same reasoning as above: a broken validator is skipped rather than failing the load, so
+        // this is where its author finds out.
+        for (name, message) in
+            crate::submit_validation::validator_errors(&repo_config.submit_validators)
+        {
+            tracing::error!(
+                validator = %name,
+                "invalid submitValidators entry '{}': {}",
+                name,
+                message
+            );
+        }
+
         Ok(repo_config)
     }
 
@@ -452,6 +497,97 @@ pub struct PlaytestProfile {
     args: String,
 }
 
+/// A named subset of the repo to hydrate, e.g. artist-maps, audio or code-only. The working tree
+/// is limited with a cone-mode sparse checkout, and LFS content inside it is further narrowed
+/// with `lfs.fetchinclude`/`lfs.fetchexclude`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ContentProfile {
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    // Directories to check out in cone mode. Files at the repo root are always checked out.
+    // Empty disables sparse checkout.
+    #[serde(default, rename = "sparseDirectories")]
+    pub sparse_directories: Vec<String>,
+
+    // Patterns use the same gitignore-style rules as git-lfs. An empty include list fetches
+    // everything that isn't excluded.
+    #[serde(default, rename = "lfsFetchInclude")]
+    pub lfs_fetch_include: Vec<String>,
+
+    #[serde(default, rename = "lfsFetchExclude")]
+    pub lfs_fetch_exclude: Vec<String>,
+}
+
+/// A project-defined check run against the files in a submit before its PR is opened. Files that
+/// fail are reported alongside blocked files, and the submit doesn't go through.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubmitValidator {
+    pub name: String,
+
+    // Shown next to the files that fail. Each rule has a generic one if it's left out.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub message: String,
+
+    // Globs, using the same rules as `blockedFileGlobs`, picking the files the rule applies to.
+    // Empty applies it to every file in the submit.
+    #[serde(default)]
+    pub files: Vec<String>,
+
+    #[serde(default, rename = "excludeFiles")]
+    pub exclude_files: Vec<String>,
+
+    // Target branches the rule applies to. Empty applies it to all of them.
+    #[serde(default)]
+    pub branches: Vec<String>,
+
+    #[serde(flatten)]
+    pub rule: SubmitValidatorRule,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SubmitValidatorRule {
+    // None of the files may be submitted
+    Forbidden,
+
+    MaxFileSize {
+        #[serde(rename = "maxBytes")]
+        max_bytes: u64,
+    },
+
+    // Every file's repo-relative path must match
+    PathRegex {
+        pattern: String,
+    },
+
+    // The commit message must match. Only checked if the submit has a file the rule applies to.
+    CommitMessageRegex {
+        pattern: String,
+    },
+
+    // Runs an external validator, which is given the submit as JSON on stdin and answers with the
+    // failures as JSON on stdout. A relative command path is resolved against the repo root.
+    Command {
+        command: String,
+
+        #[serde(default)]
+        args: Vec<String>,
+
+        #[serde(
+            default = "default_validator_timeout_seconds",
+            rename = "timeoutSeconds"
+        )]
+        timeout_seconds: u64,
+    },
+}
+
+fn default_validator_timeout_seconds() -> u64 {
+    60
+}
+
 /// Serde default for `TargetBranchConfig::uses_merge_queue`. A plain
 /// `#[serde(default)]` would fall back to `bool::default()` (`false`), which
 /// contradicts `TargetBranchConfig::default()` below (`true`) — a config
@@ -470,7 +606,68 @@ pub struct TargetBranchConfig {
     pub uses_merge_queue: bool,
 
     #[serde(default, rename = "blockedFileGlobs")]
-    pub blocked_file_globs: Vec<String>,
+    pub blocked_file_globs: Vec<BlockedFileGlob>,
+}
+
+/// One `blockedFileGlobs` entry: either a bare pattern, or a pattern with the users it doesn't
+/// apply to and the reason shown when it blocks a submit. Entries are applied in order like a
+/// `.gitignore`, so a later `!`-prefixed pattern unblocks files an earlier one blocked.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum BlockedFileGlob {
+    Pattern(String),
+    Rule(BlockedFileRule),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlockedFileRule {
+    pub pattern: String,
+
+    // GitHub usernames, compared case-insensitively, the pattern doesn't block
+    #[serde(
+        default,
+        rename = "allowedUsers",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub allowed_users: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl BlockedFileGlob {
+    pub fn pattern(&self) -> &str {
+        match self {
+            BlockedFileGlob::Pattern(pattern) => pattern,
+            BlockedFileGlob::Rule(rule) => &rule.pattern,
+        }
+    }
+
+    pub fn allowed_users(&self) -> &[String] {
+        match self {
+            BlockedFileGlob::Pattern(_) => &[],
+            BlockedFileGlob::Rule(rule) => &rule.allowed_users,
+        }
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            BlockedFileGlob::Pattern(_) => None,
+            BlockedFileGlob::Rule(rule) => rule.reason.as_deref(),
+        }
+    }
+}
+
+impl From<&str> for BlockedFileGlob {
+    fn from(pattern: &str) -> Self {
+        BlockedFileGlob::Pattern(pattern.to_string())
+    }
+}
+
+impl From<String> for BlockedFileGlob {
+    fn from(pattern: String) -> Self {
+        BlockedFileGlob::Pattern(pattern)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -537,6 +734,12 @@ pub struct RepoConfig {
 
     #[serde(default, rename = "serversEnabled")]
     pub servers_enabled: bool,
+
+    #[serde(default, rename = "contentProfiles")]
+    pub content_profiles: Vec<ContentProfile>,
+
+    #[serde(default, rename = "submitValidators")]
+    pub submit_validators: Vec<SubmitValidator>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -573,6 +776,8 @@ impl Default for RepoConfig {
             editor_url_scheme: None,
             builds_enabled: false,
             servers_enabled: false,
+            content_profiles: vec![],
+            submit_validators: vec![],
         }
     }
 }
@@ -610,13 +815,17 @@ impl RepoConfig {
     /// method returning an empty slice for an unknown branch IS that
     /// degrade-to-nothing-blocked behavior, so no separate guard is needed at
     /// call sites.
-    pub fn blocked_globs_for_branch(&self, branch: &str) -> &[String] {
+    pub fn blocked_globs_for_branch(&self, branch: &str) -> &[BlockedFileGlob] {
         self.target_branches
             .iter()
             .find(|b| b.name == branch)
             .map(|b| b.blocked_file_globs.as_slice())
             .unwrap_or(&[])
     }
+
+    pub fn content_profile(&self, name: &str) -> Option<&ContentProfile> {
+        self.content_profiles.iter().find(|p| p.name == name)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -683,6 +892,12 @@ pub struct DynamicConfig {
     #[serde(default)]
     pub storage_schema: StorageSchemaVersion,
 
+    #[serde(default, rename = "artifactProvider")]
+    pub artifact_provider: ArtifactProviderConfig,
+
+    #[serde(default, rename = "artifactRetention")]
+    pub artifact_retention: Vec<RetentionPolicy>,
+
     #[serde(default, rename = "profileDataPath")]
     pub profile_data_path: String,
 
@@ -708,6 +923,37 @@ pub struct DynamicConfig {
     pub game_server_clusters: Option<Vec<GameServerCluster>>,
 }
 
+impl DynamicConfig {
+    // Per-user overrides merged over the dynamic config in the artifact bucket
+    pub fn override_path() -> Option<PathBuf> {
+        directories_next::BaseDirs::new().map(|dirs| {
+            dirs.config_dir()
+                .join("Friendshipper")
+                .join("dynamic-config.json")
+        })
+    }
+
+    // The overrides on their own, so a site mirroring builds on a file share can pick its
+    // artifact provider before, or without, signing in to AWS
+    pub fn load_override() -> Option<DynamicConfig> {
+        let path = Self::override_path().filter(|path| path.exists())?;
+        match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(serde_json::from_str(&json)?))
+        {
+            Ok(config) => Some(config),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load dynamic config overrides from {:?}: {}",
+                    path,
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnrealVerSelDiagResponse {
     pub valid_version_selector: bool,
@@ -719,7 +965,10 @@ pub struct UnrealVerSelDiagResponse {
 #[cfg(test)]
 mod tests {
     use crate::types::config::CUSTOM_ENGINE_ASSOCIATION_REGEX;
-    use crate::types::config::{AppConfig, ProjectRepoConfig, RepoConfig, TargetBranchConfig};
+    use crate::types::config::{
+        AppConfig, BlockedFileGlob, BlockedFileRule, ProjectRepoConfig, RepoConfig,
+        SubmitValidatorRule, TargetBranchConfig,
+    };
     use tempfile::TempDir;
 
     /// Writes `yaml` to `<tempdir>/friendshipper.yaml` and runs it through the
@@ -813,7 +1062,10 @@ targetBranches:
             .expect("main branch present");
         assert_eq!(
             main_branch.blocked_file_globs,
-            vec!["**/*.uasset".to_string(), "**/*.umap".to_string()]
+            vec![
+                BlockedFileGlob::from("**/*.uasset"),
+                BlockedFileGlob::from("**/*.umap")
+            ]
         );
 
         let content_branch = repo_config
@@ -824,12 +1076,92 @@ targetBranches:
         assert!(content_branch.blocked_file_globs.is_empty());
     }
 
+    #[test]
+    fn test_submit_validators_deserialize_by_type() {
+        let yaml = r#"
+submitValidators:
+  - name: No developer folders
+    type: forbidden
+    files:
+      - "Content/Developers/**"
+  - name: Texture size
+    type: maxFileSize
+    maxBytes: 52428800
+    branches:
+      - main
+  - name: Texture dimensions
+    type: command
+    command: Tools/check_textures.py
+    args: ["--max", "4096"]
+"#;
+
+        let (_dir, result) = load_repo_config_from_yaml(yaml);
+        let repo_config = result.expect("config with submitValidators must deserialize");
+        let validators = &repo_config.submit_validators;
+
+        assert_eq!(validators.len(), 3);
+        assert_eq!(validators[0].rule, SubmitValidatorRule::Forbidden);
+        assert_eq!(
+            validators[0].files,
+            vec!["Content/Developers/**".to_string()]
+        );
+        assert_eq!(
+            validators[1].rule,
+            SubmitValidatorRule::MaxFileSize {
+                max_bytes: 52428800
+            }
+        );
+        assert_eq!(validators[1].branches, vec!["main".to_string()]);
+        assert_eq!(
+            validators[2].rule,
+            SubmitValidatorRule::Command {
+                command: "Tools/check_textures.py".to_string(),
+                args: vec!["--max".to_string(), "4096".to_string()],
+                timeout_seconds: 60,
+            }
+        );
+    }
+
+    #[test]
+    fn test_blocked_file_globs_accept_rules_alongside_bare_patterns() {
+        let yaml = r#"
+targetBranches:
+  - name: main
+    blockedFileGlobs:
+      - "Content/Characters/**"
+      - "!Content/Characters/Shared/**"
+      - pattern: "Content/Maps/**"
+        allowedUsers: ["level-designer"]
+        reason: "Maps are submitted by level design"
+"#;
+
+        let (_dir, result) = load_repo_config_from_yaml(yaml);
+        let repo_config = result.expect("config with blocked file rules must deserialize");
+        let globs = &repo_config.target_branches[0].blocked_file_globs;
+
+        assert_eq!(globs.len(), 3);
+        assert_eq!(globs[0].pattern(), "Content/Characters/**");
+        assert_eq!(globs[1].pattern(), "!Content/Characters/Shared/**");
+        assert_eq!(
+            globs[2],
+            BlockedFileGlob::Rule(BlockedFileRule {
+                pattern: "Content/Maps/**".to_string(),
+                allowed_users: vec!["level-designer".to_string()],
+                reason: Some("Maps are submitted by level design".to_string()),
+            })
+        );
+
+        let json = serde_json::to_string(globs).expect("serialize");
+        assert!(json.starts_with(r#"["Content/Characters/**","#));
+        assert!(json.contains(r#""allowedUsers":["level-designer"]"#));
+    }
+
     #[test]
     fn test_target_branch_config_serializes_camel_case() {
         let branch = TargetBranchConfig {
             name: "main".to_string(),
             uses_merge_queue: true,
-            blocked_file_globs: vec!["**/*.uasset".to_string()],
+            blocked_file_globs: vec!["**/*.uasset".into()],
         };
 
         let json = serde_json::to_string(&branch).expect("serialize");
@@ -845,14 +1177,14 @@ targetBranches:
             target_branches: vec![TargetBranchConfig {
                 name: "main".to_string(),
                 uses_merge_queue: true,
-                blocked_file_globs: vec!["**/*.uasset".to_string()],
+                blocked_file_globs: vec!["**/*.uasset".into()],
             }],
             ..Default::default()
         };
 
         assert_eq!(
             repo_config.blocked_globs_for_branch("main"),
-            &["**/*.uasset".to_string()]
+            &[BlockedFileGlob::from("**/*.uasset")]
         );
         assert!(repo_config
             .blocked_globs_for_branch("some-branch-not-in-config")
@@ -881,7 +1213,7 @@ targetBranches:
         let repo_config = result.expect("a malformed glob must not fail config load");
         assert_eq!(
             repo_config.target_branches[0].blocked_file_globs,
-            vec!["Content/[unterminated".to_string()],
+            vec![BlockedFileGlob::from("Content/[unterminated")],
             "the raw pattern list is preserved as-is; validation only logs, it does not filter"
         );
 
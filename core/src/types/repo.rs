@@ -1,3 +1,4 @@
+use crate::types::config::{BlockedFileGlob, ContentProfile};
 use crate::types::locks::Lock;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -330,6 +331,197 @@ pub struct RebaseStatusResponse {
     pub head_name_exists: bool,
 }
 
+/// The index lock of the active worktree. A lock is stale once no git process is running in the
+/// repo to release it, which happens when git crashes or is killed mid-write.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexLockStatus {
+    pub exists: bool,
+    pub path: String,
+    pub age_seconds: u64,
+    pub stale: bool,
+    pub git_processes: Vec<u32>,
+}
+
+/// One stage of an unmerged index entry, as listed by `git ls-files -u`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictBlob {
+    pub sha: String,
+    pub mode: String,
+}
+
+/// Which version of a conflicted file to keep. While rebasing, `Ours` is the upstream commit
+/// being rebased onto and `Theirs` is the local commit being replayed on top of it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebaseConflictFile {
+    pub path: String,
+
+    // Any stage can be missing: no base for a file added on both sides, no ours or theirs for a
+    // file deleted on that side
+    pub base: Option<ConflictBlob>,
+    pub ours: Option<ConflictBlob>,
+    pub theirs: Option<ConflictBlob>,
+
+    // LFS tracked or marked -text, so there is no useful 3-way merge
+    pub is_binary: bool,
+
+    // only filled in for binary files, where picking a side is the only option
+    #[serde(default)]
+    pub locked_by: Option<String>,
+    #[serde(default)]
+    pub last_change: Option<FileHistoryRevision>,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebaseConflictsResponse {
+    pub in_progress: bool,
+
+    // the commit being rebased onto and the branch being rebased
+    pub onto: Option<String>,
+    pub head_name: Option<String>,
+
+    // the local commit that stopped on conflicts
+    pub stopped_at: Option<String>,
+    pub files: Vec<RebaseConflictFile>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictMergePreview {
+    pub path: String,
+
+    // file contents with conflict markers around each hunk that could not be merged
+    pub merged: String,
+    pub conflict_count: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictResolution {
+    pub path: String,
+    pub side: ConflictSide,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveConflictsRequest {
+    #[serde(default)]
+    pub resolutions: Vec<ConflictResolution>,
+
+    // run `git rebase --continue` once every conflict is resolved
+    #[serde(default)]
+    pub continue_rebase: bool,
+}
+
+/// An LFS object tracked at HEAD, as listed by `git lfs ls-files`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsFileInfo {
+    pub path: String,
+    pub size: u64,
+
+    // already in the local LFS store, so hydrating it costs nothing
+    pub downloaded: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentProfilePlan {
+    pub profile: String,
+
+    // LFS files the profile hydrates, and the part of them not downloaded yet
+    pub included_files: u64,
+    pub included_bytes: u64,
+    pub download_files: u64,
+    pub download_bytes: u64,
+
+    // LFS files outside the sparse checkout or filtered out by the fetch rules
+    pub excluded_files: u64,
+    pub excluded_bytes: u64,
+
+    // the biggest pending downloads, largest first
+    pub largest_downloads: Vec<LfsFileInfo>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedFileAuditRequest {
+    // Defaults to the configured target branch
+    #[serde(default)]
+    pub target_branch: Option<String>,
+
+    // Candidate patterns to try out. Defaults to the ones configured for the target branch.
+    #[serde(default)]
+    pub patterns: Option<Vec<BlockedFileGlob>>,
+
+    // Checks allow lists as this GitHub user. Without one, only negated patterns let files through.
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+/// What a list of `blockedFileGlobs` patterns does to every file tracked at a commit.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedFileAudit {
+    pub target_branch: String,
+    pub commit: String,
+    pub total_files: usize,
+    pub blocked_files: usize,
+
+    // Every pattern that compiled, in order, with the files it has the last word on
+    pub patterns: Vec<BlockedFilePatternAudit>,
+
+    // Patterns that compiled but don't match a single file
+    pub unmatched_patterns: Vec<String>,
+    pub invalid: Vec<BlockedFilePatternDiagnostic>,
+    pub warnings: Vec<BlockedFilePatternDiagnostic>,
+
+    // No invalid or unmatched patterns. Warnings don't count, so CI can gate on this alone.
+    pub ok: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedFilePatternAudit {
+    pub pattern: String,
+    pub negated: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_users: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    // Blocked by this pattern, or let through by it if it's negated or allows the user
+    pub files: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedFilePatternDiagnostic {
+    pub pattern: String,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentProfilesResponse {
+    pub profiles: Vec<ContentProfile>,
+
+    // the profile last applied to this clone, if any
+    pub active: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ObjectCountResponse {
@@ -446,16 +638,121 @@ pub struct CommitFileInfo {
     pub display_name: String,
 }
 
-#[derive(Clone, Default, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotKind {
+    // Saved by the user from the UI
+    #[default]
+    Manual,
+
+    // Saved on the user's behalf before an operation that may clobber their changes, ex. pre-pull
+    Automatic,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Snapshot {
+    // Stable name under refs/friendshipper/snapshots/. The commit changes when tags are edited,
+    // the id never does.
+    #[serde(default)]
+    pub id: String,
     pub commit: String,
     pub message: String,
     pub timestamp: DateTime<Utc>,
 
+    #[serde(default)]
+    pub author: String,
+
+    // Branch checked out when the snapshot was taken, empty if HEAD was detached or unknown
+    #[serde(default)]
+    pub branch: String,
+
+    #[serde(default)]
+    pub files: Vec<String>,
+
+    #[serde(default)]
+    pub kind: SnapshotKind,
+
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    // Only set on snapshots still stored in the stash list by older versions
     #[serde(skip)]
     pub stash_index: String,
 }
 
+pub const DEFAULT_AUTOMATIC_SNAPSHOT_MAX_AGE_DAYS: u32 = 14;
+pub const DEFAULT_AUTOMATIC_SNAPSHOT_KEEP_COUNT: usize = 20;
+
+// How long snapshots are kept before maintenance prunes them
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotRetention {
+    // Automatic snapshots are kept while they're younger than this, or while they're among the
+    // most recent automatic_keep_count
+    #[serde(default = "default_automatic_snapshot_max_age_days")]
+    pub automatic_max_age_days: u32,
+
+    #[serde(default = "default_automatic_snapshot_keep_count")]
+    pub automatic_keep_count: usize,
+
+    // None keeps manual snapshots until the user deletes them
+    #[serde(default)]
+    pub manual_max_age_days: Option<u32>,
+}
+
+fn default_automatic_snapshot_max_age_days() -> u32 {
+    DEFAULT_AUTOMATIC_SNAPSHOT_MAX_AGE_DAYS
+}
+
+fn default_automatic_snapshot_keep_count() -> usize {
+    DEFAULT_AUTOMATIC_SNAPSHOT_KEEP_COUNT
+}
+
+impl Default for SnapshotRetention {
+    fn default() -> Self {
+        Self {
+            automatic_max_age_days: DEFAULT_AUTOMATIC_SNAPSHOT_MAX_AGE_DAYS,
+            automatic_keep_count: DEFAULT_AUTOMATIC_SNAPSHOT_KEEP_COUNT,
+            manual_max_age_days: None,
+        }
+    }
+}
+
+impl SnapshotRetention {
+    // The snapshots past this policy, given newest first as they're listed
+    pub fn expired<'a>(&self, snapshots: &'a [Snapshot], now: DateTime<Utc>) -> Vec<&'a Snapshot> {
+        let mut automatic = 0;
+        snapshots
+            .iter()
+            .filter(|snapshot| {
+                let age_days = (now - snapshot.timestamp).num_days();
+                match snapshot.kind {
+                    SnapshotKind::Automatic => {
+                        automatic += 1;
+                        automatic > self.automatic_keep_count
+                            && age_days >= i64::from(self.automatic_max_age_days)
+                    }
+                    SnapshotKind::Manual => self
+                        .manual_max_age_days
+                        .is_some_and(|max| age_days >= i64::from(max)),
+                }
+            })
+            .collect()
+    }
+}
+
+// Snapshots past the retention policy
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotPrunePlan {
+    // Everything in them is already in HEAD, so they're pruned without asking
+    pub prunable: Vec<Snapshot>,
+
+    // Some of their files still differ from HEAD, so they're only pruned once the user confirms
+    pub differs_from_head: Vec<Snapshot>,
+}
+
 /// One entry the user would see in the restore-snapshot preview, mirroring the
 /// shape of `ZipPreviewEntry` so the frontend can render it with the same
 /// component logic.
@@ -474,12 +771,196 @@ pub struct SnapshotPreviewEntry {
     pub exists_on_disk: bool,
 }
 
+/// What to do with a file whose working tree copy has diverged from the
+/// snapshot being restored.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotConflictResolution {
+    /// Replace the working tree copy with the snapshot's.
+    Theirs,
+    /// Keep the working tree copy and skip the file.
+    Mine,
+    /// Keep the working tree copy and write the snapshot's next to it as a
+    /// `.snapshotcopy`.
+    SaveAsCopy,
+}
+
+/// One path to restore from a snapshot.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFileRestore {
+    pub path: String,
+    /// Required when the path has uncommitted local changes that differ from
+    /// the snapshot; ignored otherwise.
+    #[serde(default)]
+    pub resolution: Option<SnapshotConflictResolution>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotFileRestoreOutcome {
+    Restored,
+    Removed,
+    Kept,
+    Copied,
+    /// The working tree copy already matched the snapshot.
+    Unchanged,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFileRestoreResult {
+    pub path: String,
+    pub outcome: SnapshotFileRestoreOutcome,
+    /// Where the snapshot's version was written for `Copied` files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copy_path: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeStatus {
+    pub path: String,
+
+    // None when HEAD is detached
+    pub branch: Option<String>,
+    pub sha: String,
+    pub is_main: bool,
+
+    // The worktree Friendshipper is currently pointed at
+    pub active: bool,
+
+    // None when the worktree's directory no longer exists
+    pub status: Option<RepoStatus>,
+}
+
+// Where a quick submit's pull request is on its way into the target branch
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TrackedSubmitState {
+    // Waiting on checks, or on being (re-)added to the merge queue
+    #[default]
+    Opened,
+    Mergeable,
+    Enqueued,
+
+    // Dropped from the merge queue without merging, ex. a failed check or a conflict with a PR
+    // ahead of it
+    Ejected,
+    Merged,
+    Closed,
+}
+
+impl TrackedSubmitState {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            TrackedSubmitState::Merged | TrackedSubmitState::Closed
+        )
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitTransition {
+    pub state: TrackedSubmitState,
+    pub timestamp: DateTime<Utc>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedSubmit {
+    pub number: u64,
+
+    // GraphQL node id, which the merge queue mutations take
+    pub id: String,
+    pub owner: String,
+    pub repo: String,
+    pub title: String,
+    pub head_branch: String,
+    pub base_branch: String,
+    pub uses_merge_queue: bool,
+    pub files: Vec<String>,
+
+    pub state: TrackedSubmitState,
+
+    // Oldest first
+    pub transitions: Vec<SubmitTransition>,
+
+    // Times the PR was rebased and re-queued after being ejected
+    #[serde(default)]
+    pub requeue_attempts: u32,
+
+    // Set when the PR was built on top of another in-flight quick submit that touches the same files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stacked_on: Option<StackedOn>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackedOn {
+    // GraphQL node id of the parent submit
+    pub id: String,
+    pub number: u64,
+
+    // Head of the parent's branch the PR was built on, used to restack it onto the base branch
+    pub commit: String,
+}
+
+impl TrackedSubmit {
+    pub fn last_transition(&self) -> Option<&SubmitTransition> {
+        self.transitions.last()
+    }
+}
+
 pub type ShowCommitFilesResponse = Vec<CommitFileInfo>;
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn snapshot(kind: SnapshotKind, days_ago: i64) -> Snapshot {
+        Snapshot {
+            id: format!("{kind:?}-{days_ago}"),
+            timestamp: Utc::now() - chrono::Duration::days(days_ago),
+            kind,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_snapshot_retention_keeps_recent_or_last_n() {
+        let snapshots = vec![
+            snapshot(SnapshotKind::Automatic, 1),
+            snapshot(SnapshotKind::Manual, 30),
+            snapshot(SnapshotKind::Automatic, 20),
+            snapshot(SnapshotKind::Automatic, 21),
+            snapshot(SnapshotKind::Automatic, 22),
+        ];
+        let retention = SnapshotRetention {
+            automatic_max_age_days: 14,
+            automatic_keep_count: 2,
+            manual_max_age_days: None,
+        };
+
+        // the 20 day old snapshot is past the age limit but among the last two automatic ones
+        let expired = retention
+            .expired(&snapshots, Utc::now())
+            .into_iter()
+            .map(|s| s.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(expired, vec!["Automatic-21", "Automatic-22"]);
+
+        let retention = SnapshotRetention {
+            manual_max_age_days: Some(7),
+            ..retention
+        };
+        assert_eq!(retention.expired(&snapshots, Utc::now()).len(), 3);
+    }
+
     #[test]
     fn test_parse_branch_success() {
         let mut status = RepoStatus::new();
@@ -96,6 +96,43 @@ fn ancestor_pids(system: &sysinfo::System, pid: u32) -> HashSet<u32> {
     ancestors
 }
 
+/// Kills `pid` along with everything it spawned. A git command runs its
+/// transport, `index-pack` and `git-lfs` as children, and killing only the
+/// top process would leave those writing into the repo after it's gone.
+/// Descendants are gathered before anything is killed, since they get
+/// reparented once their parent exits.
+pub fn kill_process_tree(pid: u32) {
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(sysinfo::ProcessRefreshKind::new());
+
+    let mut tree = vec![Pid::from_u32(pid)];
+    let mut index = 0;
+    while index < tree.len() {
+        let parent = tree[index];
+        let children = system
+            .processes()
+            .iter()
+            .filter(|(child, process)| {
+                process.thread_kind().is_none()
+                    && process.parent() == Some(parent)
+                    && !tree.contains(child)
+            })
+            .map(|(child, _)| *child)
+            .collect::<Vec<_>>();
+        tree.extend(children);
+        index += 1;
+    }
+
+    // leaves first, so nothing gets a chance to respawn work for a dead parent
+    for pid in tree.iter().rev() {
+        if let Some(process) = system.process(*pid) {
+            if !process.kill() {
+                warn!("Failed to kill process {}", pid);
+            }
+        }
+    }
+}
+
 /// True if `candidate_exe` is the `.AppImage` file we ourselves were
 /// launched from (per `$APPIMAGE`).
 ///
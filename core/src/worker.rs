@@ -1,9 +1,12 @@
 use crate::types::errors::CoreError;
+use anyhow::anyhow;
 use async_trait::async_trait;
+use parking_lot::Mutex;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
-use tracing::{error, info, instrument, span};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, instrument, span, warn};
 
 #[async_trait]
 pub trait Task {
@@ -59,9 +62,58 @@ impl TaskSequence {
     }
 }
 
+tokio::task_local! {
+    // The running sequence's token, only set while the RepoWorker is executing one of its tasks
+    static SEQUENCE_CANCEL: CancellationToken;
+}
+
+/// The cancel token of the sequence the calling task belongs to. None outside the `RepoWorker`,
+/// ex. in HTTP handlers or status polling, so git commands there are never cancelled along with a
+/// sequence. Git clients use it when `Opts::cancel` isn't set.
+pub fn sequence_cancel_token() -> Option<CancellationToken> {
+    SEQUENCE_CANCEL.try_with(CancellationToken::clone).ok()
+}
+
+/// Cancels the sequence the `RepoWorker` is running. Each sequence runs under its own token, so
+/// cancelling never reaches sequences still waiting in the queue. Git commands run by the
+/// sequence's tasks are killed when the token fires and new ones refuse to start.
+#[derive(Clone, Debug, Default)]
+pub struct CancelHandle {
+    current: Arc<Mutex<Option<CancellationToken>>>,
+}
+
+impl CancelHandle {
+    /// The token of the running sequence, None while the worker is idle.
+    pub fn token(&self) -> Option<CancellationToken> {
+        self.current.lock().clone()
+    }
+
+    /// Returns false if there was nothing to cancel.
+    pub fn cancel(&self) -> bool {
+        match &*self.current.lock() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn begin(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        *self.current.lock() = Some(token.clone());
+        token
+    }
+
+    fn end(&self) {
+        *self.current.lock() = None;
+    }
+}
+
 pub struct RepoWorker {
     queue: Receiver<TaskSequence>,
     pause_file_watcher: Arc<AtomicBool>,
+    cancel: CancelHandle,
 }
 
 impl RepoWorker {
@@ -69,9 +121,14 @@ impl RepoWorker {
         RepoWorker {
             queue: tx,
             pause_file_watcher,
+            cancel: CancelHandle::default(),
         }
     }
 
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
     // For running git tasks that could take a while, like pulling or pushing.
     pub async fn run(&mut self) {
         while let Some(sequence) = self.queue.recv().await {
@@ -80,8 +137,16 @@ impl RepoWorker {
 
             self.pause_file_watcher
                 .store(true, std::sync::atomic::Ordering::Relaxed);
+            let cancel = self.cancel.begin();
             for task in sequence.tasks {
-                match self.run_task(task, &span).await {
+                // a task that doesn't run git can finish after a cancel, but nothing after it starts
+                if cancel.is_cancelled() {
+                    warn!("Sequence cancelled before running {}", task.get_name());
+                    err = Some(CoreError::Input(anyhow!("The operation was cancelled")));
+                    break;
+                }
+
+                match self.run_task(task, &cancel, &span).await {
                     Ok(_) => {}
                     Err(e) => {
                         error!("caught error running task: {}", &e);
@@ -90,6 +155,7 @@ impl RepoWorker {
                     }
                 }
             }
+            self.cancel.end();
             self.pause_file_watcher
                 .store(false, std::sync::atomic::Ordering::Relaxed);
 
@@ -103,10 +169,11 @@ impl RepoWorker {
     async fn run_task(
         &self,
         op: Box<dyn Task + Send + Sync>,
+        cancel: &CancellationToken,
         _span: &tracing::Span,
     ) -> Result<(), CoreError> {
         info!("Running: {:?}", op.get_name());
-        match op.execute().await {
+        match SEQUENCE_CANCEL.scope(cancel.clone(), op.execute()).await {
             Ok(_) => {}
             Err(e) => {
                 return Err(e);
@@ -115,3 +182,52 @@ impl RepoWorker {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct RecordToken {
+        token: Arc<Mutex<Option<CancellationToken>>>,
+    }
+
+    #[async_trait]
+    impl Task for RecordToken {
+        async fn execute(&self) -> Result<(), CoreError> {
+            *self.token.lock() = sequence_cancel_token();
+            Ok(())
+        }
+
+        fn get_name(&self) -> String {
+            String::from("RecordToken")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sequence_token_is_scoped_to_worker_tasks() {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let mut worker = RepoWorker::new(rx, Arc::new(AtomicBool::new(false)));
+        let handle = worker.cancel_handle();
+        tokio::spawn(async move { worker.run().await });
+
+        let token = Arc::new(Mutex::new(None));
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+        let mut sequence = TaskSequence::new().with_completion_tx(done_tx);
+        sequence.push(Box::new(RecordToken {
+            token: token.clone(),
+        }));
+        tx.send(sequence).await.unwrap();
+        assert!(done_rx.await.unwrap().is_none());
+
+        // the task saw its sequence's token, which is gone once the sequence finishes
+        let token = token
+            .lock()
+            .clone()
+            .expect("task should see the sequence token");
+        assert!(!handle.cancel());
+        assert!(!token.is_cancelled());
+
+        // code outside the worker never sees a sequence token
+        assert!(sequence_cancel_token().is_none());
+    }
+}
@@ -1,13 +1,23 @@
+use std::collections::HashSet;
 use std::fs;
+use std::path::PathBuf;
 
 use anyhow::Context;
+use aws_sdk_s3::config::Credentials;
 use axum::extract::{Query, State};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use chrono::{DateTime, Local, Utc};
+use ethos_core::longtail::bisect::{BisectExport, BisectSession, BisectStatus, MAX_BISECT_COMMITS};
+use ethos_core::longtail::cache::{CacheConsumer, CacheStatus};
+use ethos_core::longtail::history::SyncSummary;
+use ethos_core::longtail::installs::{seed_install, InstalledBuild};
 use ethos_core::longtail::CacheControl;
+use ethos_core::storage::config::Project;
+use ethos_core::storage::retention::RetentionReport;
 use ethos_core::storage::{
-    ArtifactBuildConfig, ArtifactConfig, ArtifactEntry, ArtifactKind, ArtifactList, Platform,
+    ArtifactBuildConfig, ArtifactConfig, ArtifactEntry, ArtifactFilter, ArtifactKind, ArtifactList,
+    ArtifactListQuery, Platform, RetentionPlan, RetentionProtections,
 };
 use ethos_core::utils::junit::JunitOutput;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
@@ -27,10 +37,14 @@ use ethos_core::clients::obs;
 use ethos_core::types::argo::workflow::{
     CreatePromoteBuildWorkflowRequest, Workflow, WorkflowStatus,
 };
-use ethos_core::types::builds::{LaunchMode, SyncClientRequest};
+use ethos_core::types::builds::{
+    InstallRequest, LaunchMode, LaunchOptions, MarkBisectRequest, StartBisectRequest,
+    SyncBisectRequest, SyncClientRequest,
+};
 use ethos_core::types::errors::CoreError;
 use ethos_core::types::gameserver::GameServerResults;
 
+use crate::repo::operations::{fetch_commit_info, is_valid_sha};
 use crate::state::AppState;
 
 const UNKNOWN_PUSHER: &str = "unknown";
@@ -45,7 +59,17 @@ where
         .route("/client/sync", post(sync_client))
         .route("/client/cancel", post(cancel_download))
         .route("/client/wipe", post(wipe_client_data))
-        .route("/longtail/reset", post(reset_longtail))
+        .route("/client/history", get(get_sync_history))
+        .route("/cache", get(get_chunk_cache_status))
+        .route("/installs", get(get_installs))
+        .route("/installs/launch", post(launch_install))
+        .route("/installs/delete", post(delete_install))
+        .route("/bisect", get(get_bisect))
+        .route("/bisect/start", post(start_bisect))
+        .route("/bisect/mark", post(mark_bisect))
+        .route("/bisect/sync", post(sync_bisect_candidate))
+        .route("/bisect/export", get(export_bisect))
+        .route("/bisect/reset", post(reset_bisect))
         .route("/server/verify", get(verify_server_image))
         .route("/workflows", get(get_workflows))
         .route("/workflows/nodes", get(get_workflow_nodes))
@@ -61,6 +85,9 @@ where
             "/workflows/promote-build",
             post(create_promote_build_workflow),
         )
+        .route("/index/invalidate", post(invalidate_build_index))
+        .route("/retention/plan", get(get_retention_plan))
+        .route("/retention/execute", post(execute_retention))
 }
 
 #[derive(Default, Deserialize)]
@@ -76,9 +103,6 @@ async fn get_build<T>(
 where
     T: EngineProvider,
 {
-    let aws_client = ensure_aws_client(state.aws_client.read().await.clone())?;
-    aws_client.check_expiration().await?;
-
     let project_param = params.project.clone();
 
     let project = if let Some(project) = project_param {
@@ -112,10 +136,37 @@ where
 }
 
 #[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct GetBuildsParams {
     #[serde(default = "get_default_limit")]
     limit: usize,
     project: Option<String>,
+
+    // Setting any of the below switches to the paginated listing, which returns a next_cursor
+    #[serde(default)]
+    paginate: bool,
+    cursor: Option<String>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+
+    // Comma-separated list of full commit shas
+    commits: Option<String>,
+
+    // Inclusive commit range, resolved against the local repo
+    from_commit: Option<String>,
+    to_commit: Option<String>,
+}
+
+impl GetBuildsParams {
+    fn is_paginated(&self) -> bool {
+        self.paginate
+            || self.cursor.is_some()
+            || self.since.is_some()
+            || self.until.is_some()
+            || self.commits.is_some()
+            || self.from_commit.is_some()
+            || self.to_commit.is_some()
+    }
 }
 
 fn get_default_limit() -> usize {
@@ -129,9 +180,8 @@ async fn get_builds<T>(
 where
     T: EngineProvider,
 {
-    let aws_client = ensure_aws_client(state.aws_client.read().await.clone())?;
-    aws_client.check_expiration().await?;
-
+    // Credentials are checked by the storage provider itself, since a local filesystem
+    // provider doesn't need AWS at all.
     let project_param = params.project.clone();
 
     let project = if let Some(project) = project_param {
@@ -158,7 +208,17 @@ where
         Platform::Win64,
     );
 
-    let mut builds = storage.artifact_list(artifact_config).await;
+    if params.is_paginated() {
+        let query = ArtifactListQuery {
+            limit: params.limit,
+            cursor: params.cursor.clone(),
+            filter: build_artifact_filter(&state, &params).await?,
+        };
+        return Ok(Json(storage.artifact_page(artifact_config, &query).await?));
+    }
+
+    // Served from the local index when possible, which refreshes itself in the background
+    let mut builds = storage.artifact_list_cached(artifact_config).await;
 
     if builds.entries.len() > params.limit {
         builds.entries.truncate(params.limit);
@@ -167,6 +227,216 @@ where
     Ok(Json(builds))
 }
 
+async fn build_artifact_filter<T>(
+    state: &AppState<T>,
+    params: &GetBuildsParams,
+) -> Result<ArtifactFilter, CoreError>
+where
+    T: EngineProvider,
+{
+    let listed: Option<HashSet<String>> = params.commits.as_ref().map(|commits| {
+        commits
+            .split(',')
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty())
+            .collect()
+    });
+
+    let range: Option<HashSet<String>> = match (&params.from_commit, &params.to_commit) {
+        (Some(from), Some(to)) => {
+            for commit in [from, to] {
+                if !is_valid_sha(commit) {
+                    return Err(CoreError::Input(anyhow::anyhow!(
+                        "Invalid commit SHA: {}",
+                        commit
+                    )));
+                }
+            }
+            Some(
+                state
+                    .git()
+                    .rev_list_inclusive(from, to)
+                    .await?
+                    .into_iter()
+                    .map(|c| c.to_lowercase())
+                    .collect(),
+            )
+        }
+        (None, None) => None,
+        _ => {
+            return Err(CoreError::Input(anyhow::anyhow!(
+                "fromCommit and toCommit must be provided together"
+            )))
+        }
+    };
+
+    let commits = match (listed, range) {
+        (Some(listed), Some(range)) => Some(listed.intersection(&range).cloned().collect()),
+        (listed, range) => listed.or(range),
+    };
+
+    Ok(ArtifactFilter {
+        since: params.since,
+        until: params.until,
+        commits,
+    })
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct InvalidateBuildIndexParams {
+    project: Option<String>,
+}
+
+// Drops the cached listings so the next request goes back to storage. Without a project, the
+// indexes for every project are removed.
+#[instrument(skip(state))]
+async fn invalidate_build_index<T>(
+    State(state): State<AppState<T>>,
+    params: Query<InvalidateBuildIndexParams>,
+) -> Result<(), CoreError>
+where
+    T: EngineProvider,
+{
+    let storage = state
+        .storage
+        .read()
+        .clone()
+        .context("Storage not configured. AWS may still be initializing.")?;
+
+    if let Some(index_cache) = storage.index_cache() {
+        let project = params.project.as_deref().map(Project::from);
+        index_cache.invalidate(project.as_ref()).await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RetentionParams {
+    project: Option<String>,
+}
+
+// Builds the retention plan for the project using the policies from dynamic config. Playtest
+// versions and promoted builds are looked up from the cluster so they're never collected.
+async fn plan_retention<T>(
+    state: &AppState<T>,
+    project: Option<String>,
+) -> Result<RetentionPlan, CoreError>
+where
+    T: EngineProvider,
+{
+    let project = match project {
+        Some(project) => project,
+        None => state
+            .app_config
+            .read()
+            .clone()
+            .selected_artifact_project
+            .context("Project not configured. Repo may still be initializing.")?,
+    };
+
+    let policies = state.dynamic_config.read().artifact_retention.clone();
+    if policies.is_empty() {
+        return Err(CoreError::Input(anyhow::anyhow!(
+            "No artifact retention policies are configured"
+        )));
+    }
+
+    let storage = state
+        .storage
+        .read()
+        .clone()
+        .context("Storage not configured. AWS may still be initializing.")?;
+
+    // Without the cluster we can't tell what's protected, so refuse to plan rather than guess
+    let kube_client = ensure_kube_client(state.kube_client.read().clone())?;
+    let protections = RetentionProtections {
+        playtest_commits: kube_client
+            .get_playtests()
+            .await?
+            .into_iter()
+            .map(|playtest| playtest.spec.version)
+            .collect(),
+        promoted_commits: kube_client.get_promoted_build_commits().await?,
+    };
+
+    Ok(storage
+        .plan_retention(
+            &project.as_str().into(),
+            &policies,
+            &protections,
+            std::time::SystemTime::now(),
+        )
+        .await)
+}
+
+#[instrument(skip(state))]
+async fn get_retention_plan<T>(
+    State(state): State<AppState<T>>,
+    params: Query<RetentionParams>,
+) -> Result<Json<RetentionPlan>, CoreError>
+where
+    T: EngineProvider,
+{
+    let plan = plan_retention(&state, params.project.clone()).await?;
+    Ok(Json(plan))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecuteRetentionRequest {
+    project: Option<String>,
+
+    // Keys of the artifacts the caller reviewed for deletion in the dry-run plan
+    delete: Vec<String>,
+
+    // Must be set, deleting is bucket-wide and can't be undone
+    #[serde(default)]
+    confirm: bool,
+}
+
+// Deletes the artifacts from a reviewed dry-run plan. The plan is recomputed first and nothing is
+// deleted if it no longer matches what was reviewed.
+#[instrument(skip(state, request))]
+async fn execute_retention<T>(
+    State(state): State<AppState<T>>,
+    Json(request): Json<ExecuteRetentionRequest>,
+) -> Result<Json<RetentionReport>, CoreError>
+where
+    T: EngineProvider,
+{
+    if !request.confirm {
+        return Err(CoreError::Input(anyhow::anyhow!(
+            "Executing a retention plan deletes artifacts from the bucket and must be confirmed"
+        )));
+    }
+
+    let plan = plan_retention(&state, request.project.clone()).await?;
+    if !plan.deletes_exactly(&request.delete) {
+        return Err(CoreError::Input(anyhow::anyhow!(
+            "The retention plan has changed since it was reviewed ({} artifacts reviewed, {} now planned). Review the new plan before deleting.",
+            request.delete.len(),
+            plan.delete.len()
+        )));
+    }
+
+    let storage = state
+        .storage
+        .read()
+        .clone()
+        .context("Storage not configured. AWS may still be initializing.")?;
+
+    let report = storage.execute_retention_plan(&plan).await?;
+    info!(
+        "Retention removed {} artifacts ({} objects), kept {}",
+        report.deleted_artifacts,
+        report.deleted_objects,
+        plan.keep.len()
+    );
+
+    Ok(Json(report))
+}
+
 #[derive(Default, Deserialize)]
 struct VerifyServerImageParams {
     commit: String,
@@ -195,7 +465,12 @@ async fn sync_client<T>(
 where
     T: EngineProvider,
 {
-    let aws_client = ensure_aws_client(state.aws_client.read().await.clone())?;
+    let credentials = match ensure_aws_client(state.aws_client.read().await.clone()) {
+        Ok(aws_client) => aws_client.get_credentials().await,
+        // Local mirrors are read straight off disk, so AWS is optional for them
+        Err(_) if payload.method_prefix.is_local() => Credentials::from_keys("", "", None),
+        Err(e) => return Err(e),
+    };
 
     let mut local_path = state.longtail.download_path.0.clone();
     let remote_path = payload
@@ -203,12 +478,6 @@ where
         .get_storage_url(&payload.artifact_entry);
     let tx = state.longtail_tx.clone();
 
-    // make a client_cache dir if it doesn't exist
-    let client_cache_dir = local_path.join("client_cache");
-    if !client_cache_dir.exists() {
-        fs::create_dir_all(client_cache_dir.clone())?;
-    }
-
     if let Some(project) = state.app_config.read().clone().selected_artifact_project {
         local_path = local_path.join(project);
     }
@@ -217,11 +486,29 @@ where
         local_path = local_path.join(sub_path);
     }
 
-    local_path = local_path.join(payload.artifact_entry.base_name());
+    // Builds with a known commit get their own install so several can be kept side by side
+    let base_name = payload.artifact_entry.base_name();
+    let commit = payload.artifact_entry.commit.clone();
+    local_path = match &commit {
+        Some(commit) => local_path.join(InstalledBuild::dir_name(&base_name, commit)),
+        None => local_path.join(&base_name),
+    };
 
     let mut archive_urls: Vec<String> = vec![remote_path];
 
-    if state.app_config.read().game_client_download_symbols {
+    let download_symbols = state.app_config.read().game_client_download_symbols;
+
+    // v2 manifests name their symbols archive directly, so prefer that over deriving the path
+    let manifest_symbols = payload
+        .artifact_entry
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.symbols.clone())
+        .filter(|_| download_symbols);
+
+    if let Some(symbols) = manifest_symbols {
+        archive_urls.push(format!("{}{}", payload.method_prefix, symbols));
+    } else if download_symbols {
         let project = state
             .app_config
             .read()
@@ -257,143 +544,398 @@ where
     }
 
     let cache_control = CacheControl {
-        path: client_cache_dir,
-        max_size_bytes: state.app_config.read().max_client_cache_size_gb * 1024 * 1024 * 1024,
+        consumer: CacheConsumer::Client,
+        quota_bytes: state.app_config.read().max_client_cache_size_gb * 1024 * 1024 * 1024,
     };
 
+    let installs = state.longtail.installs();
+    if commit.is_some() && !local_path.exists() {
+        if let Some(closest) = installs.closest(&base_name, &local_path) {
+            if let Err(e) = seed_install(&closest.path, &local_path) {
+                warn!("Failed to seed install from {:?}: {:?}", closest.path, e);
+            }
+        }
+    }
+
     let local_path_clone = local_path.clone();
-    match fs::create_dir_all(&local_path_clone) {
+    let download_result = match fs::create_dir_all(&local_path_clone) {
         Ok(_) => {
             let (cancel_tx, mut cancel_rx) = oneshot::channel();
             state.cancel_tx.write().await.replace(cancel_tx);
 
             info!("Starting download...");
-            let longtail = state.longtail.clone();
+            let cancel = state.longtail.cancel_token();
             tokio::select! {
                 cancel_result = &mut cancel_rx => {
                     info!("Cancel branch hit with result: {:?}", cancel_result);
-
-                    let mut guard = longtail.child_process.lock();
-                    if let Some(mut child) = guard.take() {
-                        info!("Killing child process");
-                        child.kill().unwrap();
-                    }
+                    cancel.cancel();
 
                     return Ok(Json(false));
                 }
-                download_result = async move {
-                    let credentials = aws_client.get_credentials().await;
-                    tokio::task::spawn_blocking(move || {
-                        info!("Starting actual download...");
-                        state.longtail.get_archive(
-                            &local_path_clone,
-                            Some(cache_control),
-                            &archive_urls,
-                            tx,
-                            credentials,
-                        )
-                    }).await
-                } => {
+                download_result = state.longtail.get_archive(
+                    &local_path_clone,
+                    Some(cache_control),
+                    &archive_urls,
+                    tx,
+                    credentials,
+                    cancel.clone(),
+                ) => {
                     info!("Download branch complete with result: {:?}", download_result);
+                    download_result
                 }
             }
         }
         Err(e) => return Err(CoreError::Internal(e.into())),
-    }
+    };
 
     // reset cancel_tx to none
     state.cancel_tx.write().await.take();
 
     T::post_download(&local_path).await;
 
+    if let (Some(commit), Ok(())) = (commit, &download_result) {
+        installs.record(InstalledBuild {
+            commit,
+            base_name,
+            path: local_path.clone(),
+            archives: archive_urls,
+            installed_at: Utc::now(),
+            last_launched_at: None,
+        })?;
+        let max_installs = state.app_config.read().max_installed_builds;
+        installs.enforce_limit(max_installs, &local_path)?;
+    }
+
     if let Some(launch_options) = payload.launch_options {
-        match launch_options.launch_mode {
-            LaunchMode::WithServer => {
-                if !launch_options.name.is_empty() {
-                    let kube_client = ensure_kube_client(state.kube_client.read().clone())?;
-                    let game_server = kube_client.get_gameserver(&launch_options.name).await?;
-
-                    if let Some(status) = game_server.status {
-                        info!(
-                            "Launching game client with server host {:?}:{}",
-                            status.ip, status.port
-                        );
-
-                        // Assume this GameServerResults type will become an engine-specific type in the future.
-                        // Right now, we're asking the client to basically look up game servers, then send us back
-                        // the IP, port, and netimgui port, and that seems inefficient. We should be able to have the client
-                        // send us a unique identifier for the server, and then we can call a generic GameServer -> LaunchConfig
-                        // style method.
-                        let game_server_results = GameServerResults {
-                            // these fields don't matter
-                            name: "".to_string(),
-                            display_name: "".to_string(),
-                            version: "".to_string(),
-                            creation_timestamp: Time(Utc::now()),
-
-                            // these fields matter
-                            ip: status.ip,
-                            port: status.port,
-                            netimgui_port: status.netimgui_port,
-                            ready: status.ready.unwrap_or(false),
-                        };
-
-                        let args = state.engine.create_launch_args(
-                            state.app_config.read().clone(),
-                            state.repo_config.read().clone(),
-                            game_server_results,
-                        );
-                        let child = match state.engine.launch(local_path, args) {
-                            Ok(child) => child,
-                            Err(e) => {
-                                error!("Failed to launch game client with error: {}", e);
-                                return Err(CoreError::Internal(e));
-                            }
-                        };
-
-                        if let Some(mut child) = child {
-                            if state.app_config.read().record_play {
-                                let client = obs::Client::default();
-                                match client.start_recording().await {
+        launch_client(&state, local_path, launch_options).await?;
+    }
+
+    Ok(Json(true))
+}
+
+// Launches the client installed at local_path, connecting to a game server if asked to
+async fn launch_client<T>(
+    state: &AppState<T>,
+    local_path: PathBuf,
+    launch_options: LaunchOptions,
+) -> Result<(), CoreError>
+where
+    T: EngineProvider,
+{
+    match launch_options.launch_mode {
+        LaunchMode::WithServer => {
+            if !launch_options.name.is_empty() {
+                let kube_client = ensure_kube_client(state.kube_client.read().clone())?;
+                let game_server = kube_client.get_gameserver(&launch_options.name).await?;
+
+                if let Some(status) = game_server.status {
+                    info!(
+                        "Launching game client with server host {:?}:{}",
+                        status.ip, status.port
+                    );
+
+                    // Assume this GameServerResults type will become an engine-specific type in the future.
+                    // Right now, we're asking the client to basically look up game servers, then send us back
+                    // the IP, port, and netimgui port, and that seems inefficient. We should be able to have the client
+                    // send us a unique identifier for the server, and then we can call a generic GameServer -> LaunchConfig
+                    // style method.
+                    let game_server_results = GameServerResults {
+                        // these fields don't matter
+                        name: "".to_string(),
+                        display_name: "".to_string(),
+                        version: "".to_string(),
+                        creation_timestamp: Time(Utc::now()),
+
+                        // these fields matter
+                        ip: status.ip,
+                        port: status.port,
+                        netimgui_port: status.netimgui_port,
+                        ready: status.ready.unwrap_or(false),
+                    };
+
+                    let args = state.engine.create_launch_args(
+                        state.app_config.read().clone(),
+                        state.repo_config.read().clone(),
+                        game_server_results,
+                    );
+                    let child = match state.engine.launch(local_path, args) {
+                        Ok(child) => child,
+                        Err(e) => {
+                            error!("Failed to launch game client with error: {}", e);
+                            return Err(CoreError::Internal(e));
+                        }
+                    };
+
+                    if let Some(mut child) = child {
+                        if state.app_config.read().record_play {
+                            let client = obs::Client::default();
+                            match client.start_recording().await {
+                                Ok(_) => {}
+                                Err(e) => {
+                                    return Err(e);
+                                }
+                            };
+
+                            tokio::spawn(async move {
+                                match child.wait() {
                                     Ok(_) => {}
                                     Err(e) => {
-                                        return Err(e);
+                                        error!("Error waiting for child: {}", e);
                                     }
-                                };
-
-                                tokio::spawn(async move {
-                                    match child.wait() {
-                                        Ok(_) => {}
-                                        Err(e) => {
-                                            error!("Error waiting for child: {}", e);
-                                        }
-                                    }
-                                    match client.stop_recording().await {
-                                        Ok(_) => {}
-                                        Err(_) => {
-                                            error!("Error stopping recording");
-                                        }
+                                }
+                                match client.stop_recording().await {
+                                    Ok(_) => {}
+                                    Err(_) => {
+                                        error!("Error stopping recording");
                                     }
-                                });
-                            }
+                                }
+                            });
                         }
                     }
                 }
             }
-            LaunchMode::WithoutServer => {
-                let empty_args: Vec<String> = Vec::new();
-                let _child = match state.engine.launch(local_path, empty_args) {
-                    Ok(_child) => _child,
-                    Err(e) => {
-                        error!("Failed to launch game client with error: {}", e);
-                        return Err(CoreError::Internal(e));
-                    }
-                };
-            }
+        }
+        LaunchMode::WithoutServer => {
+            let empty_args: Vec<String> = Vec::new();
+            let _child = match state.engine.launch(local_path, empty_args) {
+                Ok(_child) => _child,
+                Err(e) => {
+                    error!("Failed to launch game client with error: {}", e);
+                    return Err(CoreError::Internal(e));
+                }
+            };
         }
     }
 
-    Ok(Json(true))
+    Ok(())
+}
+
+// Installed client builds, most recently installed first
+async fn get_installs<T>(State(state): State<AppState<T>>) -> Json<Vec<InstalledBuild>>
+where
+    T: EngineProvider,
+{
+    Json(state.longtail.installs().load())
+}
+
+fn find_install<T>(
+    state: &AppState<T>,
+    payload: &InstallRequest,
+) -> Result<InstalledBuild, CoreError>
+where
+    T: EngineProvider,
+{
+    state
+        .longtail
+        .installs()
+        .find(&payload.commit, payload.base_name.as_deref())
+        .ok_or_else(|| {
+            CoreError::Input(anyhow::anyhow!(
+                "No installed build found for commit {}",
+                payload.commit
+            ))
+        })
+}
+
+#[instrument(skip(state))]
+async fn launch_install<T>(
+    State(state): State<AppState<T>>,
+    Json(payload): Json<InstallRequest>,
+) -> Result<(), CoreError>
+where
+    T: EngineProvider,
+{
+    let install = find_install(&state, &payload)?;
+    state.longtail.installs().mark_launched(&install.path)?;
+
+    let launch_options = payload.launch_options.unwrap_or(LaunchOptions {
+        name: String::new(),
+        launch_mode: LaunchMode::WithoutServer,
+    });
+    launch_client(&state, install.path, launch_options).await
+}
+
+#[instrument(skip(state))]
+async fn delete_install<T>(
+    State(state): State<AppState<T>>,
+    Json(payload): Json<InstallRequest>,
+) -> Result<Json<InstalledBuild>, CoreError>
+where
+    T: EngineProvider,
+{
+    let install = find_install(&state, &payload)?;
+    Ok(Json(state.longtail.installs().remove(&install.path)?))
+}
+
+// The bisect session in progress, if any
+async fn get_bisect<T>(State(state): State<AppState<T>>) -> Json<Option<BisectStatus>>
+where
+    T: EngineProvider,
+{
+    Json(
+        state
+            .longtail
+            .bisect()
+            .load()
+            .map(|session| session.status()),
+    )
+}
+
+// Starts bisecting the client builds published between a known good and a known bad commit,
+// replacing any session in progress
+#[instrument(skip(state))]
+async fn start_bisect<T>(
+    State(state): State<AppState<T>>,
+    Json(payload): Json<StartBisectRequest>,
+) -> Result<Json<BisectStatus>, CoreError>
+where
+    T: EngineProvider,
+{
+    for commit in [&payload.good, &payload.bad] {
+        if !is_valid_sha(commit) {
+            return Err(CoreError::Input(anyhow::anyhow!(
+                "Invalid commit SHA: {}",
+                commit
+            )));
+        }
+    }
+
+    let project = match payload.project {
+        Some(project) => project,
+        None => state
+            .app_config
+            .read()
+            .clone()
+            .selected_artifact_project
+            .context("Project not configured. Repo may still be initializing.")?,
+    };
+
+    let storage = state
+        .storage
+        .read()
+        .clone()
+        .context("Storage not configured. AWS may still be initializing.")?;
+
+    let artifact_config = ArtifactConfig::new(
+        project.as_str().into(),
+        ArtifactKind::Client,
+        ArtifactBuildConfig::Development,
+        Platform::Win64,
+    );
+
+    let commits = state
+        .git()
+        .log(
+            MAX_BISECT_COMMITS,
+            &format!("{}..{}", payload.good, payload.bad),
+        )
+        .await?
+        .into_iter()
+        .map(|entry| entry.sha.to_lowercase())
+        .collect();
+    let artifacts = storage.artifact_list(artifact_config).await;
+
+    let session = BisectSession::new(
+        project,
+        artifacts.method_prefix,
+        payload.good,
+        payload.bad,
+        commits,
+        &artifacts.entries,
+    )
+    .map_err(CoreError::Input)?;
+    state.longtail.bisect().save(&session)?;
+
+    info!(
+        "Started bisect of {} commits between {} and {}",
+        session.commits.len(),
+        session.good,
+        session.bad
+    );
+    Ok(Json(session.status()))
+}
+
+#[instrument(skip(state))]
+async fn mark_bisect<T>(
+    State(state): State<AppState<T>>,
+    Json(payload): Json<MarkBisectRequest>,
+) -> Result<Json<BisectStatus>, CoreError>
+where
+    T: EngineProvider,
+{
+    let status = state
+        .longtail
+        .bisect()
+        .update(|session| {
+            session.mark(&payload.commit, payload.verdict)?;
+            Ok(session.status())
+        })
+        .map_err(CoreError::Input)?;
+    Ok(Json(status))
+}
+
+// Installs the next build to test through the regular client sync
+#[instrument(skip(state))]
+async fn sync_bisect_candidate<T>(
+    State(state): State<AppState<T>>,
+    Json(payload): Json<SyncBisectRequest>,
+) -> Result<Json<bool>, CoreError>
+where
+    T: EngineProvider,
+{
+    let session = state
+        .longtail
+        .bisect()
+        .load()
+        .ok_or_else(|| CoreError::Input(anyhow::anyhow!("No bisect session in progress")))?;
+    let candidate = session
+        .next_candidate()
+        .and_then(|commit| commit.artifact.clone())
+        .ok_or_else(|| CoreError::Input(anyhow::anyhow!("Bisect is already finished")))?;
+
+    let request = SyncClientRequest {
+        artifact_entry: candidate,
+        method_prefix: session.method_prefix,
+        sub_path: None,
+        launch_options: payload.launch_options,
+    };
+    sync_client(State(state), Json(request)).await
+}
+
+// The remaining range with each commit's details, for pasting into a bug report
+#[instrument(skip(state))]
+async fn export_bisect<T>(State(state): State<AppState<T>>) -> Result<Json<BisectExport>, CoreError>
+where
+    T: EngineProvider,
+{
+    let session = state
+        .longtail
+        .bisect()
+        .load()
+        .ok_or_else(|| CoreError::Input(anyhow::anyhow!("No bisect session in progress")))?;
+    let range = session.range();
+
+    let git = state.git();
+    let mut commits = Vec::with_capacity(range.commits.len());
+    for sha in range.commits.iter().rev() {
+        commits.push(fetch_commit_info(&git, sha).await?);
+    }
+
+    Ok(Json(BisectExport {
+        project: session.project.clone(),
+        last_good: range.last_good,
+        first_bad: range.first_bad,
+        finished: session.next_candidate().is_none(),
+        commits,
+    }))
+}
+
+async fn reset_bisect<T>(State(state): State<AppState<T>>) -> Result<(), CoreError>
+where
+    T: EngineProvider,
+{
+    state.longtail.bisect().clear()?;
+    Ok(())
 }
 
 pub async fn cancel_download<T>(State(state): State<AppState<T>>) -> Result<(), CoreError>
@@ -413,6 +955,28 @@ where
     Ok(())
 }
 
+// Recent longtail syncs, newest first
+async fn get_sync_history<T>(State(state): State<AppState<T>>) -> Json<Vec<SyncSummary>>
+where
+    T: EngineProvider,
+{
+    Json(state.longtail.sync_history().load())
+}
+
+#[instrument(skip(state))]
+async fn get_chunk_cache_status<T>(
+    State(state): State<AppState<T>>,
+) -> Result<Json<CacheStatus>, CoreError>
+where
+    T: EngineProvider,
+{
+    let cache = state.longtail.chunk_cache();
+    let status = tokio::task::spawn_blocking(move || cache.status())
+        .await
+        .context("Failed to read chunk cache status")?;
+    Ok(Json(status))
+}
+
 pub async fn wipe_client_data<T>(State(state): State<AppState<T>>) -> Result<(), CoreError>
 where
     T: EngineProvider,
@@ -436,19 +1000,6 @@ where
     Ok(())
 }
 
-pub async fn reset_longtail<T>(State(state): State<AppState<T>>) -> Result<(), CoreError>
-where
-    T: EngineProvider,
-{
-    let longtail_path = state.longtail.exec_path.clone();
-
-    if let Some(longtail_path) = longtail_path {
-        fs::remove_file(longtail_path)?;
-    }
-
-    Ok(())
-}
-
 #[derive(Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommitWorkflowInfo {
@@ -2,11 +2,17 @@ use ethos_core::utils::junit::JunitOutput;
 use friendshipper::engine::router::OpenUrlForPathRequest;
 use tracing::error;
 
+use ethos_core::longtail::bisect::{BisectExport, BisectStatus};
+use ethos_core::longtail::cache::CacheStatus;
+use ethos_core::longtail::history::SyncSummary;
+use ethos_core::longtail::installs::InstalledBuild;
 use ethos_core::storage::{ArtifactEntry, ArtifactList};
-use ethos_core::tauri::command::{check_error, restart};
+use ethos_core::tauri::command::check_error;
 use ethos_core::tauri::error::TauriError;
 use ethos_core::tauri::State;
-use ethos_core::types::builds::SyncClientRequest;
+use ethos_core::types::builds::{
+    InstallRequest, MarkBisectRequest, StartBisectRequest, SyncBisectRequest, SyncClientRequest,
+};
 use ethos_core::types::config::{AppConfig, DynamicConfig, UnrealVerSelDiagResponse};
 use ethos_core::types::gameserver::{GameServerResults, LaunchRequest};
 use ethos_core::types::github::merge_queue::get_merge_queue::GetMergeQueueRepositoryMergeQueue;
@@ -16,16 +22,20 @@ use ethos_core::types::playtests::{
 };
 use ethos_core::types::project::ProjectConfig;
 use ethos_core::types::repo::{
-    ChangeSet, CommitFileInfo, CommitInfo, FileHistoryResponse, PushRequest, RepoDirectoryListing,
-    RepoStatus, Snapshot,
+    BlockedFileAudit, BlockedFileAuditRequest, ChangeSet, CommitFileInfo, CommitInfo,
+    ContentProfilePlan, ContentProfilesResponse, FileHistoryResponse, IndexLockStatus, PushRequest,
+    RepoDirectoryListing, RepoStatus, Snapshot, SnapshotFileRestore, SnapshotFileRestoreResult,
+    SnapshotPrunePlan, TrackedSubmit, WorktreeStatus,
 };
 use ethos_core::types::utrace::{
     DownloadTraceRequest, OpenTraceRequest, RecentTracesResponse, TraceEntry,
 };
 use friendshipper::builds::router::GetWorkflowsResponse;
 use friendshipper::repo::operations::{
-    ImportZippedChangesRequest, RestoreFileToRevisionRequest, RestoreSnapshotRequest,
-    SaveChangeSetRequest, SaveSnapshotRequest, ZipLocalChangesRequest,
+    AddWorktreeRequest, ContentProfileRequest, ImportZippedChangesRequest, PruneSnapshotsRequest,
+    RestoreFileToRevisionRequest, RestoreSnapshotFilesRequest, RestoreSnapshotRequest,
+    SaveChangeSetRequest, SaveSnapshotRequest, SnapshotIdRequest, SnapshotTagsRequest,
+    WorktreePathRequest, ZipLocalChangesRequest,
 };
 
 // Update the TauriError creation to include status_code
@@ -101,6 +111,43 @@ pub async fn run_git_gc(state: tauri::State<'_, State>) -> Result<(), TauriError
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_index_lock_status(
+    state: tauri::State<'_, State>,
+) -> Result<IndexLockStatus, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/repo/diagnostics/index-lock", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn remove_stale_index_lock(
+    state: tauri::State<'_, State>,
+) -> Result<IndexLockStatus, TauriError> {
+    let res = state
+        .client
+        .post(format!(
+            "{}/repo/diagnostics/index-lock/remove",
+            state.server_url
+        ))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
 #[tauri::command]
 pub async fn get_github_status(
     state: tauri::State<'_, State>,
@@ -194,10 +241,17 @@ pub async fn get_build(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_builds(
     state: tauri::State<'_, State>,
     limit: Option<u32>,
     project: Option<String>,
+    cursor: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    commits: Option<Vec<String>>,
+    from_commit: Option<String>,
+    to_commit: Option<String>,
 ) -> Result<ArtifactList, TauriError> {
     let mut req = state.client.get(format!("{}/builds", state.server_url));
 
@@ -209,6 +263,30 @@ pub async fn get_builds(
         req = req.query(&[("project", project)]);
     }
 
+    if let Some(cursor) = cursor {
+        req = req.query(&[("cursor", cursor)]);
+    }
+
+    if let Some(since) = since {
+        req = req.query(&[("since", since)]);
+    }
+
+    if let Some(until) = until {
+        req = req.query(&[("until", until)]);
+    }
+
+    if let Some(commits) = commits {
+        req = req.query(&[("commits", commits.join(","))]);
+    }
+
+    if let Some(from_commit) = from_commit {
+        req = req.query(&[("fromCommit", from_commit)]);
+    }
+
+    if let Some(to_commit) = to_commit {
+        req = req.query(&[("toCommit", to_commit)]);
+    }
+
     match req.send().await {
         Ok(res) => {
             if is_error_status(res.status()) {
@@ -230,6 +308,27 @@ pub async fn get_builds(
     }
 }
 
+#[tauri::command]
+pub async fn invalidate_build_index(
+    state: tauri::State<'_, State>,
+    project: Option<String>,
+) -> Result<(), TauriError> {
+    let mut req = state
+        .client
+        .post(format!("{}/builds/index/invalidate", state.server_url));
+
+    if let Some(project) = project {
+        req = req.query(&[("project", project)]);
+    }
+
+    let res = req.send().await?;
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn show_commit_files(
     state: tauri::State<'_, State>,
@@ -403,6 +502,165 @@ pub async fn sync_client(
     Ok(res.json().await?)
 }
 
+#[tauri::command]
+pub async fn get_installs(
+    state: tauri::State<'_, State>,
+) -> Result<Vec<InstalledBuild>, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/builds/installs", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn launch_install(
+    state: tauri::State<'_, State>,
+    req: InstallRequest,
+) -> Result<(), TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/builds/installs/launch", state.server_url))
+        .json(&req)
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_install(
+    state: tauri::State<'_, State>,
+    req: InstallRequest,
+) -> Result<InstalledBuild, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/builds/installs/delete", state.server_url))
+        .json(&req)
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn get_bisect(
+    state: tauri::State<'_, State>,
+) -> Result<Option<BisectStatus>, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/builds/bisect", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn start_bisect(
+    state: tauri::State<'_, State>,
+    req: StartBisectRequest,
+) -> Result<BisectStatus, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/builds/bisect/start", state.server_url))
+        .json(&req)
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn mark_bisect(
+    state: tauri::State<'_, State>,
+    req: MarkBisectRequest,
+) -> Result<BisectStatus, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/builds/bisect/mark", state.server_url))
+        .json(&req)
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn sync_bisect(
+    state: tauri::State<'_, State>,
+    req: SyncBisectRequest,
+) -> Result<bool, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/builds/bisect/sync", state.server_url))
+        .json(&req)
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn export_bisect(state: tauri::State<'_, State>) -> Result<BisectExport, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/builds/bisect/export", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn reset_bisect(state: tauri::State<'_, State>) -> Result<(), TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/builds/bisect/reset", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn cancel_download(state: tauri::State<'_, State>) -> Result<(), TauriError> {
     let res = state
@@ -420,36 +678,52 @@ pub async fn cancel_download(state: tauri::State<'_, State>) -> Result<(), Tauri
 }
 
 #[tauri::command]
-pub async fn wipe_client_data(state: tauri::State<'_, State>) -> Result<(), TauriError> {
+pub async fn get_sync_history(
+    state: tauri::State<'_, State>,
+) -> Result<Vec<SyncSummary>, TauriError> {
     let res = state
         .client
-        .post(format!("{}/builds/client/wipe", state.server_url))
+        .get(format!("{}/builds/client/history", state.server_url))
         .send()
         .await?;
 
-    if let Some(err) = check_error(res.status(), res.text().await?).await {
-        error!("Error wiping client data: {}", err.message);
-        return Err(err);
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
     }
 
-    Ok(())
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn get_chunk_cache_status(
+    state: tauri::State<'_, State>,
+) -> Result<CacheStatus, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/builds/cache", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
 }
 
 #[tauri::command]
-pub async fn reset_longtail(state: tauri::State<'_, State>) -> Result<(), TauriError> {
+pub async fn wipe_client_data(state: tauri::State<'_, State>) -> Result<(), TauriError> {
     let res = state
         .client
-        .post(format!("{}/builds/longtail/reset", state.server_url))
+        .post(format!("{}/builds/client/wipe", state.server_url))
         .send()
         .await?;
 
     if let Some(err) = check_error(res.status(), res.text().await?).await {
-        error!("Error resetting longtail: {}", err.message);
+        error!("Error wiping client data: {}", err.message);
         return Err(err);
     }
 
-    restart(state).await?;
-
     Ok(())
 }
 
@@ -485,6 +759,22 @@ pub async fn refetch_repo(state: tauri::State<'_, State>) -> Result<(), TauriErr
     Ok(())
 }
 
+#[tauri::command]
+pub async fn cancel_repo_operation(state: tauri::State<'_, State>) -> Result<(), TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/cancel", state.server_url))
+        .send()
+        .await?;
+
+    if let Some(err) = check_error(res.status(), res.text().await?).await {
+        error!("Error cancelling repo operation: {}", err.message);
+        return Err(err);
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn reset_repo_to_commit(
     state: tauri::State<'_, State>,
@@ -677,7 +967,7 @@ pub async fn list_snapshots(state: tauri::State<'_, State>) -> Result<Vec<Snapsh
 #[tauri::command]
 pub async fn restore_snapshot(
     state: tauri::State<'_, State>,
-    commit: String,
+    id: String,
     files: Option<Vec<String>>,
     overwrite_local: Option<bool>,
 ) -> Result<(), TauriError> {
@@ -685,7 +975,7 @@ pub async fn restore_snapshot(
         .client
         .post(format!("{}/repo/snapshots/restore", state.server_url))
         .json(&RestoreSnapshotRequest {
-            commit,
+            id,
             files,
             overwrite_local: overwrite_local.unwrap_or(false),
         })
@@ -699,16 +989,36 @@ pub async fn restore_snapshot(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn restore_snapshot_files(
+    state: tauri::State<'_, State>,
+    id: String,
+    files: Vec<SnapshotFileRestore>,
+) -> Result<Vec<SnapshotFileRestoreResult>, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/snapshots/restore-files", state.server_url))
+        .json(&RestoreSnapshotFilesRequest { id, files })
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
 #[tauri::command]
 pub async fn preview_snapshot(
     state: tauri::State<'_, State>,
-    commit: String,
+    id: String,
 ) -> Result<serde_json::Value, TauriError> {
-    let encoded = urlencoding::encode(&commit);
+    let encoded = urlencoding::encode(&id);
     let res = state
         .client
         .get(format!(
-            "{}/repo/snapshots/preview?commit={}",
+            "{}/repo/snapshots/preview?id={}",
             state.server_url, encoded
         ))
         .send()
@@ -726,11 +1036,18 @@ pub async fn save_snapshot(
     state: tauri::State<'_, State>,
     message: String,
     files: Vec<String>,
+    tags: Option<Vec<String>>,
+    automatic: Option<bool>,
 ) -> Result<(), TauriError> {
     let res = state
         .client
         .post(format!("{}/repo/snapshots/save", state.server_url))
-        .json(&SaveSnapshotRequest { message, files })
+        .json(&SaveSnapshotRequest {
+            message,
+            files,
+            tags: tags.unwrap_or_default(),
+            automatic: automatic.unwrap_or(false),
+        })
         .send()
         .await?;
 
@@ -741,15 +1058,132 @@ pub async fn save_snapshot(
 }
 
 #[tauri::command]
-pub async fn delete_snapshot(
+pub async fn delete_snapshot(state: tauri::State<'_, State>, id: String) -> Result<(), TauriError> {
+    let res = state
+        .client
+        .delete(format!(
+            "{}/repo/snapshots?id={}",
+            state.server_url,
+            urlencoding::encode(&id)
+        ))
+        .send()
+        .await?;
+
+    if let Some(err) = check_error(res.status(), res.text().await?).await {
+        return Err(err);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_snapshot_prune_plan(
     state: tauri::State<'_, State>,
-    commit: String,
+) -> Result<SnapshotPrunePlan, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/repo/snapshots/prune", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn prune_snapshots(
+    state: tauri::State<'_, State>,
+    confirm: Vec<String>,
+) -> Result<Vec<Snapshot>, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/snapshots/prune", state.server_url))
+        .json(&PruneSnapshotsRequest { confirm })
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn list_worktrees(
+    state: tauri::State<'_, State>,
+) -> Result<Vec<WorktreeStatus>, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/repo/worktrees", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn add_worktree(
+    state: tauri::State<'_, State>,
+    branch: String,
+    path: Option<String>,
+    start_point: Option<String>,
+) -> Result<WorktreeStatus, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/worktrees", state.server_url))
+        .json(&AddWorktreeRequest {
+            branch,
+            path,
+            start_point,
+        })
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn switch_worktree(
+    state: tauri::State<'_, State>,
+    path: String,
+) -> Result<(), TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/worktrees/switch", state.server_url))
+        .json(&WorktreePathRequest { path })
+        .send()
+        .await?;
+
+    if let Some(err) = check_error(res.status(), res.text().await?).await {
+        return Err(err);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_worktree(
+    state: tauri::State<'_, State>,
+    path: String,
+    force: bool,
 ) -> Result<(), TauriError> {
     let res = state
         .client
         .delete(format!(
-            "{}/repo/snapshots?commit={}",
-            state.server_url, commit
+            "{}/repo/worktrees?path={}&force={}",
+            state.server_url,
+            urlencoding::encode(&path),
+            force
         ))
         .send()
         .await?;
@@ -760,6 +1194,137 @@ pub async fn delete_snapshot(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn audit_blocked_files(
+    state: tauri::State<'_, State>,
+    request: BlockedFileAuditRequest,
+) -> Result<BlockedFileAudit, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/blocked-files/audit", state.server_url))
+        .json(&request)
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn list_content_profiles(
+    state: tauri::State<'_, State>,
+) -> Result<ContentProfilesResponse, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/repo/content-profiles", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn plan_content_profile(
+    state: tauri::State<'_, State>,
+    name: String,
+) -> Result<ContentProfilePlan, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/repo/content-profiles/plan", state.server_url))
+        .query(&ContentProfileRequest { name })
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn apply_content_profile(
+    state: tauri::State<'_, State>,
+    name: String,
+) -> Result<(), TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/content-profiles/apply", state.server_url))
+        .json(&ContentProfileRequest { name })
+        .send()
+        .await?;
+
+    if let Some(err) = check_error(res.status(), res.text().await?).await {
+        return Err(err);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_snapshot_tags(
+    state: tauri::State<'_, State>,
+    id: String,
+    tags: Vec<String>,
+) -> Result<Snapshot, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/snapshots/tags", state.server_url))
+        .json(&SnapshotTagsRequest { id, tags })
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn push_snapshot(
+    state: tauri::State<'_, State>,
+    id: String,
+) -> Result<Snapshot, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/snapshots/push", state.server_url))
+        .json(&SnapshotIdRequest { id })
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
+#[tauri::command]
+pub async fn fetch_snapshot(
+    state: tauri::State<'_, State>,
+    id: String,
+) -> Result<Snapshot, TauriError> {
+    let res = state
+        .client
+        .post(format!("{}/repo/snapshots/fetch", state.server_url))
+        .json(&SnapshotIdRequest { id })
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
 #[tauri::command]
 pub async fn save_changeset(
     state: tauri::State<'_, State>,
@@ -944,6 +1509,23 @@ pub async fn get_merge_queue(
     Ok(res.json().await?)
 }
 
+#[tauri::command]
+pub async fn get_tracked_submits(
+    state: tauri::State<'_, State>,
+) -> Result<Vec<TrackedSubmit>, TauriError> {
+    let res = state
+        .client
+        .get(format!("{}/repo/gh/submits", state.server_url))
+        .send()
+        .await?;
+
+    if is_error_status(res.status()) {
+        return Err(create_tauri_error(res).await);
+    }
+
+    Ok(res.json().await?)
+}
+
 // Servers
 #[tauri::command]
 pub async fn get_servers(
@@ -275,6 +275,10 @@ where
         }
     }
 
+    state
+        .longtail
+        .set_chunk_cache_budget(payload.max_chunk_cache_size_gb * 1024 * 1024 * 1024);
+
     {
         let mut lock = state.app_config.write();
         *lock = payload;
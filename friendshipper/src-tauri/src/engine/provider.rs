@@ -60,6 +60,7 @@ pub trait EngineProvider: Clone + Send + Sync + 'static {
     ) -> Vec<String>;
 
     /// Given a path, finds the appropriate client executable to launch and returns its full path.
+    /// The path is the directory of an installed build, see `ethos_core::longtail::installs`.
     fn find_client_executable(&self, path: PathBuf) -> Result<PathBuf>;
 
     fn launch(&self, path: PathBuf, args: Vec<String>) -> Result<Option<Child>> {
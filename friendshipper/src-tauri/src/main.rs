@@ -18,8 +18,10 @@ use tauri::{Emitter, Manager, WebviewWindow};
 use tauri_plugin_notification::NotificationExt;
 use tracing::{debug, error, info, warn};
 
+use ethos_core::longtail::get::{format_duration, GetProgress};
+use ethos_core::msg::{GitProgress, LongtailMsg, LongtailPhase};
 use ethos_core::tauri::State;
-use ethos_core::{clients, msg::LongtailMsg, utils, utils::logging};
+use ethos_core::{clients, utils, utils::logging};
 use friendshipper::state::{FrontendOp, Notification};
 use friendshipper::APP_NAME;
 
@@ -29,18 +31,21 @@ use ethos_core::tauri::command::*;
 pub static VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[derive(Clone, Debug, Serialize)]
-struct LongtailProgressCaptures {
-    progress: String,
+#[serde(rename_all = "camelCase")]
+struct LongtailSyncProgress {
+    phase: LongtailPhase,
+    progress: u64,
+    done_bytes: u64,
+    total_bytes: u64,
+    chunks_done: u64,
+    chunks_total: u64,
     elapsed: String,
     remaining: String,
 }
 
 mod command;
 
-// see test_longtail_regex() for examples of matches
 lazy_static! {
-    static ref LONGTAIL_PROGRESS_REGEX: Regex =
-        Regex::new(r"(\d{1,3}%).*?((?:\d+[a-z])+):?((?:\d+[a-z])+)?").unwrap();
     static ref ANSI_REGEX: Regex =
         Regex::new(r"[\u001b\u009b]\[[()#;?]*(?:[0-9]{1,4}(?:;[0-9]{0,4})*)?[0-9A-ORZcf-nqry=><]")
             .unwrap();
@@ -190,17 +195,24 @@ fn main() -> Result<(), CoreError> {
                 configure_git_user,
                 copy_profile_data_from_gameserver,
                 create_playtest,
+                delete_install,
                 delete_playtest,
                 delete_snapshot,
                 download_server_logs,
                 download_utrace,
+                export_bisect,
+                fetch_snapshot,
                 fix_rebase,
                 get_build,
+                get_snapshot_prune_plan,
                 get_builds,
+                get_chunk_cache_status,
                 get_commits,
                 get_branch_comparison,
                 get_dynamic_config,
                 get_app_config,
+                get_bisect,
+                get_installs,
                 get_log_path,
                 update_app_config,
                 get_logs,
@@ -210,6 +222,8 @@ fn main() -> Result<(), CoreError> {
                 get_project_config,
                 get_pull_request,
                 get_pull_requests,
+                get_rebase_conflicts,
+                get_conflict_merge_preview,
                 get_rebase_status,
                 get_repo_config,
                 get_recent_utraces,
@@ -220,14 +234,20 @@ fn main() -> Result<(), CoreError> {
                 get_utraces_for_date,
                 get_cluster_servers,
                 init_additional_clusters,
+                get_sync_history,
                 get_system_status,
                 get_workflows,
                 get_workflow_nodes,
                 get_workflow_junit_artifact,
                 get_workflow_node_logs,
                 install_git,
+                invalidate_build_index,
+                launch_install,
                 launch_server,
+                mark_bisect,
+                list_content_profiles,
                 list_snapshots,
+                list_worktrees,
                 logout,
                 open_logs_folder,
                 open_system_logs_folder,
@@ -237,24 +257,39 @@ fn main() -> Result<(), CoreError> {
                 get_object_count,
                 get_github_status,
                 run_git_gc,
+                get_index_lock_status,
+                remove_stale_index_lock,
                 open_url,
+                plan_content_profile,
                 quick_submit,
                 rebase,
+                resolve_rebase_conflicts,
                 refresh_login,
                 acquire_locks,
+                apply_content_profile,
+                audit_blocked_files,
                 release_locks,
                 reset_config,
                 preview_snapshot,
+                prune_snapshots,
+                push_snapshot,
+                add_worktree,
+                remove_worktree,
+                switch_worktree,
                 restore_snapshot,
+                restore_snapshot_files,
                 revert_files,
                 restore_file_to_revision,
                 force_download_dlls,
                 force_download_engine,
                 reset_engine,
+                reset_bisect,
                 get_merge_queue,
+                get_tracked_submits,
                 open_url_for_path,
                 reinstall_git_hooks,
                 save_snapshot,
+                set_snapshot_tags,
                 save_changeset,
                 load_changeset,
                 stop_workflow,
@@ -263,21 +298,23 @@ fn main() -> Result<(), CoreError> {
                 sync_uproject_commit_with_engine,
                 reset_repo,
                 refetch_repo,
+                cancel_repo_operation,
                 reset_repo_to_commit,
                 restart,
                 generate_sln,
                 open_sln,
-                reset_longtail,
                 show_commit_files,
                 list_repo_directory,
                 get_file_history,
                 get_commit_info,
                 shutdown_server,
+                start_bisect,
                 start_gameserver_log_tail,
                 stop_gameserver_log_tail,
                 start_workflow_log_tail,
                 stop_workflow_log_tail,
                 sync_client,
+                sync_bisect,
                 sync_latest,
                 open_project,
                 terminate_server,
@@ -434,6 +471,16 @@ fn main() -> Result<(), CoreError> {
                     }
                 });
 
+                let (git_progress_tx, git_progress_rx) = std::sync::mpsc::channel::<GitProgress>();
+                let git_progress_app_handle = handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    while let Ok(progress) = git_progress_rx.recv() {
+                        git_progress_app_handle
+                            .emit("git-progress", &progress)
+                            .unwrap();
+                    }
+                });
+
                 // Channel for high-level sync phase labels (e.g. "Pulling latest
                 // changes from GitHub"). Separate from `git_tx` so phase labels
                 // in the pulling modal don't get stomped by the verbose `Running
@@ -452,39 +499,45 @@ fn main() -> Result<(), CoreError> {
                     while let Ok(msg) = longtail_rx.recv() {
                         Longtail::log_message(msg.clone());
 
-                        if let LongtailMsg::Log(s) = msg {
-                            longtail_handle.emit("longtail-log", &s).unwrap();
-
-                            if let Some(captures) = LONGTAIL_PROGRESS_REGEX.captures(&s) {
-                                let progress: String = captures
-                                    .get(1)
-                                    .map(|m| m.as_str())
-                                    .unwrap_or("")
-                                    .to_string();
-                                let elapsed: String = captures
-                                    .get(2)
-                                    .map(|m| m.as_str())
-                                    .unwrap_or("")
-                                    .to_string();
-                                let remaining: String = captures
-                                    .get(3)
-                                    .map(|m| m.as_str())
-                                    .unwrap_or("")
-                                    .to_string();
+                        match msg {
+                            LongtailMsg::Log(s) => {
+                                longtail_handle.emit("longtail-log", &s).unwrap();
+                            }
+                            LongtailMsg::Progress {
+                                phase,
+                                done_bytes,
+                                total_bytes,
+                                chunks_done,
+                                chunks_total,
+                                elapsed,
+                                eta,
+                            } => {
+                                let progress = GetProgress {
+                                    phase,
+                                    done_bytes,
+                                    total_bytes,
+                                    chunks_done,
+                                    chunks_total,
+                                    elapsed,
+                                };
 
                                 longtail_handle
                                     .emit(
                                         "longtail-sync-progress",
-                                        LongtailProgressCaptures {
-                                            progress,
-                                            elapsed,
-                                            remaining,
+                                        LongtailSyncProgress {
+                                            phase,
+                                            progress: progress.percent(),
+                                            done_bytes,
+                                            total_bytes,
+                                            chunks_done,
+                                            chunks_total,
+                                            elapsed: format_duration(elapsed),
+                                            remaining: eta.map(format_duration).unwrap_or_default(),
                                         },
                                     )
                                     .unwrap();
-                            } else {
-                                warn!("failed to parse longtail log: {}", &s);
                             }
+                            _ => {}
                         }
                     }
                 });
@@ -527,6 +580,7 @@ fn main() -> Result<(), CoreError> {
                         frontend_op_tx,
                         server_log_path,
                         git_tx.clone(),
+                        git_progress_tx.clone(),
                         sync_phase_tx.clone(),
                         gameserver_log_tx.clone(),
                         workflow_log_tx.clone(),
@@ -593,18 +647,3 @@ fn main() -> Result<(), CoreError> {
         )))
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use crate::LONGTAIL_PROGRESS_REGEX;
-
-    #[test]
-    fn test_longtail_regex() {
-        let caps = LONGTAIL_PROGRESS_REGEX.captures("Updating version             9%: |████                                              |: [30s:6m7s]");
-        caps.expect("Failed to match string");
-        let caps = LONGTAIL_PROGRESS_REGEX.captures("Indexing version            55%:|███████████████████████████                       |: [0s]");
-        caps.expect("Failed to match string");
-        let caps = LONGTAIL_PROGRESS_REGEX.captures("Updating version             1%: |                                                  |: [0s]");
-        caps.expect("Failed to match string");
-    }
-}
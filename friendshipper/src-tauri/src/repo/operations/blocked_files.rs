@@ -0,0 +1,53 @@
+use axum::extract::State;
+use axum::Json;
+use tracing::instrument;
+
+use crate::engine::EngineProvider;
+use crate::state::AppState;
+use ethos_core::blocked_files;
+use ethos_core::clients::git;
+use ethos_core::types::errors::CoreError;
+use ethos_core::types::repo::{BlockedFileAudit, BlockedFileAuditRequest};
+
+/// Runs a `blockedFileGlobs` list against every file tracked at HEAD without changing anything,
+/// so project leads (or CI) can see what a pattern change would block before committing it.
+#[instrument(skip(state))]
+pub async fn audit_blocked_files<T>(
+    State(state): State<AppState<T>>,
+    Json(request): Json<BlockedFileAuditRequest>,
+) -> Result<Json<BlockedFileAudit>, CoreError>
+where
+    T: EngineProvider,
+{
+    let target_branch = request
+        .target_branch
+        .unwrap_or_else(|| state.app_config.read().target_branch.clone());
+    let patterns = match request.patterns {
+        Some(patterns) => patterns,
+        None => state
+            .repo_config
+            .read()
+            .blocked_globs_for_branch(&target_branch)
+            .to_vec(),
+    };
+
+    let git_client = state.git();
+    let commit = git_client
+        .run_and_collect_output(&["rev-parse", "HEAD"], git::Opts::new_without_logs())
+        .await?
+        .trim()
+        .to_string();
+    let files = git_client.tracked_files(&commit).await?;
+
+    let audit = blocked_files::audit(
+        &patterns,
+        &files,
+        request.username.as_deref().unwrap_or_default(),
+    );
+
+    Ok(Json(BlockedFileAudit {
+        target_branch,
+        commit,
+        ..audit
+    }))
+}
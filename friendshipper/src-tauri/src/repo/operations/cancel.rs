@@ -0,0 +1,23 @@
+use axum::extract::State;
+use tracing::{info, instrument};
+
+use crate::engine::EngineProvider;
+use crate::state::AppState;
+use ethos_core::types::errors::CoreError;
+
+// Cancels whatever sequence the repo worker is running. Its git command is killed, and the
+// sequence's own error handling runs as it would for any failed git command. Nothing running is
+// not an error, the operation may have finished as the user clicked.
+#[instrument(skip(state))]
+pub async fn cancel_handler<T>(State(state): State<AppState<T>>) -> Result<(), CoreError>
+where
+    T: EngineProvider,
+{
+    if state.repo_cancel.cancel() {
+        info!("Cancelled the running repo operation");
+    } else {
+        info!("No repo operation running to cancel");
+    }
+
+    Ok(())
+}
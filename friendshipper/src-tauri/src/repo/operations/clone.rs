@@ -4,7 +4,6 @@ use anyhow::anyhow;
 use axum::extract::State;
 use axum::Json;
 use ethos_core::clients::git::configure_global;
-use fs_extra::dir::get_size;
 use tracing::info;
 
 use crate::engine::EngineProvider;
@@ -57,26 +56,10 @@ where
     let repo_path = PathBuf::from(&request.path).join(repo_name);
     let repo_path_str = repo_path.to_str().unwrap_or_default();
 
-    let tx = state.git_tx.clone();
-    let size_check_path = repo_path.clone();
-    let status_handle = tokio::spawn(async move {
-        loop {
-            let mut size = get_size(&size_check_path).unwrap_or_default() as f64 / 1024.0 / 1024.0;
-
-            if size > 1024.0 {
-                size /= 1024.0;
-                tx.send(format!("Downloaded: {size:.2} GB")).unwrap();
-            } else {
-                tx.send(format!("Downloaded: {size:.2} MB")).unwrap();
-            }
-
-            tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-        }
-    });
-
     // set pack window
     configure_global("pack.window", "1").await?;
 
+    // --progress reports each clone phase over git-progress, LFS checkout included
     state
         .git()
         .run(
@@ -85,8 +68,6 @@ where
         )
         .await?;
 
-    status_handle.abort();
-
     {
         // We need to force a read of the in-repo configuration file.
         let config = state.app_config.read();
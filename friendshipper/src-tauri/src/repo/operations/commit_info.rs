@@ -1,6 +1,6 @@
 use axum::extract::{Query, State};
 use axum::Json;
-use ethos_core::clients::git::Opts;
+use ethos_core::clients::git::{Git, Opts};
 use ethos_core::types::errors::CoreError;
 use ethos_core::types::repo::CommitInfo;
 use serde::Deserialize;
@@ -77,10 +77,14 @@ where
         )));
     }
 
-    let output = state
-        .git()
+    Ok(Json(fetch_commit_info(&state.git(), &params.sha).await?))
+}
+
+// Looks up a single commit with git show
+pub async fn fetch_commit_info(git: &Git, sha: &str) -> Result<CommitInfo, CoreError> {
+    let output = git
         .run_and_collect_output(
-            &["show", "--no-patch", GIT_SHOW_FORMAT, &params.sha],
+            &["show", "--no-patch", GIT_SHOW_FORMAT, sha],
             Opts {
                 skip_notify_frontend: true,
                 should_log_stdout: false,
@@ -90,7 +94,7 @@ where
         .await
         .map_err(|e| CoreError::Internal(anyhow::anyhow!("git show failed: {}", e)))?;
 
-    Ok(Json(parse_git_show_output(&output)?))
+    parse_git_show_output(&output)
 }
 
 #[cfg(test)]
@@ -0,0 +1,129 @@
+use anyhow::anyhow;
+use axum::extract::{Query, State};
+use axum::{async_trait, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+
+use crate::engine::EngineProvider;
+use crate::state::AppState;
+use ethos_core::clients::git::Git;
+use ethos_core::content_profiles;
+use ethos_core::types::config::ContentProfile;
+use ethos_core::types::errors::CoreError;
+use ethos_core::types::repo::{ContentProfilePlan, ContentProfilesResponse};
+use ethos_core::worker::{Task, TaskSequence};
+
+use super::StatusOp;
+
+#[derive(Clone)]
+pub struct ApplyContentProfileOp {
+    pub git_client: Git,
+    pub profile: ContentProfile,
+}
+
+#[async_trait]
+impl Task for ApplyContentProfileOp {
+    #[instrument(skip(self), name = "ApplyContentProfileOp::execute", fields(profile = %self.profile.name))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        // catch a broken pattern before anything in the clone changes
+        content_profiles::ContentProfileMatcher::compile(&self.profile)
+            .map_err(CoreError::Input)?;
+
+        self.git_client.apply_content_profile(&self.profile).await?;
+
+        info!("Applied content profile {}", self.profile.name);
+        Ok(())
+    }
+
+    fn get_name(&self) -> String {
+        "ApplyContentProfileOp".to_string()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ContentProfileRequest {
+    pub name: String,
+}
+
+fn find_profile<T>(state: &AppState<T>, name: &str) -> Result<ContentProfile, CoreError>
+where
+    T: EngineProvider,
+{
+    state
+        .repo_config
+        .read()
+        .content_profile(name)
+        .cloned()
+        .ok_or_else(|| CoreError::Input(anyhow!("Unknown content profile {}", name)))
+}
+
+#[instrument(skip(state))]
+pub async fn list_content_profiles<T>(
+    State(state): State<AppState<T>>,
+) -> Result<Json<ContentProfilesResponse>, CoreError>
+where
+    T: EngineProvider,
+{
+    let profiles = state.repo_config.read().content_profiles.clone();
+    let active = state.git().active_content_profile().await?;
+
+    Ok(Json(ContentProfilesResponse { profiles, active }))
+}
+
+#[instrument(skip(state))]
+pub async fn plan_content_profile<T>(
+    State(state): State<AppState<T>>,
+    Query(params): Query<ContentProfileRequest>,
+) -> Result<Json<ContentProfilePlan>, CoreError>
+where
+    T: EngineProvider,
+{
+    let profile = find_profile(&state, &params.name)?;
+    let files = state.git().lfs_files().await?;
+    let plan = content_profiles::plan(&profile, &files).map_err(CoreError::Input)?;
+
+    Ok(Json(plan))
+}
+
+#[instrument(skip(state))]
+pub async fn apply_content_profile<T>(
+    State(state): State<AppState<T>>,
+    Json(request): Json<ContentProfileRequest>,
+) -> Result<(), CoreError>
+where
+    T: EngineProvider,
+{
+    let profile = find_profile(&state, &request.name)?;
+
+    let (tx, rx) = tokio::sync::oneshot::channel::<Option<CoreError>>();
+    let mut sequence = TaskSequence::new().with_completion_tx(tx);
+    sequence.push(Box::new(ApplyContentProfileOp {
+        git_client: state.git(),
+        profile,
+    }));
+
+    // files came and went, make sure the status reflects the new working tree
+    sequence.push(Box::new(StatusOp {
+        repo_status: state.repo_status.clone(),
+        app_config: state.app_config.clone(),
+        repo_config: state.repo_config.clone(),
+        engine: state.engine.clone(),
+        git_client: state.git(),
+        github_username: state.github_username(),
+        aws_client: None,
+        storage: None,
+        allow_offline_communication: false,
+        skip_display_names: true,
+        skip_engine_update: true,
+    }));
+
+    state.operation_tx.send(sequence).await?;
+
+    match rx.await {
+        Ok(Some(e)) => Err(e),
+        Ok(None) => Ok(()),
+        Err(_) => Err(CoreError::Internal(anyhow!(
+            "Error executing content profile operation"
+        ))),
+    }
+}
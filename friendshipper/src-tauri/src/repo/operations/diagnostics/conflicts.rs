@@ -0,0 +1,195 @@
+use anyhow::anyhow;
+use axum::extract::{Query, State};
+use axum::{async_trait, Json};
+use serde::Deserialize;
+use tracing::{info, instrument, warn};
+
+use crate::engine::EngineProvider;
+use crate::repo::operations::file_history::last_revision;
+use crate::repo::operations::{new_pull_op, ResumePullOp, StatusOp};
+use ethos_core::clients::git::Git;
+use ethos_core::types::errors::CoreError;
+use ethos_core::types::repo::{
+    ConflictMergePreview, ConflictResolution, RebaseConflictsResponse, ResolveConflictsRequest,
+};
+use ethos_core::worker::{Task, TaskSequence};
+
+use crate::state::{AppState, Notification};
+
+// Applies per-file resolutions to a stopped rebase and optionally continues it. Runs on the
+// worker so it can't interleave with a sync, and so the file watcher is paused while files are
+// checked out.
+#[derive(Clone)]
+pub struct ResolveConflictsOp {
+    pub git_client: Git,
+    pub resolutions: Vec<ConflictResolution>,
+    pub continue_rebase: bool,
+}
+
+#[async_trait]
+impl Task for ResolveConflictsOp {
+    #[instrument(name = "ResolveConflictsOp::execute", skip(self))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        if !self.git_client.rebase_in_progress().await? {
+            return Err(CoreError::Input(anyhow!("No rebase is in progress")));
+        }
+
+        for resolution in self.resolutions.iter() {
+            info!(
+                "Resolving conflict in {} with {:?}",
+                resolution.path, resolution.side
+            );
+            self.git_client
+                .resolve_conflict(&resolution.path, resolution.side)
+                .await
+                .map_err(CoreError::Input)?;
+        }
+
+        if self.continue_rebase {
+            self.git_client
+                .continue_rebase()
+                .await
+                .map_err(CoreError::Input)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> String {
+        String::from("ResolveConflictsOp")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConflictPreviewParams {
+    pub path: String,
+}
+
+async fn get_rebase_conflicts<T>(state: &AppState<T>) -> Result<RebaseConflictsResponse, CoreError>
+where
+    T: EngineProvider,
+{
+    let mut conflicts = state.git().list_rebase_conflicts().await?;
+
+    // Binary assets can only be resolved by picking a side, so show who holds the lock and who
+    // last touched the file upstream to help decide whose version wins
+    let onto = conflicts.onto.clone();
+    for file in conflicts.files.iter_mut().filter(|f| f.is_binary) {
+        {
+            let repo_status = state.repo_status.read();
+            file.locked_by = repo_status
+                .locks_ours
+                .iter()
+                .chain(repo_status.locks_theirs.iter())
+                .find(|lock| lock.path == file.path)
+                .map(|lock| {
+                    lock.display_name.clone().unwrap_or_else(|| {
+                        lock.owner
+                            .as_ref()
+                            .map(|owner| owner.name.clone())
+                            .unwrap_or_default()
+                    })
+                });
+        }
+
+        if let Some(onto) = &onto {
+            match last_revision(state, onto, &file.path).await {
+                Ok(revision) => file.last_change = revision,
+                Err(e) => warn!("Failed to get history for {}: {}", file.path, e),
+            }
+        }
+    }
+
+    Ok(conflicts)
+}
+
+#[instrument(skip(state))]
+pub async fn rebase_conflicts_handler<T>(
+    State(state): State<AppState<T>>,
+) -> Result<Json<RebaseConflictsResponse>, CoreError>
+where
+    T: EngineProvider,
+{
+    Ok(Json(get_rebase_conflicts(&state).await?))
+}
+
+#[instrument(skip(state))]
+pub async fn conflict_preview_handler<T>(
+    State(state): State<AppState<T>>,
+    Query(params): Query<ConflictPreviewParams>,
+) -> Result<Json<ConflictMergePreview>, CoreError>
+where
+    T: EngineProvider,
+{
+    let preview = state
+        .git()
+        .merge_conflict_preview(&params.path)
+        .await
+        .map_err(CoreError::Input)?;
+
+    Ok(Json(preview))
+}
+
+#[instrument(skip(state))]
+pub async fn resolve_conflicts_handler<T>(
+    State(state): State<AppState<T>>,
+    Json(request): Json<ResolveConflictsRequest>,
+) -> Result<Json<RebaseConflictsResponse>, CoreError>
+where
+    T: EngineProvider,
+{
+    let (tx, rx) = tokio::sync::oneshot::channel::<Option<CoreError>>();
+    let mut sequence = TaskSequence::new().with_completion_tx(tx);
+
+    sequence.push(Box::new(ResolveConflictsOp {
+        git_client: state.git(),
+        resolutions: request.resolutions,
+        continue_rebase: request.continue_rebase,
+    }));
+
+    // Once the rebase finishes, the sync that stopped on these conflicts still has to restore
+    // its snapshot and bring DLLs and the engine up to date. Without AWS that has to wait for
+    // another sync, but the branch and files have changed, so at least pick that up right away.
+    if request.continue_rebase {
+        match new_pull_op(&state).await {
+            Ok(pull) => sequence.push(Box::new(ResumePullOp { pull })),
+            Err(e) => {
+                warn!(
+                    "Unable to finish the stopped sync, sync again once resolved: {}",
+                    e
+                );
+                state.send_notification(Notification::Error(
+                    "Unable to finish the stopped sync. Please sync again once the rebase is done."
+                        .to_string(),
+                ));
+                sequence.push(Box::new(StatusOp {
+                    repo_status: state.repo_status.clone(),
+                    app_config: state.app_config.clone(),
+                    repo_config: state.repo_config.clone(),
+                    engine: state.engine.clone(),
+                    git_client: state.git(),
+                    github_username: state.github_username(),
+                    aws_client: None,
+                    storage: None,
+                    allow_offline_communication: false,
+                    skip_display_names: true,
+                    skip_engine_update: true,
+                }));
+            }
+        }
+    }
+
+    state.operation_tx.send(sequence).await?;
+
+    match rx.await {
+        Ok(Some(e)) => return Err(e),
+        Ok(None) => {}
+        Err(_) => {
+            return Err(CoreError::Internal(anyhow!(
+                "Error executing conflict resolution"
+            )))
+        }
+    }
+
+    Ok(Json(get_rebase_conflicts(&state).await?))
+}
@@ -0,0 +1,42 @@
+use anyhow::anyhow;
+use axum::extract::State;
+use axum::Json;
+
+use crate::engine::EngineProvider;
+use ethos_core::types::errors::CoreError;
+use ethos_core::types::repo::IndexLockStatus;
+
+use crate::state::AppState;
+
+pub async fn index_lock_handler<T>(
+    State(state): State<AppState<T>>,
+) -> Result<Json<IndexLockStatus>, CoreError>
+where
+    T: EngineProvider,
+{
+    Ok(Json(state.git().index_lock_status()?))
+}
+
+pub async fn remove_index_lock_handler<T>(
+    State(state): State<AppState<T>>,
+) -> Result<Json<IndexLockStatus>, CoreError>
+where
+    T: EngineProvider,
+{
+    let git = state.git();
+    let status = git.index_lock_status()?;
+    if status.exists && !status.stale {
+        return Err(CoreError::Input(anyhow!(
+            "index.lock is still held by a running git process ({}). Wait for it to finish or close it first.",
+            status
+                .git_processes
+                .iter()
+                .map(|pid| pid.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    git.remove_stale_index_lock()?;
+    Ok(Json(git.index_lock_status()?))
+}
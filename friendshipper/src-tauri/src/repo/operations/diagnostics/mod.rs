@@ -1,8 +1,16 @@
+mod conflicts;
 mod github_status;
+mod index_lock;
 mod object_count;
 mod rebase;
 
+pub use conflicts::conflict_preview_handler;
+pub use conflicts::rebase_conflicts_handler;
+pub use conflicts::resolve_conflicts_handler;
+pub use conflicts::ResolveConflictsOp;
 pub use github_status::github_status_handler;
+pub use index_lock::index_lock_handler;
+pub use index_lock::remove_index_lock_handler;
 pub use object_count::object_count_handler;
 pub use object_count::run_gc_handler;
 pub use rebase::rebase_handler;
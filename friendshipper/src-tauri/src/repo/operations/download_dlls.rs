@@ -17,6 +17,7 @@ use crate::engine::EngineProvider;
 use ethos_core::clients::aws::ensure_aws_client;
 use ethos_core::clients::git;
 use ethos_core::longtail;
+use ethos_core::longtail::cache::CacheConsumer;
 use ethos_core::msg::LongtailMsg;
 use ethos_core::storage::config::Project;
 use ethos_core::storage::ArtifactStorage;
@@ -76,9 +77,6 @@ where
         let mut binaries_staging_path =
             Path::join(&self.longtail.download_path.0, Path::new("editor_staging"));
 
-        let mut binaries_cache_path =
-            Path::join(&self.longtail.download_path.0, Path::new("editor_cache"));
-
         let mut binaries_destination_path = PathBuf::from(&self.git_client.repo_path);
 
         // If the project has a "Source" directory in the root, we place the DLLs there, otherwise
@@ -87,7 +85,6 @@ where
             .join("Source")
             .is_dir();
         if !source_exists {
-            binaries_cache_path = binaries_cache_path.join(&self.project_name);
             binaries_staging_path = binaries_staging_path.join(&self.project_name);
             binaries_destination_path = binaries_destination_path.join(&self.project_name);
         };
@@ -146,16 +143,20 @@ where
             };
         }
 
-        let dll_download_result = self.longtail.get_archive(
-            &binaries_staging_path,
-            Some(longtail::CacheControl {
-                path: binaries_cache_path,
-                max_size_bytes: 5 * 1024 * 1024 * 1024, // 5 GB
-            }),
-            &archive_urls,
-            self.tx.clone(),
-            self.aws_client.get_credentials().await,
-        );
+        let dll_download_result = self
+            .longtail
+            .get_archive(
+                &binaries_staging_path,
+                Some(longtail::CacheControl {
+                    consumer: CacheConsumer::Dlls,
+                    quota_bytes: 5 * 1024 * 1024 * 1024, // 5 GB
+                }),
+                &archive_urls,
+                self.tx.clone(),
+                self.aws_client.get_credentials().await,
+                self.longtail.cancel_token(),
+            )
+            .await;
         match dll_download_result {
             Ok(()) => {}
             Err(e) => {
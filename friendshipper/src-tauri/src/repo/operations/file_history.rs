@@ -216,6 +216,40 @@ where
     }))
 }
 
+/// The most recent revision of `path` reachable from `git_ref`, if any commit touched it.
+pub async fn last_revision<T>(
+    state: &AppState<T>,
+    git_ref: &str,
+    path: &str,
+) -> Result<Option<FileHistoryRevision>, CoreError>
+where
+    T: EngineProvider,
+{
+    let output = state
+        .git()
+        .run_and_collect_output(
+            &[
+                "log",
+                "-1",
+                "--pretty=format:%H|%an|%ad|%s",
+                "--date=raw",
+                "--name-status",
+                git_ref,
+                "--",
+                path,
+            ],
+            Default::default(),
+        )
+        .await?;
+
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return Ok(None);
+    }
+
+    get_revision(state, &lines[..2], false).await.map(Some)
+}
+
 // We do have separate code for this conversion in the core library, but this is meant to
 // be a nicer string for editor UI.
 fn translate_action(action: &str) -> String {
@@ -2,10 +2,12 @@ pub mod commits;
 pub mod merge_queue;
 pub mod pulls;
 pub mod submit;
+pub mod submit_tracker;
 mod user;
 
 pub use commits::get_commit_statuses;
 pub use merge_queue::get_merge_queue;
 pub use pulls::{get_pull_request, get_pull_requests};
 pub use submit::submit_handler;
+pub use submit_tracker::get_tracked_submits;
 pub use user::get_user;
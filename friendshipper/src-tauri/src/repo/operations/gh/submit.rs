@@ -10,25 +10,29 @@ use tracing::{debug, error, info, instrument, warn};
 
 use crate::engine::CommunicationType;
 use crate::engine::EngineProvider;
-use crate::repo::operations::pull::PullOp;
+use crate::repo::operations::gh::submit_tracker::SubmitTracker;
+use crate::repo::operations::pull::{PullOp, StoppedPullRef};
 use crate::repo::operations::validate::validate_repo_state;
 use crate::repo::operations::StatusOp;
 use crate::repo::RepoStatusRef;
 use crate::state::AppState;
 use crate::state::Notification;
+use ethos_core::blocked_files::BlockedFileMatcher;
 use ethos_core::clients::git;
 use ethos_core::clients::github;
 use ethos_core::longtail::Longtail;
 use ethos_core::msg::LongtailMsg;
 use ethos_core::operations::{AddOp, CommitOp, LockOp, RestoreOp};
 use ethos_core::storage::ArtifactStorage;
+use ethos_core::submit_validation::{run_submit_validators, SubmitValidation};
 use ethos_core::types::config::AppConfigRef;
 use ethos_core::types::config::RepoConfigRef;
+use ethos_core::types::config::SubmitValidator;
 use ethos_core::types::errors::CoreError;
 use ethos_core::types::github::TokenNotFoundError;
 use ethos_core::types::locks::LockOperation;
 use ethos_core::types::repo::SubmitStatus;
-use ethos_core::types::repo::{File, PushRequest, RepoStatus};
+use ethos_core::types::repo::{File, FileState, PushRequest, RepoStatus, StackedOn, TrackedSubmit};
 use ethos_core::worker::{Task, TaskSequence};
 use ethos_core::AWSClient;
 
@@ -37,9 +41,12 @@ pub struct GitHubSubmitOp {
     pub head_branch: String,
     pub base_branch: String,
     pub commit_message: String,
+    pub files: Vec<String>,
     pub repo_status: RepoStatusRef,
     pub token: String,
     pub client: github::GraphQLClient,
+    pub submit_tracker: SubmitTracker,
+    pub stacked_on: Option<StackedOn>,
     pub use_merge_queue: bool,
 }
 
@@ -65,10 +72,12 @@ where
     /// quicksubmit merge so the pulling modal shows the same phase labels
     /// a standalone Sync would.
     pub sync_phase_tx: Sender<String>,
+    pub stopped_pull: StoppedPullRef,
 
     pub git_client: git::Git,
     pub token: String,
     pub github_client: github::GraphQLClient,
+    pub submit_tracker: SubmitTracker,
 }
 
 const SUBMIT_PREFIX: &str = "[quick submit]";
@@ -389,9 +398,21 @@ async fn reject_unsubmittable_files<T: EngineProvider>(
         )
         .await;
 
+    // Only consulted for a configured reason. A file StatusOp marked Blocked
+    // is blocked by the last pattern matching it, which didn't allow this
+    // user, so looking it up without a username finds the same pattern.
+    let blocked_matcher = {
+        let target_branch = app_config.read().target_branch.clone();
+        let globs = repo_config
+            .read()
+            .blocked_globs_for_branch(&target_branch)
+            .to_vec();
+        BlockedFileMatcher::compile(&globs).matcher
+    };
+
     // Group by reason so the toast reads as "here are your N blocked files"
     // rather than N separate lines repeating the same explanation.
-    let mut by_reason: Vec<(&'static str, Vec<String>)> = vec![];
+    let mut by_reason: Vec<(String, Vec<String>)> = vec![];
 
     for (file, display_name) in unsubmittable_files
         .iter()
@@ -414,19 +435,88 @@ async fn reject_unsubmittable_files<T: EngineProvider>(
             SubmitStatus::CheckedOutByOtherUser => "This file is an asset and must be checked out (locked) before submitting, but it is locked by another user",
             SubmitStatus::Unmerged => "This file is unmerged and must be reverted to continue",
             SubmitStatus::Conflicted => "A newer version of this file exists; this file must be reverted to continue",
-            SubmitStatus::Blocked => "This file matches a blocked-file-glob pattern configured for this target branch and cannot be submitted",
+            SubmitStatus::Blocked => match blocked_matcher
+                .blocking_pattern(&file.path, "")
+                .and_then(|pattern| pattern.reason.as_deref())
+            {
+                Some(reason) => reason,
+                None => "This file matches a blocked-file-glob pattern configured for this target branch and cannot be submitted",
+            },
         };
         tracing::error!("{}: {}", reason, name_formatted);
+        group_by_reason(&mut by_reason, reason.to_string(), name_formatted);
+    }
 
-        match by_reason.iter_mut().find(|(r, _)| *r == reason) {
-            Some((_, paths)) => paths.push(name_formatted),
-            None => by_reason.push((reason, vec![name_formatted])),
-        }
+    Err(CoreError::Input(anyhow!(rejection_message(
+        "Some files are not allowed to be submitted:",
+        &by_reason
+    ))))
+}
+
+/// Runs the project's `submitValidators` against the files being submitted, rejecting the submit
+/// with every failure grouped by reason, the same way blocked files are reported.
+async fn reject_failed_validations(
+    validators: &[SubmitValidator],
+    requested_files: &[String],
+    repo_status: &RepoStatus,
+    repo_path: &std::path::Path,
+    target_branch: &str,
+    commit_message: &str,
+) -> Result<(), CoreError> {
+    // deleting a file is always allowed
+    let files: Vec<String> = requested_files
+        .iter()
+        .filter(|path| {
+            !repo_status
+                .modified_files
+                .0
+                .iter()
+                .any(|file| file.path == **path && file.state == FileState::Deleted)
+        })
+        .cloned()
+        .collect();
+
+    let failures = run_submit_validators(
+        validators,
+        &SubmitValidation {
+            repo_path,
+            target_branch,
+            commit_message,
+            files: &files,
+        },
+    )
+    .await;
+    if failures.is_empty() {
+        return Ok(());
     }
 
+    let mut by_reason: Vec<(String, Vec<String>)> = vec![];
+    for failure in failures {
+        let reason = format!("{}: {}", failure.validator, failure.reason);
+        let path = failure
+            .path
+            .unwrap_or_else(|| "(the submit as a whole)".to_string());
+        tracing::error!("{}: {}", reason, path);
+        group_by_reason(&mut by_reason, reason, path);
+    }
+
+    Err(CoreError::Input(anyhow!(rejection_message(
+        "Some files didn't pass this project's submit checks:",
+        &by_reason
+    ))))
+}
+
+fn group_by_reason(by_reason: &mut Vec<(String, Vec<String>)>, reason: String, path: String) {
+    match by_reason.iter_mut().find(|(r, _)| *r == reason) {
+        Some((_, paths)) => paths.push(path),
+        None => by_reason.push((reason, vec![path])),
+    }
+}
+
+fn rejection_message(header: &str, by_reason: &[(String, Vec<String>)]) -> String {
     const MAX_PATHS_PER_REASON: usize = 10;
-    let mut message = String::from("Some files are not allowed to be submitted:\n");
-    for (reason, paths) in &by_reason {
+    let mut message = format!("{header}\n");
+    for (reason, paths) in by_reason {
         message.push_str(&format!("\n{reason}:\n"));
         for path in paths.iter().take(MAX_PATHS_PER_REASON) {
             message.push_str(&format!("  - {path}\n"));
@@ -439,7 +529,7 @@ async fn reject_unsubmittable_files<T: EngineProvider>(
         }
     }
 
-    Err(CoreError::Input(anyhow!(message)))
+    message
 }
 
 /// Count the leading run of byte `c` at the start of `s`. Used to recognize a
@@ -602,6 +692,76 @@ where
     fn emit_phase(&self, phase: impl Into<String>) {
         let _ = self.sync_phase_tx.send(phase.into());
     }
+
+    /// Picks the commit a batched quick submit should be built on: the head of the newest in-flight
+    /// submit that touches any of the same files, or the target branch if there isn't one.
+    async fn stack_base(
+        &self,
+        git_client_worktree: &git::Git,
+        in_flight: &[TrackedSubmit],
+        target_branch: &str,
+    ) -> Result<StackBase, CoreError> {
+        let git_opts_lfs_stubs = git::Opts::default().with_lfs_stubs();
+
+        if let Some(parent) = newest_overlapping_submit(in_flight, &self.files) {
+            match git_client_worktree
+                .run(
+                    &["fetch", "origin", &parent.head_branch],
+                    git_opts_lfs_stubs,
+                )
+                .await
+            {
+                Ok(_) => {
+                    let commit = git_client_worktree
+                        .run_and_collect_output(
+                            &["rev-parse", &format!("origin/{}", parent.head_branch)],
+                            git_opts_lfs_stubs,
+                        )
+                        .await?
+                        .trim()
+                        .to_string();
+                    return Ok(StackBase {
+                        description: format!("#{}", parent.number),
+                        stacked_on: Some(StackedOn {
+                            id: parent.id.clone(),
+                            number: parent.number,
+                            commit: commit.clone(),
+                        }),
+                        commit,
+                    });
+                }
+                Err(e) => {
+                    // The branch is deleted once the PR merges, so it may have landed since we last polled
+                    warn!(
+                        "Failed to fetch {} for #{}, submitting on top of {}: {}",
+                        parent.head_branch, parent.number, target_branch, e
+                    );
+                }
+            }
+        }
+
+        Ok(StackBase {
+            commit: format!("origin/{target_branch}"),
+            stacked_on: None,
+            description: format!("origin/{target_branch}"),
+        })
+    }
+}
+
+struct StackBase {
+    commit: String,
+    stacked_on: Option<StackedOn>,
+    description: String,
+}
+
+/// Returns the newest submit in `in_flight` (ordered newest first) that touches any of `files`.
+fn newest_overlapping_submit<'a>(
+    in_flight: &'a [TrackedSubmit],
+    files: &[String],
+) -> Option<&'a TrackedSubmit> {
+    in_flight
+        .iter()
+        .find(|submit| submit.files.iter().any(|file| files.contains(file)))
 }
 
 #[async_trait]
@@ -636,44 +796,37 @@ impl Task for GitHubSubmitOp {
             .send()
             .await?;
 
-        if self.use_merge_queue {
-            // Fire-and-forget for merge queue path
-            let self_clone = self.clone();
-            let octocrab_clone = octocrab.clone();
-            let owner_clone = owner.clone();
-            let repo_clone = repo.clone();
-
-            tokio::spawn(async move {
-                match self_clone
-                    .poll_for_mergeable(
-                        octocrab_clone.clone(),
-                        pr,
-                        owner_clone.clone(),
-                        repo_clone.clone(),
-                    )
+        // the merge queue mutations the tracker makes take the node id rather than the number
+        let id = match pr.node_id.clone() {
+            Some(id) => Ok(id),
+            None => {
+                self.client
+                    .get_pull_request_id(owner.clone(), repo.clone(), pr.number as i64)
                     .await
-                {
-                    Ok(updated_pr) => {
-                        if let Ok(id) = self_clone
-                            .client
-                            .get_pull_request_id(
-                                owner_clone.clone(),
-                                repo_clone.clone(),
-                                updated_pr.number as i64,
-                            )
-                            .await
-                        {
-                            if let Err(e) = self_clone.client.enqueue_pull_request(id).await {
-                                warn!("Failed to enqueue pull request: {:?}", e);
-                            }
-                        } else {
-                            warn!("Failed to get pull request ID");
-                        }
-                    }
-                    Err(e) => warn!("Failed to poll for mergeable state: {:?}", e),
-                }
-            });
+            }
+        };
+        match id {
+            Ok(id) => self.submit_tracker.track(TrackedSubmit {
+                number: pr.number,
+                id,
+                owner: owner.clone(),
+                repo: repo.clone(),
+                title: truncated_message,
+                head_branch: self.head_branch.clone(),
+                base_branch: self.base_branch.clone(),
+                uses_merge_queue: self.use_merge_queue,
+                files: self.files.clone(),
+                stacked_on: self.stacked_on.clone(),
+                ..Default::default()
+            }),
+            Err(e) => warn!(
+                "Failed to get ID of PR #{}, not tracking it: {}",
+                pr.number, e
+            ),
+        }
 
+        if self.use_merge_queue {
+            // The submit tracker adds it to the merge queue once checks pass, and follows it from there
             Ok(())
         } else {
             // Non-merge-queue: wait inline for merge to complete with timeout
@@ -755,17 +908,17 @@ impl GitHubSubmitOp {
 // with the latest changes in main.
 // The general logic for quick submit pushes go like this:
 // 1. User initiates quick submit
-// 2. If the current branch has an existing quick submit change in the merge queue, cancel it. We'll just reuse the current branch.
-//    We need to cancel the in-flight one since if it lands in main, it will conflict with what we try to put in the merge queue, so
-//    instead we just resolve all the changes locally again, push them all up to the same branch, and resubmit to the merge queue.
-// 3. Make a new f11r-<timestamp> branch to contain the changes if needed.
-// 4. Commit new changes
-// 5. If a scratch worktree folder doesn't exist, make one.
-// 6. In the workree directory:
-//    a. Make a branch called f11r-<timestamp>-wt and ensure it's up to date with exactly what's on f11r-<timestamp>.
-//    b. Resolve local changes with latest main
+// 2. If earlier quick submits from the current branch are still on their way into main, leave them alone and keep committing on
+//    the current branch. Otherwise, make a new f11r-<timestamp> branch off latest main to contain the changes.
+// 3. Commit new changes
+// 4. If a scratch worktree folder doesn't exist, make one.
+// 5. In the workree directory, make a branch called f11r-<timestamp>-wt:
+//    a. With nothing in flight, it matches f11r-<timestamp> exactly and is rebased onto latest main.
+//    b. Otherwise, only the new commit is cherry-picked onto latest main, or onto the head of the newest in-flight submit that
+//       shares files with it. Stacking keeps the two PRs from conflicting with each other in the merge queue; the tracker
+//       restacks the dependent PR onto main if its parent is ejected or closed.
 //    c. Push changes to the remote
-// 7. Trigger PR via github
+// 6. Trigger PR via github
 #[async_trait]
 impl<T> Task for SubmitOp<T>
 where
@@ -841,6 +994,24 @@ where
             }
         }
 
+        // Project-defined checks from friendshipper.yaml. Also before the snapshot, for the same reason.
+        let validators = self.repo_config.read().submit_validators.clone();
+        if !validators.is_empty() {
+            self.emit_phase("Running submit checks");
+            let repo_status = self.repo_status.read().clone();
+            let repo_path = PathBuf::from(self.app_config.read().repo_path.clone());
+            let target_branch = self.app_config.read().target_branch.clone();
+            reject_failed_validations(
+                &validators,
+                &self.files,
+                &repo_status,
+                &repo_path,
+                &target_branch,
+                &self.commit_message,
+            )
+            .await?;
+        }
+
         // save a snapshot before submitting with all modified/added files
         // make sure we have a temp dir for copying our files
         let status = self.repo_status.read().clone();
@@ -922,7 +1093,7 @@ where
             })?
             .uses_merge_queue;
 
-        let mut f11r_branch = {
+        let submit_branch = {
             let display_name = &self.app_config.read().user_display_name;
             let santized_display_name = display_name.replace(' ', "-");
             format!(
@@ -932,73 +1103,21 @@ where
                 chrono::Utc::now().timestamp()
             )
         };
+        let mut f11r_branch = submit_branch.clone();
 
-        // If the target branch uses merge queue, it's possible there's an inflight quicksubmit.
-        // Cancel it - we can be reasonably sure
-        let mut needs_new_pr = true;
-        let mut quicksubmit_pr_id: Option<String> = None;
-        if use_merge_queue && is_quicksubmit_branch(&prev_branch) {
-            let owner: String;
-            let repo: String;
-            {
-                let status = self.repo_status.read();
-                owner = status.repo_owner.clone();
-                repo = status.repo_name.clone();
-            }
-
-            // Skip merge queue check if repo owner/name are not set (during startup)
-            if owner.is_empty() || repo.is_empty() {
-                debug!("Skipping merge queue check: repo owner/name not yet configured (owner='{}', repo='{}')", owner, repo);
-                return Err(CoreError::Internal(anyhow::anyhow!("Repository information not yet available - please wait for repo status to initialize")));
-            }
-
-            let merge_queue = self
-                .github_client
-                .get_merge_queue_no_retry(&owner, &repo)
-                .await?;
-            if let Some(entries) = merge_queue.entries {
-                if let Some(nodes) = entries.nodes {
-                    for node in nodes.into_iter().flatten() {
-                        if let Some(commit) = node.head_commit {
-                            if let Some(author) = commit.author {
-                                if let Some(pr) = node.pull_request {
-                                    if let Some(user) = author.user {
-                                        if user.login == self.github_client.username
-                                            && pr.title.starts_with(SUBMIT_PREFIX)
-                                        {
-                                            // There should only be one quicksubmit PR in merge queue at a time
-                                            quicksubmit_pr_id = Some(pr.id);
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            if let Some(id) = &quicksubmit_pr_id {
-                // Silently absorb errors - the PR may have been already merged in this case
-                let res = self
-                    .github_client
-                    .dequeue_pull_request(id.to_string())
-                    .await;
-                match res {
-                    Ok(_) => {
-                        needs_new_pr = false;
-                    }
-                    Err(e) => {
-                        warn!(
-                            "Failed to cancel existing pull request {}. Reason: {}",
-                            id, e
-                        );
-                    }
-                }
-            }
-        }
+        // Quick submits of ours still on their way into the target branch. While there are any,
+        // the new change is committed on top of them locally and gets a PR of its own, stacked on
+        // the latest one it shares files with.
+        let in_flight = if use_merge_queue && is_quicksubmit_branch(&prev_branch) {
+            let status = self.repo_status.read();
+            self.submit_tracker
+                .in_flight(&status.repo_owner, &status.repo_name, &target_branch)
+        } else {
+            vec![]
+        };
+        let batched = !in_flight.is_empty();
 
-        if needs_new_pr {
+        if !batched {
             // If we're currently on a quicksubmit branch, we need to first checkout
             // the target branch to ensure the new f11r branch is created from the
             // latest target branch, not from the old quicksubmit branch.
@@ -1121,83 +1240,18 @@ where
         }
 
         self.emit_phase("Pushing to GitHub");
-        let worktree_path: PathBuf = 'path: {
-            let repo_path = PathBuf::from(self.app_config.read().repo_path.clone());
+        let repo_path = PathBuf::from(self.app_config.read().repo_path.clone());
+        let git_client_worktree =
+            prepare_quick_submit_worktree(&self.git_client, repo_path).await?;
 
-            let worktrees = self.git_client.list_worktrees().await?;
-            for tree in worktrees.iter() {
-                if tree.directory != repo_path {
-                    // if the directory exists on disk, break
-                    if tree.directory.exists() {
-                        break 'path tree.directory.clone();
-                    }
-
-                    // if the directory doesn't exist, remove the worktree
-                    self.git_client
-                        .run(
-                            &[
-                                "worktree",
-                                "remove",
-                                tree.directory.to_string_lossy().as_ref(),
-                            ],
-                            Default::default(),
-                        )
-                        .await?;
-                }
-            }
-
-            let repo_folder_name: String = repo_path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-
-            // create worktree if it doesn't exist yet
-            let mut worktree_path = repo_path.clone();
-            worktree_path.pop();
-            worktree_path.push(format!(".{repo_folder_name}-wt"));
-
-            self.git_client
-                .run(
-                    &[
-                        "worktree",
-                        "add",
-                        "--detach",
-                        &worktree_path.to_string_lossy(),
-                    ],
-                    git::Opts::default().with_lfs_stubs(),
-                )
-                .await?;
-
-            worktree_path.clone()
-        };
-
-        let worktree_branch = format!("{f11r_branch}-wt");
-
-        let mut git_client_worktree = self.git_client.clone();
-        git_client_worktree.repo_path.clone_from(&worktree_path);
+        let worktree_branch = format!("{submit_branch}-wt");
 
         // To make the worktree as cheap as possible, we need to make sure no LFS files are checked out and
         // they remain stubs
         let git_opts_lfs_stubs = git::Opts::default().with_lfs_stubs();
 
-        // Abort any in-progress rebase from a previous failed submit
-        _ = git_client_worktree
-            .run(
-                &["rebase", "--abort"],
-                git::Opts::new_with_ignored(&["no rebase in progress"]).with_lfs_stubs(),
-            )
-            .await;
-
-        // make sure the worktree is hard reset
-        git_client_worktree
-            .run(&["reset", "--hard"], git_opts_lfs_stubs)
-            .await?;
-        git_client_worktree
-            .run(&["clean", "-fd"], git_opts_lfs_stubs)
-            .await?;
-
         // resolve changes with latest main and push up to the remote
+        let mut stacked_on: Option<StackedOn> = None;
         {
             let worktree_prev_branch = git_client_worktree.current_branch().await?;
 
@@ -1209,27 +1263,73 @@ where
                     .await;
             }
 
-            // Checkout a new branch for the worktree in the same state as the f11r branch
-            self.git_client
-                .run(
-                    &["branch", &worktree_branch, &f11r_branch],
-                    git::Opts::default(),
-                )
-                .await?;
-
-            // now we can resolve any new changes in main with the current changes and push up to the remote
-            git_client_worktree
-                .run(&["checkout", &worktree_branch], git_opts_lfs_stubs)
-                .await?;
             git_client_worktree
                 .run(&["fetch", "origin", &*target_branch], git_opts_lfs_stubs)
                 .await?;
-            git_client_worktree
-                .run(
-                    &["rebase", &format!("origin/{target_branch}")],
-                    git_opts_lfs_stubs,
-                )
-                .await?;
+
+            if batched {
+                // The f11r branch still carries the submits in flight, so only the new commit goes
+                // in this PR, on top of the one it depends on if any
+                let commit = self
+                    .git_client
+                    .run_and_collect_output(&["rev-parse", "HEAD"], git::Opts::default())
+                    .await?;
+                let base = self
+                    .stack_base(&git_client_worktree, &in_flight, &target_branch)
+                    .await?;
+                if let Some(parent) = &base.stacked_on {
+                    self.emit_phase(format!("Stacking on #{}", parent.number));
+                }
+
+                git_client_worktree
+                    .run(
+                        &["checkout", "-b", &worktree_branch, &base.commit],
+                        git_opts_lfs_stubs,
+                    )
+                    .await?;
+                if let Err(e) = git_client_worktree
+                    .run(&["cherry-pick", commit.trim()], git_opts_lfs_stubs)
+                    .await
+                {
+                    _ = git_client_worktree
+                        .run(
+                            &["cherry-pick", "--abort"],
+                            git::Opts::new_with_ignored(&["no cherry-pick"]).with_lfs_stubs(),
+                        )
+                        .await;
+                    cleanup_worktree_branch(
+                        &git_client_worktree,
+                        &worktree_branch,
+                        git_opts_lfs_stubs,
+                    )
+                    .await;
+                    return Err(CoreError::Input(anyhow!(
+                        "Your change doesn't apply cleanly on top of {}: {}. Sync, then submit again.",
+                        base.description,
+                        e
+                    )));
+                }
+                stacked_on = base.stacked_on;
+            } else {
+                // Checkout a new branch for the worktree in the same state as the f11r branch
+                self.git_client
+                    .run(
+                        &["branch", &worktree_branch, &f11r_branch],
+                        git::Opts::default(),
+                    )
+                    .await?;
+
+                // now we can resolve any new changes in main with the current changes and push up to the remote
+                git_client_worktree
+                    .run(&["checkout", &worktree_branch], git_opts_lfs_stubs)
+                    .await?;
+                git_client_worktree
+                    .run(
+                        &["rebase", &format!("origin/{target_branch}")],
+                        git_opts_lfs_stubs,
+                    )
+                    .await?;
+            }
 
             // force is needed when pushing changes because we may be reusing a remote branch
             git_client_worktree
@@ -1249,169 +1349,129 @@ where
             }
         }
 
-        // If we already have a PR, we must be using the merge queue so just requeue it.
-        // Otherwise create a whole new Github submit op.
-        match quicksubmit_pr_id {
-            // We have a PR, this is a requeue
-            Some(pr_id) => {
-                self.emit_phase("Re-queuing pull request");
-                info!("Reusing existing PR with ID: {}", pr_id);
-
-                // Wait for GitHub to process the force push and run checks before enqueueing.
-                // GitHub may silently reject the enqueue if the PR isn't ready yet.
-                info!(
-                    "Waiting 15 seconds for GitHub to process force push before re-enqueueing..."
-                );
-                tokio::time::sleep(std::time::Duration::from_secs(15)).await;
-
-                info!("Re-enqueueing PR {} to merge queue", pr_id);
-                match self.github_client.enqueue_pull_request(pr_id.clone()).await {
-                    Ok(_) => {
-                        info!("Successfully re-enqueued PR {}", pr_id);
-                    }
-                    Err(e) => {
-                        error!("Failed to re-enqueue PR {}: {}", pr_id, e);
-                        return Err(e.into());
-                    }
-                }
-                cleanup_worktree_branch(&git_client_worktree, &worktree_branch, git_opts_lfs_stubs)
-                    .await;
-            }
-            // We have no active PR in queue, lets setup a new one
-            None => {
-                let gh_op = GitHubSubmitOp {
-                    head_branch: worktree_branch.clone(),
-                    base_branch: target_branch.clone(),
-                    token: self.token.clone(),
-                    commit_message: self.commit_message.clone(),
-                    repo_status: self.repo_status.clone(),
-                    client: self.github_client.clone(),
-                    use_merge_queue,
-                };
-
-                if use_merge_queue {
-                    self.emit_phase("Adding to merge queue");
-                    gh_op.execute().await?;
-
-                    cleanup_worktree_branch(
-                        &git_client_worktree,
-                        &worktree_branch,
-                        git_opts_lfs_stubs,
-                    )
-                    .await;
-
-                    return Ok(());
-                } else {
-                    self.emit_phase("Submitting pull request");
-                    // Non-merge-queue: gh_op.execute() blocks until merge completes
-                    let merge_result = gh_op.execute().await;
+        let gh_op = GitHubSubmitOp {
+            head_branch: worktree_branch.clone(),
+            base_branch: target_branch.clone(),
+            token: self.token.clone(),
+            commit_message: self.commit_message.clone(),
+            files: self.files.clone(),
+            repo_status: self.repo_status.clone(),
+            client: self.github_client.clone(),
+            submit_tracker: self.submit_tracker.clone(),
+            stacked_on,
+            use_merge_queue,
+        };
 
-                    cleanup_worktree_branch(
-                        &git_client_worktree,
-                        &worktree_branch,
-                        git_opts_lfs_stubs,
-                    )
-                    .await;
+        if use_merge_queue {
+            self.emit_phase("Adding to merge queue");
+            gh_op.execute().await?;
 
-                    match merge_result {
-                        Ok(()) => {
-                            self.emit_phase("Releasing file locks");
-                            info!("PR merge confirmed, unlocking files");
-                            let github_username = self.github_client.username.clone();
-                            let lock_op = LockOp {
-                                git_client: self.git_client.clone(),
-                                paths: self.files.clone(),
-                                op: LockOperation::Unlock,
-                                response_tx: None,
-                                github_pat: self.token.clone(),
+            cleanup_worktree_branch(&git_client_worktree, &worktree_branch, git_opts_lfs_stubs)
+                .await;
+        } else {
+            self.emit_phase("Submitting pull request");
+            // Non-merge-queue: gh_op.execute() blocks until merge completes
+            let merge_result = gh_op.execute().await;
+
+            cleanup_worktree_branch(&git_client_worktree, &worktree_branch, git_opts_lfs_stubs)
+                .await;
+
+            match merge_result {
+                Ok(()) => {
+                    self.emit_phase("Releasing file locks");
+                    info!("PR merge confirmed, unlocking files");
+                    let github_username = self.github_client.username.clone();
+                    let lock_op = LockOp {
+                        git_client: self.git_client.clone(),
+                        paths: self.files.clone(),
+                        op: LockOperation::Unlock,
+                        response_tx: None,
+                        github_pat: self.token.clone(),
+                        repo_status: self.repo_status.clone(),
+                        github_username,
+                        force: false,
+                    };
+                    lock_op.execute().await?;
+
+                    // Autosync if editor is not running
+                    if self.engine.check_ready_to_sync_repo().await.is_ok() {
+                        if let (Some(aws_client), Some(storage)) =
+                            (self.aws_client.clone(), self.storage.clone())
+                        {
+                            info!("Auto-syncing back to target branch after quicksubmit merge");
+                            let pull_op = PullOp {
+                                app_config: self.app_config.clone(),
+                                repo_config: self.repo_config.clone(),
                                 repo_status: self.repo_status.clone(),
-                                github_username,
-                                force: false,
+                                longtail: self.longtail.clone(),
+                                longtail_tx: self.longtail_tx.clone(),
+                                aws_client,
+                                storage,
+                                git_client: self.git_client.clone(),
+                                github_client: Some(self.github_client.clone()),
+                                engine: self.engine.clone(),
+                                sync_phase_tx: self.sync_phase_tx.clone(),
+                                // The pre-submit snapshot already covers the working
+                                // tree, so skip the expensive duplicate snapshot inside
+                                // PullOp. git pull --autostash still protects dirty
+                                // files during the rebase.
+                                skip_snapshot: true,
+                                stopped_pull: self.stopped_pull.clone(),
                             };
-                            lock_op.execute().await?;
-
-                            // Autosync if editor is not running
-                            if self.engine.check_ready_to_sync_repo().await.is_ok() {
-                                if let (Some(aws_client), Some(storage)) =
-                                    (self.aws_client.clone(), self.storage.clone())
-                                {
-                                    info!(
-                                        "Auto-syncing back to target branch after quicksubmit merge"
-                                    );
-                                    let pull_op = PullOp {
-                                        app_config: self.app_config.clone(),
-                                        repo_config: self.repo_config.clone(),
-                                        repo_status: self.repo_status.clone(),
-                                        longtail: self.longtail.clone(),
-                                        longtail_tx: self.longtail_tx.clone(),
-                                        aws_client,
-                                        storage,
-                                        git_client: self.git_client.clone(),
-                                        github_client: Some(self.github_client.clone()),
-                                        engine: self.engine.clone(),
-                                        sync_phase_tx: self.sync_phase_tx.clone(),
-                                        // The pre-submit snapshot already covers the working
-                                        // tree, so skip the expensive duplicate snapshot inside
-                                        // PullOp. git pull --autostash still protects dirty
-                                        // files during the rebase.
-                                        skip_snapshot: true,
-                                    };
-                                    if let Err(e) = pull_op.execute().await {
-                                        // Don't return Err here — the commit/push/merge all
-                                        // succeeded, and returning Err would trigger the
-                                        // destructive snapshot-restore in execute().
-                                        let msg = format!(
-                                            "Your changes were submitted successfully! \
-                                            Auto-sync back to the target branch failed: {}. \
-                                            If you see conflicts with your local files, you can \
-                                            restore them from the pre-submit snapshot in the \
-                                            Snapshots tab. Otherwise, just Sync manually when ready.",
-                                            e
-                                        );
-                                        error!("{}", msg);
-                                        let _ = self.notification_tx.send(Notification::Error(msg));
-                                        return Ok(());
-                                    }
-                                    let _ = self.notification_tx.send(Notification::Success(
-                                        "Changes submitted and auto-sync complete.".to_string(),
-                                    ));
-                                } else {
-                                    // Note: don't expect this to be hit since we shouldn't be able to get here, but logging error if we do.
-                                    error!("AWS client or storage not available, skipping autosync after quicksubmit");
-                                    let _ = self.notification_tx.send(
-                                        Notification::Error("AWS client or storage not available, unable to auto-sync. Please sync manually.".to_string())
-                                    );
-                                }
-                            } else {
-                                let msg = "Quicksubmit merge complete, but skipping auto-sync since Editor is running".to_string();
-                                info!("{}", msg);
-                                let _ = self.notification_tx.send(Notification::Success(msg));
-                            }
-
-                            return Ok(());
-                        }
-                        Err(e) => {
-                            // Merge failed or timed out. Files stay locked.
-                            // Don't return Err — the commit/push/PR all succeeded, and
-                            // returning Err would trigger the destructive snapshot-restore
-                            // in execute().
-                            warn!("PR created but merge did not complete: {}", e);
-                            if self.engine.check_ready_to_sync_repo().await.is_ok() {
+                            if let Err(e) = pull_op.execute().await {
+                                // Don't return Err here — the commit/push/merge all
+                                // succeeded, and returning Err would trigger the
+                                // destructive snapshot-restore in execute().
                                 let msg = format!(
-                                    "PR was created but merge did not confirm: {}. Files remain locked.",
+                                    "Your changes were submitted successfully! \
+                                    Auto-sync back to the target branch failed: {}. \
+                                    If you see conflicts with your local files, you can \
+                                    restore them from the pre-submit snapshot in the \
+                                    Snapshots tab. Otherwise, just Sync manually when ready.",
                                     e
                                 );
                                 error!("{}", msg);
                                 let _ = self.notification_tx.send(Notification::Error(msg));
-                            } else {
-                                info!("Editor running, merge timeout is not critical. PR may still merge via GitHub.");
-                                let _ = self.notification_tx.send(
-                                    Notification::Error(format!("PR was created but merge did not confirm: {}. PR should be available to merge manually via GitHub.", e))
-                                );
+                                return Ok(());
                             }
-                            return Ok(());
+                            let _ = self.notification_tx.send(Notification::Success(
+                                "Changes submitted and auto-sync complete.".to_string(),
+                            ));
+                        } else {
+                            // Note: don't expect this to be hit since we shouldn't be able to get here, but logging error if we do.
+                            error!("AWS client or storage not available, skipping autosync after quicksubmit");
+                            let _ = self.notification_tx.send(
+                                Notification::Error("AWS client or storage not available, unable to auto-sync. Please sync manually.".to_string())
+                            );
                         }
+                    } else {
+                        let msg = "Quicksubmit merge complete, but skipping auto-sync since Editor is running".to_string();
+                        info!("{}", msg);
+                        let _ = self.notification_tx.send(Notification::Success(msg));
                     }
+
+                    return Ok(());
+                }
+                Err(e) => {
+                    // Merge failed or timed out. Files stay locked.
+                    // Don't return Err — the commit/push/PR all succeeded, and
+                    // returning Err would trigger the destructive snapshot-restore
+                    // in execute().
+                    warn!("PR created but merge did not complete: {}", e);
+                    if self.engine.check_ready_to_sync_repo().await.is_ok() {
+                        let msg = format!(
+                            "PR was created but merge did not confirm: {}. Files remain locked.",
+                            e
+                        );
+                        error!("{}", msg);
+                        let _ = self.notification_tx.send(Notification::Error(msg));
+                    } else {
+                        info!("Editor running, merge timeout is not critical. PR may still merge via GitHub.");
+                        let _ = self.notification_tx.send(
+                            Notification::Error(format!("PR was created but merge did not confirm: {}. PR should be available to merge manually via GitHub.", e))
+                        );
+                    }
+                    return Ok(());
                 }
             }
         }
@@ -1429,6 +1489,69 @@ async fn cleanup_worktree_branch(git_client: &git::Git, branch: &str, opts: git:
         .await;
 }
 
+/// Creates the scratch worktree quick submits are rebased and pushed from if it doesn't exist yet,
+/// and returns a client for it with anything left over from a previous submit cleared out.
+pub(crate) async fn prepare_quick_submit_worktree(
+    git_client: &git::Git,
+    repo_path: PathBuf,
+) -> Result<git::Git, CoreError> {
+    // The scratch worktree lives next to the main worktree rather than the active one, and
+    // is looked up by path so a worktree the user created is never reset and reused here
+    let worktrees = git_client.list_worktrees().await?;
+    let main_path = worktrees
+        .iter()
+        .find(|tree| tree.is_main)
+        .map(|tree| tree.directory.clone())
+        .unwrap_or(repo_path);
+    let worktree_path = git::quick_submit_worktree_path(&main_path);
+
+    let registered = worktrees.iter().any(|tree| tree.directory == worktree_path);
+    if registered && !worktree_path.exists() {
+        // the directory was deleted out from under git, drop its registration
+        git_client
+            .run(&["worktree", "prune"], Default::default())
+            .await?;
+    }
+
+    if !registered || !worktree_path.exists() {
+        // create worktree if it doesn't exist yet
+        git_client
+            .run(
+                &[
+                    "worktree",
+                    "add",
+                    "--detach",
+                    &worktree_path.to_string_lossy(),
+                ],
+                git::Opts::default().with_lfs_stubs(),
+            )
+            .await?;
+    }
+
+    let mut git_client_worktree = git_client.clone();
+    git_client_worktree.repo_path.clone_from(&worktree_path);
+
+    let git_opts_lfs_stubs = git::Opts::default().with_lfs_stubs();
+
+    // Abort any in-progress rebase from a previous failed submit
+    _ = git_client_worktree
+        .run(
+            &["rebase", "--abort"],
+            git::Opts::new_with_ignored(&["no rebase in progress"]).with_lfs_stubs(),
+        )
+        .await;
+
+    // make sure the worktree is hard reset
+    git_client_worktree
+        .run(&["reset", "--hard"], git_opts_lfs_stubs)
+        .await?;
+    git_client_worktree
+        .run(&["clean", "-fd"], git_opts_lfs_stubs)
+        .await?;
+
+    Ok(git_client_worktree)
+}
+
 #[instrument(skip(state))]
 pub async fn submit_handler<T>(
     State(state): State<AppState<T>>,
@@ -1473,10 +1596,12 @@ where
         longtail_tx: state.longtail_tx.clone(),
         notification_tx: state.notification_tx.clone(),
         sync_phase_tx: state.sync_phase_tx.clone(),
+        stopped_pull: state.stopped_pull.clone(),
 
         git_client: state.git(),
         token: token.to_string(),
         github_client,
+        submit_tracker: state.submit_tracker.clone(),
     };
 
     let (tx, rx) = tokio::sync::oneshot::channel::<Option<CoreError>>();
@@ -1507,7 +1632,7 @@ mod tests {
     use std::sync::mpsc;
     use std::sync::Arc;
 
-    use ethos_core::types::config::{AppConfig, RepoConfig};
+    use ethos_core::types::config::{AppConfig, BlockedFileGlob, BlockedFileRule, RepoConfig};
     use ethos_core::types::repo::FileList;
     use parking_lot::RwLock;
 
@@ -1623,6 +1748,51 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn blocked_file_rejection_uses_the_configured_reason() {
+        let repo_status = RepoStatus {
+            modified_files: FileList(vec![File {
+                path: "Content/Characters/Hero.uasset".to_string(),
+                submit_status: SubmitStatus::Blocked,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let requested_files = vec!["Content/Characters/Hero.uasset".to_string()];
+        let (app_config, repo_config) = test_app_and_repo_config();
+        {
+            let target_branch = app_config.read().target_branch.clone();
+            let mut repo_config = repo_config.write();
+            repo_config.target_branches[0].name = target_branch;
+            repo_config.target_branches[0].blocked_file_globs =
+                vec![BlockedFileGlob::Rule(BlockedFileRule {
+                    pattern: "Content/Characters/**".to_string(),
+                    allowed_users: vec!["techartist".to_string()],
+                    reason: Some("Characters go through tech art".to_string()),
+                })];
+        }
+        let engine = test_engine();
+
+        let err = reject_unsubmittable_files(
+            &requested_files,
+            &repo_status,
+            &app_config,
+            &repo_config,
+            &engine,
+        )
+        .await
+        .expect_err("must reject");
+        let message = err.to_string();
+        assert!(
+            message.contains("Characters go through tech art"),
+            "message missing the configured reason: {message}"
+        );
+        assert!(
+            !message.contains("blocked-file-glob"),
+            "the configured reason replaces the generic one: {message}"
+        );
+    }
+
     #[tokio::test]
     async fn truncates_long_file_lists() {
         let mut files = Vec::new();
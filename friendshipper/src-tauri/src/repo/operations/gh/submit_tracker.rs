@@ -0,0 +1,978 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender as STDSender;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::{async_trait, Json};
+use parking_lot::Mutex;
+use tracing::{info, instrument, warn};
+
+use crate::engine::EngineProvider;
+use crate::repo::operations::changeset::FRIENDSHIPPER_APPDATA_DIR;
+use crate::repo::operations::gh::submit::prepare_quick_submit_worktree;
+use crate::state::{AppState, Notification};
+use ethos_core::clients::git;
+use ethos_core::clients::github;
+use ethos_core::types::errors::CoreError;
+use ethos_core::types::github::merge_queue::get_merge_queue::GetMergeQueueRepositoryMergeQueue;
+use ethos_core::types::github::pulls::get_pull_request::{
+    GetPullRequestRepositoryPullRequest, MergeableState, PullRequestState,
+};
+use ethos_core::types::repo::{SubmitTransition, TrackedSubmit, TrackedSubmitState};
+use ethos_core::worker::{Task, TaskSequence};
+
+pub const SUBMIT_TRACKER_FILE: &str = "submits.json";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+// GitHub may silently reject an enqueue right after a push, before it has caught up with the new
+// head, so a PR has to stay mergeable this long before it's enqueued
+const ENQUEUE_SETTLE_TIME: Duration = Duration::from_secs(15);
+
+// An ejected PR is rebased and re-queued at most this many times before it's left to the user
+const MAX_REQUEUE_ATTEMPTS: u32 = 3;
+
+// Merged and closed submits past this many are dropped, oldest first
+const MAX_FINISHED_SUBMITS: usize = 50;
+
+/// Quick submit PRs followed from creation until they're merged or closed. Submits are persisted so
+/// tracking picks back up after a restart, and every state change raises a notification.
+#[derive(Clone)]
+pub struct SubmitTracker {
+    path: Option<PathBuf>,
+
+    // Newest first
+    submits: Arc<Mutex<Vec<TrackedSubmit>>>,
+
+    // PRs something else is working on, which polling leaves alone
+    held: Arc<Mutex<HashSet<String>>>,
+
+    // Enqueued PRs the last poll found out of the queue without being merged
+    left_queue: Arc<Mutex<HashSet<String>>>,
+    notification_tx: STDSender<Notification>,
+}
+
+/// Keeps the tracker from acting on a PR until dropped.
+pub struct SubmitHold {
+    held: Arc<Mutex<HashSet<String>>>,
+    id: String,
+}
+
+impl Drop for SubmitHold {
+    fn drop(&mut self) {
+        self.held.lock().remove(&self.id);
+    }
+}
+
+impl SubmitTracker {
+    /// Without a path the tracker only lives as long as the app.
+    pub fn load(path: Option<PathBuf>, notification_tx: STDSender<Notification>) -> Self {
+        let submits = match &path {
+            Some(path) if path.exists() => match std::fs::read_to_string(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| Ok(serde_json::from_str(&json)?))
+            {
+                Ok(submits) => submits,
+                Err(e) => {
+                    warn!("Failed to load tracked submits from {:?}: {}", path, e);
+                    vec![]
+                }
+            },
+            _ => vec![],
+        };
+
+        SubmitTracker {
+            path,
+            submits: Arc::new(Mutex::new(submits)),
+            held: Arc::new(Mutex::new(HashSet::new())),
+            left_queue: Arc::new(Mutex::new(HashSet::new())),
+            notification_tx,
+        }
+    }
+
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_local_dir().map(|dir| {
+            dir.join(FRIENDSHIPPER_APPDATA_DIR)
+                .join(SUBMIT_TRACKER_FILE)
+        })
+    }
+
+    pub fn list(&self) -> Vec<TrackedSubmit> {
+        self.submits.lock().clone()
+    }
+
+    pub fn get(&self, id: &str) -> Option<TrackedSubmit> {
+        self.submits
+            .lock()
+            .iter()
+            .find(|submit| submit.id == id)
+            .cloned()
+    }
+
+    /// Starts following a newly opened PR. A PR that's already tracked was resubmitted with more
+    /// changes, so it picks up the new files and goes back to waiting on the merge queue.
+    pub fn track(&self, submit: TrackedSubmit) {
+        let mut submits = self.submits.lock();
+        match submits.iter_mut().find(|tracked| tracked.id == submit.id) {
+            Some(tracked) => {
+                for file in submit.files {
+                    if !tracked.files.contains(&file) {
+                        tracked.files.push(file);
+                    }
+                }
+                tracked.head_branch = submit.head_branch;
+                tracked.requeue_attempts = 0;
+                push_transition(
+                    tracked,
+                    TrackedSubmitState::Opened,
+                    Some("was resubmitted with new changes".to_string()),
+                );
+            }
+            None => {
+                let mut submit = submit;
+                push_transition(&mut submit, TrackedSubmitState::Opened, None);
+                info!("Tracking quick submit PR #{}", submit.number);
+                submits.insert(0, submit);
+            }
+        }
+
+        self.save(&mut submits);
+    }
+
+    /// Records the PR moving to `state` and lets the user know. `message` replaces the default
+    /// description of the new state.
+    pub fn transition(&self, id: &str, state: TrackedSubmitState, message: Option<String>) {
+        let mut submits = self.submits.lock();
+        let Some(submit) = submits.iter_mut().find(|submit| submit.id == id) else {
+            return;
+        };
+
+        info!(
+            "Quick submit PR #{} went from {:?} to {:?}",
+            submit.number, submit.state, state
+        );
+        let text = message.unwrap_or_else(|| describe(submit, state));
+        push_transition(submit, state, Some(text.clone()));
+        self.notify(submit, state, &text);
+
+        self.save(&mut submits);
+    }
+
+    /// Submits into `base` that haven't merged yet and are still expected to, newest first. New quick
+    /// submits get their own PR alongside these rather than replacing them.
+    pub fn in_flight(&self, owner: &str, repo: &str, base: &str) -> Vec<TrackedSubmit> {
+        self.submits
+            .lock()
+            .iter()
+            .filter(|submit| {
+                submit.owner == owner
+                    && submit.repo == repo
+                    && submit.base_branch == base
+                    && submit.uses_merge_queue
+                    && !submit.state.is_finished()
+                    && submit.state != TrackedSubmitState::Ejected
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// True if the submit was stacked on a PR that was closed, or ejected with no re-queues left,
+    /// and carries changes that won't be merged along with it. A parent that's about to be rebased
+    /// and re-queued is left to rejoin the queue ahead of it.
+    pub fn needs_restack(&self, submit: &TrackedSubmit) -> bool {
+        let Some(stacked_on) = &submit.stacked_on else {
+            return false;
+        };
+        if submit.state.is_finished() || submit.requeue_attempts >= MAX_REQUEUE_ATTEMPTS {
+            return false;
+        }
+
+        self.get(&stacked_on.id)
+            .is_some_and(|parent| match parent.state {
+                TrackedSubmitState::Closed => true,
+                TrackedSubmitState::Ejected => parent.requeue_attempts >= MAX_REQUEUE_ATTEMPTS,
+                _ => false,
+            })
+    }
+
+    /// None if something else already holds the PR.
+    pub fn hold(&self, id: &str) -> Option<SubmitHold> {
+        if !self.held.lock().insert(id.to_string()) {
+            return None;
+        }
+
+        Some(SubmitHold {
+            held: self.held.clone(),
+            id: id.to_string(),
+        })
+    }
+
+    // Unfinished submits nothing is holding, the ones polling should look at
+    fn pending(&self) -> Vec<TrackedSubmit> {
+        let held = self.held.lock().clone();
+        self.submits
+            .lock()
+            .iter()
+            .filter(|submit| !submit.state.is_finished() && !held.contains(&submit.id))
+            .cloned()
+            .collect()
+    }
+
+    // Records whether the latest poll found the PR out of the queue, and returns whether the poll
+    // before it did too
+    fn left_queue(&self, id: &str, left: bool) -> bool {
+        let mut left_queue = self.left_queue.lock();
+        if left {
+            !left_queue.insert(id.to_string())
+        } else {
+            left_queue.remove(id);
+            false
+        }
+    }
+
+    fn requeued(&self, id: &str, message: String) {
+        if let Some(submit) = self.submits.lock().iter_mut().find(|s| s.id == id) {
+            submit.requeue_attempts += 1;
+        }
+        self.transition(id, TrackedSubmitState::Opened, Some(message));
+    }
+
+    fn restacked(&self, id: &str, message: String) {
+        if let Some(submit) = self.submits.lock().iter_mut().find(|s| s.id == id) {
+            submit.stacked_on = None;
+        }
+        self.transition(id, TrackedSubmitState::Opened, Some(message));
+    }
+
+    // Leaves an ejected PR for the user to deal with
+    fn give_up(&self, id: &str, message: String) {
+        if let Some(submit) = self.submits.lock().iter_mut().find(|s| s.id == id) {
+            submit.requeue_attempts = MAX_REQUEUE_ATTEMPTS;
+        }
+        self.transition(id, TrackedSubmitState::Ejected, Some(message));
+    }
+
+    fn notify(&self, submit: &TrackedSubmit, state: TrackedSubmitState, text: &str) {
+        let text = format!(
+            "Quick submit #{} \"{}\" {}",
+            submit.number, submit.title, text
+        );
+        let notification = match state {
+            TrackedSubmitState::Ejected | TrackedSubmitState::Closed => Notification::Error(text),
+            _ => Notification::Success(text),
+        };
+        let _ = self.notification_tx.send(notification);
+    }
+
+    fn save(&self, submits: &mut Vec<TrackedSubmit>) {
+        let mut finished = 0;
+        submits.retain(|submit| {
+            if submit.state.is_finished() {
+                finished += 1;
+                finished <= MAX_FINISHED_SUBMITS
+            } else {
+                true
+            }
+        });
+
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let result = (|| -> anyhow::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let tmp_path = path.with_extension("json.tmp");
+            std::fs::write(&tmp_path, serde_json::to_vec_pretty(submits)?)?;
+            std::fs::rename(&tmp_path, path)?;
+            Ok(())
+        })();
+        if let Err(e) = result {
+            warn!("Failed to save tracked submits to {:?}: {}", path, e);
+        }
+    }
+}
+
+fn push_transition(submit: &mut TrackedSubmit, state: TrackedSubmitState, message: Option<String>) {
+    submit.state = state;
+    submit.transitions.push(SubmitTransition {
+        state,
+        timestamp: chrono::Utc::now(),
+        message,
+    });
+}
+
+fn describe(submit: &TrackedSubmit, state: TrackedSubmitState) -> String {
+    match state {
+        TrackedSubmitState::Opened => "is waiting to enter the merge queue".to_string(),
+        TrackedSubmitState::Mergeable => "passed its checks".to_string(),
+        TrackedSubmitState::Enqueued => "entered the merge queue".to_string(),
+        TrackedSubmitState::Ejected => "was removed from the merge queue".to_string(),
+        TrackedSubmitState::Merged => format!("was merged into {}", submit.base_branch),
+        TrackedSubmitState::Closed => "was closed without being merged".to_string(),
+    }
+}
+
+// What a poll found out about a PR
+#[derive(Clone, Copy, Debug, Default)]
+struct Observation {
+    merged: bool,
+    closed: bool,
+    mergeable: bool,
+    in_queue: bool,
+
+    // The previous poll also found it out of the queue. GitHub briefly reports a PR the queue is
+    // merging as neither queued nor merged, so an enqueued PR is only ejected once two polls agree.
+    left_queue_earlier: bool,
+}
+
+impl Observation {
+    fn new(pr: &GetPullRequestRepositoryPullRequest, in_queue: bool) -> Self {
+        Observation {
+            merged: pr.merged || matches!(pr.state, PullRequestState::MERGED),
+            closed: matches!(pr.state, PullRequestState::CLOSED),
+            mergeable: matches!(pr.mergeable, MergeableState::MERGEABLE),
+            in_queue: in_queue || pr.merge_queue_entry.is_some(),
+            left_queue_earlier: false,
+        }
+    }
+
+    fn left_queue(&self) -> bool {
+        !self.merged && !self.closed && !self.in_queue
+    }
+}
+
+fn next_state(current: TrackedSubmitState, observed: Observation) -> TrackedSubmitState {
+    if observed.merged {
+        return TrackedSubmitState::Merged;
+    }
+    if observed.closed {
+        return TrackedSubmitState::Closed;
+    }
+    if observed.in_queue {
+        return TrackedSubmitState::Enqueued;
+    }
+
+    match current {
+        // it left the queue without merging
+        TrackedSubmitState::Enqueued if observed.left_queue_earlier => TrackedSubmitState::Ejected,
+        TrackedSubmitState::Opened if observed.mergeable => TrackedSubmitState::Mergeable,
+        other => other,
+    }
+}
+
+fn queued_pull_request_ids(queue: GetMergeQueueRepositoryMergeQueue) -> HashSet<String> {
+    queue
+        .entries
+        .and_then(|entries| entries.nodes)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .filter_map(|node| node.pull_request)
+        .map(|pr| pr.id)
+        .collect()
+}
+
+/// Polls every tracked submit until it's merged or closed, for as long as the app runs.
+pub async fn run_submit_tracker<T>(state: AppState<T>)
+where
+    T: EngineProvider,
+{
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        poll_submits(&state).await;
+    }
+}
+
+#[instrument(skip(state))]
+async fn poll_submits<T>(state: &AppState<T>)
+where
+    T: EngineProvider,
+{
+    let pending = state.submit_tracker.pending();
+    if pending.is_empty() {
+        return;
+    }
+
+    let Some(client) = state.github_client.read().clone() else {
+        return;
+    };
+
+    // the queue is looked up once per repo rather than once per PR
+    let mut queues: HashMap<(String, String), HashSet<String>> = HashMap::new();
+    for submit in pending {
+        if state.submit_tracker.needs_restack(&submit) {
+            if is_current_repo(state, &submit) {
+                queue_restack(state, &client, submit).await;
+            }
+            continue;
+        }
+
+        let key = (submit.owner.clone(), submit.repo.clone());
+        if submit.uses_merge_queue && !queues.contains_key(&key) {
+            let queued = match client.get_merge_queue(&submit.owner, &submit.repo).await {
+                Ok(queue) => queued_pull_request_ids(queue),
+                Err(e) => {
+                    // the PR's own queue entry is still checked below
+                    warn!("Failed to get merge queue for {}/{}: {}", key.0, key.1, e);
+                    HashSet::new()
+                }
+            };
+            queues.insert(key.clone(), queued);
+        }
+
+        let pr = match client
+            .get_pull_request(
+                submit.owner.clone(),
+                submit.repo.clone(),
+                submit.number as i64,
+            )
+            .await
+        {
+            Ok(pr) => pr,
+            Err(e) => {
+                warn!("Failed to get quick submit PR #{}: {}", submit.number, e);
+                continue;
+            }
+        };
+
+        let in_queue = queues
+            .get(&key)
+            .is_some_and(|queued| queued.contains(&submit.id));
+        let mut observed = Observation::new(&pr, in_queue);
+        observed.left_queue_earlier = state.submit_tracker.left_queue(
+            &submit.id,
+            submit.state == TrackedSubmitState::Enqueued && observed.left_queue(),
+        );
+        let next = next_state(submit.state, observed);
+        let can_requeue = submit.uses_merge_queue
+            && submit.requeue_attempts < MAX_REQUEUE_ATTEMPTS
+            && is_current_repo(state, &submit);
+
+        if next != submit.state {
+            let message = match next {
+                TrackedSubmitState::Ejected if can_requeue => Some(format!(
+                    "was removed from the merge queue. Rebasing it onto {} to try again.",
+                    submit.base_branch
+                )),
+                TrackedSubmitState::Ejected => Some(
+                    "was removed from the merge queue. Fix it up and submit again.".to_string(),
+                ),
+                _ => None,
+            };
+            state.submit_tracker.transition(&submit.id, next, message);
+            continue;
+        }
+
+        match next {
+            TrackedSubmitState::Mergeable if submit.uses_merge_queue => {
+                let settled = submit.last_transition().is_none_or(|last| {
+                    (chrono::Utc::now() - last.timestamp)
+                        .to_std()
+                        .is_ok_and(|age| age >= ENQUEUE_SETTLE_TIME)
+                });
+                if settled {
+                    info!(
+                        "Adding quick submit PR #{} to the merge queue",
+                        submit.number
+                    );
+                    // a rejected enqueue is tried again on the next poll
+                    if let Err(e) = client.enqueue_pull_request(submit.id.clone()).await {
+                        warn!("Failed to enqueue PR #{}: {}", submit.number, e);
+                    }
+                }
+            }
+            TrackedSubmitState::Ejected if can_requeue => {
+                let Some(hold) = state.submit_tracker.hold(&submit.id) else {
+                    continue;
+                };
+
+                let mut sequence = TaskSequence::new();
+                sequence.push(Box::new(RequeueSubmitOp {
+                    repo_path: PathBuf::from(state.app_config.read().repo_path.clone()),
+                    git_client: state.git(),
+                    github_client: client.clone(),
+                    submit_tracker: state.submit_tracker.clone(),
+                    submit,
+                    _hold: hold,
+                }));
+                if let Err(e) = state.operation_tx.send(sequence).await {
+                    warn!("Failed to queue re-queue of ejected PR: {}", e);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn queue_restack<T>(
+    state: &AppState<T>,
+    client: &github::GraphQLClient,
+    submit: TrackedSubmit,
+) where
+    T: EngineProvider,
+{
+    let Some(hold) = state.submit_tracker.hold(&submit.id) else {
+        return;
+    };
+
+    let mut sequence = TaskSequence::new();
+    sequence.push(Box::new(RestackSubmitOp {
+        repo_path: PathBuf::from(state.app_config.read().repo_path.clone()),
+        git_client: state.git(),
+        github_client: client.clone(),
+        submit_tracker: state.submit_tracker.clone(),
+        submit,
+        _hold: hold,
+    }));
+    if let Err(e) = state.operation_tx.send(sequence).await {
+        warn!("Failed to queue restack of quick submit: {}", e);
+    }
+}
+
+// An ejected PR can only be rebased from a clone of its own repo
+fn is_current_repo<T>(state: &AppState<T>, submit: &TrackedSubmit) -> bool
+where
+    T: EngineProvider,
+{
+    let status = state.repo_status.read();
+    status.repo_owner == submit.owner && status.repo_name == submit.repo
+}
+
+/// Rebases an ejected quick submit onto its base branch in the scratch worktree and force pushes
+/// it, so the tracker can put it back in the merge queue.
+pub struct RequeueSubmitOp {
+    pub repo_path: PathBuf,
+    pub git_client: git::Git,
+    pub github_client: github::GraphQLClient,
+    pub submit_tracker: SubmitTracker,
+    pub submit: TrackedSubmit,
+    _hold: SubmitHold,
+}
+
+#[async_trait]
+impl Task for RequeueSubmitOp {
+    #[instrument(name = "RequeueSubmitOp::execute", skip(self), fields(pr = self.submit.number))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        // the user may have resubmitted it while this waited behind other operations
+        let current = self.submit_tracker.get(&self.submit.id);
+        if current.map(|submit| submit.state) != Some(TrackedSubmitState::Ejected) {
+            return Ok(());
+        }
+
+        // it may have been merged, closed or put back in the queue since it was polled
+        let pr = match self
+            .github_client
+            .get_pull_request(
+                self.submit.owner.clone(),
+                self.submit.repo.clone(),
+                self.submit.number as i64,
+            )
+            .await
+        {
+            Ok(pr) => pr,
+            Err(e) => {
+                // the next poll tries again
+                warn!(
+                    "Failed to get quick submit PR #{}: {}",
+                    self.submit.number, e
+                );
+                return Ok(());
+            }
+        };
+        let observed = Observation::new(&pr, false);
+        if !observed.left_queue() {
+            self.submit_tracker.transition(
+                &self.submit.id,
+                next_state(TrackedSubmitState::Ejected, observed),
+                None,
+            );
+            return Ok(());
+        }
+
+        let base = &self.submit.base_branch;
+        match rebase_and_push(&self.git_client, &self.repo_path, &self.submit, None).await {
+            Ok(true) => {
+                self.submit_tracker.requeued(
+                    &self.submit.id,
+                    format!("was rebased onto {base} and will rejoin the merge queue once its checks pass"),
+                );
+                Ok(())
+            }
+            Ok(false) => {
+                self.submit_tracker.give_up(
+                    &self.submit.id,
+                    format!("conflicts with {base} and couldn't be re-queued. Sync, resolve the conflicts and submit again."),
+                );
+                Ok(())
+            }
+            Err(e) => {
+                self.submit_tracker.give_up(
+                    &self.submit.id,
+                    format!("couldn't be rebased and re-queued: {e}"),
+                );
+                Err(e)
+            }
+        }
+    }
+
+    fn get_name(&self) -> String {
+        "RequeueSubmitOp".to_string()
+    }
+}
+
+/// Takes a quick submit whose parent PR was closed, or ejected for good, and moves its
+/// own commits off the parent and onto its base branch.
+pub struct RestackSubmitOp {
+    pub repo_path: PathBuf,
+    pub git_client: git::Git,
+    pub github_client: github::GraphQLClient,
+    pub submit_tracker: SubmitTracker,
+    pub submit: TrackedSubmit,
+    _hold: SubmitHold,
+}
+
+#[async_trait]
+impl Task for RestackSubmitOp {
+    #[instrument(name = "RestackSubmitOp::execute", skip(self), fields(pr = self.submit.number))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        let Some(parent) = &self.submit.stacked_on else {
+            return Ok(());
+        };
+        let current = self.submit_tracker.get(&self.submit.id);
+        if !current.is_some_and(|submit| self.submit_tracker.needs_restack(&submit)) {
+            return Ok(());
+        }
+
+        // it would otherwise merge the parent's changes along with its own
+        if let Err(e) = self
+            .github_client
+            .dequeue_pull_request(self.submit.id.clone())
+            .await
+        {
+            // not being in the queue is fine
+            info!("Failed to dequeue PR #{}: {}", self.submit.number, e);
+        }
+
+        let base = &self.submit.base_branch;
+        match rebase_and_push(
+            &self.git_client,
+            &self.repo_path,
+            &self.submit,
+            Some(&parent.commit),
+        )
+        .await
+        {
+            Ok(true) => {
+                self.submit_tracker.restacked(
+                    &self.submit.id,
+                    format!(
+                        "was moved onto {base} since #{} won't be merged, and will rejoin the merge queue once its checks pass",
+                        parent.number
+                    ),
+                );
+                Ok(())
+            }
+            Ok(false) => {
+                self.submit_tracker.give_up(
+                    &self.submit.id,
+                    format!(
+                        "depends on #{}, which won't be merged, and conflicts with {base} without it. Sync, resolve the conflicts and submit again.",
+                        parent.number
+                    ),
+                );
+                Ok(())
+            }
+            Err(e) => {
+                self.submit_tracker.give_up(
+                    &self.submit.id,
+                    format!("couldn't be moved off #{}: {e}", parent.number),
+                );
+                Err(e)
+            }
+        }
+    }
+
+    fn get_name(&self) -> String {
+        "RestackSubmitOp".to_string()
+    }
+}
+
+// Rebases the submit's branch onto its base branch in the scratch worktree and force pushes it.
+// With `upstream`, only the commits after it are moved. False if the rebase ran into conflicts.
+async fn rebase_and_push(
+    git_client: &git::Git,
+    repo_path: &std::path::Path,
+    submit: &TrackedSubmit,
+    upstream: Option<&str>,
+) -> Result<bool, CoreError> {
+    let worktree = prepare_quick_submit_worktree(git_client, repo_path.to_path_buf()).await?;
+    let git_opts_lfs_stubs = git::Opts::default().with_lfs_stubs();
+    let head = &submit.head_branch;
+    let base = &submit.base_branch;
+
+    worktree
+        .run(&["fetch", "origin", base, head], git_opts_lfs_stubs)
+        .await?;
+    // The push below only replaces the branch if it's still what we rebased, so anything pushed
+    // to it in the meantime isn't thrown away
+    let fetched_head = worktree
+        .run_and_collect_output(
+            &[
+                "rev-parse",
+                "--verify",
+                &format!("origin/{head}^{{commit}}"),
+            ],
+            git::Opts::new_without_logs(),
+        )
+        .await?
+        .trim()
+        .to_string();
+    worktree
+        .run(&["checkout", "--detach", &fetched_head], git_opts_lfs_stubs)
+        .await?;
+
+    let onto = format!("origin/{base}");
+    let rebase_args = match upstream {
+        Some(upstream) => vec!["rebase", "--onto", &onto, upstream],
+        None => vec!["rebase", &onto],
+    };
+    if let Err(e) = worktree.run(&rebase_args, git_opts_lfs_stubs).await {
+        warn!("Failed to rebase {} onto {}: {}", head, base, e);
+        _ = worktree
+            .run(
+                &["rebase", "--abort"],
+                git::Opts::new_with_ignored(&["no rebase in progress"]).with_lfs_stubs(),
+            )
+            .await;
+        return Ok(false);
+    }
+
+    worktree
+        .run(
+            &[
+                "push",
+                &format!("--force-with-lease=refs/heads/{head}:{fetched_head}"),
+                "origin",
+                &format!("HEAD:refs/heads/{head}"),
+            ],
+            git::Opts::default(),
+        )
+        .await?;
+
+    Ok(true)
+}
+
+#[instrument(skip(state))]
+pub async fn get_tracked_submits<T>(
+    State(state): State<AppState<T>>,
+) -> Result<Json<Vec<TrackedSubmit>>, CoreError>
+where
+    T: EngineProvider,
+{
+    Ok(Json(state.submit_tracker.list()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethos_core::types::repo::StackedOn;
+    use std::sync::mpsc;
+
+    fn submit(id: &str) -> TrackedSubmit {
+        TrackedSubmit {
+            number: 42,
+            id: id.to_string(),
+            owner: "owner".to_string(),
+            repo: "repo".to_string(),
+            title: "Fix the door".to_string(),
+            head_branch: "f11r-main-someone-1-wt".to_string(),
+            base_branch: "main".to_string(),
+            uses_merge_queue: true,
+            files: vec!["Content/Door.uasset".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn follows_a_pr_through_the_merge_queue() {
+        use TrackedSubmitState::*;
+
+        let waiting = Observation::default();
+        let mergeable = Observation {
+            mergeable: true,
+            ..Default::default()
+        };
+        let queued = Observation {
+            in_queue: true,
+            ..Default::default()
+        };
+
+        assert_eq!(next_state(Opened, waiting), Opened);
+        assert_eq!(next_state(Opened, mergeable), Mergeable);
+        assert_eq!(next_state(Mergeable, queued), Enqueued);
+        assert_eq!(next_state(Enqueued, queued), Enqueued);
+        assert_eq!(next_state(Enqueued, mergeable), Enqueued);
+        assert_eq!(
+            next_state(
+                Enqueued,
+                Observation {
+                    left_queue_earlier: true,
+                    ..mergeable
+                }
+            ),
+            Ejected
+        );
+        assert_eq!(next_state(Ejected, mergeable), Ejected);
+        assert_eq!(
+            next_state(
+                Enqueued,
+                Observation {
+                    merged: true,
+                    ..Default::default()
+                }
+            ),
+            Merged
+        );
+        assert_eq!(
+            next_state(
+                Ejected,
+                Observation {
+                    closed: true,
+                    ..Default::default()
+                }
+            ),
+            Closed
+        );
+    }
+
+    #[test]
+    fn tracked_submits_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SUBMIT_TRACKER_FILE);
+        let (tx, rx) = mpsc::channel();
+
+        let tracker = SubmitTracker::load(Some(path.clone()), tx.clone());
+        tracker.track(submit("PR_1"));
+        tracker.transition("PR_1", TrackedSubmitState::Enqueued, None);
+        assert!(matches!(rx.try_recv(), Ok(Notification::Success(_))));
+
+        let reloaded = SubmitTracker::load(Some(path), tx);
+        let submits = reloaded.list();
+        assert_eq!(submits.len(), 1);
+        assert_eq!(submits[0].state, TrackedSubmitState::Enqueued);
+        assert_eq!(submits[0].transitions.len(), 2);
+        assert_eq!(reloaded.pending().len(), 1);
+    }
+
+    #[test]
+    fn resubmitting_reopens_and_merges_files() {
+        let (tx, _rx) = mpsc::channel();
+        let tracker = SubmitTracker::load(None, tx);
+        tracker.track(submit("PR_1"));
+        tracker.transition("PR_1", TrackedSubmitState::Ejected, None);
+
+        let mut resubmit = submit("PR_1");
+        resubmit.files = vec![
+            "Content/Door.uasset".to_string(),
+            "Content/Wall.uasset".to_string(),
+        ];
+        tracker.track(resubmit);
+
+        let tracked = tracker.get("PR_1").unwrap();
+        assert_eq!(tracked.state, TrackedSubmitState::Opened);
+        assert_eq!(tracked.files.len(), 2);
+        assert_eq!(tracked.transitions.len(), 3);
+    }
+
+    #[test]
+    fn stacked_submits_are_restacked_when_their_parent_is_dropped() {
+        let (tx, _rx) = mpsc::channel();
+        let tracker = SubmitTracker::load(None, tx);
+        tracker.track(submit("PR_1"));
+        let mut child = submit("PR_2");
+        child.stacked_on = Some(StackedOn {
+            id: "PR_1".to_string(),
+            number: 42,
+            commit: "abc123".to_string(),
+        });
+        tracker.track(child);
+
+        let in_flight = tracker.in_flight("owner", "repo", "main");
+        assert_eq!(in_flight.len(), 2);
+        assert_eq!(in_flight[0].id, "PR_2");
+        assert!(tracker.in_flight("owner", "repo", "release").is_empty());
+
+        let child = tracker.get("PR_2").unwrap();
+        tracker.transition("PR_1", TrackedSubmitState::Enqueued, None);
+        assert!(!tracker.needs_restack(&child));
+
+        tracker.transition("PR_1", TrackedSubmitState::Closed, None);
+        assert!(tracker.needs_restack(&child));
+        assert_eq!(tracker.in_flight("owner", "repo", "main").len(), 1);
+
+        tracker.restacked("PR_2", "moved".to_string());
+        let child = tracker.get("PR_2").unwrap();
+        assert!(child.stacked_on.is_none());
+        assert!(!tracker.needs_restack(&child));
+    }
+
+    #[test]
+    fn stacked_submits_wait_for_an_ejected_parent_to_be_requeued() {
+        let (tx, _rx) = mpsc::channel();
+        let tracker = SubmitTracker::load(None, tx);
+        tracker.track(submit("PR_1"));
+        let mut child = submit("PR_2");
+        child.stacked_on = Some(StackedOn {
+            id: "PR_1".to_string(),
+            number: 42,
+            commit: "abc123".to_string(),
+        });
+        tracker.track(child);
+        let child = tracker.get("PR_2").unwrap();
+
+        tracker.transition("PR_1", TrackedSubmitState::Enqueued, None);
+        tracker.transition("PR_1", TrackedSubmitState::Ejected, None);
+        assert!(!tracker.needs_restack(&child));
+
+        tracker.requeued("PR_1", "rebased".to_string());
+        assert!(!tracker.needs_restack(&child));
+
+        tracker.transition("PR_1", TrackedSubmitState::Ejected, None);
+        assert!(!tracker.needs_restack(&child));
+
+        tracker.give_up("PR_1", "conflicts".to_string());
+        assert!(tracker.needs_restack(&child));
+    }
+
+    #[test]
+    fn ejection_waits_for_a_second_poll() {
+        let (tx, _rx) = mpsc::channel();
+        let tracker = SubmitTracker::load(None, tx);
+
+        assert!(!tracker.left_queue("PR_1", true));
+        assert!(tracker.left_queue("PR_1", true));
+
+        // back in the queue, or merged, starts over
+        assert!(!tracker.left_queue("PR_1", false));
+        assert!(!tracker.left_queue("PR_1", true));
+    }
+
+    #[test]
+    fn held_submits_are_not_polled() {
+        let (tx, _rx) = mpsc::channel();
+        let tracker = SubmitTracker::load(None, tx);
+        tracker.track(submit("PR_1"));
+
+        let hold = tracker.hold("PR_1").unwrap();
+        assert!(tracker.hold("PR_1").is_none());
+        assert!(tracker.pending().is_empty());
+
+        drop(hold);
+        assert_eq!(tracker.pending().len(), 1);
+    }
+}
@@ -1,29 +1,43 @@
+pub use blocked_files::audit_blocked_files;
 pub use branch_compare::branch_compare_handler;
 pub use browse::list_directory_handler;
+pub use cancel::cancel_handler;
 pub use changeset::{load_changeset, save_changeset, SaveChangeSetRequest};
 pub use checkout::{checkout_target_branch_handler, checkout_trunk_handler, CheckoutOp};
 pub use clone::clone_handler;
-pub use commit_info::commit_info_handler;
+pub use commit_info::{commit_info_handler, fetch_commit_info};
+pub use content_profile::{
+    apply_content_profile, list_content_profiles, plan_content_profile, ApplyContentProfileOp,
+    ContentProfileRequest,
+};
 pub use diff::{diff_handler, DiffOp};
 pub use download_dlls::{download_dlls_handler, DownloadDllsOp};
 pub use file_history::file_history_handler;
 pub use install_git_hooks::{install_git_hooks_handler, InstallGitHooksOp};
 pub use locks::{acquire_locks_handler, release_locks_handler};
 pub use log::log_handler;
-pub use pull::{pull_handler, PullOp};
-pub use refetch::refetch_repo;
+pub use pull::{new_pull_op, pull_handler, PullOp, ResumePullOp, StoppedPullRef};
+pub use refetch::{refetch_repo, RefetchOp};
 pub use reset::{reset_repo, reset_repo_to_commit};
 pub use restore::{restore_file_to_revision_handler, RestoreFileToRevisionRequest};
 pub use revert::{revert_files_handler, RevertFilesOp};
 pub use show::show_commit_files;
 pub use snapshot::{
-    delete_snapshot, list_snapshots, preview_snapshot, restore_snapshot, save_snapshot,
-    PreviewSnapshotParams, PreviewSnapshotResponse, RestoreSnapshotRequest, SaveSnapshotRequest,
+    delete_snapshot, fetch_snapshot, list_snapshots, preview_snapshot, prune_snapshots,
+    push_snapshot, restore_snapshot, restore_snapshot_files, save_snapshot, set_snapshot_tags,
+    snapshot_prune_plan, MigrateStashSnapshotsOp, PreviewSnapshotParams, PreviewSnapshotResponse,
+    PruneSnapshotsRequest, RestoreSnapshotFilesOp, RestoreSnapshotFilesRequest,
+    RestoreSnapshotRequest, SaveSnapshotRequest, SnapshotIdRequest, SnapshotTagsRequest,
 };
 pub use status::{status_handler, RepoStatusRef, StatusOp};
 pub use update_engine::{
     reset_engine_handler, update_engine_handler, UpdateEngineOp, WipeEngineOp,
 };
+pub use worktree::{
+    add_worktree, list_worktrees, remove_worktree, switch_worktree, AddWorktreeOp,
+    AddWorktreeRequest, RemoveWorktreeOp, RemoveWorktreeParams, SwitchWorktreeOp,
+    WorktreePathRequest,
+};
 pub use zip_changes::{
     import_zipped_changes_handler, preview_import_zip_handler, zip_local_changes_handler,
     ImportZippedChangesRequest, ZipLocalChangesRequest,
@@ -153,12 +167,15 @@ mod tests {
     }
 }
 
+mod blocked_files;
 mod branch_compare;
 mod browse;
+mod cancel;
 mod changeset;
 mod checkout;
 mod clone;
 mod commit_info;
+mod content_profile;
 pub mod diagnostics;
 mod diff;
 mod download_dlls;
@@ -177,4 +194,5 @@ mod snapshot;
 mod status;
 mod update_engine;
 pub mod validate;
+mod worktree;
 mod zip_changes;
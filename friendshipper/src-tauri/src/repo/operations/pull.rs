@@ -1,9 +1,11 @@
 use std::path::PathBuf;
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
 
 use anyhow::{anyhow, Context};
 use axum::{async_trait, extract::State, Json};
 use ethos_core::storage::config::Project;
+use parking_lot::Mutex;
 use tokio::sync::oneshot::error::RecvError;
 use tracing::{error, info, instrument};
 
@@ -16,7 +18,7 @@ use ethos_core::msg::LongtailMsg;
 use ethos_core::storage::ArtifactStorage;
 use ethos_core::types::config::{AppConfigRef, RepoConfig, UProject};
 use ethos_core::types::errors::CoreError;
-use ethos_core::types::repo::PullResponse;
+use ethos_core::types::repo::{File, PullResponse, Snapshot};
 use ethos_core::worker::{Task, TaskSequence};
 use ethos_core::AWSClient;
 
@@ -29,6 +31,17 @@ use crate::state::AppState;
 
 use super::{DownloadDllsOp, RepoStatusRef, StatusOp};
 
+/// What a sync that stopped on rebase conflicts still has left to do once
+/// they're resolved and the rebase continues.
+#[derive(Clone, Default)]
+pub struct StoppedPull {
+    snapshot: Option<Snapshot>,
+    snapshot_modified_files: Vec<File>,
+    old_uproject: Option<UProject>,
+}
+
+pub type StoppedPullRef = Arc<Mutex<Option<StoppedPull>>>;
+
 #[derive(Clone)]
 pub struct PullOp<T> {
     pub app_config: AppConfigRef,
@@ -46,7 +59,9 @@ pub struct PullOp<T> {
     /// GitHub", "Restoring local changes from temporary snapshot", …).
     /// Surfaced to the frontend as `sync-phase` Tauri events so the
     /// pulling modal can show a persistent, user-friendly step label
-    /// distinct from the verbose `git-log` stream.
+    /// distinct from the verbose `git-log` stream. Percentages for the git
+    /// command behind a phase come from the git client's `git-progress`
+    /// events, which the modal pairs with the current phase.
     pub sync_phase_tx: Sender<String>,
 
     /// When true, skip taking a pre-pull snapshot of dirty files. This is used
@@ -55,6 +70,10 @@ pub struct PullOp<T> {
     /// expensive duplicate stash cycle on hundreds of files. The git pull
     /// `--autostash` flag still protects dirty files during the rebase itself.
     pub skip_snapshot: bool,
+
+    /// Set when the pull stops on rebase conflicts, so `ResumePullOp` can
+    /// finish the sync after they're resolved.
+    pub stopped_pull: StoppedPullRef,
 }
 
 impl<T> PullOp<T> {
@@ -303,6 +322,9 @@ where
             Ok(uproject) => Some(uproject),
         };
 
+        // whatever an earlier sync left stopped was abandoned, or it couldn't have gotten this far
+        self.stopped_pull.lock().take();
+
         // run git pull but retry one time if it fails
         self.emit_phase("Pulling latest changes from GitHub");
         match self
@@ -312,6 +334,29 @@ where
         {
             Ok(_) => {}
             Err(e) => {
+                // A rebase that stopped on conflicts would fail the retry too, leave it for the
+                // user to resolve instead
+                if self.git_client.rebase_in_progress().await.unwrap_or(false) {
+                    let conflicts = match self.git_client.list_rebase_conflicts().await {
+                        Ok(conflicts) => conflicts,
+                        Err(list_err) => {
+                            error!("Failed to list rebase conflicts: {:?}", list_err);
+                            return Err(CoreError::Internal(e.context("Sync stopped mid-rebase")));
+                        }
+                    };
+                    if !conflicts.files.is_empty() {
+                        *self.stopped_pull.lock() = Some(StoppedPull {
+                            snapshot,
+                            snapshot_modified_files,
+                            old_uproject,
+                        });
+                        return Err(CoreError::Input(anyhow!(
+                            "Sync stopped on conflicts in {} file(s). Resolve them in Diagnostics to finish syncing.",
+                            conflicts.files.len()
+                        )));
+                    }
+                }
+
                 error!("Failed to pull, retrying once. Error: {}", e);
                 self.git_client
                     .pull(PullStrategy::Rebase, PullStashStrategy::Autostash)
@@ -319,6 +364,31 @@ where
             }
         }
 
+        self.finish_pull(StoppedPull {
+            snapshot,
+            snapshot_modified_files,
+            old_uproject,
+        })
+        .await
+    }
+
+    /// Everything a sync does once the rebase is done: restoring the pre-pull
+    /// snapshot, refreshing status and downloading DLLs and engine updates.
+    async fn finish_pull(&self, stopped: StoppedPull) -> Result<(), CoreError> {
+        let StoppedPull {
+            snapshot,
+            snapshot_modified_files,
+            old_uproject,
+        } = stopped;
+
+        let github_username = self
+            .github_client
+            .clone()
+            .map_or(String::default(), |x| x.username.clone());
+        let app_config = self.app_config.read().clone();
+        let uproject_path_relative = self.repo_config.read().uproject_path.clone();
+        let uproject_path = PathBuf::from(&app_config.repo_path).join(&uproject_path_relative);
+
         // Collect any errors from the following operations, but continue where possible
         // This needs to remain serial because we don't have support for multiple concurrent
         // progress bars, and longtail already saturates most connections.
@@ -458,6 +528,71 @@ where
     }
 }
 
+/// Finishes a sync that stopped on rebase conflicts, once resolving them has
+/// let the rebase run to completion.
+#[derive(Clone)]
+pub struct ResumePullOp<T> {
+    pub pull: PullOp<T>,
+}
+
+#[async_trait]
+impl<T> Task for ResumePullOp<T>
+where
+    T: EngineProvider,
+{
+    #[instrument(name = "ResumePullOp::execute", skip(self))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        // stopped again on the next commit's conflicts
+        if self.pull.git_client.rebase_in_progress().await? {
+            return Ok(());
+        }
+
+        // Without the stopped pull, ex. after a restart, there's no pre-pull snapshot to restore
+        // or engine association to compare against, but DLLs are still brought up to date
+        let stopped = self.pull.stopped_pull.lock().take().unwrap_or_default();
+        self.pull.emit_phase("Finishing sync");
+        self.pull.finish_pull(stopped).await
+    }
+
+    fn get_name(&self) -> String {
+        String::from("ResumePull")
+    }
+}
+
+/// Builds a pull from the app's current clients, failing if AWS or artifact
+/// storage isn't ready yet.
+pub async fn new_pull_op<T>(state: &AppState<T>) -> Result<PullOp<T>, CoreError>
+where
+    T: EngineProvider,
+{
+    let aws_client = ensure_aws_client(state.aws_client.read().await.clone())?;
+
+    let storage = match state.storage.read().clone() {
+        Some(storage) => storage,
+        None => {
+            return Err(CoreError::Internal(anyhow!(
+                "Storage not configured. AWS may still be initializing."
+            )));
+        }
+    };
+
+    Ok(PullOp {
+        app_config: state.app_config.clone(),
+        repo_config: state.repo_config.clone(),
+        repo_status: state.repo_status.clone(),
+        longtail: state.longtail.clone(),
+        longtail_tx: state.longtail_tx.clone(),
+        aws_client,
+        storage,
+        git_client: state.git(),
+        github_client: state.github_client.read().clone(),
+        engine: state.engine.clone(),
+        sync_phase_tx: state.sync_phase_tx.clone(),
+        skip_snapshot: false,
+        stopped_pull: state.stopped_pull.clone(),
+    })
+}
+
 #[instrument(skip(state))]
 pub async fn pull_handler<T>(
     State(state): State<AppState<T>>,
@@ -510,31 +645,7 @@ where
         }
     }
 
-    let aws_client = ensure_aws_client(state.aws_client.read().await.clone())?;
-
-    let storage = match state.storage.read().clone() {
-        Some(storage) => storage,
-        None => {
-            return Err(CoreError::Internal(anyhow!(
-                "Storage not configured. AWS may still be initializing."
-            )));
-        }
-    };
-
-    let pull_op = PullOp {
-        app_config: state.app_config.clone(),
-        repo_config: state.repo_config.clone(),
-        repo_status: state.repo_status.clone(),
-        longtail: state.longtail.clone(),
-        longtail_tx: state.longtail_tx.clone(),
-        aws_client: aws_client.clone(),
-        storage,
-        git_client: state.git(),
-        github_client: state.github_client.read().clone(),
-        engine: state.engine.clone(),
-        sync_phase_tx: state.sync_phase_tx.clone(),
-        skip_snapshot: false,
-    };
+    let pull_op = new_pull_op(&state).await?;
 
     let (tx, rx) = tokio::sync::oneshot::channel::<Option<CoreError>>();
     let mut sequence = TaskSequence::new().with_completion_tx(tx);
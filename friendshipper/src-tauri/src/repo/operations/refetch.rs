@@ -1,13 +1,51 @@
+use anyhow::anyhow;
+use axum::async_trait;
+use axum::extract::State;
+use tracing::instrument;
+
 use crate::state::AppState;
 use crate::EngineProvider;
-use axum::extract::State;
+use ethos_core::clients::git::Git;
 use ethos_core::types::errors::CoreError;
+use ethos_core::worker::{Task, TaskSequence};
+
+#[derive(Clone)]
+pub struct RefetchOp {
+    pub git_client: Git,
+}
 
+#[async_trait]
+impl Task for RefetchOp {
+    #[instrument(name = "RefetchOp::execute", skip(self))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        self.git_client.refetch().await?;
+        self.git_client.rewrite_graph().await?;
+        Ok(())
+    }
+
+    fn get_name(&self) -> String {
+        "RefetchOp".to_string()
+    }
+}
+
+// Runs on the worker so a refetch that takes too long can be cancelled from /repo/cancel
 pub async fn refetch_repo<T>(State(state): State<AppState<T>>) -> Result<(), CoreError>
 where
     T: EngineProvider,
 {
-    state.git().refetch().await?;
-    state.git().rewrite_graph().await?;
-    Ok(())
+    let (tx, rx) = tokio::sync::oneshot::channel::<Option<CoreError>>();
+    let mut sequence = TaskSequence::new().with_completion_tx(tx);
+    sequence.push(Box::new(RefetchOp {
+        git_client: state.git(),
+    }));
+
+    state.operation_tx.send(sequence).await?;
+
+    match rx.await {
+        Ok(Some(e)) => Err(e),
+        Ok(None) => Ok(()),
+        Err(_) => Err(CoreError::Internal(anyhow!(
+            "Error executing refetch operation"
+        ))),
+    }
 }
@@ -1,15 +1,37 @@
 use crate::engine::EngineProvider;
 use crate::state::AppState;
+use anyhow::anyhow;
 use axum::extract::{Query, State};
-use axum::Json;
+use axum::{async_trait, Json};
+use ethos_core::clients::git::Git;
 use ethos_core::types::errors::CoreError;
-use ethos_core::types::repo::{Snapshot, SnapshotPreviewEntry};
+use ethos_core::types::repo::{
+    Snapshot, SnapshotFileRestore, SnapshotFileRestoreResult, SnapshotKind, SnapshotPreviewEntry,
+    SnapshotPrunePlan,
+};
+use ethos_core::worker::{Task, TaskSequence};
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::instrument;
+
+use super::RepoStatusRef;
 
 #[derive(Deserialize, Serialize)]
 pub struct SaveSnapshotRequest {
     pub message: String,
     pub files: Vec<String>,
+
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Set when the UI snapshots on the user's behalf, ex. before importing
+    /// a zip, so the snapshot is pruned like the ones taken before a pull.
+    #[serde(default)]
+    pub automatic: bool,
+}
+
+async fn find_snapshot(git: &Git, id: &str) -> Result<Snapshot, CoreError> {
+    git.find_snapshot(id).await.map_err(CoreError::Input)
 }
 
 pub async fn list_snapshots<T>(
@@ -47,14 +69,24 @@ where
         req.files
     };
 
-    state.git().save_snapshot(&req.message, files).await?;
+    let kind = if req.automatic {
+        SnapshotKind::Automatic
+    } else {
+        SnapshotKind::Manual
+    };
+    state
+        .git()
+        .save_tagged_snapshot(&req.message, files, kind, req.tags)
+        .await?;
 
     Ok(())
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct DeleteSnapshotParams {
-    pub commit: String,
+    /// Snapshot id. Older clients pass the snapshot's commit instead.
+    #[serde(alias = "commit")]
+    pub id: String,
 }
 
 pub async fn delete_snapshot<T>(
@@ -64,7 +96,7 @@ pub async fn delete_snapshot<T>(
 where
     T: EngineProvider,
 {
-    state.git().delete_snapshot(&params.commit).await?;
+    state.git().delete_snapshot(&params.id).await?;
 
     Ok(())
 }
@@ -72,7 +104,9 @@ where
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RestoreSnapshotRequest {
-    pub commit: String,
+    /// Snapshot id. Older clients pass the snapshot's commit instead.
+    #[serde(alias = "commit")]
+    pub id: String,
     /// Optional subset of repo-relative paths to restore. When `None` or
     /// empty, every file in the snapshot is restored using the existing
     /// cherry-pick path. When set, only the listed paths are touched.
@@ -113,17 +147,139 @@ where
     // user deselects everything, and we'd rather no-op than fall through to
     // a full restore in that case.
     let paths_filter = req.files.filter(|v| !v.is_empty());
-    state
-        .git()
-        .restore_snapshot(&req.commit, local_files, req.overwrite_local, paths_filter)
-        .await?;
+    let git = state.git();
+    let snapshot = find_snapshot(&git, &req.id).await?;
+    git.restore_snapshot(
+        &snapshot.commit,
+        local_files,
+        req.overwrite_local,
+        paths_filter,
+    )
+    .await?;
 
     Ok(())
 }
 
+#[derive(Clone)]
+pub struct RestoreSnapshotFilesOp {
+    pub git_client: Git,
+    pub commit: String,
+    pub files: Vec<SnapshotFileRestore>,
+    pub repo_status: RepoStatusRef,
+    pub response_tx: Option<mpsc::Sender<Vec<SnapshotFileRestoreResult>>>,
+}
+
+#[async_trait]
+impl Task for RestoreSnapshotFilesOp {
+    #[instrument(skip(self), name = "RestoreSnapshotFilesOp::execute", fields(commit = %self.commit))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        // Read at execution time rather than when queued, so an operation
+        // that ran ahead of us in the worker is reflected in what counts as
+        // a local change.
+        let local_files = {
+            let status = self.repo_status.read();
+            let mut v = status.modified_files.0.clone();
+            v.extend(status.untracked_files.0.iter().cloned());
+            v
+        };
+
+        let results = self
+            .git_client
+            .restore_snapshot_files(&self.commit, &self.files, &local_files)
+            .await?;
+
+        if let Some(tx) = &self.response_tx {
+            let _ = tx.send(results).await;
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> String {
+        "RestoreSnapshotFilesOp".to_string()
+    }
+}
+
+/// Moves snapshots older versions saved to the stash list under
+/// `refs/friendshipper/snapshots/`. Queued once at startup so listing
+/// snapshots stays read-only.
+#[derive(Clone)]
+pub struct MigrateStashSnapshotsOp {
+    pub git_client: Git,
+}
+
+#[async_trait]
+impl Task for MigrateStashSnapshotsOp {
+    #[instrument(skip(self), name = "MigrateStashSnapshotsOp::execute")]
+    async fn execute(&self) -> Result<(), CoreError> {
+        self.git_client.migrate_stash_snapshots().await?;
+        Ok(())
+    }
+
+    fn get_name(&self) -> String {
+        "MigrateStashSnapshotsOp".to_string()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSnapshotFilesRequest {
+    #[serde(alias = "commit")]
+    pub id: String,
+    pub files: Vec<SnapshotFileRestore>,
+}
+
+pub async fn restore_snapshot_files<T>(
+    State(state): State<AppState<T>>,
+    Json(req): Json<RestoreSnapshotFilesRequest>,
+) -> Result<Json<Vec<SnapshotFileRestoreResult>>, CoreError>
+where
+    T: EngineProvider,
+{
+    if req.files.is_empty() {
+        return Err(CoreError::Input(anyhow!("No files selected to restore")));
+    }
+
+    let git = state.git();
+    let snapshot = find_snapshot(&git, &req.id).await?;
+
+    let (response_tx, mut response_rx) = mpsc::channel::<Vec<SnapshotFileRestoreResult>>(1);
+    let op = RestoreSnapshotFilesOp {
+        git_client: git,
+        commit: snapshot.commit,
+        files: req.files,
+        repo_status: state.repo_status.clone(),
+        response_tx: Some(response_tx),
+    };
+
+    let (task_tx, task_rx) = tokio::sync::oneshot::channel::<Option<CoreError>>();
+    let mut sequence = TaskSequence::new().with_completion_tx(task_tx);
+    sequence.push(Box::new(op));
+    state.operation_tx.send(sequence).await?;
+
+    match task_rx.await {
+        Ok(Some(e)) => return Err(e),
+        Ok(None) => {}
+        Err(_) => {
+            return Err(CoreError::Internal(anyhow!(
+                "Error executing snapshot file restore"
+            )))
+        }
+    }
+
+    match response_rx.recv().await {
+        Some(results) => Ok(Json(results)),
+        None => Err(CoreError::Internal(anyhow!(
+            "Failed to get snapshot file restore results"
+        ))),
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct PreviewSnapshotParams {
-    pub commit: String,
+    /// Snapshot id. Older clients pass the snapshot's commit instead.
+    #[serde(alias = "commit")]
+    pub id: String,
 }
 
 #[derive(Serialize)]
@@ -139,9 +295,10 @@ pub async fn preview_snapshot<T>(
 where
     T: EngineProvider,
 {
-    let raw_entries = state
-        .git()
-        .get_snapshot_entries_with_state(&params.commit)
+    let git = state.git();
+    let snapshot = find_snapshot(&git, &params.id).await?;
+    let raw_entries = git
+        .get_snapshot_entries_with_state(&snapshot.commit)
         .await?;
 
     let (modified_files, untracked_files) = {
@@ -172,3 +329,87 @@ where
 
     Ok(Json(PreviewSnapshotResponse { entries }))
 }
+
+#[derive(Deserialize, Serialize)]
+pub struct SnapshotTagsRequest {
+    pub id: String,
+    pub tags: Vec<String>,
+}
+
+pub async fn set_snapshot_tags<T>(
+    State(state): State<AppState<T>>,
+    Json(req): Json<SnapshotTagsRequest>,
+) -> Result<Json<Snapshot>, CoreError>
+where
+    T: EngineProvider,
+{
+    let git = state.git();
+    find_snapshot(&git, &req.id).await?;
+    let snapshot = git.set_snapshot_tags(&req.id, req.tags).await?;
+    Ok(Json(snapshot))
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SnapshotIdRequest {
+    pub id: String,
+}
+
+/// Pushes a snapshot to origin as a backup, or so a teammate can fetch it by id.
+pub async fn push_snapshot<T>(
+    State(state): State<AppState<T>>,
+    Json(req): Json<SnapshotIdRequest>,
+) -> Result<Json<Snapshot>, CoreError>
+where
+    T: EngineProvider,
+{
+    let git = state.git();
+    find_snapshot(&git, &req.id).await?;
+    let snapshot = git.push_snapshot(&req.id).await?;
+    Ok(Json(snapshot))
+}
+
+pub async fn fetch_snapshot<T>(
+    State(state): State<AppState<T>>,
+    Json(req): Json<SnapshotIdRequest>,
+) -> Result<Json<Snapshot>, CoreError>
+where
+    T: EngineProvider,
+{
+    let snapshot = state.git().fetch_snapshot(&req.id).await?;
+    Ok(Json(snapshot))
+}
+
+/// Lists the snapshots past the retention policy, including the ones
+/// maintenance leaves alone because their files still differ from HEAD.
+pub async fn snapshot_prune_plan<T>(
+    State(state): State<AppState<T>>,
+) -> Result<Json<SnapshotPrunePlan>, CoreError>
+where
+    T: EngineProvider,
+{
+    let retention = state.app_config.read().snapshot_retention.clone();
+    let plan = state.git().plan_snapshot_prune(&retention).await?;
+    Ok(Json(plan))
+}
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct PruneSnapshotsRequest {
+    /// Ids of snapshots that still differ from HEAD the user agreed to prune.
+    #[serde(default)]
+    pub confirm: Vec<String>,
+}
+
+pub async fn prune_snapshots<T>(
+    State(state): State<AppState<T>>,
+    Json(req): Json<PruneSnapshotsRequest>,
+) -> Result<Json<Vec<Snapshot>>, CoreError>
+where
+    T: EngineProvider,
+{
+    let retention = state.app_config.read().snapshot_retention.clone();
+    let pruned = state
+        .git()
+        .prune_snapshots(&retention, &req.confirm)
+        .await?;
+    Ok(Json(pruned))
+}
@@ -379,7 +379,9 @@ where
                     // explicitly rather than relying on GlobSet to notice.
                     if !blocked_matcher.is_empty()
                         && file.state != FileState::Deleted
-                        && blocked_matcher.is_blocked(&file.path)
+                        && blocked_matcher
+                            .blocking_pattern(&file.path, &self.github_username)
+                            .is_some()
                     {
                         file.submit_status = SubmitStatus::Blocked;
                     } else if file.state == FileState::Unmerged {
@@ -910,7 +912,7 @@ mod tests {
             TargetBranchConfig {
                 name: "main".to_string(),
                 uses_merge_queue: false,
-                blocked_file_globs: vec!["**/*.uasset".to_string()],
+                blocked_file_globs: vec!["**/*.uasset".into()],
             },
             TargetBranchConfig {
                 name: "content-main".to_string(),
@@ -948,7 +950,7 @@ mod tests {
         let target_branches = vec![TargetBranchConfig {
             name: "main".to_string(),
             uses_merge_queue: false,
-            blocked_file_globs: vec!["**/*.uasset".to_string(), "**/*.dat".to_string()],
+            blocked_file_globs: vec!["**/*.uasset".into(), "**/*.dat".into()],
         }];
         let (status_op, _repo_status) = build_status_op(git, dir.path(), "main", target_branches);
 
@@ -1007,7 +1009,7 @@ mod tests {
         let target_branches = vec![TargetBranchConfig {
             name: "main".to_string(),
             uses_merge_queue: false,
-            blocked_file_globs: vec!["**/*.uasset".to_string()],
+            blocked_file_globs: vec!["**/*.uasset".into()],
         }];
         let (status_op, _repo_status) = build_status_op(git, dir.path(), "main", target_branches);
 
@@ -1051,7 +1053,7 @@ mod tests {
         let target_branches = vec![TargetBranchConfig {
             name: "main".to_string(),
             uses_merge_queue: false,
-            blocked_file_globs: vec!["**/*.uasset".to_string()],
+            blocked_file_globs: vec!["**/*.uasset".into()],
         }];
         let (status_op, _repo_status) = build_status_op(git, dir.path(), "main", target_branches);
 
@@ -1078,7 +1080,7 @@ mod tests {
         let target_branches = vec![TargetBranchConfig {
             name: "main".to_string(),
             uses_merge_queue: false,
-            blocked_file_globs: vec!["**/*.uasset".to_string()],
+            blocked_file_globs: vec!["**/*.uasset".into()],
         }];
         let (status_op, _repo_status) = build_status_op(git, dir.path(), "main", target_branches);
 
@@ -1102,7 +1104,7 @@ mod tests {
             TargetBranchConfig {
                 name: "main".to_string(),
                 uses_merge_queue: false,
-                blocked_file_globs: vec!["**/*.uasset".to_string()],
+                blocked_file_globs: vec!["**/*.uasset".into()],
             },
             TargetBranchConfig {
                 name: "content-main".to_string(),
@@ -1154,7 +1156,7 @@ mod tests {
             TargetBranchConfig {
                 name: "main".to_string(),
                 uses_merge_queue: false,
-                blocked_file_globs: vec!["**/*.uasset".to_string()],
+                blocked_file_globs: vec!["**/*.uasset".into()],
             },
             TargetBranchConfig {
                 name: "content-main".to_string(),
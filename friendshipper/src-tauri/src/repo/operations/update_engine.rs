@@ -16,6 +16,7 @@ use tracing::{info, instrument};
 use ethos_core::clients::aws::ensure_aws_client;
 use ethos_core::clients::git;
 use ethos_core::longtail;
+use ethos_core::longtail::cache::{CacheConsumer, ChunkCache};
 use ethos_core::msg::LongtailMsg;
 use ethos_core::types::config::EngineType;
 use ethos_core::types::config::UProject;
@@ -30,7 +31,7 @@ use crate::AppState;
 #[derive(Clone)]
 pub struct WipeEngineOp {
     pub engine_path: PathBuf,
-    pub engine_cache_path: PathBuf,
+    pub chunk_cache: ChunkCache,
 }
 
 #[derive(Clone)]
@@ -54,18 +55,21 @@ impl Task for WipeEngineOp {
     async fn execute(&self) -> Result<(), CoreError> {
         let mut errors: Vec<String> = vec![];
 
-        let paths: Vec<&Path> = vec![&self.engine_path, &self.engine_cache_path];
-        for path in paths {
-            match path.try_exists() {
-                Ok(exists) => {
-                    if exists {
-                        if let Err(e) = std::fs::remove_dir_all(path) {
-                            errors.push(format!("{}: {}", path.display(), e));
-                        }
+        let path: &Path = &self.engine_path;
+        match path.try_exists() {
+            Ok(exists) => {
+                if exists {
+                    if let Err(e) = std::fs::remove_dir_all(path) {
+                        errors.push(format!("{}: {}", path.display(), e));
                     }
                 }
-                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
             }
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+
+        // Blocks the client or DLLs also use stay in the shared cache
+        if let Err(e) = self.chunk_cache.clear(CacheConsumer::Engine) {
+            errors.push(format!("{}: {}", self.chunk_cache.root().display(), e));
         }
 
         if !errors.is_empty() {
@@ -154,18 +158,20 @@ where
                     };
                 }
 
-                let cache_path = get_engine_cache_path(&self.longtail);
-
-                let download_result = self.longtail.get_archive(
-                    &PathBuf::from(&self.engine_path),
-                    Some(longtail::CacheControl {
-                        path: cache_path,
-                        max_size_bytes: 100 * 1024 * 1024 * 1024, // 100 GB
-                    }),
-                    &archive_urls,
-                    self.longtail_tx.clone(),
-                    self.aws_client.get_credentials().await,
-                );
+                let download_result = self
+                    .longtail
+                    .get_archive(
+                        &PathBuf::from(&self.engine_path),
+                        Some(longtail::CacheControl {
+                            consumer: CacheConsumer::Engine,
+                            quota_bytes: 100 * 1024 * 1024 * 1024, // 100 GB
+                        }),
+                        &archive_urls,
+                        self.longtail_tx.clone(),
+                        self.aws_client.get_credentials().await,
+                        self.longtail.cancel_token(),
+                    )
+                    .await;
                 match download_result {
                     Ok(()) => {}
                     Err(e) => {
@@ -236,10 +242,6 @@ where
     }
 }
 
-fn get_engine_cache_path(longtail: &longtail::Longtail) -> PathBuf {
-    longtail.download_path.0.join("engine_cache/")
-}
-
 #[instrument(skip(state))]
 async fn get_update_op<T>(state: &AppState<T>) -> Result<UpdateEngineOp<T>, CoreError>
 where
@@ -379,7 +381,7 @@ where
 
     let wipe_op = WipeEngineOp {
         engine_path: update_op.engine_path.clone(),
-        engine_cache_path: get_engine_cache_path(&state.longtail),
+        chunk_cache: state.longtail.chunk_cache(),
     };
 
     let (tx, rx) = tokio::sync::oneshot::channel::<Option<CoreError>>();
@@ -0,0 +1,375 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use axum::extract::{Query, State};
+use axum::{async_trait, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+use crate::config::RepoConfigRef;
+use crate::engine::EngineProvider;
+use crate::state::AppState;
+use ethos_core::clients::git::{quick_submit_worktree_path, Git, WorktreeInfo};
+use ethos_core::types::config::AppConfigRef;
+use ethos_core::types::errors::CoreError;
+use ethos_core::types::repo::{RepoStatus, WorktreeStatus};
+use ethos_core::worker::{Task, TaskSequence};
+
+use super::{RepoStatusRef, StatusOp};
+
+#[derive(Clone)]
+pub struct AddWorktreeOp {
+    pub git_client: Git,
+    pub path: PathBuf,
+    pub branch: String,
+    pub start_point: Option<String>,
+}
+
+#[async_trait]
+impl Task for AddWorktreeOp {
+    #[instrument(skip(self), name = "AddWorktreeOp::execute", fields(branch = %self.branch))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        self.git_client
+            .add_worktree(&self.path, &self.branch, self.start_point.as_deref())
+            .await
+            .map_err(CoreError::Input)
+    }
+
+    fn get_name(&self) -> String {
+        "AddWorktreeOp".to_string()
+    }
+}
+
+#[derive(Clone)]
+pub struct RemoveWorktreeOp {
+    pub git_client: Git,
+    pub path: PathBuf,
+    pub force: bool,
+}
+
+#[async_trait]
+impl Task for RemoveWorktreeOp {
+    #[instrument(skip(self), name = "RemoveWorktreeOp::execute", fields(path = ?self.path))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        self.git_client
+            .remove_worktree(&self.path, self.force)
+            .await
+            .map_err(CoreError::Input)
+    }
+
+    fn get_name(&self) -> String {
+        "RemoveWorktreeOp".to_string()
+    }
+}
+
+// Points the app at another worktree of the same repo. Runs on the worker so it can't land in
+// the middle of a pull or submit in the old worktree.
+#[derive(Clone)]
+pub struct SwitchWorktreeOp<T>
+where
+    T: EngineProvider,
+{
+    pub path: String,
+    pub git_tx: std::sync::mpsc::Sender<String>,
+    pub app_config: AppConfigRef,
+    pub repo_config: RepoConfigRef,
+    pub repo_status: RepoStatusRef,
+    pub engine: T,
+    pub github_username: String,
+}
+
+#[async_trait]
+impl<T> Task for SwitchWorktreeOp<T>
+where
+    T: EngineProvider,
+{
+    #[instrument(skip(self), name = "SwitchWorktreeOp::execute", fields(path = %self.path))]
+    async fn execute(&self) -> Result<(), CoreError> {
+        // Each worktree has its own copy of the repo config, load it before committing to the
+        // switch so a broken one leaves the app where it was
+        let mut config = self.app_config.read().clone();
+        config.repo_path.clone_from(&self.path);
+        if let Some(project) = config.selected_artifact_project.clone() {
+            if let Some(project) = config.projects.get_mut(&project) {
+                project.repo_path.clone_from(&self.path);
+            }
+        }
+        let repo_config = config.initialize_repo_config()?;
+
+        *self.app_config.write() = config;
+        *self.repo_config.write() = repo_config;
+
+        let status_op = StatusOp {
+            repo_status: self.repo_status.clone(),
+            app_config: self.app_config.clone(),
+            repo_config: self.repo_config.clone(),
+            engine: self.engine.clone(),
+            git_client: Git::new(PathBuf::from(&self.path), self.git_tx.clone()),
+            github_username: self.github_username.clone(),
+            aws_client: None,
+            storage: None,
+            allow_offline_communication: false,
+            skip_display_names: true,
+            skip_engine_update: true,
+        };
+        status_op.execute().await
+    }
+
+    fn get_name(&self) -> String {
+        "SwitchWorktreeOp".to_string()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddWorktreeRequest {
+    pub branch: String,
+
+    // Defaults to a sibling of the main worktree named after the branch
+    #[serde(default)]
+    pub path: Option<String>,
+
+    // Where a new branch starts, defaults to origin/<branch>. Ignored if the branch exists.
+    #[serde(default)]
+    pub start_point: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WorktreePathRequest {
+    pub path: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RemoveWorktreeParams {
+    pub path: String,
+
+    #[serde(default)]
+    pub force: bool,
+}
+
+fn normalize_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn main_worktree(worktrees: &[WorktreeInfo]) -> Result<&WorktreeInfo, CoreError> {
+    worktrees
+        .iter()
+        .find(|w| w.is_main)
+        .ok_or_else(|| CoreError::Internal(anyhow!("Unable to find the main worktree")))
+}
+
+// The worktrees a user can see and switch to, leaving out quick submit's scratch worktree
+async fn user_worktrees(git: &Git) -> Result<Vec<WorktreeInfo>, CoreError> {
+    let worktrees = git.list_worktrees().await?;
+    let scratch = quick_submit_worktree_path(&main_worktree(&worktrees)?.directory);
+    Ok(worktrees
+        .into_iter()
+        .filter(|w| w.directory != scratch)
+        .collect())
+}
+
+async fn find_worktree(git: &Git, path: &str) -> Result<WorktreeInfo, CoreError> {
+    let path = PathBuf::from(path);
+    user_worktrees(git)
+        .await?
+        .into_iter()
+        .find(|w| w.directory == path)
+        .ok_or_else(|| {
+            CoreError::Input(anyhow!("{} is not a worktree of this repo", path.display()))
+        })
+}
+
+async fn queue_and_wait<T>(
+    state: &AppState<T>,
+    op: Box<dyn Task + Send + Sync>,
+) -> Result<(), CoreError>
+where
+    T: EngineProvider,
+{
+    let (tx, rx) = tokio::sync::oneshot::channel::<Option<CoreError>>();
+    let mut sequence = TaskSequence::new().with_completion_tx(tx);
+    sequence.push(op);
+    state.operation_tx.send(sequence).await?;
+
+    match rx.await {
+        Ok(Some(e)) => Err(e),
+        Ok(None) => Ok(()),
+        Err(_) => Err(CoreError::Internal(anyhow!(
+            "Error executing worktree operation"
+        ))),
+    }
+}
+
+pub async fn list_worktrees<T>(
+    State(state): State<AppState<T>>,
+) -> Result<Json<Vec<WorktreeStatus>>, CoreError>
+where
+    T: EngineProvider,
+{
+    let git = state.git();
+    let active = PathBuf::from(state.app_config.read().repo_path.clone());
+
+    let mut statuses = vec![];
+    for worktree in user_worktrees(&git).await? {
+        let is_active = worktree.directory == active;
+        let status = if is_active {
+            Some(state.repo_status.read().clone())
+        } else if worktree.directory.exists() {
+            // Only what git status reports, locks and upstream checks are left to the active one
+            let mut worktree_git = git.clone();
+            worktree_git.repo_path.clone_from(&worktree.directory);
+            match worktree_git.status(vec![]).await {
+                Ok(output) => {
+                    let mut status = RepoStatus::new();
+                    for line in output.lines() {
+                        status.parse_file_line(line);
+                    }
+                    status.has_local_changes =
+                        !status.modified_files.is_empty() || !status.untracked_files.is_empty();
+                    Some(status)
+                }
+                Err(e) => {
+                    warn!("Failed to get status of {:?}: {}", worktree.directory, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        statuses.push(WorktreeStatus {
+            path: normalize_path(&worktree.directory),
+            branch: worktree
+                .branch
+                .map(|b| b.trim_start_matches("refs/heads/").to_string()),
+            sha: worktree.sha,
+            is_main: worktree.is_main,
+            active: is_active,
+            status,
+        });
+    }
+
+    Ok(Json(statuses))
+}
+
+pub async fn add_worktree<T>(
+    State(state): State<AppState<T>>,
+    Json(req): Json<AddWorktreeRequest>,
+) -> Result<Json<WorktreeStatus>, CoreError>
+where
+    T: EngineProvider,
+{
+    let branch = req.branch.trim().to_string();
+    if branch.is_empty() {
+        return Err(CoreError::Input(anyhow!("A branch is required")));
+    }
+
+    let git = state.git();
+    let path = match req.path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path.trim()),
+        None => {
+            let worktrees = git.list_worktrees().await?;
+            let main = &main_worktree(&worktrees)?.directory;
+            let folder_name = main.file_name().unwrap_or_default().to_string_lossy();
+            main.with_file_name(format!("{}-{}", folder_name, branch.replace('/', "-")))
+        }
+    };
+
+    let op = AddWorktreeOp {
+        git_client: git.clone(),
+        path: path.clone(),
+        branch: branch.clone(),
+        start_point: req.start_point.filter(|s| !s.trim().is_empty()),
+    };
+    queue_and_wait(&state, Box::new(op)).await?;
+
+    info!("Added worktree for {} at {:?}", branch, path);
+
+    // a branch can only be checked out in one worktree, so it identifies the new one
+    let branch_ref = format!("refs/heads/{branch}");
+    let worktree = user_worktrees(&git)
+        .await?
+        .into_iter()
+        .find(|w| w.branch.as_deref() == Some(branch_ref.as_str()))
+        .ok_or_else(|| CoreError::Internal(anyhow!("Unable to find the new worktree")))?;
+    Ok(Json(WorktreeStatus {
+        path: normalize_path(&worktree.directory),
+        branch: Some(branch),
+        sha: worktree.sha,
+        is_main: false,
+        active: false,
+        status: None,
+    }))
+}
+
+pub async fn switch_worktree<T>(
+    State(state): State<AppState<T>>,
+    Json(req): Json<WorktreePathRequest>,
+) -> Result<(), CoreError>
+where
+    T: EngineProvider,
+{
+    let worktree = find_worktree(&state.git(), &req.path).await?;
+    if !worktree.directory.exists() {
+        return Err(CoreError::Input(anyhow!(
+            "{} no longer exists",
+            worktree.directory.display()
+        )));
+    }
+
+    let path = normalize_path(&worktree.directory);
+    if path == state.app_config.read().repo_path {
+        return Ok(());
+    }
+
+    let op = SwitchWorktreeOp {
+        path: path.clone(),
+        git_tx: state.git_tx.clone(),
+        app_config: state.app_config.clone(),
+        repo_config: state.repo_config.clone(),
+        repo_status: state.repo_status.clone(),
+        engine: state.engine.clone(),
+        github_username: state.github_username(),
+    };
+    queue_and_wait(&state, Box::new(op)).await?;
+
+    // remember the active worktree across restarts
+    {
+        let mut config = state.app_config.read().clone();
+        config.github_pat = None;
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&state.config_file)?;
+        serde_yaml::to_writer(file, &config)?;
+    }
+
+    state.active_worktree_tx.send_replace(path.clone());
+    info!("Switched active worktree to {}", path);
+
+    Ok(())
+}
+
+pub async fn remove_worktree<T>(
+    State(state): State<AppState<T>>,
+    Query(params): Query<RemoveWorktreeParams>,
+) -> Result<(), CoreError>
+where
+    T: EngineProvider,
+{
+    let git = state.git();
+    let worktree = find_worktree(&git, &params.path).await?;
+    if normalize_path(&worktree.directory) == state.app_config.read().repo_path {
+        return Err(CoreError::Input(anyhow!(
+            "Switch to another worktree before removing this one"
+        )));
+    }
+
+    let op = RemoveWorktreeOp {
+        git_client: git,
+        path: worktree.directory,
+        force: params.force,
+    };
+    queue_and_wait(&state, Box::new(op)).await
+}
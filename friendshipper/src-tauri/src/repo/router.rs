@@ -14,18 +14,49 @@ where
         .route("/diff", get(operations::diff_handler))
         .route("/log", get(operations::log_handler))
         .route("/branch-compare", get(operations::branch_compare_handler))
+        .route(
+            "/blocked-files/audit",
+            post(operations::audit_blocked_files),
+        )
         .route("/pull", post(operations::pull_handler))
         .route("/show", get(operations::show_commit_files))
         .route("/file-history", get(operations::file_history_handler))
         .route("/browse", get(operations::list_directory_handler))
         .route("/commit-info", get(operations::commit_info_handler))
+        .route("/content-profiles", get(operations::list_content_profiles))
+        .route(
+            "/content-profiles/plan",
+            get(operations::plan_content_profile),
+        )
+        .route(
+            "/content-profiles/apply",
+            post(operations::apply_content_profile),
+        )
         .route(
             "/snapshots",
             get(operations::list_snapshots).delete(operations::delete_snapshot),
         )
+        .route("/snapshots/fetch", post(operations::fetch_snapshot))
         .route("/snapshots/preview", get(operations::preview_snapshot))
+        .route(
+            "/snapshots/prune",
+            get(operations::snapshot_prune_plan).post(operations::prune_snapshots),
+        )
+        .route("/snapshots/push", post(operations::push_snapshot))
         .route("/snapshots/restore", post(operations::restore_snapshot))
+        .route(
+            "/snapshots/restore-files",
+            post(operations::restore_snapshot_files),
+        )
         .route("/snapshots/save", post(operations::save_snapshot))
+        .route("/snapshots/tags", post(operations::set_snapshot_tags))
+        .route(
+            "/worktrees",
+            get(operations::list_worktrees)
+                .post(operations::add_worktree)
+                .delete(operations::remove_worktree),
+        )
+        .route("/worktrees/switch", post(operations::switch_worktree))
         .route("/changeset/save", post(operations::save_changeset))
         .route("/changeset/load", get(operations::load_changeset))
         .route("/status", get(operations::status_handler))
@@ -38,6 +69,18 @@ where
             "/diagnostics/rebase/fix",
             post(operations::diagnostics::remediate_rebase_handler),
         )
+        .route(
+            "/diagnostics/rebase/conflicts",
+            get(operations::diagnostics::rebase_conflicts_handler),
+        )
+        .route(
+            "/diagnostics/rebase/conflicts/preview",
+            get(operations::diagnostics::conflict_preview_handler),
+        )
+        .route(
+            "/diagnostics/rebase/conflicts/resolve",
+            post(operations::diagnostics::resolve_conflicts_handler),
+        )
         .route(
             "/diagnostics/object-count",
             get(operations::diagnostics::object_count_handler),
@@ -46,6 +89,14 @@ where
             "/diagnostics/gc",
             post(operations::diagnostics::run_gc_handler),
         )
+        .route(
+            "/diagnostics/index-lock",
+            get(operations::diagnostics::index_lock_handler),
+        )
+        .route(
+            "/diagnostics/index-lock/remove",
+            post(operations::diagnostics::remove_index_lock_handler),
+        )
         .route(
             "/diagnostics/github-status",
             get(operations::diagnostics::github_status_handler),
@@ -60,6 +111,7 @@ where
         )
         .route("/reset", post(operations::reset_repo))
         .route("/refetch", post(operations::refetch_repo))
+        .route("/cancel", post(operations::cancel_handler))
         .route("/reset/:commit", post(operations::reset_repo_to_commit))
         .route("/revert", post(operations::revert_files_handler::<T>))
         .route(
@@ -74,6 +126,7 @@ where
         )
         .route("/gh/queue", get(operations::gh::get_merge_queue))
         .route("/gh/submit", post(operations::gh::submit_handler))
+        .route("/gh/submits", get(operations::gh::get_tracked_submits))
         .route("/gh/pulls", get(operations::gh::get_pull_requests))
         .route("/gh/pulls/:id", get(operations::gh::get_pull_request))
         .route("/gh/user", get(operations::gh::get_user))
@@ -19,9 +19,8 @@ use tokio::sync::mpsc;
 use tokio::sync::oneshot::error::RecvError;
 use tracing::{debug, error, info, instrument, warn};
 
-use ethos_core::msg::LongtailMsg;
-use ethos_core::storage::ArtifactStorage;
-use ethos_core::types::config::{AppConfig, DynamicConfig, ProjectRepoConfig};
+use ethos_core::msg::{GitProgress, LongtailMsg};
+use ethos_core::types::config::{AppConfig, AppConfigRef, DynamicConfig, ProjectRepoConfig};
 use ethos_core::types::errors::CoreError;
 use ethos_core::types::repo::RepoStatus;
 use ethos_core::types::repo::RepoStatusRef;
@@ -30,11 +29,29 @@ use ethos_core::worker::{RepoWorker, TaskSequence};
 
 use crate::client::FriendshipperClient;
 use crate::engine::{EngineProvider, UnrealEngineProvider};
-use crate::repo::operations::InstallGitHooksOp;
-use crate::state::{FrontendOp, Notification};
+use crate::repo::operations::gh::submit_tracker::run_submit_tracker;
+use crate::repo::operations::{InstallGitHooksOp, MigrateStashSnapshotsOp};
+use crate::state::{create_artifact_storage, FrontendOp, Notification};
 use crate::APP_NAME;
 use crate::{state::AppState, KEYRING_USER, VERSION};
 
+// The engine's content directory in the active worktree, None until a repo is configured
+fn watched_content_dir<T>(state: &AppState<T>) -> Option<PathBuf>
+where
+    T: EngineProvider,
+{
+    let repo_path = state.app_config.read().repo_path.clone();
+    if repo_path.is_empty() {
+        return None;
+    }
+
+    let mut content_dir = PathBuf::from(repo_path);
+    content_dir.push(state.repo_config.read().uproject_path.clone());
+    content_dir.pop(); // pop off uproject filename
+    content_dir.push(state.engine.get_default_content_subdir());
+    Some(content_dir)
+}
+
 pub struct Server {
     port: u16,
     longtail_tx: STDSender<LongtailMsg>,
@@ -42,6 +59,7 @@ pub struct Server {
     frontend_op_tx: STDSender<FrontendOp>,
     log_path: PathBuf,
     git_tx: STDSender<String>,
+    git_progress_tx: STDSender<GitProgress>,
     sync_phase_tx: STDSender<String>,
     gameserver_log_tx: STDSender<String>,
     workflow_log_tx: STDSender<String>,
@@ -57,6 +75,7 @@ impl Server {
         frontend_op_tx: STDSender<FrontendOp>,
         log_path: PathBuf,
         git_tx: STDSender<String>,
+        git_progress_tx: STDSender<GitProgress>,
         sync_phase_tx: STDSender<String>,
         gameserver_log_tx: STDSender<String>,
         workflow_log_tx: STDSender<String>,
@@ -69,6 +88,7 @@ impl Server {
             frontend_op_tx,
             log_path,
             git_tx,
+            git_progress_tx,
             sync_phase_tx,
             gameserver_log_tx,
             workflow_log_tx,
@@ -97,24 +117,20 @@ impl Server {
 
         // configure file watcher
         let watcher_status = shared_state.repo_status.clone();
-        let watcher_git = shared_state.git().clone();
 
-        // this debouncer must stay in scope for the duration of the server run
+        // this debouncer must stay in scope for the duration of the server run, so it's moved
+        // into the task that re-targets it below
         let mut debouncer = self.create_file_watcher(
             watcher_status,
-            watcher_git,
+            shared_state.app_config.clone(),
+            shared_state.git_tx.clone(),
             shared_state.engine.clone(),
             pause_background_tasks.clone(),
             refresh_tx,
         )?;
 
-        let repo_path = shared_state.app_config.read().repo_path.clone();
-        if !repo_path.is_empty() {
-            let mut content_dir = PathBuf::from(shared_state.app_config.read().repo_path.clone());
-            content_dir.push(shared_state.repo_config.read().uproject_path.clone());
-            content_dir.pop(); // pop off uproject filename
-            content_dir.push(shared_state.engine.get_default_content_subdir());
-
+        let mut watched_dir = watched_content_dir(&shared_state);
+        if let Some(content_dir) = &watched_dir {
             let inner_span = tracing::info_span!("watcher_start_watch").entered();
             debouncer
                 .watcher()
@@ -122,6 +138,30 @@ impl Server {
             inner_span.exit();
         }
 
+        // follow the active worktree when the user switches it
+        let mut worktree_rx = shared_state.active_worktree_tx.subscribe();
+        let watcher_state = shared_state.clone();
+        tokio::spawn(async move {
+            while worktree_rx.changed().await.is_ok() {
+                if let Some(old_dir) = watched_dir.take() {
+                    if let Err(e) = debouncer.watcher().unwatch(old_dir.as_path()) {
+                        warn!("Failed to stop watching {:?}: {}", old_dir, e);
+                    }
+                }
+
+                if let Some(content_dir) = watched_content_dir(&watcher_state) {
+                    info!("Re-targeting file watcher to {:?}", content_dir);
+                    match debouncer
+                        .watcher()
+                        .watch(content_dir.as_path(), RecursiveMode::Recursive)
+                    {
+                        Ok(_) => watched_dir = Some(content_dir),
+                        Err(e) => error!("Failed to watch {:?}: {}", content_dir, e),
+                    }
+                }
+            }
+        });
+
         info!("starting server at {}", address);
         startup_tx.send("Starting server".to_string())?;
 
@@ -138,11 +178,7 @@ impl Server {
                 info!("Shutting down server");
 
                 // cancel any longtail downloads
-                let longtail = shared_state.longtail.clone();
-                let child = longtail.child_process.lock().take();
-                if let Some(mut child) = child {
-                    child.kill().unwrap();
-                }
+                shared_state.longtail.cancel_all();
 
                 // Wait up to 30 seconds for index.lock to go away
                 let repo_path = shared_state.app_config.read().repo_path.clone();
@@ -197,8 +233,11 @@ impl Server {
 
         let app_config = Arc::new(RwLock::new(config.clone()));
         let repo_config = Arc::new(RwLock::new(app_config.read().initialize_repo_config()?));
-        let dynamic_config = Arc::new(RwLock::new(DynamicConfig::default()));
-        let storage: Option<ArtifactStorage> = None;
+        // Replaced once AWS is signed in to, but a local artifact provider set in the overrides
+        // serves the builds page before then, or without AWS at all
+        let dynamic_config = DynamicConfig::load_override().unwrap_or_default();
+        let storage = create_artifact_storage(&dynamic_config, None);
+        let dynamic_config = Arc::new(RwLock::new(dynamic_config));
 
         // Initialize branch defaults if not set and save config if updated
         let app_config_error = {
@@ -243,6 +282,7 @@ impl Server {
         startup_tx.send("Starting operation worker".to_string())?;
         let (op_tx, op_rx) = mpsc::channel(32);
         let mut worker = RepoWorker::new(op_rx, pause_background_tasks.clone());
+        let repo_cancel = worker.cancel_handle();
         tokio::spawn(async move {
             worker.run().await;
         });
@@ -256,6 +296,7 @@ impl Server {
             storage,
             self.longtail_tx.clone(),
             op_tx.clone(),
+            repo_cancel,
             self.notification_tx.clone(),
             self.frontend_op_tx.clone(),
             VERSION.to_string(),
@@ -263,6 +304,7 @@ impl Server {
             self.log_path.clone(),
             Some(self.otel_reload_handle.clone()),
             self.git_tx.clone(),
+            self.git_progress_tx.clone(),
             self.sync_phase_tx.clone(),
             self.gameserver_log_tx.clone(),
             self.workflow_log_tx.clone(),
@@ -279,18 +321,17 @@ impl Server {
 
         let tx = shared_state.git_tx.clone();
         if !repo_path.is_empty() {
-            // Check for and remove index.lock if it exists
+            // Remove an index.lock left by a crashed git, but not one a git running in a shell
+            // or IDE still holds
             let span = tracing::info_span!("remove_index_lock").entered();
 
-            let index_lock_path = PathBuf::from(&repo_path).join(".git").join("index.lock");
-            if index_lock_path.exists() {
-                match fs::remove_file(&index_lock_path) {
-                    Ok(_) => {
-                        info!("Removed existing index.lock file");
-                    }
-                    Err(e) => {
-                        warn!("Failed to remove index.lock file: {:?}", e);
-                    }
+            match shared_state.git().remove_stale_index_lock() {
+                Ok(true) => {
+                    info!("Removed stale index.lock file");
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("Failed to remove index.lock file: {:?}", e);
                 }
             }
 
@@ -345,6 +386,27 @@ impl Server {
                 startup_tx.send("Performing git repo maintenance".to_string())?;
                 git.expire_reflog().await?;
 
+                // moves snapshots older versions left in the stash list; queued so it runs
+                // after anything else already on the worker rather than racing it
+                {
+                    let op = MigrateStashSnapshotsOp {
+                        git_client: git.clone(),
+                    };
+                    let migrate_state = hooks_state.clone();
+                    tokio::spawn(async move {
+                        let (tx, rx) = tokio::sync::oneshot::channel::<Option<CoreError>>();
+                        let mut sequence = TaskSequence::new().with_completion_tx(tx);
+                        sequence.push(Box::new(op));
+                        let _ = migrate_state.operation_tx.send(sequence).await;
+
+                        match rx.await {
+                            Ok(Some(e)) => warn!("Failed to migrate stash snapshots: {}", e),
+                            Ok(None) => {}
+                            Err(e) => warn!("Failed to migrate stash snapshots: {}", e),
+                        }
+                    });
+                }
+
                 startup_tx.send("Installing git hooks".to_string())?;
                 if let Some(git_hooks_path) = git_hooks_path {
                     tokio::spawn(async move {
@@ -382,7 +444,8 @@ impl Server {
         if !repo_path.is_empty() {
             let maintenance_runner =
                 GitMaintenanceRunner::new(repo_path, pause_background_tasks.clone(), tx)
-                    .with_fetch_interval(Duration::from_secs(5));
+                    .with_fetch_interval(Duration::from_secs(5))
+                    .with_app_config(shared_state.app_config.clone());
             tokio::spawn(async move {
                 match maintenance_runner.run().await {
                     Ok(_) => {}
@@ -393,6 +456,9 @@ impl Server {
             });
         }
 
+        // pick up following any quick submits still in flight when the app last closed
+        tokio::spawn(run_submit_tracker(shared_state.clone()));
+
         let span = tracing::info_span!("create_router").entered();
         let app = crate::router(&shared_state.log_path, self.port)?
             .with_state(shared_state.clone())
@@ -408,12 +474,13 @@ impl Server {
 
     #[instrument(
         level = "info",
-        skip(self, status, git_client, engine, pause_rx, refresh_tx)
+        skip(self, status, app_config, git_tx, engine, pause_rx, refresh_tx)
     )]
     fn create_file_watcher<T>(
         &self,
         status: RepoStatusRef,
-        git_client: Git,
+        app_config: AppConfigRef,
+        git_tx: STDSender<String>,
         engine: T,
         pause_rx: Arc<AtomicBool>,
         refresh_tx: STDSender<()>,
@@ -448,27 +515,30 @@ impl Server {
                             return;
                         }
 
+                        // built per event so a worktree switch takes effect immediately
+                        let git_client = Git::new(
+                            PathBuf::from(app_config.read().repo_path.clone()),
+                            git_tx.clone(),
+                        );
+
+                        // events can still arrive from the previous worktree right after a switch.
+                        // No paths at all (ex. only deletions) still means a full status below.
+                        let paths = modified
+                            .iter()
+                            .filter_map(|p| p.strip_prefix(&git_client.repo_path).ok())
+                            .filter_map(|p| p.to_str())
+                            .map(|p| p.to_string())
+                            .collect::<Vec<_>>();
+                        if paths.is_empty() && !modified.is_empty() {
+                            return;
+                        }
+
                         let mut status = status.write();
                         let rt = tokio::runtime::Builder::new_current_thread()
                             .enable_all()
                             .build()
                             .unwrap();
-                        match rt.block_on(async {
-                            git_client
-                                .status(
-                                    modified
-                                        .iter()
-                                        .map(|p| {
-                                            p.strip_prefix(&git_client.repo_path)
-                                                .unwrap()
-                                                .to_str()
-                                                .unwrap()
-                                                .to_string()
-                                        })
-                                        .collect(),
-                                )
-                                .await
-                        }) {
+                        match rt.block_on(async { git_client.status(paths).await }) {
                             Ok(output) => {
                                 for line in output.lines() {
                                     status.parse_file_line(line);
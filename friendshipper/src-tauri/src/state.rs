@@ -4,25 +4,29 @@ use anyhow::{anyhow, Result};
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::trace::Sampler;
 use opentelemetry_sdk::Resource;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use tokio::sync::mpsc::Sender as MPSCSender;
-use tokio::sync::{oneshot, RwLock as TokioRwLock};
+use tokio::sync::{oneshot, watch, RwLock as TokioRwLock};
 use tracing::{debug, error, info, instrument, warn};
 
 use crate::config::{DynamicConfigRef, RepoConfigRef};
 use crate::engine::EngineProvider;
+use crate::repo::operations::gh::submit_tracker::SubmitTracker;
+use crate::repo::operations::StoppedPullRef;
 use crate::repo::RepoStatusRef;
 use ethos_core::clients::git;
 use ethos_core::clients::github;
 use ethos_core::clients::kube::KubeClient;
+use ethos_core::fs::LocalDownloadPath;
 use ethos_core::longtail::Longtail;
-use ethos_core::msg::LongtailMsg;
-use ethos_core::storage::ArtifactStorage;
-use ethos_core::types::config::AppConfigRef;
+use ethos_core::msg::{GitProgress, LongtailMsg};
+use ethos_core::storage::index::DEFAULT_INDEX_MAX_AGE;
+use ethos_core::storage::{ArtifactIndexCache, ArtifactStorage};
+use ethos_core::types::config::{AppConfigRef, DynamicConfig};
 use ethos_core::types::errors::CoreError;
 use ethos_core::types::repo::RepoStatus;
 use ethos_core::utils::logging::{OtelReloadHandle, OTEL_TRACER_PROTOCOL, OTEL_TRACER_TIMEOUT};
-use ethos_core::worker::TaskSequence;
+use ethos_core::worker::{CancelHandle, TaskSequence};
 use ethos_core::AWSClient;
 
 pub enum FrontendOp {
@@ -35,6 +39,28 @@ pub enum Notification {
     Error(String),
 }
 
+// Directory under the local download path holding the cached artifact listings
+const ARTIFACT_INDEX_DIR: &str = "artifact-index";
+
+// Storage for the configured artifact provider, None for S3 until there's an AWS client. A local
+// provider doesn't need AWS, so sites mirroring builds on a file share get storage at startup.
+pub fn create_artifact_storage(
+    dynamic_config: &DynamicConfig,
+    aws_client: Option<&AWSClient>,
+) -> Option<ArtifactStorage> {
+    let provider = dynamic_config
+        .artifact_provider
+        .create_provider(aws_client)?;
+    let index_cache = ArtifactIndexCache::new(
+        LocalDownloadPath::new(crate::APP_NAME).join(ARTIFACT_INDEX_DIR),
+        DEFAULT_INDEX_MAX_AGE,
+    );
+    Some(
+        ArtifactStorage::new(provider, dynamic_config.storage_schema.clone())
+            .with_index_cache(index_cache),
+    )
+}
+
 #[derive(Clone)]
 pub struct AppState<T> {
     pub app_config: AppConfigRef,
@@ -44,11 +70,15 @@ pub struct AppState<T> {
     pub storage: Arc<RwLock<Option<ArtifactStorage>>>,
 
     pub repo_status: RepoStatusRef,
+    /// A sync stopped on rebase conflicts, finished once they're resolved.
+    pub stopped_pull: StoppedPullRef,
 
     pub longtail: Longtail,
     pub longtail_tx: STDSender<LongtailMsg>,
 
     pub operation_tx: MPSCSender<TaskSequence>,
+    /// Cancels the sequence `operation_tx`'s worker is running, killing its git command.
+    pub repo_cancel: CancelHandle,
     pub notification_tx: STDSender<Notification>,
     pub frontend_op_tx: STDSender<FrontendOp>,
 
@@ -57,6 +87,7 @@ pub struct AppState<T> {
     pub additional_kube_clients: Arc<RwLock<HashMap<String, KubeClient>>>,
 
     pub github_client: Arc<RwLock<Option<github::GraphQLClient>>>,
+    pub submit_tracker: SubmitTracker,
 
     pub version: String,
     pub log_path: PathBuf,
@@ -65,6 +96,10 @@ pub struct AppState<T> {
     pub gameserver_log_tx: STDSender<String>,
     pub workflow_log_tx: STDSender<String>,
     pub git_tx: STDSender<String>,
+    /// Typed progress parsed from git and git-lfs output, emitted to the
+    /// frontend as `git-progress` Tauri events so pulls and clones can show
+    /// a determinate progress bar.
+    pub git_progress_tx: STDSender<GitProgress>,
     /// Emits high-level sync phase labels (e.g. "Pulling latest changes from
     /// GitHub") to the frontend as `sync-phase` Tauri events. Used by the
     /// pulling modal to show coarse progress distinct from the noisier
@@ -73,6 +108,10 @@ pub struct AppState<T> {
 
     pub engine: T,
 
+    /// Carries the new repo path whenever the active worktree is switched so
+    /// the file watcher can follow it.
+    pub active_worktree_tx: Arc<watch::Sender<String>>,
+
     pub cancel_tx: Arc<TokioRwLock<Option<oneshot::Sender<()>>>>,
     pub workflow_log_cancel_tx: Arc<TokioRwLock<Option<oneshot::Sender<()>>>>,
 }
@@ -91,6 +130,7 @@ where
         storage: Option<ArtifactStorage>,
         longtail_tx: STDSender<LongtailMsg>,
         operation_tx: MPSCSender<TaskSequence>,
+        repo_cancel: CancelHandle,
         notification_tx: STDSender<Notification>,
         frontend_op_tx: STDSender<FrontendOp>,
         version: String,
@@ -98,23 +138,23 @@ where
         log_path: PathBuf,
         otel_reload_handle: Option<OtelReloadHandle>,
         git_tx: STDSender<String>,
+        git_progress_tx: STDSender<GitProgress>,
         sync_phase_tx: STDSender<String>,
         server_log_tx: STDSender<String>,
         workflow_log_tx: STDSender<String>,
     ) -> Result<Self> {
-        let mut longtail = Longtail::new(crate::APP_NAME);
-
-        debug!("Checking longtail");
-        if longtail.exec_path.is_none() && longtail.update_exec().is_err() {
-            match longtail.get_longtail(longtail_tx.clone()) {
-                Ok(_) => {
-                    longtail.update_exec()?;
-                }
-                Err(e) => {
-                    return Err(anyhow!("Failed to get longtail exe. Any operations depending on longtail will fail. Reason: {}", e));
-                }
+        let longtail = Longtail::new(crate::APP_NAME);
+        longtail
+            .set_chunk_cache_budget(app_config.read().max_chunk_cache_size_gb * 1024 * 1024 * 1024);
+
+        // Blocks from the per-consumer caches that predate the shared one are kept
+        let chunk_cache = longtail.chunk_cache();
+        let download_path = longtail.download_path.0.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = chunk_cache.migrate_legacy_caches(&download_path) {
+                warn!("Failed to migrate legacy chunk caches: {:?}", e);
             }
-        }
+        });
 
         debug!("Creating repo status");
         let repo_status = Arc::new(RwLock::new(RepoStatus {
@@ -181,6 +221,11 @@ where
         let mut engine = T::new_from_config(app_config.read().clone(), repo_config.read().clone());
         engine.load_caches().await;
 
+        let submit_tracker =
+            SubmitTracker::load(SubmitTracker::default_path(), notification_tx.clone());
+
+        let (active_worktree_tx, _) = watch::channel(app_config.read().repo_path.clone());
+
         debug!("AppState preparation complete.");
         Ok(Self {
             app_config,
@@ -189,23 +234,28 @@ where
             config_file,
             storage: Arc::new(RwLock::new(storage)),
             repo_status,
+            stopped_pull: Arc::new(Mutex::new(None)),
             longtail,
             longtail_tx,
             operation_tx,
+            repo_cancel,
             notification_tx,
             frontend_op_tx,
             aws_client: Arc::new(TokioRwLock::new(aws_client)),
             kube_client,
             additional_kube_clients: Arc::new(RwLock::new(HashMap::new())),
             github_client,
+            submit_tracker,
             version,
             log_path,
             otel_reload_handle,
             git_tx,
+            git_progress_tx,
             sync_phase_tx,
             gameserver_log_tx: server_log_tx,
             workflow_log_tx,
             engine,
+            active_worktree_tx: Arc::new(active_worktree_tx),
             cancel_tx: Arc::new(TokioRwLock::new(None)),
             workflow_log_cancel_tx: Arc::new(TokioRwLock::new(None)),
         })
@@ -213,7 +263,7 @@ where
 
     pub fn git(&self) -> git::Git {
         let repo_path = PathBuf::from(self.app_config.read().repo_path.clone());
-        git::Git::new(repo_path, self.git_tx.clone())
+        git::Git::new(repo_path, self.git_tx.clone()).with_progress_tx(self.git_progress_tx.clone())
     }
 
     pub fn send_notification(&self, notification: Notification) {
@@ -262,6 +312,7 @@ where
             let mut dynamic_config = self.dynamic_config.write();
             *dynamic_config = new_dynamic_config.clone();
         }
+        let new_storage = create_artifact_storage(&new_dynamic_config, Some(&client));
 
         // reload handle for otlp
         if let Some(endpoint) = new_dynamic_config.otlp_endpoint.clone() {
@@ -411,14 +462,6 @@ where
             }
         }
 
-        // TODO Hardcoding for now, but this will move to dynamic_config
-        let s3ap =
-            ethos_core::storage::S3ArtifactProvider::new(&client, &client.get_artifact_bucket());
-        let new_storage = Some(ArtifactStorage::new(
-            Arc::new(s3ap),
-            new_dynamic_config.storage_schema.clone(),
-        ));
-
         {
             let mut storage = self.storage.write();
             *storage = new_storage;
@@ -445,6 +445,7 @@ pub async fn setup_with_repo_config(
     info!("Starting operation worker");
     let (op_tx, op_rx) = mpsc::channel(32);
     let mut worker = RepoWorker::new(op_rx, pause_file_watcher);
+    let repo_cancel = worker.cancel_handle();
     tokio::spawn(async move {
         worker.run().await;
     });
@@ -452,6 +453,7 @@ pub async fn setup_with_repo_config(
     info!("Started operation worker. Creating channels for longtail, git, and gameserver.");
     let (longtail_tx, longtail_rx) = std::sync::mpsc::channel();
     let (git_tx, git_rx) = std::sync::mpsc::channel();
+    let (git_progress_tx, _git_progress_rx) = std::sync::mpsc::channel();
     let (sync_phase_tx, _sync_phase_rx) = std::sync::mpsc::channel::<String>();
     let (gs_tx, _gs_rx) = std::sync::mpsc::channel();
     let (workflow_tx, _workflow_rx) = std::sync::mpsc::channel();
@@ -499,6 +501,7 @@ pub async fn setup_with_repo_config(
         Some(storage),
         longtail_tx,
         op_tx,
+        repo_cancel,
         notification_tx,
         frontend_op_tx,
         String::from("0.0.0"),
@@ -506,6 +509,7 @@ pub async fn setup_with_repo_config(
         PathBuf::from_str("test-path").unwrap(),
         None,
         git_tx,
+        git_progress_tx,
         sync_phase_tx,
         gs_tx,
         workflow_tx,
@@ -516,23 +520,6 @@ pub async fn setup_with_repo_config(
 
     state.longtail.download_path = LocalDownloadPath(TEST_DIR.join("longtail-downloads"));
 
-    if state.longtail.exec_path.is_none() && state.longtail.update_exec().is_err() {
-        let tx_lock = state.longtail_tx.clone();
-        if let Err(e) = state.longtail.get_longtail(tx_lock.clone()) {
-            info!("failed to get longtail executable: {:?}", e);
-            info!(
-                "[testing module] failed to get longtail executable: {:?}",
-                e
-            );
-        }
-        _ = state.longtail.update_exec();
-    };
-
-    info!(
-        "[testing module] longtail update done. exe path: {:?}",
-        &state.longtail.exec_path
-    );
-
     let mut server = TestServer::new(state, exit_tx);
 
     server.start(exit_rx).await;
@@ -2,11 +2,12 @@ use test_log::test;
 use tokio::{fs, io::AsyncWriteExt};
 use tracing::info;
 
+use ethos_core::clients::git_read::{CliReadBackend, GitReadBackend, InProcessReadBackend};
 use ethos_core::middleware::nonce::{NONCE, NONCE_HEADER};
 use ethos_core::types::config::{RepoConfig, TargetBranchConfig};
 use ethos_core::types::repo::FileState;
 use ethos_core::types::repo::SubmitStatus;
-use ethos_core::types::repo::{PushRequest, RepoStatus};
+use ethos_core::types::repo::{BlockedFileAudit, BlockedFileAuditRequest, PushRequest, RepoStatus};
 
 mod common;
 
@@ -161,7 +162,7 @@ async fn test_status_endpoint_reports_blocked_files() -> Result<(), Box<dyn std:
         target_branches: vec![TargetBranchConfig {
             name: "main".to_string(),
             uses_merge_queue: false,
-            blocked_file_globs: vec!["**/*.uasset".to_string()],
+            blocked_file_globs: vec!["**/*.uasset".into()],
         }],
         ..Default::default()
     };
@@ -200,6 +201,59 @@ async fn test_status_endpoint_reports_blocked_files() -> Result<(), Box<dyn std:
     Ok(())
 }
 
+// The dry run reads the tree at HEAD, which for the test repo is a single
+// empty commit — so the configured pattern is the one reported as matching
+// nothing, and a candidate list sent in the request replaces it.
+#[test(tokio::test(flavor = "multi_thread"))]
+async fn test_blocked_files_audit_endpoint() -> Result<(), Box<dyn std::error::Error>> {
+    info!("Starting test_blocked_files_audit_endpoint");
+    let repo_config = RepoConfig {
+        trunk_branch: "main".to_string(),
+        target_branches: vec![TargetBranchConfig {
+            name: "main".to_string(),
+            uses_merge_queue: false,
+            blocked_file_globs: vec!["**/*.uasset".into()],
+        }],
+        ..Default::default()
+    };
+    let mut server = common::setup_with_repo_config("v1".parse().unwrap(), repo_config).await?;
+
+    let client = reqwest::Client::new();
+    let audit = client
+        .post("http://localhost:8585/repo/blocked-files/audit")
+        .header(NONCE_HEADER, NONCE.to_string())
+        .json(&BlockedFileAuditRequest::default())
+        .send()
+        .await?
+        .json::<BlockedFileAudit>()
+        .await?;
+    assert_eq!(audit.target_branch, "main");
+    assert_eq!(audit.total_files, 0);
+    assert_eq!(audit.unmatched_patterns, vec!["**/*.uasset".to_string()]);
+    assert!(!audit.ok);
+
+    let audit = client
+        .post("http://localhost:8585/repo/blocked-files/audit")
+        .header(NONCE_HEADER, NONCE.to_string())
+        .json(&BlockedFileAuditRequest {
+            patterns: Some(vec!["Content/[broken".into()]),
+            ..Default::default()
+        })
+        .send()
+        .await?
+        .json::<BlockedFileAudit>()
+        .await?;
+    assert!(audit.patterns.is_empty());
+    assert_eq!(audit.invalid.len(), 1);
+    assert_eq!(audit.invalid[0].pattern, "Content/[broken");
+
+    server.shutdown().await;
+
+    common::teardown().await;
+
+    Ok(())
+}
+
 #[test(tokio::test(flavor = "multi_thread"))]
 async fn test_unreal_workflow() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting test_unreal_workflow");
@@ -261,3 +315,50 @@ async fn test_unreal_workflow() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+// Runs the read-only queries `StatusOp` fans out into against both git backends on the shared
+// test repo, so the in-process answers are checked against the CLI's with a real remote.
+#[test(tokio::test(flavor = "multi_thread"))]
+async fn test_git_read_backends_agree() -> Result<(), Box<dyn std::error::Error>> {
+    info!("Starting test_git_read_backends_agree");
+    let mut server = common::setup("v1".parse().unwrap()).await?;
+
+    let repo_path = common::TEST_DIR.join("test-local");
+    fs::write(repo_path.join("ahead.txt"), "ahead").await?;
+    common::add_file("ahead.txt").await;
+    common::commit("ahead of origin").await;
+
+    let git = server.state.git();
+    let backends: Vec<Box<dyn GitReadBackend>> = vec![
+        Box::new(CliReadBackend::new(git.clone())),
+        Box::new(InProcessReadBackend::new(git.repo_path.clone())),
+    ];
+
+    let mut results = vec![];
+    for backend in &backends {
+        results.push((
+            backend.current_branch().await?,
+            backend.get_ahead_behind("origin/main", "main").await?,
+            backend.log(10, "main").await?,
+            backend.has_local_branch("main").await?,
+            backend.diff_filenames("origin/main...HEAD").await?,
+            backend.list_snapshots().await?.len(),
+        ));
+    }
+
+    let (branch, ahead_behind, log, has_main, diff, snapshots) = &results[0];
+    assert_eq!(branch, "main");
+    assert_eq!(*ahead_behind, (1, 0));
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].message, "ahead of origin");
+    assert!(*has_main);
+    assert_eq!(diff, &vec!["ahead.txt".to_string()]);
+    assert_eq!(*snapshots, 0);
+    assert_eq!(results[0], results[1]);
+
+    server.shutdown().await;
+
+    common::teardown().await;
+
+    Ok(())
+}
@@ -1,8 +1,18 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
 	GetWorkflowsResponse,
+	ArtifactListOptions,
 	ArtifactListResponse,
+	BisectExport,
+	BisectStatus,
+	ChunkCacheStatus,
+	InstalledBuild,
+	InstallRequest,
+	MarkBisectRequest,
+	StartBisectRequest,
+	SyncBisectRequest,
 	SyncClientRequest,
+	SyncSummary,
 	JunitOutput,
 	ArtifactEntry,
 	Workflow
@@ -11,8 +21,14 @@ import type {
 export const getBuild = async (commit: string, project?: string): Promise<ArtifactEntry> =>
 	invoke('get_build', { commit, project });
 
-export const getBuilds = async (limit?: number, project?: string): Promise<ArtifactListResponse> =>
-	invoke('get_builds', { limit, project });
+export const getBuilds = async (
+	limit?: number,
+	project?: string,
+	options: ArtifactListOptions = {}
+): Promise<ArtifactListResponse> => invoke('get_builds', { limit, project, ...options });
+
+export const invalidateBuildIndex = async (project?: string): Promise<void> =>
+	invoke('invalidate_build_index', { project });
 
 export const syncClient = async (req: SyncClientRequest): Promise<boolean> =>
 	invoke('sync_client', { req });
@@ -21,7 +37,33 @@ export const cancelDownload = async (): Promise<void> => invoke('cancel_download
 
 export const wipeClientData = async (): Promise<void> => invoke('wipe_client_data');
 
-export const resetLongtail = async (): Promise<void> => invoke('reset_longtail');
+export const getInstalls = async (): Promise<InstalledBuild[]> => invoke('get_installs');
+
+export const launchInstall = async (req: InstallRequest): Promise<void> =>
+	invoke('launch_install', { req });
+
+export const deleteInstall = async (req: InstallRequest): Promise<InstalledBuild> =>
+	invoke('delete_install', { req });
+
+export const getBisect = async (): Promise<BisectStatus | null> => invoke('get_bisect');
+
+export const startBisect = async (req: StartBisectRequest): Promise<BisectStatus> =>
+	invoke('start_bisect', { req });
+
+export const markBisect = async (req: MarkBisectRequest): Promise<BisectStatus> =>
+	invoke('mark_bisect', { req });
+
+export const syncBisect = async (req: SyncBisectRequest): Promise<boolean> =>
+	invoke('sync_bisect', { req });
+
+export const exportBisect = async (): Promise<BisectExport> => invoke('export_bisect');
+
+export const resetBisect = async (): Promise<void> => invoke('reset_bisect');
+
+export const getSyncHistory = async (): Promise<SyncSummary[]> => invoke('get_sync_history');
+
+export const getChunkCacheStatus = async (): Promise<ChunkCacheStatus> =>
+	invoke('get_chunk_cache_status');
 
 export const getWorkflows = async (
 	engine: boolean = false,
@@ -1,22 +1,35 @@
 import type { ChangeSet, Commit, CommitFileInfo, ModifiedFile } from '@ethos/core';
 import { invoke } from '@tauri-apps/api/core';
 import type {
+	BlockedFileAudit,
+	BlockedFileAuditRequest,
 	CloneRequest,
 	CommitInfo,
+	ConflictMergePreview,
+	ConflictResolution,
+	ContentProfilePlan,
+	ContentProfilesResponse,
 	FileHistoryResponse,
 	GitHubPullRequest,
 	GitHubStatusResponse,
 	ImportZippedChangesResponse,
+	IndexLockStatus,
 	MergeQueue,
 	ObjectCountResponse,
 	PushRequest,
+	RebaseConflictsResponse,
 	RebaseStatusResponse,
 	RepoDirectoryListing,
 	RepoStatus,
 	RestoreFileToRevisionRequest,
 	RevertFilesRequest,
 	Snapshot,
+	SnapshotFileRestore,
+	SnapshotFileRestoreResult,
 	SnapshotPreviewResponse,
+	SnapshotPrunePlan,
+	TrackedSubmit,
+	WorktreeStatus,
 	ZipLocalChangesResponse,
 	ZipPreviewResponse
 } from '$lib/types';
@@ -92,19 +105,43 @@ export const quickSubmit = async (req: PushRequest): Promise<void> =>
 export const listSnapshots = async (): Promise<Snapshot[]> => invoke('list_snapshots');
 
 export const restoreSnapshot = async (
-	commit: string,
+	id: string,
 	files?: string[],
 	overwriteLocal: boolean = false
-): Promise<void> => invoke('restore_snapshot', { commit, files: files ?? null, overwriteLocal });
+): Promise<void> => invoke('restore_snapshot', { id, files: files ?? null, overwriteLocal });
+
+export const restoreSnapshotFiles = async (
+	id: string,
+	files: SnapshotFileRestore[]
+): Promise<SnapshotFileRestoreResult[]> => invoke('restore_snapshot_files', { id, files });
+
+export const previewSnapshot = async (id: string): Promise<SnapshotPreviewResponse> =>
+	invoke('preview_snapshot', { id });
+
+export const saveSnapshot = async (
+	message: string,
+	files: string[],
+	tags: string[] = [],
+	automatic: boolean = false
+): Promise<void> => invoke('save_snapshot', { message, files, tags, automatic });
+
+export const deleteSnapshot = async (id: string): Promise<void> =>
+	invoke('delete_snapshot', { id });
 
-export const previewSnapshot = async (commit: string): Promise<SnapshotPreviewResponse> =>
-	invoke('preview_snapshot', { commit });
+export const setSnapshotTags = async (id: string, tags: string[]): Promise<Snapshot> =>
+	invoke('set_snapshot_tags', { id, tags });
 
-export const saveSnapshot = async (message: string, files: string[]): Promise<void> =>
-	invoke('save_snapshot', { message, files });
+export const pushSnapshot = async (id: string): Promise<Snapshot> =>
+	invoke('push_snapshot', { id });
 
-export const deleteSnapshot = async (commit: string): Promise<void> =>
-	invoke('delete_snapshot', { commit });
+export const getSnapshotPrunePlan = async (): Promise<SnapshotPrunePlan> =>
+	invoke('get_snapshot_prune_plan');
+
+export const pruneSnapshots = async (confirm: string[]): Promise<Snapshot[]> =>
+	invoke('prune_snapshots', { confirm });
+
+export const fetchSnapshot = async (id: string): Promise<Snapshot> =>
+	invoke('fetch_snapshot', { id });
 
 export const saveChangeSet = async (changeSets: ChangeSet[]): Promise<void> =>
 	invoke('save_changeset', { changeSets });
@@ -212,6 +249,31 @@ export const fixRebase = async (): Promise<void> => invoke('fix_rebase');
 
 export const rebase = async (): Promise<void> => invoke('rebase');
 
+export const getRebaseConflicts = async (): Promise<RebaseConflictsResponse> =>
+	invoke('get_rebase_conflicts');
+
+export const getConflictMergePreview = async (path: string): Promise<ConflictMergePreview> =>
+	invoke('get_conflict_merge_preview', { path });
+
+export const resolveRebaseConflicts = async (
+	resolutions: ConflictResolution[],
+	continueRebase: boolean
+): Promise<RebaseConflictsResponse> =>
+	invoke('resolve_rebase_conflicts', { req: { resolutions, continueRebase } });
+
+export const auditBlockedFiles = async (
+	request: BlockedFileAuditRequest
+): Promise<BlockedFileAudit> => invoke('audit_blocked_files', { request });
+
+export const listContentProfiles = async (): Promise<ContentProfilesResponse> =>
+	invoke('list_content_profiles');
+
+export const planContentProfile = async (name: string): Promise<ContentProfilePlan> =>
+	invoke('plan_content_profile', { name });
+
+export const applyContentProfile = async (name: string): Promise<void> =>
+	invoke('apply_content_profile', { name });
+
 export const getObjectCount = async (): Promise<ObjectCountResponse> => invoke('get_object_count');
 
 export const getGithubStatus = async (): Promise<GitHubStatusResponse> =>
@@ -223,6 +285,14 @@ export const resetRepo = async (): Promise<void> => invoke('reset_repo');
 
 export const refetchRepo = async (): Promise<void> => invoke('refetch_repo');
 
+export const cancelRepoOperation = async (): Promise<void> => invoke('cancel_repo_operation');
+
+export const getIndexLockStatus = async (): Promise<IndexLockStatus> =>
+	invoke('get_index_lock_status');
+
+export const removeStaleIndexLock = async (): Promise<IndexLockStatus> =>
+	invoke('remove_stale_index_lock');
+
 export const resetRepoToCommit = async (commit: string): Promise<void> =>
 	invoke('reset_repo_to_commit', { commit });
 
@@ -257,6 +327,9 @@ export const getCommitFileTextClass = (action: string) => {
 
 export const getMergeQueue = async (): Promise<MergeQueue> => invoke('get_merge_queue');
 
+export const getTrackedSubmits = async (): Promise<TrackedSubmit[]> =>
+	invoke('get_tracked_submits');
+
 export const checkoutTargetBranch = async (): Promise<void> => invoke('checkout_target_branch');
 
 export const listRepoDirectory = async (path: string): Promise<RepoDirectoryListing> =>
@@ -283,3 +356,18 @@ export const importZippedChanges = async (
 	source: string,
 	files: string[] | null = null
 ): Promise<ImportZippedChangesResponse> => invoke('import_zipped_changes', { source, files });
+
+export const listWorktrees = async (): Promise<WorktreeStatus[]> => invoke('list_worktrees');
+
+export const addWorktree = async (
+	branch: string,
+	path?: string,
+	startPoint?: string
+): Promise<WorktreeStatus> =>
+	invoke('add_worktree', { branch, path: path ?? null, startPoint: startPoint ?? null });
+
+export const switchWorktree = async (path: string): Promise<void> =>
+	invoke('switch_worktree', { path });
+
+export const removeWorktree = async (path: string, force: boolean = false): Promise<void> =>
+	invoke('remove_worktree', { path, force });
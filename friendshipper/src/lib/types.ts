@@ -84,6 +84,9 @@ export interface AppConfig {
 	serverless: boolean;
 	selectedArtifactProject: string;
 	maxClientCacheSizeGb: number;
+	maxChunkCacheSizeGb: number;
+	maxInstalledBuilds: number;
+	snapshotRetention: SnapshotRetention;
 	playtestRegion: string;
 	initialized: boolean;
 	lastQuickSubmitType?: string;
@@ -96,10 +99,70 @@ export interface PlaytestProfile {
 	args: string;
 }
 
+export interface ContentProfile {
+	name: string;
+	description: string;
+	sparseDirectories: string[];
+	lfsFetchInclude: string[];
+	lfsFetchExclude: string[];
+}
+
+export type SubmitValidatorRule =
+	| { type: 'forbidden' }
+	| { type: 'maxFileSize'; maxBytes: number }
+	| { type: 'pathRegex'; pattern: string }
+	| { type: 'commitMessageRegex'; pattern: string }
+	| { type: 'command'; command: string; args: string[]; timeoutSeconds: number };
+
+export type SubmitValidator = SubmitValidatorRule & {
+	name: string;
+	message?: string;
+	files: string[];
+	excludeFiles: string[];
+	branches: string[];
+};
+
+export interface BlockedFileRule {
+	pattern: string;
+	allowedUsers?: string[];
+	reason?: string;
+}
+
+export interface BlockedFileAuditRequest {
+	targetBranch?: string;
+	patterns?: (string | BlockedFileRule)[];
+	username?: string;
+}
+
+export interface BlockedFilePatternAudit {
+	pattern: string;
+	negated: boolean;
+	allowedUsers?: string[];
+	reason?: string;
+	files: string[];
+}
+
+export interface BlockedFilePatternDiagnostic {
+	pattern: string;
+	message: string;
+}
+
+export interface BlockedFileAudit {
+	targetBranch: string;
+	commit: string;
+	totalFiles: number;
+	blockedFiles: number;
+	patterns: BlockedFilePatternAudit[];
+	unmatchedPatterns: string[];
+	invalid: BlockedFilePatternDiagnostic[];
+	warnings: BlockedFilePatternDiagnostic[];
+	ok: boolean;
+}
+
 export interface TargetBranchConfig {
 	name: string;
 	usesMergeQueue: boolean;
-	blockedFileGlobs: string[];
+	blockedFileGlobs: (string | BlockedFileRule)[];
 }
 
 export interface PromoteBuildShard {
@@ -119,6 +182,8 @@ export interface RepoConfig {
 	playtestProfiles: PlaytestProfile[];
 	buildsEnabled: boolean;
 	serversEnabled: boolean;
+	contentProfiles: ContentProfile[];
+	submitValidators: SubmitValidator[];
 }
 
 // Kubernetes API types
@@ -136,16 +201,38 @@ export interface LocalObjectReference {
 }
 
 // Builds types
+export interface ArtifactManifest {
+	commit: string;
+	branch?: string;
+	pusher?: string;
+	buildTimestamp?: string;
+	size?: number;
+	engineVersion?: string;
+	symbols?: string;
+	tags: string[];
+}
+
 export interface ArtifactEntry {
 	key: string;
 	displayName: string;
 	lastModified: number;
 	commit: string;
+	metadata?: ArtifactManifest;
 }
 
 export interface ArtifactListResponse {
 	methodPrefix: string;
 	entries: ArtifactEntry[];
+	nextCursor?: string;
+}
+
+export interface ArtifactListOptions {
+	cursor?: string;
+	since?: string;
+	until?: string;
+	commits?: string[];
+	fromCommit?: string;
+	toCommit?: string;
 }
 
 export enum LaunchMode {
@@ -165,6 +252,129 @@ export interface SyncClientRequest {
 	launchOptions?: LaunchOptions;
 }
 
+export interface InstalledBuild {
+	commit: string;
+	baseName: string;
+	path: string;
+	archives: string[];
+	installedAt: string;
+	lastLaunchedAt?: string;
+}
+
+export interface InstallRequest {
+	commit: string;
+	baseName?: string;
+	launchOptions?: LaunchOptions;
+}
+
+export type BisectVerdict = 'good' | 'bad' | 'skip';
+
+export interface BisectCommit {
+	sha: string;
+	artifact?: ArtifactEntry;
+	verdict?: BisectVerdict;
+}
+
+export interface BisectRange {
+	lastGood: string;
+	firstBad: string;
+	commits: string[];
+}
+
+export interface BisectSession {
+	project: string;
+	methodPrefix: string;
+	good: string;
+	bad: string;
+	commits: BisectCommit[];
+	startedAt: string;
+}
+
+export interface BisectStatus {
+	session: BisectSession;
+	candidate?: BisectCommit;
+	remaining: number;
+	range: BisectRange;
+}
+
+export interface BisectExport {
+	project: string;
+	lastGood: string;
+	firstBad: string;
+	finished: boolean;
+	commits: CommitInfo[];
+}
+
+export interface StartBisectRequest {
+	good: string;
+	bad: string;
+	project?: string;
+}
+
+export interface MarkBisectRequest {
+	commit: string;
+	verdict: BisectVerdict;
+}
+
+export interface SyncBisectRequest {
+	launchOptions?: LaunchOptions;
+}
+
+export interface GitProgress {
+	operation: string;
+	phase: string;
+	current: number;
+	total: Nullable<number>;
+	throughput: Nullable<string>;
+}
+
+export type LongtailPhase = 'Indexing' | 'Downloading' | 'Writing' | 'Verifying';
+
+export interface LongtailSyncProgress {
+	phase: LongtailPhase;
+	progress: number;
+	doneBytes: number;
+	totalBytes: number;
+	chunksDone: number;
+	chunksTotal: number;
+	elapsed: string;
+	remaining: string;
+}
+
+export interface SyncSummary {
+	startedAt: string;
+	target: string;
+	archives: string[];
+	downloadedBytes: number;
+	cachedBytes: number;
+	writtenBytes: number;
+	resumedAssets: number;
+	repairedAssets: number;
+	wallTimeMs: number;
+	retries: number;
+	succeeded: boolean;
+	error?: string;
+}
+
+export type CacheConsumer = 'client' | 'engine' | 'dlls';
+
+export interface ConsumerUsage {
+	// null for blocks no consumer has claimed yet
+	consumer: CacheConsumer | null;
+	usedBytes: number;
+	pinnedBytes: number;
+	blockCount: number;
+	quotaBytes?: number;
+}
+
+export interface ChunkCacheStatus {
+	root: string;
+	totalBytes: number;
+	maxSizeBytes: number;
+	blockCount: number;
+	consumers: ConsumerUsage[];
+}
+
 // GameServer types
 
 export interface GameServerResult {
@@ -304,6 +514,42 @@ export interface MergeQueue {
 	entries: MergeQueueEntryConnection;
 }
 
+export type TrackedSubmitState =
+	| 'opened'
+	| 'mergeable'
+	| 'enqueued'
+	| 'ejected'
+	| 'merged'
+	| 'closed';
+
+export interface SubmitTransition {
+	state: TrackedSubmitState;
+	timestamp: string;
+	message?: string;
+}
+
+export interface TrackedSubmit {
+	number: number;
+	id: string;
+	owner: string;
+	repo: string;
+	title: string;
+	headBranch: string;
+	baseBranch: string;
+	usesMergeQueue: boolean;
+	files: string[];
+	state: TrackedSubmitState;
+	transitions: SubmitTransition[];
+	requeueAttempts: number;
+	stackedOn?: StackedOn;
+}
+
+export interface StackedOn {
+	id: string;
+	number: number;
+	commit: string;
+}
+
 export interface GithubPullRequestCommit {
 	oid: string;
 	message: string;
@@ -365,22 +611,108 @@ export interface RebaseStatusResponse {
 	headNameExists: boolean;
 }
 
+export interface ConflictBlob {
+	sha: string;
+	mode: string;
+}
+
+// while rebasing, ours is the upstream version and theirs is the local commit being replayed
+export type ConflictSide = 'ours' | 'theirs';
+
+export interface RebaseConflictFile {
+	path: string;
+	base: Nullable<ConflictBlob>;
+	ours: Nullable<ConflictBlob>;
+	theirs: Nullable<ConflictBlob>;
+	isBinary: boolean;
+	lockedBy: Nullable<string>;
+	lastChange: Nullable<FileHistoryRevision>;
+}
+
+export interface RebaseConflictsResponse {
+	inProgress: boolean;
+	onto: Nullable<string>;
+	headName: Nullable<string>;
+	stoppedAt: Nullable<string>;
+	files: RebaseConflictFile[];
+}
+
+export interface ConflictMergePreview {
+	path: string;
+	merged: string;
+	conflictCount: number;
+}
+
+export interface ConflictResolution {
+	path: string;
+	side: ConflictSide;
+}
+
+export interface LfsFileInfo {
+	path: string;
+	size: number;
+	downloaded: boolean;
+}
+
+export interface ContentProfilePlan {
+	profile: string;
+	includedFiles: number;
+	includedBytes: number;
+	downloadFiles: number;
+	downloadBytes: number;
+	excludedFiles: number;
+	excludedBytes: number;
+	largestDownloads: LfsFileInfo[];
+}
+
+export interface ContentProfilesResponse {
+	profiles: ContentProfile[];
+	active: Nullable<string>;
+}
+
 export interface ObjectCountResponse {
 	inPackCount: number;
 	isHealthy: boolean;
 	rawOutput: string;
 }
 
+export interface IndexLockStatus {
+	exists: boolean;
+	path: string;
+	ageSeconds: number;
+	stale: boolean;
+	gitProcesses: number[];
+}
+
 export interface GitHubStatusResponse {
 	indicator: 'none' | 'minor' | 'major' | 'critical' | 'maintenance';
 	description: string;
 	url: string;
 }
 
+export type SnapshotKind = 'manual' | 'automatic';
+
 export interface Snapshot {
+	id: string;
 	commit: string;
 	message: string;
 	timestamp: string;
+	author: string;
+	branch: string;
+	files: string[];
+	kind: SnapshotKind;
+	tags: string[];
+}
+
+export interface SnapshotRetention {
+	automaticMaxAgeDays: number;
+	automaticKeepCount: number;
+	manualMaxAgeDays?: number | null;
+}
+
+export interface SnapshotPrunePlan {
+	prunable: Snapshot[];
+	differsFromHead: Snapshot[];
 }
 
 // System types
@@ -587,6 +919,15 @@ export interface ImportZippedChangesResponse {
 	deleted: number;
 }
 
+export interface WorktreeStatus {
+	path: string;
+	branch: Nullable<string>;
+	sha: string;
+	isMain: boolean;
+	active: boolean;
+	status: Nullable<RepoStatus>;
+}
+
 export interface SnapshotPreviewEntry {
 	path: string;
 	state: 'Unknown' | 'Added' | 'Modified' | 'Deleted' | 'Unmerged';
@@ -598,6 +939,19 @@ export interface SnapshotPreviewResponse {
 	entries: SnapshotPreviewEntry[];
 }
 
+export type SnapshotConflictResolution = 'theirs' | 'mine' | 'saveAsCopy';
+
+export interface SnapshotFileRestore {
+	path: string;
+	resolution?: SnapshotConflictResolution;
+}
+
+export interface SnapshotFileRestoreResult {
+	path: string;
+	outcome: 'restored' | 'removed' | 'kept' | 'copied' | 'unchanged';
+	copyPath?: string;
+}
+
 export interface TraceEntry {
 	date: string;
 	serverName: string;